@@ -133,10 +133,45 @@ use core::panic::PanicInfo;
 #[lang = "eh_personality"]
 extern "C" fn eh_personality() {}
 
+// Called by the panic handler before it prints anything. Panics don't unwind,
+// so this is the only chance to undo state that outlives the process, such as
+// a terminal left in raw mode.
 #[cfg(target_os = "dragonos")]
+static PANIC_CLEANUP: core::sync::atomic::AtomicUsize = core::sync::atomic::AtomicUsize::new(0);
+
+/// Sets the function the panic handler calls before ending the process,
+/// replacing the one set before.
+#[cfg(target_os = "dragonos")]
+pub fn set_panic_cleanup(cleanup: fn()) {
+    PANIC_CLEANUP.store(cleanup as usize, core::sync::atomic::Ordering::Release);
+}
+
+#[cfg(target_os = "dragonos")]
+fn run_panic_cleanup() {
+    // Taken out first, so that a panic in the cleanup doesn't run it again.
+    let cleanup = PANIC_CLEANUP.swap(0, core::sync::atomic::Ordering::AcqRel);
+    if cleanup != 0 {
+        let cleanup: fn() = unsafe { core::mem::transmute(cleanup) };
+        cleanup();
+    }
+}
+
+#[cfg(all(target_os = "dragonos", feature = "minimal-runtime"))]
+#[panic_handler]
+fn panic_handler(_info: &core::panic::PanicInfo) -> ! {
+    const MSG: &[u8] = b"panicked, aborting\n";
+    run_panic_cleanup();
+    unsafe {
+        crate::unix::platform::pal::write(2, MSG.as_ptr().cast(), MSG.len());
+        core::intrinsics::abort()
+    }
+}
+
+#[cfg(all(target_os = "dragonos", not(feature = "minimal-runtime")))]
 #[panic_handler]
 fn panic_handler(info: &core::panic::PanicInfo) -> ! {
     // 在这里执行自定义的处理逻辑
+    run_panic_cleanup();
     println!("Panic occurred: {:?}", info);
 
     // 可以选择进行一些清理或其他操作
@@ -0,0 +1,123 @@
+//! The stable C ABI of dlibc.
+//!
+//! Everything dlibc exports with `#[no_mangle]` is visible to C programs, but
+//! only the symbols listed here are promised to keep their name, signature and
+//! behaviour when dlibc internals are refactored. Programs that only link
+//! against this subset keep working against newer dlibc builds.
+//!
+//! The same list is kept as a GNU version script in `dlibc.map` at the crate
+//! root, so a shared dlibc can tag the symbols with `DLIBC_0.1` by linking
+//! with `-Wl,--version-script=dlibc.map`. The static archive carries no
+//! version tags; there the list is the contract. Symbols outside the list stay
+//! exported, just without a version. The tests below check that the two lists
+//! agree and that every symbol links under its C name with the documented
+//! signature.
+//!
+//! Adding a symbol is backwards compatible. Removing one, renaming one or
+//! changing its signature needs a new version node.
+
+use unix::header::stdio::FILE;
+use unix::header::{errno, stdio, stdlib, string};
+use unix::platform::dragonos::pal::relibc_adapter::pal;
+
+macro_rules! stable_abi {
+    (
+        version = $version:expr;
+        $(
+            $name:ident = $path:path : fn($($arg:ty),*) $(-> $ret:ty)*;
+        )*
+    ) => {
+        /// Version node of the stable symbol set.
+        pub const VERSION: &'static str = $version;
+
+        /// C names of the symbols in the stable set, in `dlibc.map` order.
+        pub static SYMBOLS: &'static [&'static str] = &[$(stringify!($name)),*];
+
+        #[cfg(test)]
+        mod link_names {
+            #[allow(unused_imports)]
+            use super::*;
+
+            // Checked at compile time: the definition still has the
+            // documented signature.
+            $(
+                #[allow(dead_code)]
+                const $name: unsafe extern "C" fn($($arg),*) $(-> $ret)* = $path;
+            )*
+
+            #[test]
+            fn symbols_resolve_to_definitions() {
+                $(
+                    {
+                        extern "C" {
+                            #[link_name = stringify!($name)]
+                            fn external($($arg),*) $(-> $ret)*;
+                        }
+                        let definition = $path as unsafe extern "C" fn($($arg),*) $(-> $ret)*;
+                        assert_eq!(
+                            definition as usize,
+                            external as usize,
+                            "`{}` does not link to its dlibc definition",
+                            stringify!($name)
+                        );
+                    }
+                )*
+            }
+        }
+    };
+}
+
+stable_abi! {
+    version = "DLIBC_0.1";
+
+    __errno_location = errno::__errno_location: fn() -> *mut ::c_int;
+
+    memcmp = string::memcmp: fn(*const ::c_void, *const ::c_void, ::size_t) -> ::c_int;
+    memcpy = string::memcpy: fn(*mut ::c_void, *const ::c_void, ::size_t) -> *mut ::c_void;
+    memmove = string::memmove: fn(*mut ::c_void, *const ::c_void, ::size_t) -> *mut ::c_void;
+    memset = string::memset: fn(*mut ::c_void, ::c_int, ::size_t) -> *mut ::c_void;
+    strchr = string::strchr: fn(*const ::c_char, ::c_int) -> *mut ::c_char;
+    strcmp = string::strcmp: fn(*const ::c_char, *const ::c_char) -> ::c_int;
+    strlen = string::strlen: fn(*const ::c_char) -> ::size_t;
+    strncmp = string::strncmp: fn(*const ::c_char, *const ::c_char, ::size_t) -> ::c_int;
+
+    atexit = stdlib::atexit: fn(Option<extern "C" fn()>) -> ::c_int;
+    calloc = stdlib::calloc: fn(::size_t, ::size_t) -> *mut ::c_void;
+    exit = stdlib::exit: fn(::c_int);
+    free = stdlib::free: fn(*mut ::c_void);
+    getenv = stdlib::getenv: fn(*const ::c_char) -> *mut ::c_char;
+    malloc = stdlib::malloc: fn(::size_t) -> *mut ::c_void;
+    realloc = stdlib::realloc: fn(*mut ::c_void, ::size_t) -> *mut ::c_void;
+
+    fclose = stdio::fclose: fn(*mut FILE) -> ::c_int;
+    fflush = stdio::fflush: fn(*mut FILE) -> ::c_int;
+    fopen = stdio::fopen: fn(*const ::c_char, *const ::c_char) -> *mut FILE;
+    fputs = stdio::fputs: fn(*const ::c_char, *mut FILE) -> ::c_int;
+    puts = stdio::puts: fn(*const ::c_char) -> ::c_int;
+
+    close = pal::close: fn(::c_int) -> ::c_int;
+    getpid = pal::getpid: fn() -> ::pid_t;
+    lseek = pal::lseek: fn(::c_int, ::off_t, ::c_int) -> ::off_t;
+    mmap = pal::mmap: fn(*mut ::c_void, ::size_t, ::c_int, ::c_int, ::c_int, ::off_t) -> *mut ::c_void;
+    munmap = pal::munmap: fn(*mut ::c_void, ::size_t) -> ::c_int;
+    read = pal::read: fn(::c_int, *mut ::c_void, ::size_t) -> ::ssize_t;
+    write = pal::write: fn(::c_int, *const ::c_void, ::size_t) -> ::ssize_t;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn version_script_matches() {
+        let script = include_str!("../../dlibc.map");
+        let mut lines = script.lines().map(str::trim).filter(|l| !l.is_empty());
+        assert_eq!(lines.next(), Some(&*format!("{} {{", VERSION)));
+        assert_eq!(lines.next(), Some("global:"));
+        for &name in SYMBOLS {
+            assert_eq!(lines.next(), Some(&*format!("{};", name)));
+        }
+        assert_eq!(lines.next(), Some("};"));
+        assert_eq!(lines.next(), None);
+    }
+}
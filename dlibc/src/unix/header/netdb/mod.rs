@@ -214,9 +214,9 @@ pub unsafe extern "C" fn gethostbyaddr(
             if (*cp).is_null() {
                 break;
             }
-            let mut cp_slice: [i8; 4] = [0i8; 4];
+            let mut cp_slice: [::c_char; 4] = [0; 4];
             (*cp).copy_to(cp_slice.as_mut_ptr(), 4);
-            let cp_s_addr = mem::transmute::<[i8; 4], u32>(cp_slice);
+            let cp_s_addr = mem::transmute::<[::c_char; 4], u32>(cp_slice);
             if cp_s_addr == addr.s_addr {
                 sethostent(HOST_STAYOPEN);
                 return p;
@@ -228,7 +228,7 @@ pub unsafe extern "C" fn gethostbyaddr(
     //TODO actually get aliases
     let mut _host_aliases: Vec<Vec<u8>> = Vec::new();
     _host_aliases.push(vec![b'\0']);
-    let mut host_aliases: Vec<*mut i8> = Vec::new();
+    let mut host_aliases: Vec<*mut ::c_char> = Vec::new();
     host_aliases.push(ptr::null_mut());
     HOST_ALIASES = Some(_host_aliases);
 
@@ -240,7 +240,7 @@ pub unsafe extern "C" fn gethostbyaddr(
             HOST_NAME = Some(host_name);
             HOST_ENTRY = hostent {
                 h_name: HOST_NAME.as_mut().unwrap().as_mut_ptr() as *mut ::c_char,
-                h_aliases: host_aliases.as_mut_slice().as_mut_ptr() as *mut *mut i8,
+                h_aliases: host_aliases.as_mut_slice().as_mut_ptr() as *mut *mut ::c_char,
                 h_addrtype: format,
                 h_length: length as i32,
                 h_addr_list: HOST_ADDR_LIST.as_mut_ptr(),
@@ -332,14 +332,14 @@ pub unsafe extern "C" fn gethostbyname(name: *const ::c_char) -> *mut hostent {
     //TODO actually get aliases
     let mut _host_aliases: Vec<Vec<u8>> = Vec::new();
     _host_aliases.push(vec![b'\0']);
-    let mut host_aliases: Vec<*mut i8> = Vec::new();
+    let mut host_aliases: Vec<*mut ::c_char> = Vec::new();
     host_aliases.push(ptr::null_mut());
     host_aliases.push(ptr::null_mut());
     HOST_ALIASES = Some(_host_aliases);
 
     HOST_ENTRY = hostent {
         h_name: HOST_NAME.as_mut().unwrap().as_mut_ptr() as *mut ::c_char,
-        h_aliases: host_aliases.as_mut_slice().as_mut_ptr() as *mut *mut i8,
+        h_aliases: host_aliases.as_mut_slice().as_mut_ptr() as *mut *mut ::c_char,
         h_addrtype: AF_INET,
         h_length: 4,
         h_addr_list: HOST_ADDR_LIST.as_mut_ptr(),
@@ -374,7 +374,7 @@ pub unsafe extern "C" fn getnetbyname(name: *const ::c_char) -> *mut netent {
 #[no_mangle]
 pub unsafe extern "C" fn getnetent() -> *mut netent {
     if NETDB == 0 {
-        NETDB = platform::pal::open("/etc/networks".as_ptr() as *const i8, O_RDONLY, 0);
+        NETDB = platform::pal::open("/etc/networks".as_ptr() as *const ::c_char, O_RDONLY, 0);
     }
 
     let mut rlb = RawLineBuffer::new(NETDB);
@@ -403,7 +403,7 @@ pub unsafe extern "C" fn getnetent() -> *mut netent {
 
     let mut addr_vec = iter.next().unwrap().as_bytes().to_vec();
     addr_vec.push(b'\0');
-    let addr_cstr = addr_vec.as_slice().as_ptr() as *const i8;
+    let addr_cstr = addr_vec.as_slice().as_ptr() as *const ::c_char;
     let mut addr = mem::MaybeUninit::uninit();
     inet_aton(addr_cstr, addr.as_mut_ptr());
     let addr = addr.assume_init();
@@ -415,16 +415,16 @@ pub unsafe extern "C" fn getnetent() -> *mut netent {
         alias.push(b'\0');
         _net_aliases.push(alias);
     }
-    let mut net_aliases: Vec<*mut i8> = _net_aliases
+    let mut net_aliases: Vec<*mut ::c_char> = _net_aliases
         .iter_mut()
-        .map(|x| x.as_mut_ptr() as *mut i8)
+        .map(|x| x.as_mut_ptr() as *mut ::c_char)
         .collect();
     net_aliases.push(ptr::null_mut());
     NET_ALIASES = Some(_net_aliases);
 
     NET_ENTRY = netent {
         n_name: NET_NAME.as_mut().unwrap().as_mut_ptr() as *mut ::c_char,
-        n_aliases: net_aliases.as_mut_slice().as_mut_ptr() as *mut *mut i8,
+        n_aliases: net_aliases.as_mut_slice().as_mut_ptr() as *mut *mut ::c_char,
         n_addrtype: AF_INET,
         n_net: NET_ADDR.unwrap() as ::c_ulong,
     };
@@ -488,7 +488,7 @@ pub unsafe extern "C" fn getprotobynumber(number: ::c_int) -> *mut protoent {
 #[no_mangle]
 pub unsafe extern "C" fn getprotoent() -> *mut protoent {
     if PROTODB == 0 {
-        PROTODB = platform::pal::open("/etc/protocols".as_ptr() as *const i8, O_RDONLY, 0);
+        PROTODB = platform::pal::open("/etc/protocols".as_ptr() as *const ::c_char, O_RDONLY, 0);
     }
 
     let mut rlb = RawLineBuffer::new(PROTODB);
@@ -516,7 +516,7 @@ pub unsafe extern "C" fn getprotoent() -> *mut protoent {
 
     let mut num = iter.next().unwrap().as_bytes().to_vec();
     num.push(b'\0');
-    PROTO_NUM = Some(atoi(num.as_mut_slice().as_mut_ptr() as *mut i8));
+    PROTO_NUM = Some(atoi(num.as_mut_slice().as_mut_ptr() as *mut ::c_char));
 
     let mut _proto_aliases: Vec<Vec<u8>> = Vec::new();
     for s in iter {
@@ -524,9 +524,9 @@ pub unsafe extern "C" fn getprotoent() -> *mut protoent {
         alias.push(b'\0');
         _proto_aliases.push(alias);
     }
-    let mut proto_aliases: Vec<*mut i8> = _proto_aliases
+    let mut proto_aliases: Vec<*mut ::c_char> = _proto_aliases
         .iter_mut()
-        .map(|x| x.as_mut_ptr() as *mut i8)
+        .map(|x| x.as_mut_ptr() as *mut ::c_char)
         .collect();
     proto_aliases.push(ptr::null_mut());
 
@@ -535,7 +535,7 @@ pub unsafe extern "C" fn getprotoent() -> *mut protoent {
 
     PROTO_ENTRY = protoent {
         p_name: PROTO_NAME.as_mut().unwrap().as_mut_slice().as_mut_ptr() as *mut ::c_char,
-        p_aliases: proto_aliases.as_mut_slice().as_mut_ptr() as *mut *mut i8,
+        p_aliases: proto_aliases.as_mut_slice().as_mut_ptr() as *mut *mut ::c_char,
         p_proto: PROTO_NUM.unwrap(),
     };
     if PROTO_STAYOPEN == 0 {
@@ -607,7 +607,7 @@ pub unsafe extern "C" fn getservbyport(port: ::c_int, proto: *const ::c_char) ->
 #[no_mangle]
 pub unsafe extern "C" fn getservent() -> *mut servent {
     if SERVDB == 0 {
-        SERVDB = platform::pal::open("/etc/services".as_ptr() as *const i8, O_RDONLY, 0);
+        SERVDB = platform::pal::open("/etc/services".as_ptr() as *const ::c_char, O_RDONLY, 0);
     }
     let mut rlb = RawLineBuffer::new(SERVDB);
     rlb.seek(S_POS);
@@ -642,7 +642,7 @@ pub unsafe extern "C" fn getservent() -> *mut servent {
         };
         port.push(b'\0');
         SERV_PORT =
-            Some(htons(atoi(port.as_mut_slice().as_mut_ptr() as *mut i8) as u16) as u32 as i32);
+            Some(htons(atoi(port.as_mut_slice().as_mut_ptr() as *mut ::c_char) as u16) as u32 as i32);
         let mut proto = match split.next() {
             Some(proto) => proto.as_bytes().to_vec(),
             None => continue,
@@ -662,13 +662,13 @@ pub unsafe extern "C" fn getservent() -> *mut servent {
          *    alias.push(b'\0');
          *    _serv_aliases.push(alias);
          *}
-         *let mut serv_aliases: Vec<*mut i8> = _serv_aliases.iter_mut().map(|x| x.as_mut_ptr() as *mut i8).collect();
+         *let mut serv_aliases: Vec<*mut ::c_char> = _serv_aliases.iter_mut().map(|x| x.as_mut_ptr() as *mut ::c_char).collect();
          *serv_aliases.push(ptr::null_mut());
          *
          */
         let mut _serv_aliases: Vec<Vec<u8>> = Vec::new();
         _serv_aliases.push(vec![b'\0']);
-        let mut serv_aliases: Vec<*mut i8> = Vec::new();
+        let mut serv_aliases: Vec<*mut ::c_char> = Vec::new();
         serv_aliases.push(ptr::null_mut());
         serv_aliases.push(ptr::null_mut());
 
@@ -678,7 +678,7 @@ pub unsafe extern "C" fn getservent() -> *mut servent {
 
         SERV_ENTRY = servent {
             s_name: SERV_NAME.as_mut().unwrap().as_mut_slice().as_mut_ptr() as *mut ::c_char,
-            s_aliases: serv_aliases.as_mut_slice().as_mut_ptr() as *mut *mut i8,
+            s_aliases: serv_aliases.as_mut_slice().as_mut_ptr() as *mut *mut ::c_char,
             s_port: SERV_PORT.unwrap(),
             s_proto: SERV_PROTO.as_mut().unwrap().as_mut_slice().as_mut_ptr() as *mut ::c_char,
         };
@@ -694,7 +694,7 @@ pub unsafe extern "C" fn getservent() -> *mut servent {
 pub unsafe extern "C" fn setnetent(stayopen: ::c_int) {
     NET_STAYOPEN = stayopen;
     if NETDB == 0 {
-        NETDB = platform::pal::open("/etc/networks".as_ptr() as *const i8, O_RDONLY, 0)
+        NETDB = platform::pal::open("/etc/networks".as_ptr() as *const ::c_char, O_RDONLY, 0)
     } else {
         platform::pal::lseek(NETDB, 0, SEEK_SET);
         N_POS = 0;
@@ -705,7 +705,7 @@ pub unsafe extern "C" fn setnetent(stayopen: ::c_int) {
 pub unsafe extern "C" fn setprotoent(stayopen: ::c_int) {
     PROTO_STAYOPEN = stayopen;
     if PROTODB == 0 {
-        PROTODB = platform::pal::open("/etc/protocols".as_ptr() as *const i8, O_RDONLY, 0)
+        PROTODB = platform::pal::open("/etc/protocols".as_ptr() as *const ::c_char, O_RDONLY, 0)
     } else {
         platform::pal::lseek(PROTODB, 0, SEEK_SET);
         P_POS = 0;
@@ -716,7 +716,7 @@ pub unsafe extern "C" fn setprotoent(stayopen: ::c_int) {
 pub unsafe extern "C" fn setservent(stayopen: ::c_int) {
     SERV_STAYOPEN = stayopen;
     if SERVDB == 0 {
-        SERVDB = platform::pal::open("/etc/services".as_ptr() as *const i8, O_RDONLY, 0)
+        SERVDB = platform::pal::open("/etc/services".as_ptr() as *const ::c_char, O_RDONLY, 0)
     } else {
         platform::pal::lseek(SERVDB, 0, SEEK_SET);
         S_POS = 0;
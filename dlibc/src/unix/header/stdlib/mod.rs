@@ -91,7 +91,15 @@ pub unsafe extern "C" fn a64l(s: *const ::c_char) -> ::c_long {
 
 #[no_mangle]
 pub unsafe extern "C" fn abort() -> ! {
-    eprintln!("Abort");
+    // Deliver a real SIGABRT so that handlers, debuggers and the parent's
+    // wait status all observe an abort. If a handler returns, or the signal
+    // is ignored, restore the default action and raise it again.
+    ::raise(::SIGABRT);
+    ::signal(::SIGABRT, ::SIG_DFL);
+    ::raise(::SIGABRT);
+
+    // SIGABRT could not terminate the process (e.g. it is blocked), but
+    // abort() must never return.
     intrinsics::abort();
 }
 
@@ -839,7 +847,7 @@ pub unsafe extern "C" fn realpath(pathname: *const ::c_char, resolved: *mut ::c_
         };
 
         let len = out.len();
-        let read = platform::pal::fpath(*file, out[..len - 1].as_mut_ptr() as *const i8);
+        let read = platform::pal::fpath(*file, out[..len - 1].as_mut_ptr() as *const ::c_char);
         if read < 0 {
             return ptr::null_mut();
         }
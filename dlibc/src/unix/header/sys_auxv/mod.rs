@@ -1,8 +1,17 @@
 //! sys/auxv.h implementation
 
-
+use crate::unix::platform;
 
 #[no_mangle]
-pub extern "C" fn getauxval(_t: ::c_ulong) -> ::c_ulong {
-    0
+pub unsafe extern "C" fn getauxval(t: ::c_ulong) -> ::c_ulong {
+    match platform::AUXVS
+        .as_deref()
+        .and_then(|auxvs| platform::get_auxv(auxvs, t as usize))
+    {
+        Some(value) => value as ::c_ulong,
+        None => {
+            platform::errno = ::ENOENT;
+            0
+        }
+    }
 }
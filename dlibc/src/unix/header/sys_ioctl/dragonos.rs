@@ -1,8 +1,13 @@
-use crate::unix::platform;
+use crate::unix::platform::pal::e;
+use dsc::syscall;
+
 #[no_mangle]
 pub unsafe extern "C" fn ioctl(fd: ::c_int, request: ::c_ulong, out: *mut ::c_void) -> ::c_int {
-    // TODO: Somehow support varargs to syscall??
-    platform::ioctl(fd, request as i32, out)
+    // This is the `ioctl` symbol that `platform::ioctl` links against, so it
+    // has to make the system call itself. Callers declare the request as an
+    // `int`, which leaves the upper half of the register undefined, and the
+    // kernel only looks at the low 32 bits anyway.
+    e(syscall!(SYS_IOCTL, fd, request as ::c_uint, out)) as ::c_int
 }
 
 pub const TCGETS: ::c_ulong = 0x5401;
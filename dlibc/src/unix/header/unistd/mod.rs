@@ -654,7 +654,7 @@ pub extern "C" fn tcsetpgrp(fd: ::c_int, pgrp: ::pid_t) -> ::c_int {
     if unsafe { ioctl(fd, TIOCSPGRP, &pgrp as *const ::pid_t as *mut ::c_void) } < 0 {
         return -1;
     }
-    pgrp
+    0
 }
 
 #[no_mangle]
@@ -689,7 +689,7 @@ pub extern "C" fn ttyname_r(fildes: ::c_int, name: *mut ::c_char, namesize: ::si
     }
 
     let len = platform::pal::fpath(fildes, 
-        (&mut name[..namesize - 1]).as_ptr() as *const i8
+        (&mut name[..namesize - 1]).as_ptr() as *const ::c_char
     );
     if len < 0 {
         return unsafe { -platform::errno };
@@ -739,10 +739,12 @@ pub extern "C" fn usleep(useconds: ::useconds_t) -> ::c_int {
     platform::pal::nanosleep(&rqtp, rmtp)
 }
 
-// #[no_mangle]
-// pub extern "C" fn vfork() -> ::pid_t {
-//     unimplemented!();
-// }
+// vfork may share nothing with the parent, so fork is a valid implementation
+// of it and avoids running the caller's code twice on one stack.
+#[no_mangle]
+pub extern "C" fn vfork() -> ::pid_t {
+    fork()
+}
 
 // #[no_mangle]
 // pub extern "C" fn write(fildes: ::c_int, buf: *const ::c_void, nbyte: ::size_t) -> ::ssize_t {
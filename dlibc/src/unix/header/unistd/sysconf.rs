@@ -47,6 +47,7 @@ pub extern "C" fn sysconf(name: ::c_int) -> ::c_long {
         _SC_TTY_NAME_MAX => 32,
         _SC_SYMLOOP_MAX => -1,
         _SC_HOST_NAME_MAX => 64,
+        ::_SC_NPROCESSORS_CONF | ::_SC_NPROCESSORS_ONLN => nprocessors(),
         _ => {
             unsafe {
                 platform::errno = errno::EINVAL;
@@ -55,3 +56,15 @@ pub extern "C" fn sysconf(name: ::c_int) -> ::c_long {
         }
     }
 }
+
+// Counts the CPUs the calling thread may run on, like musl. DragonOS has no
+// separate notion of configured but offline CPUs, so this answers both
+// `_SC_NPROCESSORS_CONF` and `_SC_NPROCESSORS_ONLN`.
+fn nprocessors() -> ::c_long {
+    let mut set: ::cpu_set_t = unsafe { core::mem::zeroed() };
+    let size = core::mem::size_of::<::cpu_set_t>();
+    if unsafe { platform::pal::sched_getaffinity(0, size, &mut set) } != 0 {
+        return 1;
+    }
+    (::CPU_COUNT(&set) as ::c_long).max(1)
+}
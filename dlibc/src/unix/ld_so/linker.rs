@@ -33,6 +33,7 @@ use super::{
 use O_RDONLY;
 use O_CLOEXEC;
 
+use crate::eprintln;
 use crate::trace;
 use PROT_EXEC;
 use PROT_NONE;
@@ -56,6 +57,7 @@ impl Symbol {
 
 pub struct Linker {
     ld_library_path: Option<String>,
+    preload: Vec<String>,
     next_object_id: usize,
     next_tls_module_id: usize,
     tls_size: usize,
@@ -67,9 +69,10 @@ pub struct Linker {
 const root_id: usize = 1;
 
 impl Linker {
-    pub fn new(ld_library_path: Option<String>) -> Self {
+    pub fn new(ld_library_path: Option<String>, preload: Vec<String>) -> Self {
         Self {
             ld_library_path: ld_library_path,
+            preload: preload,
             next_object_id: root_id,
             next_tls_module_id: 0,
             tls_size: 0,
@@ -250,10 +253,29 @@ impl Linker {
             tcb_masters.push(master);
         }
 
-        let (runpath, dependencies) = {
+        let (runpath, mut dependencies) = {
             let parent = new_objects.last().unwrap();
             (parent.runpath.clone(), parent.dependencies.clone())
         };
+        if new_objects.last().unwrap().id == root_id {
+            // Load preloaded objects right after the program, ahead of its own
+            // dependencies, so that their symbols are found first.
+            let preload = self
+                .preload
+                .iter()
+                .filter(|name| {
+                    let found = Linker::search_object(name, &self.ld_library_path, &None).is_ok();
+                    if !found {
+                        eprintln!(
+                            "ld.so: object '{}' from LD_PRELOAD cannot be preloaded, ignored",
+                            name
+                        );
+                    }
+                    found
+                })
+                .cloned();
+            dependencies = preload.chain(dependencies).collect();
+        }
         for dep_name in dependencies.iter() {
             self.load_objects_recursive(
                 dep_name,
@@ -114,6 +114,30 @@ unsafe fn adjust_stack(sp: &'static mut Stack) {
     sp.argc -= 1;
 }
 
+/// Variables that are dropped from the environment in secure-execution mode.
+const UNSECURE_ENVS: &[&str] = &["LD_LIBRARY_PATH", "LD_PRELOAD"];
+
+/// Returns whether the program runs in secure-execution mode, i.e. with more
+/// privileges than the user who started it.
+fn is_secure(auxv: &[[usize; 2]]) -> bool {
+    if let Some(secure) = get_auxv(auxv, ::AT_SECURE) {
+        return secure != 0;
+    }
+    // Older kernels don't pass AT_SECURE; fall back to comparing the ids.
+    get_auxv(auxv, ::AT_UID) != get_auxv(auxv, ::AT_EUID)
+        || get_auxv(auxv, ::AT_GID) != get_auxv(auxv, ::AT_EGID)
+}
+
+/// Splits `LD_PRELOAD` into object names. As with glibc, entries may be
+/// separated by colons or spaces.
+fn parse_preload(value: &str) -> Vec<String> {
+    value
+        .split(|c| c == ':' || c == ' ')
+        .filter(|name| !name.is_empty())
+        .map(|name| name.to_string())
+        .collect()
+}
+
 fn resolve_path_name(
     name_or_path: &str,
     envs: &BTreeMap<String, String>,
@@ -151,7 +175,7 @@ pub extern "C" fn relibc_ld_so_start(sp: &'static mut Stack, ld_entry: usize) ->
     // First thing we initialize the mspace
     ALLOCATOR.set_book_keeper(new_mspace());
     // next we get the arguments, the environment, and the auxilary vector
-    let (argv, envs, auxv) = unsafe {
+    let (argv, mut envs, auxv) = unsafe {
         let argv_start = sp.argv() as *mut usize;
         let (argv, argv_end) = get_argv(argv_start);
         let (envs, envs_end) = get_env(argv_end.add(1));
@@ -159,6 +183,14 @@ pub extern "C" fn relibc_ld_so_start(sp: &'static mut Stack, ld_entry: usize) ->
         (argv, envs, auxv)
     };
 
+    // A setuid or setgid program runs on behalf of whoever started it, so it
+    // must not let them choose which libraries get loaded into it.
+    if is_secure(&auxv) {
+        for key in UNSECURE_ENVS {
+            envs.remove(*key);
+        }
+    }
+
     unsafe {
         platform::OUR_ENVIRON = envs
             .iter()
@@ -195,6 +227,10 @@ pub extern "C" fn relibc_ld_so_start(sp: &'static mut Stack, ld_entry: usize) ->
 
     // Some variables that will be overridden by environment and auxiliary vectors
     let ld_library_path = envs.get("LD_LIBRARY_PATH").map(|s| s.to_owned());
+    let ld_preload = envs
+        .get("LD_PRELOAD")
+        .map(|s| parse_preload(s))
+        .unwrap_or_default();
 
     let name_or_path = if is_manual {
         // ld.so is run directly by user and not via execve() or similar systemcall
@@ -235,7 +271,7 @@ pub extern "C" fn relibc_ld_so_start(sp: &'static mut Stack, ld_entry: usize) ->
         }
         base
     };
-    let mut linker = Linker::new(ld_library_path);
+    let mut linker = Linker::new(ld_library_path, ld_preload);
     let entry = match linker.load_program(&path, base_addr) {
         Ok(entry) => entry,
         Err(err) => {
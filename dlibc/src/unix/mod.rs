@@ -1614,6 +1614,7 @@ pub mod sync;
 pub mod c_str;
 pub mod c_vec;
 pub mod crt0;
+pub mod abi;
 
 
 
@@ -23,9 +23,35 @@ pub const ALLOCATOR: Allocator = Allocator{
     mstate: AtomicUsize::new(0),
 };
 
+// Called with the size of every allocation made through `Allocator`, to let
+// the standard library sample allocations. 0 when unset.
+#[cfg(feature = "heap-profiling")]
+static ALLOC_HOOK: AtomicUsize = AtomicUsize::new(0);
+
+/// Sets the function called with the size of every allocation made through
+/// the global allocator, or removes it.
+///
+/// The hook runs inside the allocator, so it must not allocate itself.
+#[cfg(feature = "heap-profiling")]
+pub fn set_alloc_hook(hook: Option<fn(usize)>) {
+    ALLOC_HOOK.store(hook.map_or(0, |hook| hook as usize), Ordering::Release);
+}
+
+#[cfg(feature = "heap-profiling")]
+#[inline]
+fn run_alloc_hook(size: usize) {
+    let hook = ALLOC_HOOK.load(Ordering::Acquire);
+    if hook != 0 {
+        let hook: fn(usize) = unsafe { core::mem::transmute(hook) };
+        hook(size);
+    }
+}
+
 unsafe impl GlobalAlloc for Allocator {
     unsafe fn alloc(&self, layout: core::alloc::Layout) -> *mut u8 {
         let size = align_up(layout.size(), layout.align());
+        #[cfg(feature = "heap-profiling")]
+        run_alloc_hook(size);
         return alloc(size) as *mut u8;
     }
 
@@ -39,6 +65,8 @@ unsafe impl GlobalAlloc for Allocator {
         new_size: usize,
     ) -> *mut u8 {
         let size = align_up(new_size, layout.align());
+        #[cfg(feature = "heap-profiling")]
+        run_alloc_hook(size);
         return realloc(ptr as *mut ::c_void, size) as *mut u8;
     }
 }
@@ -4559,6 +4559,7 @@ extern "C" {
         sigmask: *const ::sigset_t,
     ) -> ::c_int;
     pub fn dup3(oldfd: ::c_int, newfd: ::c_int, flags: ::c_int) -> ::c_int;
+    pub fn vfork() -> ::pid_t;
     pub fn mkostemp(template: *mut ::c_char, flags: ::c_int) -> ::c_int;
     pub fn mkostemps(template: *mut ::c_char, suffixlen: ::c_int, flags: ::c_int) -> ::c_int;
     pub fn sigtimedwait(
@@ -4765,6 +4766,11 @@ extern "C" {
         attr: *const ::pthread_attr_t,
         guardsize: *mut ::size_t,
     ) -> ::c_int;
+    pub fn pthread_attr_setstack(
+        attr: *mut ::pthread_attr_t,
+        stackaddr: *mut ::c_void,
+        stacksize: ::size_t,
+    ) -> ::c_int;
     pub fn sethostname(name: *const ::c_char, len: ::size_t) -> ::c_int;
     pub fn sched_get_priority_min(policy: ::c_int) -> ::c_int;
     pub fn pthread_condattr_getpshared(
@@ -5124,3 +5130,25 @@ cfg_if! {
         pub use self::non_exhaustive::*;
     }
 }
+
+// `c_char` follows each architecture's psABI: signed on x86 and mips, unsigned
+// on aarch64, arm, riscv, powerpc and s390x. It has to be the very type
+// `core::ffi::c_char` is, so C strings pass between this crate and std
+// without casts, and code hardcoding `i8` fails to build instead of silently
+// changing meaning on the unsigned targets.
+const _: () = {
+    #[allow(dead_code)]
+    fn same_type(c: ::c_char) -> ::core::ffi::c_char {
+        c
+    }
+    let signed = (0 as ::c_char).wrapping_sub(1) < 0;
+    assert!(
+        signed
+            == cfg!(any(
+                target_arch = "x86",
+                target_arch = "x86_64",
+                target_arch = "mips",
+                target_arch = "mips64"
+            ))
+    );
+};
@@ -15,6 +15,11 @@ pub extern "C" fn write(fd: ::c_int, buf: *const ::c_void, count: ::size_t) -> :
 	e(unsafe { syscall!(SYS_WRITE, fd, buf, count) }) as ::ssize_t
 }
 
+#[no_mangle]
+pub extern "C" fn writev(fd: ::c_int, iov: *const ::iovec, iovcnt: ::c_int) -> ::ssize_t{
+	e(unsafe { syscall!(SYS_WRITEV, fd, iov, iovcnt) }) as ::ssize_t
+}
+
 #[no_mangle]
 pub extern "C" fn access(path: *const ::c_char, amode: ::c_int) -> ::c_int{
 	unimplemented!()
@@ -39,9 +44,26 @@ pub extern "C" fn chown(path: *const ::c_char, uid: uid_t, gid: gid_t) -> ::c_in
 	0
 }
 
+#[no_mangle]
+pub extern "C" fn clock_getres(clk_id: ::clockid_t, tp: *mut ::timespec) -> ::c_int{
+	e(unsafe { syscall!(SYS_CLOCK_GETRES, clk_id, tp) }) as ::c_int
+}
+
 #[no_mangle]
 pub extern "C" fn clock_gettime(clk_id: ::clockid_t, tp: *mut ::timespec) -> ::c_int{
-	-ENOSYS
+	e(unsafe { syscall!(SYS_CLOCK_GETTIME, clk_id, tp) }) as ::c_int
+}
+
+#[no_mangle]
+pub extern "C" fn clock_nanosleep(
+	clk_id: ::clockid_t,
+	flags: ::c_int,
+	rqtp: *const ::timespec,
+	rmtp: *mut ::timespec,
+) -> ::c_int{
+	// POSIX has clock_nanosleep return the error number, and leave errno alone.
+	let ret = unsafe { syscall!(SYS_CLOCK_NANOSLEEP, clk_id, flags, rqtp, rmtp) } as isize;
+	if ret < 0 { -ret as ::c_int } else { 0 }
 }
 
 #[no_mangle]
@@ -49,6 +71,11 @@ pub extern "C" fn close(fd: ::c_int) -> ::c_int{
 	e(unsafe { syscall!(SYS_CLOSE, fd) }) as ::c_int
 }
 
+#[no_mangle]
+pub extern "C" fn copy_file_range(fd_in: ::c_int, off_in: *mut ::off64_t, fd_out: ::c_int, off_out: *mut ::off64_t, len: ::size_t, flags: ::c_uint) -> ::ssize_t{
+	e(unsafe { syscall!(SYS_COPY_FILE_RANGE, fd_in, off_in, fd_out, off_out, len, flags) }) as ::ssize_t
+}
+
 #[no_mangle]
 pub extern "C" fn dup(fd: ::c_int) -> ::c_int{
 	e(unsafe { syscall!(SYS_DUP, fd) }) as ::c_int
@@ -132,8 +159,7 @@ pub extern "C" fn ftruncate(fd: ::c_int, length: off_t) -> ::c_int{
 }
 
 pub extern "C" fn futex(addr: *mut ::c_int, op: ::c_int, val: ::c_int, val2: usize) -> ::c_int {
-	// unsafe { syscall!(FUTEX, addr, op, val, val2, 0, 0) as ::c_int }
-	unimplemented!()
+	e(unsafe { syscall!(SYS_FUTEX, addr, op, val, val2, 0, 0) }) as ::c_int
 }
 
 #[no_mangle]
@@ -224,7 +250,7 @@ pub extern "C" fn getsid(pid: ::pid_t) -> ::pid_t{
 
 #[no_mangle]
 pub extern "C" fn gettid() -> ::pid_t{
-	unimplemented!()
+	e(unsafe { syscall!(SYS_GETTID) }) as ::pid_t
 }
 
 //#[no_mangle]
@@ -289,6 +315,38 @@ pub extern "C" fn mprotect(addr: *mut ::c_void, len: ::size_t, prot: ::c_int) ->
 	e(unsafe{syscall!(SYS_MPROTECT, addr, len, prot)}) as ::c_int
 }
 
+// Like musl, always pass six arguments; system calls that take fewer ignore
+// the rest.
+#[no_mangle]
+pub unsafe extern "C" fn syscall(num: ::c_long, mut args: ...) -> ::c_long{
+	let a1 = args.arg::<::c_long>();
+	let a2 = args.arg::<::c_long>();
+	let a3 = args.arg::<::c_long>();
+	let a4 = args.arg::<::c_long>();
+	let a5 = args.arg::<::c_long>();
+	let a6 = args.arg::<::c_long>();
+	e(dsc::syscall6(
+		num as usize,
+		a1 as usize,
+		a2 as usize,
+		a3 as usize,
+		a4 as usize,
+		a5 as usize,
+		a6 as usize,
+	)) as ::c_long
+}
+
+// Like musl, always pass four further arguments; options that take fewer
+// ignore the rest.
+#[no_mangle]
+pub unsafe extern "C" fn prctl(option: ::c_int, mut args: ...) -> ::c_int{
+	let arg2 = args.arg::<::c_ulong>();
+	let arg3 = args.arg::<::c_ulong>();
+	let arg4 = args.arg::<::c_ulong>();
+	let arg5 = args.arg::<::c_ulong>();
+	e(syscall!(SYS_PRCTL, option, arg2, arg3, arg4, arg5)) as ::c_int
+}
+
 #[no_mangle]
 pub extern "C" fn msync(addr: *mut ::c_void, len: ::size_t, flags: ::c_int) -> ::c_int{
 	unimplemented!()
@@ -338,6 +396,11 @@ pub extern "C" fn read(fd: ::c_int, buf: *mut ::c_void, count: ::size_t) -> ::ss
 	e(unsafe { syscall!(SYS_READ, fd, buf, count) }) as ::ssize_t
 }
 
+#[no_mangle]
+pub extern "C" fn readv(fd: ::c_int, iov: *const ::iovec, iovcnt: ::c_int) -> ::ssize_t{
+	e(unsafe { syscall!(SYS_READV, fd, iov, iovcnt) }) as ::ssize_t
+}
+
 pub extern "C" fn readlink(path: *const c_char, buf: *mut c_char, bufsz: ::size_t) -> ::ssize_t{
     unimplemented!()
 }
@@ -357,6 +420,39 @@ pub extern "C" fn sched_yield() -> ::c_int{
 	unimplemented!()
 }
 
+#[no_mangle]
+pub unsafe extern "C" fn sched_getaffinity(pid: ::pid_t, cpusetsize: ::size_t, cpuset: *mut cpu_set_t) -> ::c_int{
+	// The kernel returns how many bytes of the mask it wrote; clear the rest
+	// so callers can count bits over the whole set.
+	let written = e(syscall!(SYS_SCHED_GETAFFINITY, pid, cpusetsize, cpuset));
+	if written == !0 {
+		return -1;
+	}
+	let bytes = cpuset as *mut u8;
+	core::ptr::write_bytes(bytes.add(written), 0, cpusetsize - written);
+	0
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn sched_setaffinity(pid: ::pid_t, cpusetsize: ::size_t, cpuset: *const cpu_set_t) -> ::c_int{
+	e(syscall!(SYS_SCHED_SETAFFINITY, pid, cpusetsize, cpuset)) as ::c_int
+}
+
+#[no_mangle]
+pub extern "C" fn sched_getscheduler(pid: ::pid_t) -> ::c_int{
+	e(unsafe { syscall!(SYS_SCHED_GETSCHEDULER, pid) }) as ::c_int
+}
+
+#[no_mangle]
+pub extern "C" fn sched_setscheduler(pid: ::pid_t, policy: ::c_int, param: *const ::sched_param) -> ::c_int{
+	e(unsafe { syscall!(SYS_SCHED_SETSCHEDULER, pid, policy, param) }) as ::c_int
+}
+
+#[no_mangle]
+pub extern "C" fn sendfile(out_fd: ::c_int, in_fd: ::c_int, offset: *mut off_t, count: ::size_t) -> ::ssize_t{
+	e(unsafe { syscall!(SYS_SENDFILE, out_fd, in_fd, offset, count) }) as ::ssize_t
+}
+
 #[no_mangle]
 pub extern "C" fn setpgid(pid: ::pid_t, pgid: ::pid_t) -> ::c_int{
 	unimplemented!()
@@ -372,14 +468,40 @@ pub extern "C" fn setreuid(ruid: uid_t, euid: uid_t) -> ::c_int{
 	unimplemented!()
 }
 
+#[no_mangle]
+pub extern "C" fn splice(fd_in: ::c_int, off_in: *mut ::loff_t, fd_out: ::c_int, off_out: *mut ::loff_t, len: ::size_t, flags: ::c_uint) -> ::ssize_t{
+	e(unsafe { syscall!(SYS_SPLICE, fd_in, off_in, fd_out, off_out, len, flags) }) as ::ssize_t
+}
+
 #[no_mangle]
 pub extern "C" fn symlink(path1: *const ::c_char, path2: *const ::c_char) -> ::c_int{
 	unimplemented!()
 }
 
+#[no_mangle]
+pub extern "C" fn timerfd_create(clockid: ::clockid_t, flags: ::c_int) -> ::c_int{
+	e(unsafe { syscall!(SYS_TIMERFD_CREATE, clockid, flags) }) as ::c_int
+}
+
+#[no_mangle]
+pub extern "C" fn timerfd_gettime(fd: ::c_int, curr_value: *mut ::itimerspec) -> ::c_int{
+	e(unsafe { syscall!(SYS_TIMERFD_GETTIME, fd, curr_value) }) as ::c_int
+}
+
+#[no_mangle]
+pub extern "C" fn timerfd_settime(
+	fd: ::c_int,
+	flags: ::c_int,
+	new_value: *const ::itimerspec,
+	old_value: *mut ::itimerspec,
+) -> ::c_int{
+	e(unsafe { syscall!(SYS_TIMERFD_SETTIME, fd, flags, new_value, old_value) }) as ::c_int
+}
+
 #[no_mangle]
 pub extern "C" fn umask(mask: mode_t) -> mode_t{
-	unimplemented!()
+	// Always succeeds and returns the previous mask.
+	e(unsafe { syscall!(SYS_UMASK, mask) }) as mode_t
 }
 
 #[no_mangle]
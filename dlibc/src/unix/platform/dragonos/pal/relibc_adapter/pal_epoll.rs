@@ -1,15 +1,17 @@
 use crate::unix::platform::pal::{e,errno};
 use crate::unix::*;
 use dsc::syscall;
+use mem;
+
 #[no_mangle]
 pub extern "C" fn epoll_create1(flags: ::c_int) -> ::c_int{
-	unimplemented!()
+	e(unsafe { syscall!(SYS_EPOLL_CREATE1, flags) }) as ::c_int
 }
 
 #[no_mangle]
 pub extern "C" fn epoll_ctl(epfd: ::c_int, op: ::c_int, fd: ::c_int, event: *mut ::epoll_event)
 	-> ::c_int{
-	unimplemented!()
+	e(unsafe { syscall!(SYS_EPOLL_CTL, epfd, op, fd, event) }) as ::c_int
 }
 
 #[no_mangle]
@@ -20,5 +22,25 @@ pub extern "C" fn epoll_pwait(
 	timeout: ::c_int,
 	sigmask: *const ::sigset_t,
 ) -> ::c_int{
-	unimplemented!()
-}
\ No newline at end of file
+	e(unsafe {
+		syscall!(
+			SYS_EPOLL_PWAIT,
+			epfd,
+			events,
+			maxevents,
+			timeout,
+			sigmask,
+			mem::size_of::<sigset_t>()
+		)
+	}) as ::c_int
+}
+
+#[no_mangle]
+pub extern "C" fn epoll_wait(
+	epfd: ::c_int,
+	events: *mut ::epoll_event,
+	maxevents: ::c_int,
+	timeout: ::c_int,
+) -> ::c_int{
+	epoll_pwait(epfd, events, maxevents, timeout, core::ptr::null())
+}
@@ -39,12 +39,27 @@ pub extern "C" fn setitimer(which: ::c_int, new: *const ::itimerval, old: *mut :
 }
 
 use crate::unix::header::signal;
+
+extern "C" {
+	// Defined in assembly in header/signal/dragonos.rs
+	fn __restore_rt();
+}
+
 #[no_mangle]
 pub extern "C" fn sigaction(
 	signum: ::c_int,
 	act: *const sigaction,
 	oldact: *mut sigaction)
 	-> ::c_int {
+	// The kernel returns from a handler through `sa_restorer`, so every
+	// handler needs one, not just those installed with `signal`.
+	let act = unsafe { act.as_ref() }.map(|act| {
+		let mut act = *act;
+		act.sa_flags |= signal::SA_RESTORER as ::c_int;
+		act.sa_restorer = Some(__restore_rt);
+		act
+	});
+	let act = act.as_ref().map_or(core::ptr::null(), |act| act as *const sigaction);
 	e(unsafe {
 		syscall!(
 			SYS_SIGACTION,
@@ -58,12 +73,20 @@ pub extern "C" fn sigaction(
 
 #[no_mangle]
 pub extern "C" fn sigaltstack(ss: *const stack_t, old_ss: *mut stack_t) -> ::c_int {
-	// e(unsafe { syscall!(SIGALTSTACK, ss, old_ss) }) as ::c_int
-	unimplemented!()
+	if let Some(ss) = unsafe { ss.as_ref() } {
+		if ss.ss_flags & !(signal::SS_DISABLE as ::c_int) != 0 {
+			unsafe { errno = EINVAL };
+			return -1;
+		}
+		if ss.ss_flags & signal::SS_DISABLE as ::c_int == 0 && ss.ss_size < signal::MINSIGSTKSZ {
+			unsafe { errno = ENOMEM };
+			return -1;
+		}
+	}
+	e(unsafe { syscall!(SYS_SIGALTSTACK, ss, old_ss) }) as ::c_int
 }
 
 #[no_mangle]
 pub extern "C" fn sigprocmask(how: ::c_int, set: *const sigset_t, oset: *mut sigset_t) -> ::c_int {
-	// e(unsafe { syscall!(RT_SIGPROCMASK, how, set, oset, mem::size_of::<sigset_t>()) }) as ::c_int
-	unimplemented!()
+	e(unsafe { syscall!(SYS_SIGPROCMASK, how, set, oset, mem::size_of::<sigset_t>()) }) as ::c_int
 }
\ No newline at end of file
@@ -1950,6 +1950,8 @@ use alloc::{boxed::Box, vec::Vec};
 use core::{fmt, ptr};
 
 pub use self::allocator::{alloc,free,realloc,alloc_align};
+#[cfg(feature = "heap-profiling")]
+pub use self::allocator::set_alloc_hook;
 
 //#[cfg(all(not(feature = "ralloc"), target_os = "dragonos"))]
 #[path = "dragonos/allocator/dragonos_malloc.rs"]
@@ -2192,8 +2194,9 @@ impl<T: Write> Write for CountingWriter<T> {
     }
 }
 
-// TODO: Set a global variable once get_auxvs is called, and then implement getauxval based on
-// get_auxv.
+/// The auxiliary vector the program was started with, saved by `init` so that
+/// `getauxval` can look it up later.
+pub static mut AUXVS: Option<Box<[[usize; 2]]>> = None;
 
 #[cold]
 pub unsafe fn get_auxvs(mut ptr: *const usize) -> Box<[[usize; 2]]> {
@@ -2235,5 +2238,7 @@ pub fn init(auxvs: Box<[[usize; 2]]>) {
     }
 }
 #[cfg(not(target_os = "redox"))]
-pub fn init(auxvs: Box<[[usize; 2]]>) {}
+pub fn init(auxvs: Box<[[usize; 2]]>) {
+    unsafe { AUXVS = Some(auxvs) };
+}
 
@@ -187,7 +187,7 @@ pub unsafe extern "C" fn relibc_start(sp: &'static Stack) -> ! {
         static __init_array_end: extern "C" fn();
 
         fn _init();
-        fn main();
+        fn main(argc: isize, argv: *mut *mut ::c_char, envp: *mut *mut ::c_char) -> ::c_int;
     }
 
     // Ensure correct host system before executing more system calls
@@ -212,7 +212,7 @@ pub unsafe extern "C" fn relibc_start(sp: &'static Stack) -> ! {
         // Set linker pointer if necessary
         if tcb.linker_ptr.is_null() {
             //TODO: get ld path
-            let linker = Linker::new(None);
+            let linker = Linker::new(None, Vec::new());
             //TODO: load root object
             tcb.linker_ptr = Box::into_raw(Box::new(Mutex::new(linker)));
         }
@@ -283,9 +283,8 @@ pub unsafe extern "C" fn relibc_start(sp: &'static Stack) -> ! {
         }
     }
     // println!("to run main()");
-    main();
     // not argv or envp, because programs like bash try to modify this *const* pointer :|
-    platform::pal::exit(0);
+    stdlib::exit(main(argc, platform::argv, platform::environ));
 
     unreachable!();
 }
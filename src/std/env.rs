@@ -133,6 +133,8 @@ pub fn vars() -> Vars {
 /// The returned iterator contains a snapshot of the process's environment
 /// variables at the time of this invocation. Modifications to environment
 /// variables afterwards will not be reflected in the returned iterator.
+/// The snapshot is taken while holding the same lock as [`set_var`] and
+/// [`remove_var`], so it never contains half of a concurrent modification.
 ///
 /// Note that the returned iterator will not check if the environment variables
 /// are valid Unicode. If you want to panic on invalid UTF-8,
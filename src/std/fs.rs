@@ -238,7 +238,10 @@ pub fn read<P: AsRef<Path>>(path: P) -> io::Result<Vec<u8>> {
     fn inner(path: &Path) -> io::Result<Vec<u8>> {
         let mut file = File::open(path)?;
         let size = file.metadata().map(|m| m.len() as usize).ok();
-        let mut bytes = Vec::with_capacity(size.unwrap_or(0));
+        let mut bytes = Vec::new();
+        bytes
+            .try_reserve_exact(size.unwrap_or(0))
+            .map_err(|_| io::ErrorKind::OutOfMemory)?;
         io::default_read_to_end(&mut file, &mut bytes, size)?;
         Ok(bytes)
     }
@@ -279,7 +282,10 @@ pub fn read_to_string<P: AsRef<Path>>(path: P) -> io::Result<String> {
     fn inner(path: &Path) -> io::Result<String> {
         let mut file = File::open(path)?;
         let size = file.metadata().map(|m| m.len() as usize).ok();
-        let mut string = String::with_capacity(size.unwrap_or(0));
+        let mut string = String::new();
+        string
+            .try_reserve_exact(size.unwrap_or(0))
+            .map_err(|_| io::ErrorKind::OutOfMemory)?;
         io::default_read_to_string(&mut file, &mut string, size)?;
         Ok(string)
     }
@@ -726,6 +732,20 @@ fn buffer_capacity_required(mut file: &File) -> Option<usize> {
     Some(size.saturating_sub(pos) as usize)
 }
 
+/// Adds `path` to an error about it with [`io::Error::with_path`] on DragonOS.
+/// Elsewhere the error is returned as the platform reported it, so that its
+/// `Display` and `Debug` output stay the same and no allocation is made.
+#[cfg(target_os = "dragonos")]
+fn path_error(error: io::Error, path: &Path) -> io::Error {
+    error.with_path(path)
+}
+
+#[cfg(not(target_os = "dragonos"))]
+#[inline]
+fn path_error(error: io::Error, _path: &Path) -> io::Error {
+    error
+}
+
 impl Read for &File {
     #[inline]
     fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
@@ -748,16 +768,20 @@ impl Read for &File {
     }
 
     // Reserves space in the buffer based on the file size when available.
+    // The size is only a hint: the file may grow or shrink before it has
+    // been read, which `default_read_to_end` copes with either way.
     fn read_to_end(&mut self, buf: &mut Vec<u8>) -> io::Result<usize> {
         let size = buffer_capacity_required(self);
-        buf.reserve(size.unwrap_or(0));
+        buf.try_reserve(size.unwrap_or(0))
+            .map_err(|_| io::ErrorKind::OutOfMemory)?;
         io::default_read_to_end(self, buf, size)
     }
 
     // Reserves space in the buffer based on the file size when available.
     fn read_to_string(&mut self, buf: &mut String) -> io::Result<usize> {
         let size = buffer_capacity_required(self);
-        buf.reserve(size.unwrap_or(0));
+        buf.try_reserve(size.unwrap_or(0))
+            .map_err(|_| io::ErrorKind::OutOfMemory)?;
         io::default_read_to_string(self, buf, size)
     }
 }
@@ -1089,7 +1113,9 @@ impl OpenOptions {
     }
 
     fn _open(&self, path: &Path) -> io::Result<File> {
-        fs_imp::File::open(path, &self.0).map(|inner| File { inner })
+        fs_imp::File::open(path, &self.0)
+            .map(|inner| File { inner })
+            .map_err(|e| path_error(e, path))
     }
 }
 
@@ -1813,7 +1839,8 @@ impl AsInner<fs_imp::DirEntry> for DirEntry {
 /// }
 /// ```
 pub fn remove_file<P: AsRef<Path>>(path: P) -> io::Result<()> {
-    fs_imp::unlink(path.as_ref())
+    let path = path.as_ref();
+    fs_imp::unlink(path).map_err(|e| path_error(e, path))
 }
 
 /// Given a path, query the file system to get information about a file,
@@ -1850,7 +1877,10 @@ pub fn remove_file<P: AsRef<Path>>(path: P) -> io::Result<()> {
 /// }
 /// ```
 pub fn metadata<P: AsRef<Path>>(path: P) -> io::Result<Metadata> {
-    fs_imp::stat(path.as_ref()).map(Metadata)
+    let path = path.as_ref();
+    fs_imp::stat(path)
+        .map(Metadata)
+        .map_err(|e| path_error(e, path))
 }
 
 /// Query the metadata about a file without following symlinks.
@@ -1883,7 +1913,10 @@ pub fn metadata<P: AsRef<Path>>(path: P) -> io::Result<Metadata> {
 /// }
 /// ```
 pub fn symlink_metadata<P: AsRef<Path>>(path: P) -> io::Result<Metadata> {
-    fs_imp::lstat(path.as_ref()).map(Metadata)
+    let path = path.as_ref();
+    fs_imp::lstat(path)
+        .map(Metadata)
+        .map_err(|e| path_error(e, path))
 }
 
 /// Rename a file or directory to a new name, replacing the original file if
@@ -2089,7 +2122,8 @@ pub fn soft_link<P: AsRef<Path>, Q: AsRef<Path>>(original: P, link: Q) -> io::Re
 /// }
 /// ```
 pub fn read_link<P: AsRef<Path>>(path: P) -> io::Result<PathBuf> {
-    fs_imp::readlink(path.as_ref())
+    let path = path.as_ref();
+    fs_imp::readlink(path).map_err(|e| path_error(e, path))
 }
 
 /// Returns the canonical, absolute form of a path with all intermediate
@@ -2131,7 +2165,8 @@ pub fn read_link<P: AsRef<Path>>(path: P) -> io::Result<PathBuf> {
 #[doc(alias = "realpath")]
 #[doc(alias = "GetFinalPathNameByHandle")]
 pub fn canonicalize<P: AsRef<Path>>(path: P) -> io::Result<PathBuf> {
-    fs_imp::canonicalize(path.as_ref())
+    let path = path.as_ref();
+    fs_imp::canonicalize(path).map_err(|e| path_error(e, path))
 }
 
 /// Creates a new, empty directory at the provided path
@@ -2249,7 +2284,8 @@ pub fn create_dir_all<P: AsRef<Path>>(path: P) -> io::Result<()> {
 /// ```
 #[doc(alias = "rmdir")]
 pub fn remove_dir<P: AsRef<Path>>(path: P) -> io::Result<()> {
-    fs_imp::rmdir(path.as_ref())
+    let path = path.as_ref();
+    fs_imp::rmdir(path).map_err(|e| path_error(e, path))
 }
 
 /// Removes a directory at this path, after removing all its contents. Use
@@ -2295,7 +2331,8 @@ pub fn remove_dir<P: AsRef<Path>>(path: P) -> io::Result<()> {
 /// }
 /// ```
 pub fn remove_dir_all<P: AsRef<Path>>(path: P) -> io::Result<()> {
-    fs_imp::remove_dir_all(path.as_ref())
+    let path = path.as_ref();
+    fs_imp::remove_dir_all(path).map_err(|e| path_error(e, path))
 }
 
 /// Returns an iterator over the entries within a directory.
@@ -2369,7 +2406,10 @@ pub fn remove_dir_all<P: AsRef<Path>>(path: P) -> io::Result<()> {
 /// }
 /// ```
 pub fn read_dir<P: AsRef<Path>>(path: P) -> io::Result<ReadDir> {
-    fs_imp::readdir(path.as_ref()).map(ReadDir)
+    let path = path.as_ref();
+    fs_imp::readdir(path)
+        .map(ReadDir)
+        .map_err(|e| path_error(e, path))
 }
 
 /// Changes the permissions found on a file or a directory.
@@ -2403,7 +2443,8 @@ pub fn read_dir<P: AsRef<Path>>(path: P) -> io::Result<ReadDir> {
 /// }
 /// ```
 pub fn set_permissions<P: AsRef<Path>>(path: P, perm: Permissions) -> io::Result<()> {
-    fs_imp::set_perm(path.as_ref(), perm.0)
+    let path = path.as_ref();
+    fs_imp::set_perm(path, perm.0).map_err(|e| path_error(e, path))
 }
 
 impl DirBuilder {
@@ -2470,7 +2511,7 @@ impl DirBuilder {
         if self.recursive {
             self.create_dir_all(path)
         } else {
-            self.inner.mkdir(path)
+            self.inner.mkdir(path).map_err(|e| path_error(e, path))
         }
     }
 
@@ -2483,7 +2524,7 @@ impl DirBuilder {
             Ok(()) => return Ok(()),
             Err(ref e) if e.kind() == io::ErrorKind::NotFound => {}
             Err(_) if path.is_dir() => return Ok(()),
-            Err(e) => return Err(e),
+            Err(e) => return Err(path_error(e, path)),
         }
         match path.parent() {
             Some(p) => self.create_dir_all(p)?,
@@ -2497,7 +2538,7 @@ impl DirBuilder {
         match self.inner.mkdir(path) {
             Ok(()) => Ok(()),
             Err(_) if path.is_dir() => Ok(()),
-            Err(e) => Err(e),
+            Err(e) => Err(path_error(e, path)),
         }
     }
 }
@@ -2538,5 +2579,6 @@ impl AsInnerMut<fs_imp::DirBuilder> for DirBuilder {
 // instead.
 #[inline]
 pub fn try_exists<P: AsRef<Path>>(path: P) -> io::Result<bool> {
-    fs_imp::try_exists(path.as_ref())
+    let path = path.as_ref();
+    fs_imp::try_exists(path).map_err(|e| path_error(e, path))
 }
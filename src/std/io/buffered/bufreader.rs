@@ -230,6 +230,60 @@ impl<R: ?Sized> BufReader<R> {
     }
 }
 
+impl<R: ?Sized + Read> BufReader<R> {
+    /// Returns the next `n` bytes without consuming them, reading from the
+    /// underlying reader until enough are buffered.
+    ///
+    /// Fewer than `n` bytes are returned only at end of file. This lets a
+    /// parser look at a header before deciding how to read it, without
+    /// having to put anything back. Unlike [`fill_buf`], which returns what
+    /// is buffered and only reads when nothing is, this keeps reading until
+    /// `n` bytes are there.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `n` is larger than the [capacity] of the buffer.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use std::io::{BufReader, Read};
+    /// use std::fs::File;
+    ///
+    /// fn main() -> std::io::Result<()> {
+    ///     let mut reader = BufReader::new(File::open("image")?);
+    ///     if reader.peek(4)? == b"\x7fELF" {
+    ///         println!("an executable");
+    ///     }
+    ///     // The magic number is still there to be read.
+    ///     let mut magic = [0; 4];
+    ///     reader.read_exact(&mut magic)?;
+    ///     Ok(())
+    /// }
+    /// ```
+    ///
+    /// [`fill_buf`]: BufRead::fill_buf
+    /// [capacity]: BufReader::capacity
+    pub fn peek(&mut self, n: usize) -> io::Result<&[u8]> {
+        assert!(
+            n <= self.capacity(),
+            "cannot peek at more bytes than the buffer holds"
+        );
+        while self.buf.buffer().len() < n {
+            if self.buf.pos() + n > self.capacity() {
+                self.buf.backshift();
+            }
+            match self.buf.read_more(&mut self.inner) {
+                Ok(0) => return Ok(self.buf.buffer()),
+                Ok(_) => {}
+                Err(ref e) if e.is_interrupted() => {}
+                Err(e) => return Err(e),
+            }
+        }
+        Ok(&self.buf.buffer()[..n])
+    }
+}
+
 impl<R: ?Sized + Seek> BufReader<R> {
     /// Seeks relative to the current position. If the new position lies within the buffer,
     /// the buffer will not be flushed, allowing for more efficient seeks.
@@ -238,8 +292,8 @@ impl<R: ?Sized + Seek> BufReader<R> {
     pub fn seek_relative(&mut self, offset: i64) -> io::Result<()> {
         let pos = self.buf.pos() as u64;
         if offset < 0 {
-            if let Some(_) = pos.checked_sub((-offset) as u64) {
-                self.buf.unconsume((-offset) as usize);
+            if offset.unsigned_abs() <= pos {
+                self.buf.unconsume(offset.unsigned_abs() as usize);
                 return Ok(());
             }
         } else if let Some(new_pos) = pos.checked_add(offset as u64) {
@@ -377,6 +431,40 @@ impl<R: ?Sized + Read> BufRead for BufReader<R> {
     fn consume(&mut self, amt: usize) {
         self.buf.consume(amt)
     }
+
+    // A non-blocking reader can fail with `WouldBlock` in the middle of a
+    // character. Its first bytes are put back into the buffer rather than
+    // dropped with the rest of the invalid UTF-8, so that calling again with
+    // the same string carries on where the line stopped. Only DragonOS lets
+    // stdin be switched to non-blocking, see `os::dragonos::io`.
+    #[cfg(target_os = "dragonos")]
+    fn read_line(&mut self, buf: &mut String) -> io::Result<usize> {
+        let start = buf.len();
+        let mut partial = [0; 3];
+        let mut partial_len = 0;
+        let ret = unsafe {
+            crate::std::io::append_to_string(buf, |bytes| {
+                let ret = crate::std::io::read_until(self, b'\n', bytes);
+                if ret.is_err() {
+                    if let Err(e) = crate::std::str::from_utf8(&bytes[start..]) {
+                        // Only an incomplete last character, at most 3 bytes.
+                        if e.error_len().is_none() {
+                            let valid = start + e.valid_up_to();
+                            partial_len = bytes.len() - valid;
+                            partial[..partial_len].copy_from_slice(&bytes[valid..]);
+                            bytes.truncate(valid);
+                        }
+                    }
+                }
+                ret
+            })
+        };
+        if partial_len > 0 {
+            // The read failed, so the buffer is empty.
+            self.buf.backfill(&partial[..partial_len]);
+        }
+        ret
+    }
 }
 
 impl<R> fmt::Debug for BufReader<R>
@@ -101,6 +101,42 @@ impl Buffer {
         self.pos = self.pos.saturating_sub(amt);
     }
 
+    /// Moves the unread data to the start of the buffer, to make room after
+    /// it.
+    pub fn backshift(&mut self) {
+        self.buf.copy_within(self.pos..self.filled, 0);
+        self.filled -= self.pos;
+        self.pos = 0;
+    }
+
+    /// Reads more data into the room after the unread data, without
+    /// discarding any of it. Returns how many bytes were read.
+    pub fn read_more(&mut self, mut reader: impl Read) -> io::Result<usize> {
+        let mut buf = BorrowedBuf::from(&mut self.buf[self.filled..]);
+        let old_init = self.initialized - self.filled;
+        // SAFETY: `self.initialized` bytes will always have been initialized.
+        unsafe {
+            buf.set_init(old_init);
+        }
+        reader.read_buf(buf.unfilled())?;
+        self.filled += buf.len();
+        self.initialized += buf.init_len() - old_init;
+        Ok(buf.len())
+    }
+
+    /// Makes `bytes` the unread data of an empty buffer. Does nothing if the
+    /// buffer isn't empty or has no room for them.
+    #[cfg(target_os = "dragonos")]
+    pub fn backfill(&mut self, bytes: &[u8]) {
+        if self.pos < self.filled || bytes.len() > self.buf.len() {
+            return;
+        }
+        MaybeUninit::write_slice(&mut self.buf[..bytes.len()], bytes);
+        self.pos = 0;
+        self.filled = bytes.len();
+        self.initialized = cmp::max(self.initialized, bytes.len());
+    }
+
     #[inline]
     pub fn fill_buf(&mut self, mut reader: impl Read) -> io::Result<&[u8]> {
         // If we've reached the end of our internal buffer then we need to fetch
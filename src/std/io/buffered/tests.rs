@@ -143,6 +143,67 @@ fn test_buffered_reader_seek_relative() {
     assert_eq!(reader.fill_buf().ok(), Some(&[2, 3][..]));
 }
 
+#[test]
+fn test_buffered_reader_seek_relative_overflow() {
+    let inner: &[u8] = &[5, 6, 7, 0, 1, 2, 3, 4];
+    let mut reader = BufReader::with_capacity(4, io::Cursor::new(inner));
+    assert_eq!(reader.fill_buf().ok(), Some(&[5, 6, 7, 0][..]));
+    assert!(reader.seek_relative(i64::MIN).is_err());
+}
+
+#[test]
+fn test_buffered_reader_peek() {
+    // Hands out one byte per read.
+    struct Trickle<'a>(&'a [u8]);
+
+    impl Read for Trickle<'_> {
+        fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+            let n = self.0.len().min(buf.len()).min(1);
+            buf[..n].copy_from_slice(&self.0[..n]);
+            self.0 = &self.0[n..];
+            Ok(n)
+        }
+    }
+
+    let mut reader = BufReader::with_capacity(4, Trickle(&[1, 2, 3, 4, 5, 6]));
+    assert_eq!(reader.peek(3).unwrap(), &[1, 2, 3][..]);
+    assert_eq!(reader.buffer(), &[1, 2, 3][..]);
+
+    // Consumed bytes are moved out of the way to make room.
+    reader.consume(2);
+    assert_eq!(reader.peek(4).unwrap(), &[3, 4, 5, 6][..]);
+    assert_eq!(reader.peek(0).unwrap(), &[][..]);
+
+    // At end of file, whatever is left comes back.
+    reader.consume(3);
+    assert_eq!(reader.peek(4).unwrap(), &[6][..]);
+    let mut rest = Vec::new();
+    reader.read_to_end(&mut rest).unwrap();
+    assert_eq!(rest, [6]);
+}
+
+#[test]
+#[should_panic = "cannot peek at more bytes than the buffer holds"]
+fn test_buffered_reader_peek_past_capacity() {
+    let inner: &[u8] = &[1, 2, 3];
+    let mut reader = BufReader::with_capacity(2, inner);
+    let _ = reader.peek(3);
+}
+
+#[test]
+fn test_buffered_reader_peek_then_seek_relative() {
+    let inner: &[u8] = &[5, 6, 7, 0, 1, 2, 3, 4];
+    let mut reader = BufReader::with_capacity(4, io::Cursor::new(inner));
+    assert_eq!(reader.peek(4).unwrap(), &[5, 6, 7, 0][..]);
+
+    // Relative seeks over what was peeked at don't touch the inner reader.
+    reader.seek_relative(3).unwrap();
+    assert_eq!(reader.get_ref().position(), 4);
+    reader.seek_relative(-2).unwrap();
+    assert_eq!(reader.get_ref().position(), 4);
+    assert_eq!(reader.buffer(), &[6, 7, 0][..]);
+}
+
 #[test]
 fn test_buffered_reader_stream_position() {
     let inner: &[u8] = &[5, 6, 7, 0, 1, 2, 3, 4];
@@ -1092,3 +1153,129 @@ fn bufreader_full_initialize() {
     // But we initialized the whole buffer!
     assert_eq!(reader.initialized(), reader.capacity());
 }
+
+#[test]
+fn buffered_writers_keep_output_on_would_block() {
+    // Takes at most `room` bytes, then fails like a full non-blocking pipe.
+    struct Pipe {
+        written: Vec<u8>,
+        room: usize,
+    }
+
+    impl Write for Pipe {
+        fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+            if self.room == 0 {
+                return Err(io::Error::from(ErrorKind::WouldBlock));
+            }
+            let n = buf.len().min(self.room);
+            self.written.extend_from_slice(&buf[..n]);
+            self.room -= n;
+            Ok(n)
+        }
+        fn flush(&mut self) -> io::Result<()> {
+            Ok(())
+        }
+    }
+
+    let mut writer = LineWriter::new(Pipe {
+        written: Vec::new(),
+        room: 3,
+    });
+    assert_eq!(writer.write(b"hello\n").unwrap(), 6);
+    assert_eq!(writer.flush().unwrap_err().kind(), ErrorKind::WouldBlock);
+    assert_eq!(writer.get_ref().written, b"hel");
+
+    writer.get_mut().room = 10;
+    writer.flush().unwrap();
+    assert_eq!(writer.get_ref().written, b"hello\n");
+
+    let mut writer = BufWriter::with_capacity(
+        4,
+        Pipe {
+            written: Vec::new(),
+            room: 0,
+        },
+    );
+    writer.write_all(b"ab").unwrap();
+    assert_eq!(
+        writer.write_all(b"cdef").unwrap_err().kind(),
+        ErrorKind::WouldBlock
+    );
+    assert_eq!(writer.buffer(), b"ab");
+    writer.get_mut().room = 10;
+    writer.write_all(b"cdef").unwrap();
+    writer.flush().unwrap();
+    assert_eq!(writer.get_ref().written, b"abcdef");
+}
+
+// Returns each chunk in turn, failing like an empty non-blocking pipe in
+// between, and reports end of file once they run out.
+#[cfg(target_os = "dragonos")]
+struct Chunks(Vec<&'static [u8]>, bool);
+
+#[cfg(target_os = "dragonos")]
+impl Read for Chunks {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        self.1 = !self.1;
+        if self.1 {
+            return Err(io::Error::from(ErrorKind::WouldBlock));
+        }
+        if self.0.is_empty() {
+            return Ok(0);
+        }
+        let chunk = self.0.remove(0);
+        buf[..chunk.len()].copy_from_slice(chunk);
+        Ok(chunk.len())
+    }
+}
+
+#[test]
+#[cfg(target_os = "dragonos")]
+fn read_line_keeps_character_split_by_would_block() {
+    let mut reader = BufReader::new(Chunks(vec![b"caf\xc3", b"\xa9\n"], true));
+    let mut line = String::new();
+    assert_eq!(
+        reader.read_line(&mut line).unwrap_err().kind(),
+        ErrorKind::WouldBlock
+    );
+    assert_eq!(line, "caf");
+    reader.read_line(&mut line).unwrap();
+    assert_eq!(line, "café\n");
+}
+
+#[test]
+#[cfg(target_os = "dragonos")]
+fn read_line_continues_partial_line_after_would_block() {
+    let mut reader = BufReader::new(Chunks(vec![b"par", b"tial\nnext"], true));
+    let mut line = String::new();
+    assert_eq!(
+        reader.read_line(&mut line).unwrap_err().kind(),
+        ErrorKind::WouldBlock
+    );
+    assert_eq!(line, "par");
+    assert_eq!(reader.read_line(&mut line).unwrap(), 5);
+    assert_eq!(line, "partial\n");
+    assert_eq!(reader.buffer(), b"next");
+}
+
+#[test]
+#[cfg(target_os = "dragonos")]
+fn read_line_rejects_invalid_utf8() {
+    // A byte that can't start a character is invalid data, not the start of
+    // one split by `WouldBlock`, so nothing is kept for the next call.
+    let mut reader = BufReader::new(Chunks(vec![b"ab\xff"], true));
+    let mut line = String::new();
+    assert_eq!(
+        reader.read_line(&mut line).unwrap_err().kind(),
+        ErrorKind::WouldBlock
+    );
+    assert_eq!(line, "");
+    assert!(reader.buffer().is_empty());
+
+    let mut reader = BufReader::new(Chunks(vec![b"ab\xffc\n"], true));
+    assert_eq!(
+        reader.read_line(&mut line).unwrap_err().kind(),
+        ErrorKind::InvalidData
+    );
+    assert_eq!(line, "");
+}
@@ -63,7 +63,7 @@ where
     W: Write,
 {
     cfg_if::cfg_if! {
-        if #[cfg(any(target_os = "linux", target_os = "android"))] {
+        if #[cfg(any(target_os = "linux", target_os = "android", target_os = "dragonos"))] {
             crate::std::sys::kernel_copy::copy_spec(reader, writer)
         } else {
             generic_copy(reader, writer)
@@ -32,3 +32,37 @@ impl Write for WriteObserver {
         Ok(())
     }
 }
+
+#[cfg(target_os = "dragonos")]
+#[test]
+fn copy_between_files_and_pipes() {
+    use crate::std::fs::File;
+    use crate::std::thread;
+
+    let dir = crate::std::sys_common::io::test::tmpdir();
+    let data: Vec<u8> = (0..200_000u32).map(|i| i as u8).collect();
+    let src_path = dir.join("src");
+    crate::std::fs::write(&src_path, &data).unwrap();
+
+    // File to file, through copy_file_range.
+    let dst_path = dir.join("dst");
+    let mut src = File::open(&src_path).unwrap();
+    let mut dst = File::create(&dst_path).unwrap();
+    assert_eq!(copy(&mut src, &mut dst).unwrap(), data.len() as u64);
+    assert_eq!(crate::std::fs::read(&dst_path).unwrap(), data);
+
+    // File to pipe, through sendfile, and pipe to file, through splice.
+    let (mut reader, mut writer) = pipe().unwrap();
+    let src_path2 = src_path.clone();
+    let sender = thread::spawn(move || {
+        let mut src = File::open(&src_path2).unwrap();
+        let n = copy(&mut src, &mut writer).unwrap();
+        drop(writer);
+        n
+    });
+    let out_path = dir.join("out");
+    let mut out = File::create(&out_path).unwrap();
+    assert_eq!(copy(&mut reader, &mut out).unwrap(), data.len() as u64);
+    assert_eq!(sender.join().unwrap(), data.len() as u64);
+    assert_eq!(crate::std::fs::read(&out_path).unwrap(), data);
+}
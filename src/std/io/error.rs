@@ -13,6 +13,7 @@ use repr_unpacked::Repr;
 
 use crate::std::error;
 use crate::std::fmt;
+use crate::std::path::{Path, PathBuf};
 use crate::std::result;
 use crate::std::sys;
 
@@ -146,6 +147,26 @@ struct Custom {
     error: Box<dyn error::Error + Send + Sync>,
 }
 
+// The payload of an error given a path with `Error::with_path`. The
+// accessors of `Error` look through it to the error it wraps.
+#[derive(Debug)]
+struct WithPath {
+    error: Error,
+    path: PathBuf,
+}
+
+impl fmt::Display for WithPath {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} at {}", self.error, self.path.display())
+    }
+}
+
+impl error::Error for WithPath {
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        error::Error::source(&self.error)
+    }
+}
+
 /// A list specifying general categories of I/O error.
 ///
 /// This list is intended to grow over time and it is not recommended to
@@ -613,7 +634,10 @@ impl Error {
     pub fn raw_os_error(&self) -> Option<RawOsError> {
         match self.repr.data() {
             ErrorData::Os(i) => Some(i),
-            ErrorData::Custom(..) => None,
+            ErrorData::Custom(c) => c
+                .error
+                .downcast_ref::<WithPath>()
+                .and_then(|e| e.error.raw_os_error()),
             ErrorData::Simple(..) => None,
             ErrorData::SimpleMessage(..) => None,
         }
@@ -653,7 +677,10 @@ impl Error {
             ErrorData::Os(..) => None,
             ErrorData::Simple(..) => None,
             ErrorData::SimpleMessage(..) => None,
-            ErrorData::Custom(c) => Some(&*c.error),
+            ErrorData::Custom(c) => match c.error.downcast_ref::<WithPath>() {
+                Some(e) => e.error.get_ref(),
+                None => Some(&*c.error),
+            },
         }
     }
 
@@ -726,6 +753,9 @@ impl Error {
             ErrorData::Os(..) => None,
             ErrorData::Simple(..) => None,
             ErrorData::SimpleMessage(..) => None,
+            ErrorData::Custom(c) if c.error.is::<WithPath>() => {
+                c.error.downcast_mut::<WithPath>().unwrap().error.get_mut()
+            }
             ErrorData::Custom(c) => Some(&mut *c.error),
         }
     }
@@ -764,7 +794,10 @@ impl Error {
             ErrorData::Os(..) => None,
             ErrorData::Simple(..) => None,
             ErrorData::SimpleMessage(..) => None,
-            ErrorData::Custom(c) => Some(c.error),
+            ErrorData::Custom(c) => match c.error.downcast::<WithPath>() {
+                Ok(e) => e.error.into_inner(),
+                Err(error) => Some(error),
+            },
         }
     }
 
@@ -814,6 +847,10 @@ impl Error {
         E: error::Error + Send + Sync + 'static,
     {
         match self.repr.into_data() {
+            ErrorData::Custom(b) if b.error.is::<WithPath>() => {
+                let WithPath { error, path } = *b.error.downcast::<WithPath>().unwrap();
+                error.downcast::<E>().map_err(|error| error.with_path(path))
+            }
             ErrorData::Custom(b) if b.error.is::<E>() => {
                 let res = (*b).error.downcast::<E>();
 
@@ -831,6 +868,56 @@ impl Error {
         }
     }
 
+    /// Adds the path of the file that the error is about, which is then
+    /// shown after the message, as in "No such file or directory (os error
+    /// 2) at /etc/foo.conf".
+    ///
+    /// The [`kind`] and [`raw_os_error`] stay the same, and so do
+    /// [`get_ref`] and the other accessors of the payload. An error that
+    /// has a path already keeps that one, which is the more precise.
+    ///
+    /// On DragonOS, the functions of [`std::fs`] that take a single path add
+    /// it to their errors this way. Those that take two, such as
+    /// [`fs::rename`], don't, since the error doesn't say which path it is
+    /// about. Other platforms leave the errors of [`std::fs`] unchanged.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::io::{Error, ErrorKind};
+    /// use std::path::Path;
+    ///
+    /// let error = Error::from(ErrorKind::NotFound).with_path("/etc/foo.conf");
+    /// assert_eq!(error.kind(), ErrorKind::NotFound);
+    /// assert_eq!(error.path(), Some(Path::new("/etc/foo.conf")));
+    /// assert_eq!(error.to_string(), "entity not found at /etc/foo.conf");
+    /// ```
+    ///
+    /// [`kind`]: Error::kind
+    /// [`raw_os_error`]: Error::raw_os_error
+    /// [`get_ref`]: Error::get_ref
+    /// [`std::fs`]: crate::std::fs
+    /// [`fs::rename`]: crate::std::fs::rename
+    pub fn with_path<P: AsRef<Path>>(self, path: P) -> Error {
+        if self.path().is_some() {
+            return self;
+        }
+        let kind = self.kind();
+        let path = path.as_ref().to_path_buf();
+        Self::_new(kind, Box::new(WithPath { error: self, path }))
+    }
+
+    /// Returns the path added with [`with_path`], if any.
+    ///
+    /// [`with_path`]: Error::with_path
+    #[must_use]
+    pub fn path(&self) -> Option<&Path> {
+        match self.repr.data() {
+            ErrorData::Custom(c) => c.error.downcast_ref::<WithPath>().map(|e| &*e.path),
+            _ => None,
+        }
+    }
+
     /// Returns the corresponding [`ErrorKind`] for this error.
     ///
     /// This may be a value set by Rust code constructing custom `io::Error`s,
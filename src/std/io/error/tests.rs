@@ -205,3 +205,132 @@ fn test_std_io_error_downcast() {
     assert_eq!(SIMPLE_MESSAGE.kind, io_error.kind());
     assert_eq!(SIMPLE_MESSAGE.message, format!("{io_error}"));
 }
+
+#[test]
+#[cfg(target_os = "dragonos")]
+fn test_dragonos_errno_kinds() {
+    use ErrorKind::*;
+
+    let table = [
+        (dlibc::EPERM, PermissionDenied),
+        (dlibc::EACCES, PermissionDenied),
+        (dlibc::ENOENT, NotFound),
+        (dlibc::EINTR, Interrupted),
+        (dlibc::E2BIG, ArgumentListTooLong),
+        (dlibc::EAGAIN, WouldBlock),
+        (dlibc::ENOMEM, OutOfMemory),
+        (dlibc::ENOBUFS, OutOfMemory),
+        (dlibc::EBUSY, ResourceBusy),
+        (dlibc::EEXIST, AlreadyExists),
+        (dlibc::EXDEV, CrossesDevices),
+        (dlibc::ENOTDIR, NotADirectory),
+        (dlibc::EISDIR, IsADirectory),
+        (dlibc::EINVAL, InvalidInput),
+        (dlibc::ETXTBSY, ExecutableFileBusy),
+        (dlibc::EFBIG, FileTooLarge),
+        (dlibc::ENOSPC, StorageFull),
+        (dlibc::EDQUOT, FilesystemQuotaExceeded),
+        (dlibc::ESPIPE, NotSeekable),
+        (dlibc::EROFS, ReadOnlyFilesystem),
+        (dlibc::EMLINK, TooManyLinks),
+        (dlibc::EPIPE, BrokenPipe),
+        (dlibc::EDEADLK, Deadlock),
+        (dlibc::ENAMETOOLONG, InvalidFilename),
+        (dlibc::ENOSYS, Unsupported),
+        (dlibc::EOPNOTSUPP, Unsupported),
+        (dlibc::ENOTSUP, Unsupported),
+        (dlibc::EAFNOSUPPORT, Unsupported),
+        (dlibc::EPROTONOSUPPORT, Unsupported),
+        (dlibc::ENOTEMPTY, DirectoryNotEmpty),
+        (dlibc::ELOOP, FilesystemLoop),
+        (dlibc::ESTALE, StaleNetworkFileHandle),
+        (dlibc::EILSEQ, InvalidData),
+        (dlibc::ETIME, TimedOut),
+        (dlibc::ETIMEDOUT, TimedOut),
+        (dlibc::EADDRINUSE, AddrInUse),
+        (dlibc::EADDRNOTAVAIL, AddrNotAvailable),
+        (dlibc::ENETDOWN, NetworkDown),
+        (dlibc::ENETUNREACH, NetworkUnreachable),
+        (dlibc::ENETRESET, ConnectionReset),
+        (dlibc::ECONNABORTED, ConnectionAborted),
+        (dlibc::ECONNRESET, ConnectionReset),
+        (dlibc::ENOTCONN, NotConnected),
+        (dlibc::ECONNREFUSED, ConnectionRefused),
+        (dlibc::EHOSTDOWN, HostUnreachable),
+        (dlibc::EHOSTUNREACH, HostUnreachable),
+    ];
+    for (code, kind) in table {
+        assert_eq!(decode_error_kind(code), kind, "errno {code}");
+        assert_eq!(Error::from_raw_os_error(code).kind(), kind, "errno {code}");
+    }
+}
+
+#[test]
+#[cfg(target_os = "dragonos")]
+fn test_dragonos_raw_os_error_round_trip() {
+    for code in 1..=dlibc::EHWPOISON {
+        let err = Error::from_raw_os_error(code);
+        assert_eq!(err.raw_os_error(), Some(code));
+        assert_eq!(err.kind(), decode_error_kind(code));
+        assert_eq!(Error::from(err.kind()).raw_os_error(), None);
+    }
+}
+
+#[test]
+fn test_with_path() {
+    use crate::std::path::Path;
+
+    let code = 2;
+    let err = Error::from_raw_os_error(code).with_path("/etc/foo.conf");
+    assert_eq!(err.kind(), decode_error_kind(code));
+    assert_eq!(err.raw_os_error(), Some(code));
+    assert_eq!(err.path(), Some(Path::new("/etc/foo.conf")));
+    assert!(err.get_ref().is_none());
+    assert_eq!(
+        err.to_string(),
+        format!("{} (os error {code}) at /etc/foo.conf", error_string(code))
+    );
+
+    // The first path sticks.
+    let err = err.with_path("/elsewhere");
+    assert_eq!(err.path(), Some(Path::new("/etc/foo.conf")));
+    assert!(err.into_inner().is_none());
+
+    #[derive(Debug)]
+    struct Payload;
+    impl fmt::Display for Payload {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            f.write_str("payload")
+        }
+    }
+    impl error::Error for Payload {}
+
+    let err = Error::new(ErrorKind::InvalidData, Payload).with_path("x");
+    assert_eq!(err.to_string(), "payload at x");
+    assert!(err.get_ref().unwrap().is::<Payload>());
+    let err = err.downcast::<fmt::Error>().unwrap_err();
+    assert_eq!(err.path(), Some(Path::new("x")));
+    assert!(err.downcast::<Payload>().is_ok());
+}
+
+#[test]
+#[cfg(target_os = "dragonos")]
+fn test_fs_errors_have_paths() {
+    use crate::std::fs;
+
+    let dir = crate::std::sys_common::io::test::tmpdir();
+    let missing = dir.join("missing");
+    let err = fs::File::open(&missing).unwrap_err();
+    assert_eq!(err.kind(), ErrorKind::NotFound);
+    assert_eq!(err.path(), Some(&*missing));
+    assert!(err
+        .to_string()
+        .ends_with(&format!(" at {}", missing.display())));
+    assert!(err.raw_os_error().is_some());
+
+    fs::write(dir.join("file"), b"").unwrap();
+    let err = fs::create_dir_all(dir.join("file/a")).unwrap_err();
+    assert_eq!(err.path(), Some(&*dir.join("file/a")));
+    let err = fs::metadata(missing.join("x")).unwrap_err();
+    assert_eq!(err.path(), Some(&*missing.join("x")));
+}
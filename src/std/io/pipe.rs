@@ -0,0 +1,162 @@
+#[cfg(test)]
+mod tests;
+
+use crate::std::fmt;
+use crate::std::io::{self, BorrowedCursor, IoSlice, IoSliceMut, Read, Write};
+use crate::std::sys::pipe::{anon_pipe, AnonPipe};
+
+/// Creates an anonymous pipe.
+///
+/// Bytes written to the [`PipeWriter`] can be read from the [`PipeReader`],
+/// in order. Reading returns end of file once every writer, including the
+/// clones made with [`PipeWriter::try_clone`] and the copies inherited by
+/// child processes, has been dropped. Both ends are created close-on-exec,
+/// so a child process only gets the ones handed to it as [`Stdio`].
+///
+/// # Examples
+///
+/// Send the output of two commands through one pipe:
+///
+/// ```no_run
+/// use std::io::{self, Read};
+/// use std::process::Command;
+///
+/// let (mut reader, writer) = io::pipe()?;
+///
+/// let mut first = Command::new("echo").arg("hello").stdout(writer.try_clone()?).spawn()?;
+/// let mut second = Command::new("echo").arg("world").stdout(writer).spawn()?;
+///
+/// // Both writers now belong to the children, so this returns once they exit.
+/// let mut output = String::new();
+/// reader.read_to_string(&mut output)?;
+/// first.wait()?;
+/// second.wait()?;
+/// # Ok::<(), io::Error>(())
+/// ```
+///
+/// [`Stdio`]: crate::std::process::Stdio
+pub fn pipe() -> io::Result<(PipeReader, PipeWriter)> {
+    anon_pipe().map(|(reader, writer)| (PipeReader(reader), PipeWriter(writer)))
+}
+
+/// The reading end of a pipe, created by [`pipe`].
+pub struct PipeReader(pub(crate) AnonPipe);
+
+/// The writing end of a pipe, created by [`pipe`].
+pub struct PipeWriter(pub(crate) AnonPipe);
+
+impl PipeReader {
+    /// Creates a new `PipeReader` that shares the same pipe.
+    ///
+    /// Both can be read from, and each byte is returned by only one of them.
+    pub fn try_clone(&self) -> io::Result<PipeReader> {
+        self.0.try_clone().map(PipeReader)
+    }
+}
+
+impl PipeWriter {
+    /// Creates a new `PipeWriter` that shares the same pipe.
+    ///
+    /// The reader only sees end of file once this clone is dropped as well.
+    pub fn try_clone(&self) -> io::Result<PipeWriter> {
+        self.0.try_clone().map(PipeWriter)
+    }
+}
+
+impl Read for &PipeReader {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        self.0.read(buf)
+    }
+
+    fn read_buf(&mut self, buf: BorrowedCursor<'_>) -> io::Result<()> {
+        self.0.read_buf(buf)
+    }
+
+    fn read_vectored(&mut self, bufs: &mut [IoSliceMut<'_>]) -> io::Result<usize> {
+        self.0.read_vectored(bufs)
+    }
+
+    #[inline]
+    fn is_read_vectored(&self) -> bool {
+        self.0.is_read_vectored()
+    }
+
+    fn read_to_end(&mut self, buf: &mut Vec<u8>) -> io::Result<usize> {
+        self.0.read_to_end(buf)
+    }
+}
+
+impl Read for PipeReader {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        (&*self).read(buf)
+    }
+
+    fn read_buf(&mut self, buf: BorrowedCursor<'_>) -> io::Result<()> {
+        (&*self).read_buf(buf)
+    }
+
+    fn read_vectored(&mut self, bufs: &mut [IoSliceMut<'_>]) -> io::Result<usize> {
+        (&*self).read_vectored(bufs)
+    }
+
+    #[inline]
+    fn is_read_vectored(&self) -> bool {
+        self.0.is_read_vectored()
+    }
+
+    fn read_to_end(&mut self, buf: &mut Vec<u8>) -> io::Result<usize> {
+        (&*self).read_to_end(buf)
+    }
+}
+
+impl Write for &PipeWriter {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.0.write(buf)
+    }
+
+    fn write_vectored(&mut self, bufs: &[IoSlice<'_>]) -> io::Result<usize> {
+        self.0.write_vectored(bufs)
+    }
+
+    #[inline]
+    fn is_write_vectored(&self) -> bool {
+        self.0.is_write_vectored()
+    }
+
+    #[inline]
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+impl Write for PipeWriter {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        (&*self).write(buf)
+    }
+
+    fn write_vectored(&mut self, bufs: &[IoSlice<'_>]) -> io::Result<usize> {
+        (&*self).write_vectored(bufs)
+    }
+
+    #[inline]
+    fn is_write_vectored(&self) -> bool {
+        self.0.is_write_vectored()
+    }
+
+    #[inline]
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+impl fmt::Debug for PipeReader {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("PipeReader").finish_non_exhaustive()
+    }
+}
+
+impl fmt::Debug for PipeWriter {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("PipeWriter").finish_non_exhaustive()
+    }
+}
@@ -0,0 +1,44 @@
+use crate::std::io::{pipe, Read, Write};
+use crate::std::os::fd::{AsRawFd, OwnedFd};
+use crate::std::process::Stdio;
+
+#[test]
+fn pipe_eof_after_every_writer_is_dropped() {
+    let (mut reader, mut writer) = pipe().unwrap();
+    let mut clone = writer.try_clone().unwrap();
+    assert_ne!(clone.as_raw_fd(), writer.as_raw_fd());
+
+    writer.write_all(b"hello ").unwrap();
+    clone.write_all(b"world").unwrap();
+    drop(writer);
+
+    let mut buf = [0; 11];
+    reader.read_exact(&mut buf).unwrap();
+    assert_eq!(&buf, b"hello world");
+
+    drop(clone);
+    let mut rest = Vec::new();
+    assert_eq!(reader.read_to_end(&mut rest).unwrap(), 0);
+}
+
+#[test]
+fn pipe_ends_are_cloexec() {
+    let (reader, writer) = pipe().unwrap();
+    for fd in [reader.as_raw_fd(), writer.as_raw_fd()] {
+        let flags = unsafe { dlibc::fcntl(fd, dlibc::F_GETFD) };
+        assert_eq!(flags & dlibc::FD_CLOEXEC, dlibc::FD_CLOEXEC);
+    }
+}
+
+#[test]
+fn pipe_conversions() {
+    let (reader, writer) = pipe().unwrap();
+    let fd = writer.as_raw_fd();
+    let owned = OwnedFd::from(writer);
+    assert_eq!(owned.as_raw_fd(), fd);
+    let mut writer = crate::std::io::PipeWriter::from(owned);
+    writer.write_all(b"x").unwrap();
+
+    let _: Stdio = reader.into();
+    let _: Stdio = writer.into();
+}
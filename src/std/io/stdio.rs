@@ -8,9 +8,11 @@ use crate::std::io::prelude::*;
 use crate::std::cell::{Cell, RefCell};
 use crate::std::fmt;
 use crate::std::fs::File;
-use crate::std::io::{self, BorrowedCursor, BufReader, IoSlice, IoSliceMut, LineWriter, Lines};
+use crate::std::io::{
+    self, BorrowedCursor, BufReader, BufWriter, IoSlice, IoSliceMut, LineWriter, Lines,
+};
 use crate::std::sync::atomic::{AtomicBool, Ordering};
-use crate::std::sync::{Arc, Mutex, MutexGuard, OnceLock, ReentrantMutex, ReentrantMutexGuard};
+use crate::std::sync::{Arc, Mutex, MutexGuard, OnceLock, ReentrantLock, ReentrantLockGuard};
 use crate::std::sys::stdio;
 
 type LocalStream = Arc<Mutex<Vec<u8>>>;
@@ -148,6 +150,58 @@ impl Write for StdoutRaw {
     }
 }
 
+/// The buffer in front of [`StdoutRaw`].
+///
+/// Standard output is line buffered, but on DragonOS that can be changed
+/// with `os::dragonos::io::StdoutExt::set_buffer_mode`.
+#[cfg_attr(not(target_os = "dragonos"), allow(dead_code))]
+enum StdoutBuffer {
+    Line(LineWriter<StdoutRaw>),
+    Block(BufWriter<StdoutRaw>),
+    Unbuffered(StdoutRaw),
+}
+
+macro_rules! forward_stdout_buffer {
+    ($self:ident.$method:ident($($arg:expr),*)) => {
+        match $self {
+            StdoutBuffer::Line(w) => w.$method($($arg),*),
+            StdoutBuffer::Block(w) => w.$method($($arg),*),
+            StdoutBuffer::Unbuffered(w) => w.$method($($arg),*),
+        }
+    };
+}
+
+impl Write for StdoutBuffer {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        forward_stdout_buffer!(self.write(buf))
+    }
+
+    fn write_vectored(&mut self, bufs: &[IoSlice<'_>]) -> io::Result<usize> {
+        forward_stdout_buffer!(self.write_vectored(bufs))
+    }
+
+    #[inline]
+    fn is_write_vectored(&self) -> bool {
+        forward_stdout_buffer!(self.is_write_vectored())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        forward_stdout_buffer!(self.flush())
+    }
+
+    fn write_all(&mut self, buf: &[u8]) -> io::Result<()> {
+        forward_stdout_buffer!(self.write_all(buf))
+    }
+
+    fn write_all_vectored(&mut self, bufs: &mut [IoSlice<'_>]) -> io::Result<()> {
+        forward_stdout_buffer!(self.write_all_vectored(bufs))
+    }
+
+    fn write_fmt(&mut self, fmt: fmt::Arguments<'_>) -> io::Result<()> {
+        forward_stdout_buffer!(self.write_fmt(fmt))
+    }
+}
+
 impl Write for StderrRaw {
     fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
         handle_ebadf(self.0.write(buf), buf.len())
@@ -314,6 +368,7 @@ pub struct StdinLock<'a> {
 /// ```
 #[must_use]
 pub fn stdin() -> Stdin {
+    crate::std::rt::debug_assert_init(crate::std::rt::Stage::Sys, "std::io::stdin");
     static INSTANCE: OnceLock<Mutex<BufReader<StdinRaw>>> = OnceLock::new();
     Stdin {
         inner: INSTANCE.get_or_init(|| {
@@ -520,10 +575,11 @@ impl fmt::Debug for StdinLock<'_> {
 /// [`lock`]: Stdout::lock
 /// [`io::stdout`]: stdout
 pub struct Stdout {
-    // FIXME: this should be LineWriter or BufWriter depending on the state of
-    //        stdout (tty or not). Note that if this is not line buffered it
-    //        should also flush-on-panic or some form of flush-on-abort.
-    inner: &'static ReentrantMutex<RefCell<LineWriter<StdoutRaw>>>,
+    // FIXME: the default should be LineWriter or BufWriter depending on the
+    //        state of stdout (tty or not). Note that if this is not line
+    //        buffered it should also flush-on-panic or some form of
+    //        flush-on-abort.
+    inner: &'static ReentrantLock<RefCell<StdoutBuffer>>,
 }
 
 /// A locked reference to the [`Stdout`] handle.
@@ -544,10 +600,10 @@ pub struct Stdout {
 /// standard library or via raw Windows API calls, will fail.
 #[must_use = "if unused stdout will immediately unlock"]
 pub struct StdoutLock<'a> {
-    inner: ReentrantMutexGuard<'a, RefCell<LineWriter<StdoutRaw>>>,
+    inner: ReentrantLockGuard<'a, RefCell<StdoutBuffer>>,
 }
 
-static STDOUT: OnceLock<ReentrantMutex<RefCell<LineWriter<StdoutRaw>>>> = OnceLock::new();
+static STDOUT: OnceLock<ReentrantLock<RefCell<StdoutBuffer>>> = OnceLock::new();
 
 /// Constructs a new handle to the standard output of the current process.
 ///
@@ -597,20 +653,23 @@ static STDOUT: OnceLock<ReentrantMutex<RefCell<LineWriter<StdoutRaw>>>> = OnceLo
 /// ```
 #[must_use]
 pub fn stdout() -> Stdout {
+    crate::std::rt::debug_assert_init(crate::std::rt::Stage::Sys, "std::io::stdout");
     Stdout {
-        inner: STDOUT
-            .get_or_init(|| ReentrantMutex::new(RefCell::new(LineWriter::new(stdout_raw())))),
+        inner: STDOUT.get_or_init(|| {
+            ReentrantLock::new(RefCell::new(StdoutBuffer::Line(LineWriter::new(
+                stdout_raw(),
+            ))))
+        }),
     }
 }
 
 // Flush the data and disable buffering during shutdown
-// by replacing the line writer by one with zero
-// buffering capacity.
+// by replacing the buffer by none at all.
 pub fn cleanup() {
     let mut initialized = false;
     let stdout = STDOUT.get_or_init(|| {
         initialized = true;
-        ReentrantMutex::new(RefCell::new(LineWriter::with_capacity(0, stdout_raw())))
+        ReentrantLock::new(RefCell::new(StdoutBuffer::Unbuffered(stdout_raw())))
     });
 
     if !initialized {
@@ -619,7 +678,7 @@ pub fn cleanup() {
         // might have leaked a StdoutLock, which would
         // otherwise cause a deadlock here.
         if let Some(lock) = stdout.try_lock() {
-            *lock.borrow_mut() = LineWriter::with_capacity(0, stdout_raw());
+            *lock.borrow_mut() = StdoutBuffer::Unbuffered(stdout_raw());
         }
     }
 }
@@ -646,7 +705,7 @@ impl Stdout {
     /// ```
     pub fn lock(&self) -> StdoutLock<'static> {
         // Locks this handle with 'static lifetime. This depends on the
-        // implementation detail that the underlying `ReentrantMutex` is
+        // implementation detail that the underlying `ReentrantLock` is
         // static.
         StdoutLock {
             inner: self.inner.lock(),
@@ -654,6 +713,38 @@ impl Stdout {
     }
 }
 
+#[cfg(target_os = "dragonos")]
+impl Stdout {
+    pub(crate) fn buffer_mode(&self) -> crate::std::os::dragonos::io::BufferMode {
+        use crate::std::os::dragonos::io::BufferMode;
+
+        match *self.inner.lock().borrow() {
+            StdoutBuffer::Line(_) => BufferMode::Line,
+            StdoutBuffer::Block(_) => BufferMode::Block,
+            StdoutBuffer::Unbuffered(_) => BufferMode::Unbuffered,
+        }
+    }
+
+    pub(crate) fn set_buffer_mode(
+        &self,
+        mode: crate::std::os::dragonos::io::BufferMode,
+    ) -> io::Result<()> {
+        use crate::std::os::dragonos::io::BufferMode;
+
+        let lock = self.inner.lock();
+        let mut buffer = lock.borrow_mut();
+        // Nothing buffered may be lost or reordered, so this fails without
+        // changing anything if it can't be written out first.
+        buffer.flush()?;
+        *buffer = match mode {
+            BufferMode::Line => StdoutBuffer::Line(LineWriter::new(stdout_raw())),
+            BufferMode::Block => StdoutBuffer::Block(BufWriter::new(stdout_raw())),
+            BufferMode::Unbuffered => StdoutBuffer::Unbuffered(stdout_raw()),
+        };
+        Ok(())
+    }
+}
+
 impl fmt::Debug for Stdout {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         f.debug_struct("Stdout").finish_non_exhaustive()
@@ -756,7 +847,7 @@ impl fmt::Debug for StdoutLock<'_> {
 /// `Write` will do nothing and silently succeed. All other I/O operations, via the
 /// standard library or via raw Windows API calls, will fail.
 pub struct Stderr {
-    inner: &'static ReentrantMutex<RefCell<StderrRaw>>,
+    inner: &'static ReentrantLock<RefCell<StderrRaw>>,
 }
 
 /// A locked reference to the [`Stderr`] handle.
@@ -777,7 +868,7 @@ pub struct Stderr {
 /// standard library or via raw Windows API calls, will fail.
 #[must_use = "if unused stderr will immediately unlock"]
 pub struct StderrLock<'a> {
-    inner: ReentrantMutexGuard<'a, RefCell<StderrRaw>>,
+    inner: ReentrantLockGuard<'a, RefCell<StderrRaw>>,
 }
 
 /// Constructs a new handle to the standard error of the current process.
@@ -829,8 +920,8 @@ pub fn stderr() -> Stderr {
     // Note that unlike `stdout()` we don't use `at_exit` here to register a
     // destructor. Stderr is not buffered, so there's no need to run a
     // destructor for flushing the buffer
-    static INSTANCE: ReentrantMutex<RefCell<StderrRaw>> =
-        ReentrantMutex::new(RefCell::new(stderr_raw()));
+    static INSTANCE: ReentrantLock<RefCell<StderrRaw>> =
+        ReentrantLock::new(RefCell::new(stderr_raw()));
 
     Stderr { inner: &INSTANCE }
 }
@@ -858,7 +949,7 @@ impl Stderr {
     /// ```
     pub fn lock(&self) -> StderrLock<'static> {
         // Locks this handle with 'static lifetime. This depends on the
-        // implementation detail that the underlying `ReentrantMutex` is
+        // implementation detail that the underlying `ReentrantLock` is
         // static.
         StderrLock {
             inner: self.inner.lock(),
@@ -138,6 +138,25 @@ pub mod task {
     pub use alloc::task::*;
 }
 
+pub mod simd {
+    //! Portable SIMD module.
+    //!
+    //! The `StdFloat` extension trait of the real `std` is not provided.
+
+    #[doc(inline)]
+    pub use core::simd::*;
+}
+
+pub mod arch {
+    //! SIMD and vendor intrinsics module.
+    //!
+    //! The `is_*_feature_detected!` macros of the real `std` are not
+    //! provided.
+
+    #[doc(no_inline)]
+    pub use core::arch::*;
+}
+
 // #[doc = include_str!("../../stdarch/crates/core_arch/src/core_arch_docs.md")]
 // pub mod arch {
 //         // The `no_inline`-attribute is required to make the documentation of all
@@ -5,6 +5,9 @@ mod tests;
 use crate::std::sys_common::{FromInner, IntoInner};
 use dlibc as c;
 
+// The address types are core's, so their whole API, including the `const fn`
+// constructors, `to_canonical` and the `is_global` family (unstable, behind
+// the `ip` feature), is available through `std::net` as-is.
 pub use core::net::IpAddr;
 
 pub use core::net::{Ipv4Addr, Ipv6Addr};
@@ -6,3 +6,16 @@ fn to_socket_addr_socketaddr() {
     let a = sa4(Ipv4Addr::new(77, 88, 21, 11), 12345);
     assert_eq!(Ok(vec![a]), tsa(a));
 }
+
+#[test]
+fn core_ip_api_is_reexported() {
+    use crate::std::net::{IpAddr, Ipv6Addr};
+
+    const LOCALHOST: Ipv4Addr = Ipv4Addr::new(127, 0, 0, 1);
+    const MAPPED: Ipv6Addr = LOCALHOST.to_ipv6_mapped();
+
+    assert_eq!(IpAddr::V6(MAPPED).to_canonical(), IpAddr::V4(LOCALHOST));
+    assert!(!LOCALHOST.is_global());
+    assert!(Ipv4Addr::new(1, 1, 1, 1).is_global());
+    assert!(!Ipv6Addr::new(0xfc00, 0, 0, 0, 0, 0, 0, 1).is_global());
+}
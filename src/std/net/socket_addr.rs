@@ -13,6 +13,10 @@ use crate::std::sys_common::{FromInner, IntoInner};
 use crate::std::vec;
 use dlibc as c;
 
+// The address types, their `FromStr` parsers and their `Display` impls all
+// come from `core::net`, so they are upstream's byte-level parser and
+// stack-buffered, non-allocating formatting. Only the conversions to and from
+// the C socket address structs and `ToSocketAddrs` are implemented here.
 pub use core::net::{SocketAddr, SocketAddrV4, SocketAddrV6};
 
 impl FromInner<c::sockaddr_in> for SocketAddrV4 {
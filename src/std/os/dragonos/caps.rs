@@ -0,0 +1,263 @@
+//! Fine-grained privileges of the calling process.
+//!
+//! Instead of granting everything to processes running as root, privileges
+//! are split into the capabilities of Linux, read and changed through its
+//! `capget` and `capset` system calls. DragonOS may not implement those, or
+//! check every capability the way Linux does; on a kernel without them
+//! every function here fails with [`Unsupported`]. A process acts with
+//! the capabilities in its *effective* set, and may only add capabilities to
+//! it that are in its *permitted* set. Dropping a capability from the
+//! permitted set is permanent.
+//!
+//! A network daemon started as root can keep the ability to bind to ports
+//! below 1024 and give up everything else:
+//!
+//! ```no_run
+//! use std::os::dragonos::caps::{self, Capability};
+//!
+//! caps::drop_all_except(&[Capability::NetBindService])?;
+//! assert!(caps::has(Capability::NetBindService)?);
+//! assert!(!caps::has(Capability::SysAdmin)?);
+//! # Ok::<(), std::io::Error>(())
+//! ```
+//!
+//! Capabilities only apply to the calling thread; threads that already run
+//! keep theirs, so change them before spawning any.
+//!
+//! [`Unsupported`]: io::ErrorKind::Unsupported
+
+use crate::std::io;
+use crate::std::sys::cvt;
+use dlibc;
+
+/// A privilege that can be granted to a process on its own.
+///
+/// The discriminants are the capability numbers of the kernel.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+#[repr(u8)]
+pub enum Capability {
+    /// Change the owner and group of files.
+    Chown = 0,
+    /// Bypass file read, write and execute permission checks.
+    DacOverride = 1,
+    /// Bypass file read and directory search permission checks.
+    DacReadSearch = 2,
+    /// Bypass checks that require the file owner's user id.
+    Fowner = 3,
+    /// Keep set-user-id and set-group-id bits when modifying files.
+    Fsetid = 4,
+    /// Send signals to processes of other users.
+    Kill = 5,
+    /// Change group ids.
+    Setgid = 6,
+    /// Change user ids.
+    Setuid = 7,
+    /// Change the capabilities of other processes.
+    Setpcap = 8,
+    /// Set the immutable and append-only file attributes.
+    LinuxImmutable = 9,
+    /// Bind sockets to ports below 1024.
+    NetBindService = 10,
+    /// Send broadcasts and listen to multicast.
+    NetBroadcast = 11,
+    /// Configure network interfaces, routes and firewalls.
+    NetAdmin = 12,
+    /// Use raw and packet sockets.
+    NetRaw = 13,
+    /// Lock memory.
+    IpcLock = 14,
+    /// Bypass permission checks on System V IPC objects.
+    IpcOwner = 15,
+    /// Load and unload kernel modules.
+    SysModule = 16,
+    /// Access I/O ports and raw devices.
+    SysRawio = 17,
+    /// Change the root directory.
+    SysChroot = 18,
+    /// Trace and inspect other processes.
+    SysPtrace = 19,
+    /// Configure process accounting.
+    SysPacct = 20,
+    /// Perform a wide range of system administration tasks.
+    SysAdmin = 21,
+    /// Reboot the system.
+    SysBoot = 22,
+    /// Raise scheduling priorities.
+    SysNice = 23,
+    /// Exceed resource limits.
+    SysResource = 24,
+    /// Set the system clock.
+    SysTime = 25,
+    /// Configure terminals.
+    SysTtyConfig = 26,
+    /// Create device files.
+    Mknod = 27,
+    /// Take leases on files.
+    Lease = 28,
+    /// Write to the audit log.
+    AuditWrite = 29,
+    /// Configure auditing.
+    AuditControl = 30,
+    /// Set the capabilities of files.
+    Setfcap = 31,
+    /// Override mandatory access control.
+    MacOverride = 32,
+    /// Configure mandatory access control.
+    MacAdmin = 33,
+    /// Read and configure the kernel log.
+    Syslog = 34,
+    /// Set alarms that wake the system up.
+    WakeAlarm = 35,
+    /// Keep the system from suspending.
+    BlockSuspend = 36,
+    /// Read the audit log.
+    AuditRead = 37,
+    /// Monitor performance.
+    Perfmon = 38,
+    /// Load BPF programs.
+    Bpf = 39,
+    /// Checkpoint and restore processes.
+    CheckpointRestore = 40,
+}
+
+impl Capability {
+    fn bit(self) -> u64 {
+        1 << self as u8
+    }
+}
+
+/// Returns whether `cap` is in the effective set of the calling thread.
+pub fn has(cap: Capability) -> io::Result<bool> {
+    Ok(Sets::get()?.effective & cap.bit() != 0)
+}
+
+/// Adds `cap` to the effective set of the calling thread.
+///
+/// Fails with [`PermissionDenied`] if `cap` is not in the permitted set.
+///
+/// [`PermissionDenied`]: io::ErrorKind::PermissionDenied
+pub fn raise(cap: Capability) -> io::Result<()> {
+    let mut sets = Sets::get()?;
+    sets.effective |= cap.bit();
+    sets.set()
+}
+
+/// Removes `cap` from the effective, permitted and inheritable sets of the
+/// calling thread, so that it can't be raised again.
+pub fn drop_capability(cap: Capability) -> io::Result<()> {
+    let mut sets = Sets::get()?;
+    sets.retain(!cap.bit());
+    sets.set()
+}
+
+/// Removes all capabilities but those in `keep` from the effective,
+/// permitted and inheritable sets of the calling thread.
+///
+/// Capabilities in `keep` that are permitted are also made effective.
+pub fn drop_all_except(keep: &[Capability]) -> io::Result<()> {
+    let keep = keep.iter().fold(0, |mask, cap| mask | cap.bit());
+    let mut sets = Sets::get()?;
+    sets.retain(keep);
+    sets.effective = sets.permitted;
+    sets.set()
+}
+
+/// `_LINUX_CAPABILITY_VERSION_3`, which has 64-bit sets.
+const VERSION: u32 = 0x2008_0522;
+
+#[repr(C)]
+struct Header {
+    version: u32,
+    // 0 for the calling thread.
+    pid: i32,
+}
+
+// The 64-bit sets are split into two of these, low half first.
+#[repr(C)]
+#[derive(Clone, Copy, Default)]
+struct Data {
+    effective: u32,
+    permitted: u32,
+    inheritable: u32,
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+struct Sets {
+    effective: u64,
+    permitted: u64,
+    inheritable: u64,
+}
+
+impl Sets {
+    fn get() -> io::Result<Sets> {
+        let mut header = Header {
+            version: VERSION,
+            pid: 0,
+        };
+        let mut data = [Data::default(); 2];
+        cvt(unsafe {
+            dlibc::syscall(
+                dlibc::SYS_capget,
+                &mut header as *mut Header,
+                data.as_mut_ptr(),
+            )
+        })
+        .map_err(unsupported_if_missing)?;
+        Ok(Sets::from_data(data))
+    }
+
+    fn set(self) -> io::Result<()> {
+        let mut header = Header {
+            version: VERSION,
+            pid: 0,
+        };
+        let data = self.to_data();
+        cvt(unsafe {
+            dlibc::syscall(dlibc::SYS_capset, &mut header as *mut Header, data.as_ptr())
+        })
+        .map_err(unsupported_if_missing)?;
+        Ok(())
+    }
+
+    fn retain(&mut self, mask: u64) {
+        self.effective &= mask;
+        self.permitted &= mask;
+        self.inheritable &= mask;
+    }
+
+    fn from_data([low, high]: [Data; 2]) -> Sets {
+        let join = |low: u32, high: u32| (high as u64) << 32 | low as u64;
+        Sets {
+            effective: join(low.effective, high.effective),
+            permitted: join(low.permitted, high.permitted),
+            inheritable: join(low.inheritable, high.inheritable),
+        }
+    }
+
+    fn to_data(self) -> [Data; 2] {
+        let half = |shift: u32| Data {
+            effective: (self.effective >> shift) as u32,
+            permitted: (self.permitted >> shift) as u32,
+            inheritable: (self.inheritable >> shift) as u32,
+        };
+        [half(0), half(32)]
+    }
+}
+
+/// Reports a kernel without `capget` and `capset` as [`Unsupported`] rather
+/// than with the bare `ENOSYS` error.
+///
+/// [`Unsupported`]: io::ErrorKind::Unsupported
+fn unsupported_if_missing(e: io::Error) -> io::Error {
+    match e.raw_os_error() {
+        Some(dlibc::ENOSYS) => io::const_io_error!(
+            io::ErrorKind::Unsupported,
+            "capabilities are not supported by the kernel",
+        ),
+        _ => e,
+    }
+}
+
+#[cfg(test)]
+mod tests;
@@ -0,0 +1,22 @@
+use super::{has, Capability, Sets};
+
+#[test]
+fn split_sets() {
+    let sets = Sets {
+        effective: Capability::NetBindService.bit() | Capability::WakeAlarm.bit(),
+        permitted: Capability::Chown.bit() | Capability::CheckpointRestore.bit(),
+        inheritable: 0,
+    };
+    let data = sets.to_data();
+    assert_eq!(data[0].effective, 1 << 10);
+    assert_eq!(data[1].effective, 1 << 3);
+    assert_eq!(data[0].permitted, 1);
+    assert_eq!(data[1].permitted, 1 << 8);
+    assert_eq!(Sets::from_data(data), sets);
+}
+
+#[test]
+fn query() {
+    // Whatever the test runs with, the kernel must answer.
+    has(Capability::NetBindService).unwrap();
+}
@@ -0,0 +1,194 @@
+//! Resource control for groups of processes.
+//!
+//! This targets the cgroup v2 interface of Linux, which DragonOS is working
+//! towards but may only partly implement: every group is a directory in the
+//! `cgroup2` file system, conventionally mounted at `/sys/fs/cgroup`, and its
+//! limits are set by writing to the files in it.
+//! [`Cgroup`] wraps such a directory with typed setters for the controllers
+//! that matter to a container runtime. A controller the kernel doesn't
+//! provide shows up as a [`NotFound`] error from its setter.
+//!
+//! ```no_run
+//! use std::os::dragonos::cgroup::Cgroup;
+//! use std::process::Command;
+//!
+//! let group = Cgroup::root()?.create("sandbox")?;
+//! group.set_memory_max(Some(64 << 20))?;
+//! group.set_cpu_weight(50)?;
+//! let child = Command::new("/bin/worker").spawn()?;
+//! group.add_process(child.id())?;
+//! # Ok::<(), std::io::Error>(())
+//! ```
+//!
+//! [`NotFound`]: io::ErrorKind::NotFound
+
+use crate::std::fs;
+use crate::std::io::{self, Write};
+use crate::std::path::{Path, PathBuf};
+use crate::std::time::Duration;
+
+/// Where the cgroup file system is mounted.
+const MOUNT_POINT: &str = "/sys/fs/cgroup";
+
+/// A control group, given by its directory in the cgroup file system.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Cgroup {
+    path: PathBuf,
+}
+
+impl Cgroup {
+    /// Opens the group whose directory is `path`.
+    ///
+    /// Fails with [`NotFound`] if `path` is not the directory of a group.
+    ///
+    /// [`NotFound`]: io::ErrorKind::NotFound
+    pub fn open<P: AsRef<Path>>(path: P) -> io::Result<Cgroup> {
+        let path = path.as_ref();
+        if !path.join("cgroup.procs").is_file() {
+            return Err(io::const_io_error!(
+                io::ErrorKind::NotFound,
+                "not a cgroup directory",
+            ));
+        }
+        Ok(Cgroup {
+            path: path.to_path_buf(),
+        })
+    }
+
+    /// Opens the root group, which contains all processes.
+    pub fn root() -> io::Result<Cgroup> {
+        Cgroup::open(MOUNT_POINT)
+    }
+
+    /// Opens the group the calling process belongs to.
+    pub fn current() -> io::Result<Cgroup> {
+        let membership = fs::read_to_string("/proc/self/cgroup")?;
+        let relative = parse_membership(&membership).ok_or(io::const_io_error!(
+            io::ErrorKind::NotFound,
+            "the process is not in a cgroup v2 group",
+        ))?;
+        Cgroup::open(Path::new(MOUNT_POINT).join(relative))
+    }
+
+    /// Creates a group named `name` inside this one.
+    ///
+    /// The new group starts out empty; move processes into it with
+    /// [`add_process`]. Controllers are only available in it if they are
+    /// listed in this group's `cgroup.subtree_control`.
+    ///
+    /// [`add_process`]: Cgroup::add_process
+    pub fn create(&self, name: &str) -> io::Result<Cgroup> {
+        if name.is_empty() || name.contains('/') || name == "." || name == ".." {
+            return Err(io::const_io_error!(
+                io::ErrorKind::InvalidInput,
+                "invalid cgroup name",
+            ));
+        }
+        let path = self.path.join(name);
+        fs::create_dir(&path)?;
+        Cgroup::open(path)
+    }
+
+    /// Removes this group, which must contain no processes and no groups.
+    pub fn remove(self) -> io::Result<()> {
+        fs::remove_dir(&self.path)
+    }
+
+    /// Returns the directory of the group.
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Moves the process with id `pid`, with all its threads, into this
+    /// group.
+    pub fn add_process(&self, pid: u32) -> io::Result<()> {
+        self.write("cgroup.procs", &pid.to_string())
+    }
+
+    /// Returns the ids of the processes in this group, not including those
+    /// in groups inside it.
+    pub fn processes(&self) -> io::Result<Vec<u32>> {
+        fs::read_to_string(self.path.join("cgroup.procs"))?
+            .lines()
+            .map(|line| {
+                line.trim().parse().map_err(|_| {
+                    io::const_io_error!(io::ErrorKind::InvalidData, "invalid process id")
+                })
+            })
+            .collect()
+    }
+
+    /// Sets the group's share of CPU time relative to its siblings, from 1
+    /// to 10000. Groups start out with a weight of 100.
+    pub fn set_cpu_weight(&self, weight: u32) -> io::Result<()> {
+        if !(1..=10000).contains(&weight) {
+            return Err(io::const_io_error!(
+                io::ErrorKind::InvalidInput,
+                "CPU weight must be between 1 and 10000",
+            ));
+        }
+        self.write("cpu.weight", &weight.to_string())
+    }
+
+    /// Limits the group to `quota` of CPU time in every `period`, or lifts
+    /// the limit if `quota` is `None`.
+    ///
+    /// A quota larger than the period allows using more than one CPU.
+    pub fn set_cpu_max(&self, quota: Option<Duration>, period: Duration) -> io::Result<()> {
+        self.write("cpu.max", &format_cpu_max(quota, period))
+    }
+
+    /// Limits the memory the group may use to `bytes`, or lifts the limit
+    /// if `bytes` is `None`.
+    ///
+    /// Processes that exceed the limit and can't reclaim memory are killed.
+    pub fn set_memory_max(&self, bytes: Option<u64>) -> io::Result<()> {
+        self.write("memory.max", &format_limit(bytes))
+    }
+
+    /// Limits the number of processes and threads in the group to `max`,
+    /// or lifts the limit if `max` is `None`.
+    ///
+    /// Once the limit is reached, creating another process or thread in the
+    /// group fails.
+    pub fn set_pids_max(&self, max: Option<u64>) -> io::Result<()> {
+        self.write("pids.max", &format_limit(max))
+    }
+
+    fn write(&self, file: &str, value: &str) -> io::Result<()> {
+        // Without `O_CREAT`, so that a missing controller file is reported as
+        // `NotFound` instead of being created as a plain file.
+        fs::OpenOptions::new()
+            .write(true)
+            .open(self.path.join(file))?
+            .write_all(value.as_bytes())
+    }
+}
+
+/// Extracts the group path from the cgroup v2 line of `/proc/self/cgroup`,
+/// which reads `0::/path`.
+fn parse_membership(membership: &str) -> Option<&str> {
+    membership
+        .lines()
+        .find_map(|line| line.strip_prefix("0::"))
+        .map(|path| path.trim_start_matches('/'))
+}
+
+fn format_limit(limit: Option<u64>) -> String {
+    match limit {
+        Some(limit) => limit.to_string(),
+        None => "max".to_string(),
+    }
+}
+
+fn format_cpu_max(quota: Option<Duration>, period: Duration) -> String {
+    // The kernel takes both in microseconds.
+    let period = period.as_micros();
+    match quota {
+        Some(quota) => format!("{} {period}", quota.as_micros()),
+        None => format!("max {period}"),
+    }
+}
+
+#[cfg(test)]
+mod tests;
@@ -0,0 +1,56 @@
+use super::{format_cpu_max, parse_membership, Cgroup};
+use crate::std::fs;
+use crate::std::io::ErrorKind;
+use crate::std::time::Duration;
+
+#[test]
+fn membership() {
+    assert_eq!(parse_membership("0::/\n"), Some(""));
+    assert_eq!(
+        parse_membership("1:name=legacy:/x\n0::/services/web\n"),
+        Some("services/web")
+    );
+    assert_eq!(parse_membership("1:cpu:/x\n"), None);
+}
+
+#[test]
+fn cpu_max() {
+    let period = Duration::from_millis(100);
+    assert_eq!(
+        format_cpu_max(Some(Duration::from_millis(50)), period),
+        "50000 100000"
+    );
+    assert_eq!(format_cpu_max(None, period), "max 100000");
+}
+
+#[test]
+fn write_limits() {
+    // A directory with a `cgroup.procs` file stands in for the cgroup file
+    // system.
+    let dir = crate::std::sys_common::io::test::tmpdir();
+    assert_eq!(
+        Cgroup::open(dir.path()).unwrap_err().kind(),
+        ErrorKind::NotFound
+    );
+    fs::write(dir.join("cgroup.procs"), "1\n42\n").unwrap();
+    let group = Cgroup::open(dir.path()).unwrap();
+
+    assert_eq!(group.processes().unwrap(), [1, 42]);
+    group.set_memory_max(Some(1 << 20)).unwrap();
+    assert_eq!(
+        fs::read_to_string(dir.join("memory.max")).unwrap(),
+        "1048576"
+    );
+    group.set_pids_max(None).unwrap();
+    assert_eq!(fs::read_to_string(dir.join("pids.max")).unwrap(), "max");
+    group.set_cpu_weight(200).unwrap();
+    assert_eq!(fs::read_to_string(dir.join("cpu.weight")).unwrap(), "200");
+    assert_eq!(
+        group.set_cpu_weight(0).unwrap_err().kind(),
+        ErrorKind::InvalidInput
+    );
+    assert_eq!(
+        group.create("../escape").unwrap_err().kind(),
+        ErrorKind::InvalidInput
+    );
+}
@@ -0,0 +1,264 @@
+//! Inspecting the shared objects a program needs, without loading it.
+//!
+//! This mirrors what the DragonOS dynamic linker would do at startup, so it
+//! can be used to check that an installation is complete, much like `ldd`.
+
+use crate::std::collections::{BTreeSet, VecDeque};
+use crate::std::env;
+use crate::std::ffi::{OsStr, OsString};
+use crate::std::fs;
+use crate::std::io;
+use crate::std::os::unix::ffi::{OsStrExt, OsStringExt};
+use crate::std::path::{Path, PathBuf};
+
+/// A shared object needed by a program, directly or through another shared
+/// object.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Dependency {
+    name: OsString,
+    path: Option<PathBuf>,
+}
+
+impl Dependency {
+    /// The name the object is needed under, as written in `DT_NEEDED`.
+    pub fn name(&self) -> &OsStr {
+        &self.name
+    }
+
+    /// Where the dynamic linker would load the object from, or `None` if it
+    /// wouldn't find it.
+    pub fn path(&self) -> Option<&Path> {
+        self.path.as_deref()
+    }
+}
+
+/// Returns the shared objects the ELF file at `path` needs, including those
+/// needed only indirectly, in the order the dynamic linker would load them.
+///
+/// Each name is resolved the way the dynamic linker does: as a path first,
+/// then in the `DT_RUNPATH` of the object that needs it, then in the
+/// directories listed in the calling process's `LD_LIBRARY_PATH`, and
+/// finally in `/lib`. Objects that can't be found are still listed, with no
+/// path, and their own dependencies are unknown. Every object is listed
+/// once, even if several others need it.
+///
+/// Nothing is executed, so this is safe to use on untrusted files.
+///
+/// # Errors
+///
+/// Fails if `path` or a dependency that was found can't be read, or with
+/// [`InvalidData`] if one of them is not a 64-bit little-endian ELF file.
+///
+/// ```no_run
+/// use std::os::dragonos::dl;
+///
+/// for dep in dl::dependencies("/bin/shell")? {
+///     match dep.path() {
+///         Some(path) => println!("{:?} => {}", dep.name(), path.display()),
+///         None => println!("{:?} => not found", dep.name()),
+///     }
+/// }
+/// # Ok::<(), std::io::Error>(())
+/// ```
+///
+/// [`InvalidData`]: io::ErrorKind::InvalidData
+pub fn dependencies<P: AsRef<Path>>(path: P) -> io::Result<Vec<Dependency>> {
+    let ld_library_path = env::var_os("LD_LIBRARY_PATH");
+    let mut deps = Vec::new();
+    let mut seen = BTreeSet::new();
+    let mut queue = VecDeque::new();
+    queue.push_back(path.as_ref().to_path_buf());
+    while let Some(object) = queue.pop_front() {
+        let dynamic = Dynamic::parse(&fs::read(&object)?)?;
+        let runpath = dynamic
+            .runpath
+            .map(|runpath| expand_origin(&runpath, &object));
+        for name in dynamic.needed {
+            let name = OsString::from_vec(name);
+            if !seen.insert(name.clone()) {
+                continue;
+            }
+            let path = search(&name, runpath.as_deref(), ld_library_path.as_deref());
+            if let Some(path) = &path {
+                queue.push_back(path.clone());
+            }
+            deps.push(Dependency { name, path });
+        }
+    }
+    Ok(deps)
+}
+
+/// Resolves `name` like `Linker::search_object` in dlibc's dynamic linker.
+fn search(
+    name: &OsStr,
+    runpath: Option<&OsStr>,
+    ld_library_path: Option<&OsStr>,
+) -> Option<PathBuf> {
+    let name = Path::new(name);
+    if name.exists() {
+        return Some(name.to_path_buf());
+    }
+    runpath
+        .into_iter()
+        .chain(ld_library_path)
+        .flat_map(env::split_paths)
+        .chain([PathBuf::from("/lib")])
+        .map(|dir| dir.join(name))
+        .find(|path| path.exists())
+}
+
+/// Replaces `$ORIGIN` in a `DT_RUNPATH` with the directory of `object`.
+fn expand_origin(runpath: &[u8], object: &Path) -> OsString {
+    let origin = object
+        .parent()
+        .unwrap_or(Path::new("."))
+        .as_os_str()
+        .as_bytes();
+    let mut expanded = Vec::with_capacity(runpath.len());
+    let mut rest = runpath;
+    while let Some(pos) = rest.windows(7).position(|w| w == b"$ORIGIN") {
+        expanded.extend_from_slice(&rest[..pos]);
+        expanded.extend_from_slice(origin);
+        rest = &rest[pos + 7..];
+    }
+    expanded.extend_from_slice(rest);
+    OsString::from_vec(expanded)
+}
+
+const PT_LOAD: u32 = 1;
+const PT_DYNAMIC: u32 = 2;
+
+const DT_NULL: u64 = 0;
+const DT_NEEDED: u64 = 1;
+const DT_STRTAB: u64 = 5;
+const DT_RUNPATH: u64 = 29;
+
+/// The parts of an ELF file's dynamic section that matter for loading its
+/// dependencies.
+struct Dynamic {
+    needed: Vec<Vec<u8>>,
+    runpath: Option<Vec<u8>>,
+}
+
+impl Dynamic {
+    fn parse(elf: &[u8]) -> io::Result<Dynamic> {
+        if elf.get(..4) != Some(&b"\x7fELF"[..]) {
+            return Err(io::const_io_error!(
+                io::ErrorKind::InvalidData,
+                "not an ELF file"
+            ));
+        }
+        // EI_CLASS must be ELFCLASS64 and EI_DATA ELFDATA2LSB.
+        if elf.get(4..6) != Some(&[2, 1][..]) {
+            return Err(io::const_io_error!(
+                io::ErrorKind::InvalidData,
+                "not a 64-bit little-endian ELF file",
+            ));
+        }
+
+        let phoff = read_u64(elf, 0x20)?;
+        let phentsize = read_u16(elf, 0x36)? as u64;
+        let phnum = read_u16(elf, 0x38)? as u64;
+        // (p_vaddr, p_offset, p_filesz) of each PT_LOAD segment.
+        let mut loads = Vec::new();
+        let mut dynamic = None;
+        for i in 0..phnum {
+            let ph = slice_at(elf, phoff.saturating_add(i * phentsize))?;
+            let offset = read_u64(ph, 0x08)?;
+            let vaddr = read_u64(ph, 0x10)?;
+            let filesz = read_u64(ph, 0x20)?;
+            match read_u32(ph, 0)? {
+                PT_LOAD => loads.push((vaddr, offset, filesz)),
+                PT_DYNAMIC => dynamic = Some((offset, filesz)),
+                _ => {}
+            }
+        }
+        // Statically linked programs don't need anything.
+        let Some((offset, size)) = dynamic else {
+            return Ok(Dynamic {
+                needed: Vec::new(),
+                runpath: None,
+            });
+        };
+        let entries = usize::try_from(size)
+            .ok()
+            .and_then(|size| slice_at(elf, offset).ok()?.get(..size))
+            .ok_or_else(malformed)?;
+
+        let mut strtab = None;
+        let mut needed = Vec::new();
+        let mut runpath = None;
+        for entry in entries.chunks_exact(16) {
+            let val = read_u64(entry, 8)?;
+            match read_u64(entry, 0)? {
+                DT_NULL => break,
+                DT_NEEDED => needed.push(val),
+                DT_STRTAB => strtab = Some(val),
+                DT_RUNPATH => runpath = Some(val),
+                _ => {}
+            }
+        }
+        if needed.is_empty() && runpath.is_none() {
+            return Ok(Dynamic {
+                needed,
+                runpath: None,
+            });
+        }
+
+        // DT_STRTAB holds an address, which the PT_LOAD segments map back to
+        // an offset in the file.
+        let strtab = strtab
+            .and_then(|addr| {
+                loads.iter().find_map(|&(vaddr, offset, filesz)| {
+                    let delta = addr.checked_sub(vaddr).filter(|&delta| delta < filesz)?;
+                    offset.checked_add(delta)
+                })
+            })
+            .ok_or_else(malformed)?;
+        let string = |val: u64| read_str(slice_at(elf, strtab.saturating_add(val))?);
+        Ok(Dynamic {
+            needed: needed.into_iter().map(string).collect::<io::Result<_>>()?,
+            runpath: runpath.map(string).transpose()?,
+        })
+    }
+}
+
+fn malformed() -> io::Error {
+    io::const_io_error!(io::ErrorKind::InvalidData, "malformed ELF file")
+}
+
+/// Returns the contents of `elf` from `offset` on.
+fn slice_at(elf: &[u8], offset: u64) -> io::Result<&[u8]> {
+    usize::try_from(offset)
+        .ok()
+        .and_then(|offset| elf.get(offset..))
+        .ok_or_else(malformed)
+}
+
+fn read_bytes<const N: usize>(bytes: &[u8], offset: usize) -> io::Result<[u8; N]> {
+    bytes
+        .get(offset..offset + N)
+        .map(|bytes| bytes.try_into().unwrap())
+        .ok_or_else(malformed)
+}
+
+fn read_u16(bytes: &[u8], offset: usize) -> io::Result<u16> {
+    read_bytes(bytes, offset).map(u16::from_le_bytes)
+}
+
+fn read_u32(bytes: &[u8], offset: usize) -> io::Result<u32> {
+    read_bytes(bytes, offset).map(u32::from_le_bytes)
+}
+
+fn read_u64(bytes: &[u8], offset: usize) -> io::Result<u64> {
+    read_bytes(bytes, offset).map(u64::from_le_bytes)
+}
+
+/// Reads a nul-terminated string from the start of `bytes`.
+fn read_str(bytes: &[u8]) -> io::Result<Vec<u8>> {
+    let len = bytes.iter().position(|&b| b == 0).ok_or_else(malformed)?;
+    Ok(bytes[..len].to_vec())
+}
+
+#[cfg(test)]
+mod tests;
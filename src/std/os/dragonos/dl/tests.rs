@@ -0,0 +1,101 @@
+use super::dependencies;
+use crate::std::ffi::OsStr;
+use crate::std::fs;
+use crate::std::io::ErrorKind;
+
+/// Builds a minimal ELF64 object with one `PT_LOAD` segment covering the
+/// whole file and a dynamic section with the given needed names and runpath.
+fn elf(needed: &[&str], runpath: Option<&str>) -> Vec<u8> {
+    const EHDR: usize = 64;
+    const PHDR: usize = 56;
+    let dynamic = EHDR + 2 * PHDR;
+
+    let mut strtab = vec![0u8];
+    let mut add_str = |s: &str| {
+        let offset = strtab.len() as u64;
+        strtab.extend_from_slice(s.as_bytes());
+        strtab.push(0);
+        offset
+    };
+    let mut entries: Vec<(u64, u64)> = needed.iter().map(|name| (1, add_str(name))).collect();
+    if let Some(runpath) = runpath {
+        entries.push((29, add_str(runpath)));
+    }
+    let strtab_offset = dynamic + (entries.len() + 2) * 16;
+    entries.push((5, strtab_offset as u64));
+    entries.push((0, 0));
+    let len = strtab_offset + strtab.len();
+
+    let mut out = Vec::with_capacity(len);
+    out.extend_from_slice(b"\x7fELF\x02\x01\x01");
+    out.resize(0x20, 0);
+    out.extend_from_slice(&(EHDR as u64).to_le_bytes()); // e_phoff
+    out.resize(0x36, 0);
+    out.extend_from_slice(&(PHDR as u16).to_le_bytes()); // e_phentsize
+    out.extend_from_slice(&2u16.to_le_bytes()); // e_phnum
+    out.resize(EHDR, 0);
+    for (p_type, offset, size) in [(1u32, 0, len), (2, dynamic, len - dynamic)] {
+        let start = out.len();
+        out.extend_from_slice(&p_type.to_le_bytes());
+        out.resize(start + 8, 0);
+        out.extend_from_slice(&(offset as u64).to_le_bytes()); // p_offset
+        out.extend_from_slice(&(offset as u64).to_le_bytes()); // p_vaddr
+        out.resize(start + 0x20, 0);
+        out.extend_from_slice(&(size as u64).to_le_bytes()); // p_filesz
+        out.resize(start + PHDR, 0);
+    }
+    for (tag, val) in entries {
+        out.extend_from_slice(&tag.to_le_bytes());
+        out.extend_from_slice(&val.to_le_bytes());
+    }
+    out.extend_from_slice(&strtab);
+    out
+}
+
+#[test]
+fn missing_dependency_has_no_path() {
+    let dir = crate::std::sys_common::io::test::tmpdir();
+    let program = dir.join("program");
+    fs::write(&program, elf(&["libdragonos-dl-missing.so"], None)).unwrap();
+
+    let deps = dependencies(&program).unwrap();
+    assert_eq!(deps.len(), 1);
+    assert_eq!(deps[0].name(), "libdragonos-dl-missing.so");
+    assert_eq!(deps[0].path(), None);
+}
+
+#[test]
+fn resolves_through_origin_runpath() {
+    let dir = crate::std::sys_common::io::test::tmpdir();
+    fs::create_dir(dir.join("lib")).unwrap();
+    let program = dir.join("program");
+    let lib = dir.join("lib/libdragonos-dl-a.so");
+    fs::write(&program, elf(&["libdragonos-dl-a.so"], Some("$ORIGIN/lib"))).unwrap();
+    // Needs itself, which must only be listed once, and a missing object.
+    fs::write(
+        &lib,
+        elf(&["libdragonos-dl-a.so", "libdragonos-dl-b.so"], None),
+    )
+    .unwrap();
+
+    let deps = dependencies(&program).unwrap();
+    let found: Vec<_> = deps.iter().map(|d| (d.name(), d.path())).collect();
+    assert_eq!(
+        found,
+        [
+            (OsStr::new("libdragonos-dl-a.so"), Some(&*lib)),
+            (OsStr::new("libdragonos-dl-b.so"), None),
+        ]
+    );
+}
+
+#[test]
+fn rejects_non_elf() {
+    let dir = crate::std::sys_common::io::test::tmpdir();
+    let path = dir.join("script");
+    fs::write(&path, "#!/bin/sh\n").unwrap();
+    assert_eq!(
+        dependencies(&path).unwrap_err().kind(),
+        ErrorKind::InvalidData
+    );
+}
@@ -0,0 +1,433 @@
+//! Resolving host names without blocking a thread.
+//!
+//! [`ToSocketAddrs`] resolves names through dlibc's `getaddrinfo`, which
+//! blocks the calling thread until the nameserver answers or gives up. In a
+//! server that runs many connections on one thread, a slow lookup then
+//! stalls all of them. A [`Query`] made by a [`Resolver`] instead sends its
+//! requests over a non-blocking UDP socket, which an event loop can watch
+//! with `epoll` like any other descriptor, and [`Resolver::poll_query`]
+//! advances it whenever the socket is readable or the query's deadline has
+//! passed.
+//!
+//! ```no_run
+//! use std::os::dragonos::dns::Resolver;
+//! use std::task::Poll;
+//!
+//! let resolver = Resolver::from_system()?;
+//! let mut query = resolver.query("example.org")?;
+//! // Register `query.as_fd()` with epoll, and wait at most until
+//! // `query.deadline()`. Whenever that wait ends:
+//! match resolver.poll_query(&mut query) {
+//!     Poll::Ready(Ok(addrs)) => println!("{addrs:?}"),
+//!     Poll::Ready(Err(e)) => eprintln!("lookup failed: {e}"),
+//!     Poll::Pending => { /* wait again */ }
+//! }
+//! # Ok::<(), std::io::Error>(())
+//! ```
+//!
+//! Only the nameservers are asked: `/etc/hosts` isn't consulted, and no
+//! search domains are appended to the name.
+//!
+//! [`ToSocketAddrs`]: crate::std::net::ToSocketAddrs
+
+use crate::std::fs;
+use crate::std::io;
+use crate::std::net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr, UdpSocket};
+use crate::std::os::unix::io::{AsFd, AsRawFd, BorrowedFd, RawFd};
+use crate::std::task::Poll;
+use crate::std::time::{Duration, Instant};
+
+const TYPE_A: u16 = 1;
+const TYPE_AAAA: u16 = 28;
+const CLASS_IN: u16 = 1;
+
+/// Where and how to send queries, like `/etc/resolv.conf` describes.
+#[derive(Clone, Debug)]
+pub struct Resolver {
+    nameservers: Vec<SocketAddr>,
+    timeout: Duration,
+    attempts: u32,
+}
+
+impl Resolver {
+    /// Creates a resolver that asks `nameservers` in turn, waiting 5 seconds
+    /// for each and going through the list twice.
+    ///
+    /// A query is sent from a socket of the address family of the first
+    /// nameserver, and skips the nameservers of the other family.
+    pub fn with_nameservers(nameservers: &[SocketAddr]) -> Resolver {
+        Resolver {
+            nameservers: nameservers.to_vec(),
+            timeout: Duration::from_secs(5),
+            attempts: 2,
+        }
+    }
+
+    /// Creates a resolver configured by the `nameserver` lines and the
+    /// `timeout` and `attempts` options of `/etc/resolv.conf`.
+    ///
+    /// As in C libraries, a missing file or one without nameservers means
+    /// the nameserver on the local host.
+    pub fn from_system() -> io::Result<Resolver> {
+        match fs::read_to_string("/etc/resolv.conf") {
+            Ok(conf) => Ok(Resolver::parse_conf(&conf)),
+            Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(Resolver::parse_conf("")),
+            Err(e) => Err(e),
+        }
+    }
+
+    fn parse_conf(conf: &str) -> Resolver {
+        let mut resolver = Resolver::with_nameservers(&[]);
+        for line in conf.lines() {
+            let mut words = line.split_whitespace();
+            match words.next() {
+                Some("nameserver") => {
+                    if let Some(ip) = words.next().and_then(|ip| ip.parse().ok()) {
+                        resolver.nameservers.push(SocketAddr::new(ip, 53));
+                    }
+                }
+                Some("options") => {
+                    for option in words {
+                        if let Some(secs) = option.strip_prefix("timeout:") {
+                            if let Ok(secs) = secs.parse() {
+                                resolver.timeout = Duration::from_secs(secs);
+                            }
+                        } else if let Some(n) = option.strip_prefix("attempts:") {
+                            if let Ok(n) = n.parse() {
+                                resolver.attempts = n;
+                            }
+                        }
+                    }
+                }
+                _ => {}
+            }
+        }
+        if resolver.nameservers.is_empty() {
+            resolver
+                .nameservers
+                .push(SocketAddr::new(IpAddr::V4(Ipv4Addr::LOCALHOST), 53));
+        }
+        resolver
+    }
+
+    /// Sets how long to wait for a nameserver's answer before asking the
+    /// next one.
+    pub fn set_timeout(&mut self, timeout: Duration) {
+        self.timeout = timeout;
+    }
+
+    /// Sets how many times a query goes through the list of nameservers
+    /// before it fails with [`TimedOut`].
+    ///
+    /// [`TimedOut`]: io::ErrorKind::TimedOut
+    pub fn set_attempts(&mut self, attempts: u32) {
+        self.attempts = attempts;
+    }
+
+    /// Starts looking up the IPv4 and IPv6 addresses of `host`.
+    ///
+    /// The requests are sent before this returns. If `host` is an IP
+    /// address, the query is complete right away.
+    pub fn query(&self, host: &str) -> io::Result<Query> {
+        let first = self.nameservers.first().ok_or(io::const_io_error!(
+            io::ErrorKind::InvalidInput,
+            "no nameservers to query",
+        ))?;
+        let unspecified = match first {
+            SocketAddr::V4(_) => IpAddr::V4(Ipv4Addr::UNSPECIFIED),
+            SocketAddr::V6(_) => IpAddr::V6(Ipv6Addr::UNSPECIFIED),
+        };
+        let socket = UdpSocket::bind(SocketAddr::new(unspecified, 0))?;
+        socket.set_nonblocking(true)?;
+
+        let mut query = Query {
+            socket,
+            question: Vec::new(),
+            id: crate::std::sys::hashmap_random_keys().0 as u16,
+            pending: [false; 2],
+            addrs: Vec::new(),
+            server: 0,
+            attempt: 0,
+            deadline: Instant::now(),
+        };
+        if let Ok(ip) = host.parse() {
+            query.addrs.push(ip);
+            return Ok(query);
+        }
+        query.question = encode_name(host)?;
+        query.pending = [true; 2];
+        self.send(&mut query)?;
+        Ok(query)
+    }
+
+    /// Advances `query`, which must have been made by this resolver.
+    ///
+    /// Call this when the query's socket is readable or its [`deadline`]
+    /// has passed; calling it at other times is harmless. Returns the
+    /// addresses found, once both the IPv4 and the IPv6 answer are in. Fails
+    /// with [`NotFound`] if the nameserver knows no address for the name,
+    /// and with [`TimedOut`] if no nameserver answered.
+    ///
+    /// [`deadline`]: Query::deadline
+    /// [`NotFound`]: io::ErrorKind::NotFound
+    /// [`TimedOut`]: io::ErrorKind::TimedOut
+    pub fn poll_query(&self, query: &mut Query) -> Poll<io::Result<Vec<IpAddr>>> {
+        let mut buf = [0; 1232];
+        while query.pending != [false; 2] {
+            match query.socket.recv_from(&mut buf) {
+                Ok((len, from)) => {
+                    if self.nameservers.contains(&from) {
+                        query.receive(&buf[..len]);
+                    }
+                }
+                Err(e) if e.kind() == io::ErrorKind::WouldBlock => break,
+                Err(e) if e.kind() == io::ErrorKind::Interrupted => {}
+                Err(e) => return Poll::Ready(Err(e)),
+            }
+        }
+        if query.pending == [false; 2] {
+            return Poll::Ready(if query.addrs.is_empty() {
+                Err(io::const_io_error!(
+                    io::ErrorKind::NotFound,
+                    "no addresses found for the host name",
+                ))
+            } else {
+                Ok(query.addrs.clone())
+            });
+        }
+
+        if Instant::now() >= query.deadline {
+            if !self.next_server(query) {
+                return Poll::Ready(Err(io::const_io_error!(
+                    io::ErrorKind::TimedOut,
+                    "no nameserver answered",
+                )));
+            }
+            if let Err(e) = self.send(query) {
+                return Poll::Ready(Err(e));
+            }
+        }
+        Poll::Pending
+    }
+
+    /// Looks up the addresses of `host`, blocking the calling thread until
+    /// the query completes.
+    pub fn lookup(&self, host: &str) -> io::Result<Vec<IpAddr>> {
+        let mut query = self.query(host)?;
+        loop {
+            if let Poll::Ready(result) = self.poll_query(&mut query) {
+                return result;
+            }
+            let timeout = query.deadline.saturating_duration_since(Instant::now());
+            let mut pollfd = dlibc::pollfd {
+                fd: query.as_raw_fd(),
+                events: dlibc::POLLIN,
+                revents: 0,
+            };
+            let ms = timeout.as_millis().min(dlibc::c_int::MAX as u128) as dlibc::c_int;
+            if unsafe { dlibc::poll(&mut pollfd, 1, ms) } == -1 {
+                let e = io::Error::last_os_error();
+                if e.kind() != io::ErrorKind::Interrupted {
+                    return Err(e);
+                }
+            }
+        }
+    }
+
+    /// Moves `query` on to the next nameserver of its socket's family.
+    /// Returns `false` once it has been through the list `attempts` times.
+    fn next_server(&self, query: &mut Query) -> bool {
+        let ipv4 = self.nameservers[query.server].is_ipv4();
+        loop {
+            query.server += 1;
+            if query.server == self.nameservers.len() {
+                query.server = 0;
+                query.attempt += 1;
+            }
+            if query.attempt >= self.attempts {
+                return false;
+            }
+            if self.nameservers[query.server].is_ipv4() == ipv4 {
+                return true;
+            }
+        }
+    }
+
+    /// Sends the unanswered questions of `query` to its current nameserver.
+    fn send(&self, query: &mut Query) -> io::Result<()> {
+        let server = self.nameservers[query.server];
+        for (i, qtype) in [TYPE_A, TYPE_AAAA].into_iter().enumerate() {
+            if query.pending[i] {
+                let message = encode_query(query.id.wrapping_add(i as u16), &query.question, qtype);
+                match query.socket.send_to(&message, server) {
+                    // A full socket buffer counts as a lost request.
+                    Err(e) if e.kind() != io::ErrorKind::WouldBlock => return Err(e),
+                    _ => {}
+                }
+            }
+        }
+        query.deadline = Instant::now() + self.timeout;
+        Ok(())
+    }
+}
+
+/// A lookup in progress, started by [`Resolver::query`].
+///
+/// The query owns a non-blocking UDP socket, available through [`AsFd`],
+/// which stays the same until the query is dropped.
+#[derive(Debug)]
+pub struct Query {
+    socket: UdpSocket,
+    // The name in the encoding of DNS messages.
+    question: Vec<u8>,
+    // The A question uses `id`, the AAAA question `id + 1`.
+    id: u16,
+    // Whether the A and AAAA answers are still missing.
+    pending: [bool; 2],
+    addrs: Vec<IpAddr>,
+    server: usize,
+    attempt: u32,
+    deadline: Instant,
+}
+
+impl Query {
+    /// Returns when [`Resolver::poll_query`] should be called at the latest,
+    /// to ask the next nameserver or give up.
+    pub fn deadline(&self) -> Instant {
+        self.deadline
+    }
+
+    /// Takes in a response, ignoring it unless it answers a pending
+    /// question.
+    fn receive(&mut self, message: &[u8]) {
+        let Some(response) = parse_response(message) else {
+            return;
+        };
+        let i = response.id.wrapping_sub(self.id) as usize;
+        if i > 1 || !self.pending[i] || response.question != self.question {
+            return;
+        }
+        // Server failures and refusals are left to time out, so the next
+        // nameserver gets asked.
+        if response.rcode != 0 && response.rcode != 3 {
+            return;
+        }
+        self.pending[i] = false;
+        self.addrs.extend(response.addrs);
+    }
+}
+
+impl AsFd for Query {
+    fn as_fd(&self) -> BorrowedFd<'_> {
+        self.socket.as_fd()
+    }
+}
+
+impl AsRawFd for Query {
+    fn as_raw_fd(&self) -> RawFd {
+        self.socket.as_raw_fd()
+    }
+}
+
+/// Encodes `host` as a sequence of length-prefixed labels.
+fn encode_name(host: &str) -> io::Result<Vec<u8>> {
+    let host = host.strip_suffix('.').unwrap_or(host);
+    let invalid = || io::const_io_error!(io::ErrorKind::InvalidInput, "invalid host name");
+    if host.is_empty() || host.len() > 253 {
+        return Err(invalid());
+    }
+    let mut name = Vec::with_capacity(host.len() + 2);
+    for label in host.split('.') {
+        if label.is_empty() || label.len() > 63 {
+            return Err(invalid());
+        }
+        name.push(label.len() as u8);
+        name.extend(label.bytes().map(|b| b.to_ascii_lowercase()));
+    }
+    name.push(0);
+    Ok(name)
+}
+
+fn encode_query(id: u16, name: &[u8], qtype: u16) -> Vec<u8> {
+    let mut message = Vec::with_capacity(12 + name.len() + 4);
+    message.extend_from_slice(&id.to_be_bytes());
+    // A standard query that asks for recursion, with one question.
+    message.extend_from_slice(&[0x01, 0x00, 0, 1, 0, 0, 0, 0, 0, 0]);
+    message.extend_from_slice(name);
+    message.extend_from_slice(&qtype.to_be_bytes());
+    message.extend_from_slice(&CLASS_IN.to_be_bytes());
+    message
+}
+
+struct Response {
+    id: u16,
+    rcode: u8,
+    question: Vec<u8>,
+    addrs: Vec<IpAddr>,
+}
+
+fn parse_response(message: &[u8]) -> Option<Response> {
+    let header = message.get(..12)?;
+    let id = u16::from_be_bytes([header[0], header[1]]);
+    // Only responses with exactly the one question we asked.
+    if header[2] & 0x80 == 0 || header[4..6] != [0, 1] {
+        return None;
+    }
+    let rcode = header[3] & 0x0f;
+    let answers = u16::from_be_bytes([header[6], header[7]]);
+
+    let mut pos = 12;
+    let question_start = pos;
+    pos = skip_name(message, pos)?;
+    let question = message[question_start..pos].to_ascii_lowercase();
+    pos += 4;
+
+    let mut addrs = Vec::new();
+    for _ in 0..answers {
+        pos = skip_name(message, pos)?;
+        let fixed = message.get(pos..pos + 10)?;
+        let rtype = u16::from_be_bytes([fixed[0], fixed[1]]);
+        let class = u16::from_be_bytes([fixed[2], fixed[3]]);
+        let len = u16::from_be_bytes([fixed[8], fixed[9]]) as usize;
+        pos += 10;
+        let data = message.get(pos..pos + len)?;
+        pos += len;
+        // Aliases (CNAME records) come with the records of the name they
+        // point to, so only the addresses matter.
+        match (class, rtype, data.len()) {
+            (CLASS_IN, TYPE_A, 4) => {
+                addrs.push(IpAddr::V4(Ipv4Addr::from(<[u8; 4]>::try_from(data).ok()?)))
+            }
+            (CLASS_IN, TYPE_AAAA, 16) => {
+                addrs.push(IpAddr::V6(Ipv6Addr::from(<[u8; 16]>::try_from(data).ok()?)))
+            }
+            _ => {}
+        }
+    }
+    Some(Response {
+        id,
+        rcode,
+        question,
+        addrs,
+    })
+}
+
+/// Returns the position after the name at `pos`, which ends with either an
+/// empty label or a pointer to another name.
+fn skip_name(message: &[u8], mut pos: usize) -> Option<usize> {
+    loop {
+        let len = *message.get(pos)?;
+        match len {
+            0 => return Some(pos + 1),
+            len if len & 0xc0 == 0xc0 => {
+                message.get(pos + 1)?;
+                return Some(pos + 2);
+            }
+            len if len & 0xc0 == 0 => pos += 1 + len as usize,
+            _ => return None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests;
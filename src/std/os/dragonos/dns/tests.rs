@@ -0,0 +1,124 @@
+use super::{encode_name, encode_query, parse_response, Resolver, TYPE_A, TYPE_AAAA};
+use crate::std::io;
+use crate::std::net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr, UdpSocket};
+use crate::std::thread;
+use crate::std::time::Duration;
+
+#[test]
+fn resolv_conf() {
+    let resolver = Resolver::parse_conf(
+        "# comment\n\
+         nameserver 10.0.0.1\n\
+         nameserver ::1\n\
+         nameserver bogus\n\
+         options ndots:2 timeout:3 attempts:4\n",
+    );
+    assert_eq!(
+        resolver.nameservers,
+        ["10.0.0.1:53".parse().unwrap(), "[::1]:53".parse().unwrap()]
+    );
+    assert_eq!(resolver.timeout, Duration::from_secs(3));
+    assert_eq!(resolver.attempts, 4);
+
+    let resolver = Resolver::parse_conf("");
+    assert_eq!(resolver.nameservers, ["127.0.0.1:53".parse().unwrap()]);
+}
+
+#[test]
+fn names() {
+    assert_eq!(
+        encode_name("Example.org.").unwrap(),
+        b"\x07example\x03org\x00"
+    );
+    assert!(encode_name("").is_err());
+    assert!(encode_name("a..b").is_err());
+    assert!(encode_name(&"a".repeat(64)).is_err());
+}
+
+/// Answers `query` with `addrs`, pointing back at the question for the name
+/// of each record, or with NXDOMAIN if there are none.
+fn answer(query: &[u8], addrs: &[IpAddr]) -> Vec<u8> {
+    let mut response = query.to_vec();
+    response[2] |= 0x80;
+    if addrs.is_empty() {
+        response[3] |= 3;
+    }
+    response[6..8].copy_from_slice(&(addrs.len() as u16).to_be_bytes());
+    for addr in addrs {
+        response.extend_from_slice(&[0xc0, 12]);
+        let (rtype, data) = match addr {
+            IpAddr::V4(ip) => (TYPE_A, ip.octets().to_vec()),
+            IpAddr::V6(ip) => (TYPE_AAAA, ip.octets().to_vec()),
+        };
+        response.extend_from_slice(&rtype.to_be_bytes());
+        response.extend_from_slice(&[0, 1, 0, 0, 0, 60]);
+        response.extend_from_slice(&(data.len() as u16).to_be_bytes());
+        response.extend_from_slice(&data);
+    }
+    response
+}
+
+#[test]
+fn responses() {
+    let name = encode_name("example.org").unwrap();
+    let query = encode_query(7, &name, TYPE_A);
+    let ip = IpAddr::V4(Ipv4Addr::new(192, 0, 2, 1));
+    let response = parse_response(&answer(&query, &[ip])).unwrap();
+    assert_eq!((response.id, response.rcode), (7, 0));
+    assert_eq!(response.question, name);
+    assert_eq!(response.addrs, [ip]);
+
+    assert_eq!(parse_response(&answer(&query, &[])).unwrap().rcode, 3);
+    // The query itself is not a response, and truncated messages are no
+    // messages at all.
+    assert!(parse_response(&query).is_none());
+    assert!(parse_response(&answer(&query, &[ip])[..40]).is_none());
+}
+
+#[test]
+fn lookup() {
+    let server = UdpSocket::bind("127.0.0.1:0").unwrap();
+    let resolver = Resolver::with_nameservers(&[server.local_addr().unwrap()]);
+    let v4 = IpAddr::V4(Ipv4Addr::new(192, 0, 2, 1));
+    let v6 = IpAddr::V6(Ipv6Addr::new(0x2001, 0xdb8, 0, 0, 0, 0, 0, 1));
+    let t = thread::spawn(move || {
+        let mut buf = [0; 512];
+        for _ in 0..2 {
+            let (len, from) = server.recv_from(&mut buf).unwrap();
+            let query = &buf[..len];
+            let qtype = u16::from_be_bytes([query[len - 4], query[len - 3]]);
+            let addr = if qtype == TYPE_A { v4 } else { v6 };
+            server.send_to(&answer(query, &[addr]), from).unwrap();
+        }
+    });
+    let mut addrs = resolver.lookup("example.org").unwrap();
+    addrs.sort();
+    assert_eq!(addrs, [v4, v6]);
+    t.join().unwrap();
+
+    assert_eq!(
+        resolver.lookup("192.0.2.7").unwrap(),
+        [IpAddr::V4(Ipv4Addr::new(192, 0, 2, 7))]
+    );
+}
+
+#[test]
+fn timeout() {
+    // Bound, so requests aren't refused, but never answering.
+    let server = UdpSocket::bind("127.0.0.1:0").unwrap();
+    let other: SocketAddr = "[::1]:53".parse().unwrap();
+    let mut resolver = Resolver::with_nameservers(&[server.local_addr().unwrap(), other]);
+    resolver.set_timeout(Duration::from_millis(20));
+    resolver.set_attempts(2);
+    let err = resolver.lookup("example.org").unwrap_err();
+    assert_eq!(err.kind(), io::ErrorKind::TimedOut);
+
+    let mut buf = [0; 512];
+    server.set_nonblocking(true).unwrap();
+    let mut received = 0;
+    while server.recv_from(&mut buf).is_ok() {
+        received += 1;
+    }
+    // Both questions, once per attempt; the IPv6 nameserver is skipped.
+    assert_eq!(received, 4);
+}
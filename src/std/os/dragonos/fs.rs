@@ -0,0 +1,155 @@
+//! DragonOS-specific extensions to primitives in the [`std::fs`] module.
+//!
+//! [`std::fs`]: crate::std::fs
+
+use crate::std::fs;
+use crate::std::io;
+use crate::std::mem;
+use crate::std::sealed::Sealed;
+use crate::std::sync::{Arc, PoisonError, RwLock};
+use crate::std::sys_common::AsInner;
+use crate::std::thread;
+use crate::std::time::Duration;
+
+/// Os-specific extensions for [`fs::DirEntry`].
+pub trait DirEntryExt: Sealed {
+    /// Returns the inode number of the entry, the `d_ino` field of its
+    /// `dirent`.
+    ///
+    /// Two entries with the same inode number on the same file system are
+    /// hard links to the same file, which lets a traversal visit each file
+    /// once without calling [`fs::metadata`] on every entry.
+    ///
+    /// ```no_run
+    /// use std::collections::HashSet;
+    /// use std::fs;
+    /// use std::os::dragonos::fs::DirEntryExt;
+    ///
+    /// let mut seen = HashSet::new();
+    /// for entry in fs::read_dir("/data")? {
+    ///     let entry = entry?;
+    ///     if !seen.insert(entry.ino()) {
+    ///         println!("{:?} is a hard link to a file already listed", entry.file_name());
+    ///     }
+    /// }
+    /// # Ok::<(), std::io::Error>(())
+    /// ```
+    fn ino(&self) -> u64;
+
+    /// Returns the position of the directory stream just after this entry,
+    /// the `d_off` field of its `dirent`.
+    ///
+    /// The value is opaque: it is only meaningful to the file system that
+    /// returned it, as the position to continue a listing of the same
+    /// directory from with `seekdir`. Entries don't come in the order of
+    /// their offsets.
+    fn offset(&self) -> i64;
+}
+
+impl DirEntryExt for fs::DirEntry {
+    fn ino(&self) -> u64 {
+        self.as_inner().ino()
+    }
+
+    fn offset(&self) -> i64 {
+        self.as_inner().offset()
+    }
+}
+
+/// A policy for retrying file system operations, registered with
+/// [`set_retry_policy`].
+///
+/// It is shared so that operations can call it without holding the lock
+/// that [`set_retry_policy`] takes to replace it.
+pub type RetryPolicy = Arc<dyn Fn(&io::Error, u32) -> Option<Duration> + Sync + Send + 'static>;
+
+static RETRY_POLICY: RwLock<Option<RetryPolicy>> = RwLock::new(None);
+
+/// Registers a policy for retrying file system operations that fail with a
+/// transient error, replacing the previously registered policy.
+///
+/// A network file system can fail operations with `EIO`, `ESTALE` or
+/// `ETIMEDOUT` while its server fails over, and succeed again once it is
+/// back. When an operation fails with one of these, the policy is called
+/// with the error and the number of times the operation has failed so far,
+/// starting at 1. It returns how long to sleep before trying again, or
+/// `None` to give up and return the error. No policy is registered by
+/// default, so that errors are returned at once.
+///
+/// Only operations that can be repeated without changing their outcome
+/// are retried: opening a file other than with [`create_new`], reading
+/// metadata, links and directories, and [`fs::canonicalize`]. Reads and
+/// writes on an open file, and operations like [`fs::remove_file`] that
+/// may have taken effect before failing, are not.
+///
+/// The policy is global to the process. Operations that are already
+/// retrying when it is replaced keep calling the one they started with.
+///
+/// ```no_run
+/// use std::os::dragonos::fs;
+/// use std::sync::Arc;
+/// use std::time::Duration;
+///
+/// // Up to 5 retries, waiting 50ms, 100ms, 200ms, 400ms and 800ms.
+/// fs::set_retry_policy(Arc::new(|_error, attempt| {
+///     (attempt <= 5).then(|| Duration::from_millis(25 << attempt))
+/// }));
+/// ```
+///
+/// [`create_new`]: fs::OpenOptions::create_new
+pub fn set_retry_policy(policy: RetryPolicy) {
+    let mut slot = RETRY_POLICY.write().unwrap_or_else(PoisonError::into_inner);
+    let old = mem::replace(&mut *slot, Some(policy));
+    drop(slot);
+    // Only drop the old policy after releasing the lock, in case its
+    // destructor does file system operations.
+    drop(old);
+}
+
+/// Unregisters the current retry policy and returns it, so that
+/// operations fail at once again.
+///
+/// See [`set_retry_policy`].
+pub fn take_retry_policy() -> Option<RetryPolicy> {
+    RETRY_POLICY
+        .write()
+        .unwrap_or_else(PoisonError::into_inner)
+        .take()
+}
+
+/// Runs `op`, repeating it for as long as it fails with a transient error
+/// and the [retry policy](set_retry_policy) asks to try again.
+pub(crate) fn retry<T>(mut op: impl FnMut() -> io::Result<T>) -> io::Result<T> {
+    let mut attempt = 0;
+    loop {
+        let error = match op() {
+            Ok(value) => return Ok(value),
+            Err(error) => error,
+        };
+        if !matches!(
+            error.raw_os_error(),
+            Some(dlibc::EIO | dlibc::ESTALE | dlibc::ETIMEDOUT)
+        ) {
+            return Err(error);
+        }
+        attempt += 1;
+        // Call the policy without holding the lock, so that it can
+        // register another one, and so that a replaced policy is dropped
+        // outside of it.
+        let policy = RETRY_POLICY
+            .read()
+            .unwrap_or_else(PoisonError::into_inner)
+            .clone();
+        let delay = match policy {
+            Some(policy) => policy(&error, attempt),
+            None => None,
+        };
+        match delay {
+            Some(delay) => thread::sleep(delay),
+            None => return Err(error),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests;
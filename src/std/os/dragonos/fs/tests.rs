@@ -0,0 +1,121 @@
+use super::DirEntryExt;
+use crate::std::collections::HashSet;
+use crate::std::fs;
+use crate::std::io::{Read, Seek, SeekFrom};
+use crate::std::os::unix::fs::MetadataExt;
+
+#[test]
+fn ino_matches_metadata_and_hard_links() {
+    let dir = crate::std::sys_common::io::test::tmpdir();
+    fs::write(dir.join("a"), b"a").unwrap();
+    fs::hard_link(dir.join("a"), dir.join("b")).unwrap();
+    fs::write(dir.join("c"), b"c").unwrap();
+
+    let mut entries = fs::read_dir(dir.path())
+        .unwrap()
+        .map(|e| e.unwrap())
+        .collect::<Vec<_>>();
+    entries.sort_by_key(|e| e.file_name());
+    assert_eq!(entries.len(), 3);
+
+    for entry in &entries {
+        assert_eq!(entry.ino(), entry.metadata().unwrap().ino());
+    }
+    assert_eq!(entries[0].ino(), entries[1].ino());
+    assert_ne!(entries[0].ino(), entries[2].ino());
+}
+
+#[test]
+fn offsets_are_distinct() {
+    let dir = crate::std::sys_common::io::test::tmpdir();
+    for name in ["a", "b", "c", "d"] {
+        fs::write(dir.join(name), b"").unwrap();
+    }
+    let offsets = fs::read_dir(dir.path())
+        .unwrap()
+        .map(|e| e.unwrap().offset())
+        .collect::<HashSet<_>>();
+    assert_eq!(offsets.len(), 4);
+}
+
+#[test]
+fn read_to_end_is_sized_from_the_file() {
+    let dir = crate::std::sys_common::io::test::tmpdir();
+    let path = dir.join("big");
+    let data: Vec<u8> = (0..3 << 20).map(|i: u32| i as u8).collect();
+    fs::write(&path, &data).unwrap();
+
+    // One allocation of exactly the file size, not repeated doubling.
+    let bytes = fs::read(&path).unwrap();
+    assert_eq!(bytes, data);
+    assert_eq!(bytes.capacity(), data.len());
+
+    // Only what is left after the current position is reserved.
+    let mut file = fs::File::open(&path).unwrap();
+    file.seek(SeekFrom::Start(1 << 20)).unwrap();
+    let mut rest = Vec::new();
+    file.read_to_end(&mut rest).unwrap();
+    assert_eq!(rest, data[1 << 20..]);
+    assert_eq!(rest.capacity(), data.len() - (1 << 20));
+}
+
+#[test]
+fn retry_follows_policy() {
+    use super::{retry, set_retry_policy, take_retry_policy};
+    use crate::std::cell::Cell;
+    use crate::std::io;
+    use crate::std::sync::Arc;
+    use crate::std::time::Duration;
+
+    let failing = |errno, failures| {
+        let calls = Cell::new(0);
+        let result = retry(|| {
+            calls.set(calls.get() + 1);
+            if calls.get() <= failures {
+                Err(io::Error::from_raw_os_error(errno))
+            } else {
+                Ok(())
+            }
+        });
+        (result.map_err(|e| e.raw_os_error()), calls.get())
+    };
+
+    // Nothing is retried without a policy.
+    assert!(take_retry_policy().is_none());
+    assert_eq!(failing(dlibc::ESTALE, 1), (Err(Some(dlibc::ESTALE)), 1));
+
+    set_retry_policy(Arc::new(|_, attempt| {
+        (attempt <= 3).then_some(Duration::from_millis(1))
+    }));
+    assert_eq!(failing(dlibc::ESTALE, 3), (Ok(()), 4));
+    assert_eq!(failing(dlibc::EIO, 4), (Err(Some(dlibc::EIO)), 4));
+    // Errors that aren't transient are returned at once.
+    assert_eq!(failing(dlibc::ENOENT, 1), (Err(Some(dlibc::ENOENT)), 1));
+    assert!(take_retry_policy().is_some());
+}
+
+#[test]
+fn file_times_keep_nanoseconds() {
+    use crate::std::time::{Duration, SystemTime};
+
+    let dir = crate::std::sys_common::io::test::tmpdir();
+    let file = fs::File::create(dir.join("f")).unwrap();
+    let time = SystemTime::UNIX_EPOCH + Duration::new(1_700_000_000, 123_456_789);
+    file.set_modified(time).unwrap();
+    assert_eq!(file.metadata().unwrap().modified().unwrap(), time);
+
+    // Times before the epoch keep their nanoseconds positive: this one is
+    // -2 seconds plus 750_000_000 nanoseconds.
+    let before = SystemTime::UNIX_EPOCH - Duration::new(1, 250_000_000);
+    assert_eq!(
+        before
+            .duration_since(SystemTime::UNIX_EPOCH)
+            .unwrap_err()
+            .duration(),
+        Duration::new(1, 250_000_000)
+    );
+    assert_eq!(
+        time.duration_since(before).unwrap(),
+        Duration::new(1_700_000_001, 373_456_789)
+    );
+}
@@ -0,0 +1,67 @@
+//! Waiting on and waking up threads through an atomic integer.
+//!
+//! These are the futex operations the locks in [`std::sync`] are built on,
+//! for libraries that implement their own. A thread [`wait`]s while an
+//! [`AtomicU32`] still holds the value it expects; another thread changes
+//! the value and then wakes it with [`wake_one`] or [`wake_all`]. Since the
+//! kernel compares the value and puts the thread to sleep in one step, a
+//! wake-up that happens in between isn't lost.
+//!
+//! The futexes are private to the process: only threads of the same
+//! process wake each other up. For memory shared with other processes, use
+//! [`sync`] instead.
+//!
+//! ```
+//! use std::os::dragonos::futex;
+//! use std::sync::atomic::{AtomicU32, Ordering};
+//! use std::thread;
+//!
+//! static READY: AtomicU32 = AtomicU32::new(0);
+//!
+//! let t = thread::spawn(|| {
+//!     while READY.load(Ordering::Acquire) == 0 {
+//!         futex::wait(&READY, 0, None);
+//!     }
+//! });
+//! READY.store(1, Ordering::Release);
+//! futex::wake_all(&READY);
+//! t.join().unwrap();
+//! ```
+//!
+//! [`std::sync`]: crate::std::sync
+//! [`sync`]: super::sync
+
+use crate::std::sync::atomic::AtomicU32;
+use crate::std::sys::futex;
+use crate::std::time::Duration;
+
+/// Blocks the calling thread while `futex` holds `expected`, until it is
+/// woken up or `timeout` has passed.
+///
+/// Returns immediately if `futex` doesn't hold `expected`. Returns `false`
+/// if the timeout passed, and `true` otherwise, which includes spurious
+/// wake-ups: a `true` result doesn't mean [`wake_one`] or [`wake_all`] was
+/// called, so check the value again.
+///
+/// Signals don't end the wait early. If a signal handler runs while the
+/// thread is blocked, the wait resumes afterwards, still ending at the
+/// original deadline. A `timeout` too large to represent waits forever, as
+/// `None` does.
+pub fn wait(futex: &AtomicU32, expected: u32, timeout: Option<Duration>) -> bool {
+    futex::futex_wait(futex, expected, timeout)
+}
+
+/// Wakes up one thread blocked in [`wait`] on `futex`.
+///
+/// Returns whether there was a thread to wake up.
+pub fn wake_one(futex: &AtomicU32) -> bool {
+    futex::futex_wake(futex)
+}
+
+/// Wakes up all threads blocked in [`wait`] on `futex`.
+pub fn wake_all(futex: &AtomicU32) {
+    futex::futex_wake_all(futex)
+}
+
+#[cfg(test)]
+mod tests;
@@ -0,0 +1,96 @@
+use super::{wait, wake_all, wake_one};
+use crate::std::mem;
+use crate::std::os::unix::thread::JoinHandleExt;
+use crate::std::ptr;
+use crate::std::sync::atomic::{AtomicBool, AtomicU32, Ordering::SeqCst};
+use crate::std::sync::Arc;
+use crate::std::thread;
+use crate::std::time::{Duration, Instant};
+
+#[test]
+fn value_changed() {
+    let futex = AtomicU32::new(1);
+    assert!(wait(&futex, 0, None));
+    assert!(!wake_one(&futex));
+}
+
+#[test]
+fn timeout() {
+    let futex = AtomicU32::new(0);
+    let start = Instant::now();
+    assert!(!wait(&futex, 0, Some(Duration::from_millis(50))));
+    assert!(start.elapsed() >= Duration::from_millis(50));
+}
+
+#[test]
+fn huge_timeout() {
+    let futex = Arc::new(AtomicU32::new(0));
+    let waiter = {
+        let futex = futex.clone();
+        thread::spawn(move || {
+            while futex.load(SeqCst) == 0 {
+                wait(&futex, 0, Some(Duration::MAX));
+            }
+        })
+    };
+    thread::sleep(Duration::from_millis(20));
+    futex.store(1, SeqCst);
+    wake_all(&futex);
+    waiter.join().unwrap();
+}
+
+#[test]
+fn wake() {
+    let futex = Arc::new(AtomicU32::new(0));
+    let waiters: Vec<_> = (0..4)
+        .map(|_| {
+            let futex = futex.clone();
+            thread::spawn(move || {
+                while futex.load(SeqCst) == 0 {
+                    wait(&futex, 0, None);
+                }
+            })
+        })
+        .collect();
+    futex.store(1, SeqCst);
+    wake_all(&futex);
+    for waiter in waiters {
+        waiter.join().unwrap();
+    }
+}
+
+#[test]
+fn signal_does_not_end_wait() {
+    extern "C" fn handler(_: dlibc::c_int) {
+        HANDLED.store(true, SeqCst);
+    }
+    static HANDLED: AtomicBool = AtomicBool::new(false);
+
+    // The handler is process-wide, so put the previous one back afterwards
+    // for the other tests in this process.
+    let mut previous: dlibc::sigaction = unsafe { mem::zeroed() };
+    unsafe {
+        let mut action: dlibc::sigaction = mem::zeroed();
+        action.sa_sigaction = handler as dlibc::sighandler_t;
+        assert_eq!(dlibc::sigaction(dlibc::SIGUSR2, &action, &mut previous), 0);
+    }
+    let start = Instant::now();
+    let waiter = thread::spawn(|| {
+        let futex = AtomicU32::new(0);
+        wait(&futex, 0, Some(Duration::from_millis(200)))
+    });
+    thread::sleep(Duration::from_millis(50));
+    unsafe {
+        dlibc::pthread_kill(waiter.as_pthread_t(), dlibc::SIGUSR2);
+    }
+    let notified = waiter.join().unwrap();
+    unsafe {
+        assert_eq!(
+            dlibc::sigaction(dlibc::SIGUSR2, &previous, ptr::null_mut()),
+            0
+        );
+    }
+    assert!(!notified);
+    assert!(HANDLED.load(SeqCst));
+    assert!(start.elapsed() >= Duration::from_millis(200));
+}
@@ -0,0 +1,306 @@
+//! Sampling of heap allocations, to find out where a program allocates
+//! without any external tooling.
+//!
+//! After [`start_sampling`], every Nth allocation made through the global
+//! allocator records its size and the return addresses of up to
+//! [`MAX_FRAMES`] of its callers. The most recent [`CAPACITY`] samples are
+//! kept; [`samples`] returns them, [`dump`] writes them out as text, and
+//! [`dump_on_signal`] does the same whenever the process gets a signal, so
+//! that a long-running service can be inspected from a shell:
+//!
+//! ```no_run
+//! use std::fs::File;
+//! use std::num::NonZeroUsize;
+//! use std::os::dragonos::heap;
+//!
+//! heap::start_sampling(NonZeroUsize::new(64).unwrap());
+//! heap::dump_on_signal(dlibc::SIGUSR2, File::create("/tmp/heap.txt")?.into())?;
+//! // `kill -USR2 <pid>` now appends the samples to /tmp/heap.txt.
+//! # Ok::<(), std::io::Error>(())
+//! ```
+//!
+//! Callers are found by following frame pointers, so the program should be
+//! built with `-C force-frame-pointers=yes`. They are only looked for on the
+//! stack of the allocating thread, which must be the main thread or one
+//! spawned by [`std::thread`]; samples from other threads have no callers.
+//! The addresses are written as they are in memory; subtract the load
+//! address of the executable, from `/proc/<pid>/maps`, before looking them
+//! up with `addr2line`.
+//!
+//! This module is only available with the `heap-profiling` feature.
+//!
+//! [`std::thread`]: crate::std::thread
+
+#[cfg(test)]
+mod tests;
+
+use crate::std::io::{self, Write};
+use crate::std::mem;
+use crate::std::num::NonZeroUsize;
+use crate::std::os::unix::io::{IntoRawFd, OwnedFd};
+use crate::std::ptr;
+use crate::std::sync::atomic::{fence, AtomicI32, AtomicUsize, Ordering};
+use crate::std::sys::cvt;
+use crate::std::sys_common::backtrace::trace_frame_pointers;
+use dlibc;
+
+/// The number of samples kept; older ones are overwritten.
+pub const CAPACITY: usize = 1024;
+
+/// The most callers recorded for each sample.
+pub const MAX_FRAMES: usize = 16;
+
+// Frames of the allocation hook and of the allocator calling it, which are
+// the same in every sample.
+const SKIPPED_FRAMES: usize = 2;
+
+// One sample, written by the hook and read by `samples` and the signal
+// handler. `seq` is odd while the sample is being written, and 0 until it
+// has been written once.
+struct Slot {
+    seq: AtomicUsize,
+    size: AtomicUsize,
+    len: AtomicUsize,
+    frames: [AtomicUsize; MAX_FRAMES],
+}
+
+impl Slot {
+    const EMPTY_FRAME: AtomicUsize = AtomicUsize::new(0);
+    const EMPTY: Slot = Slot {
+        seq: AtomicUsize::new(0),
+        size: AtomicUsize::new(0),
+        len: AtomicUsize::new(0),
+        frames: [Slot::EMPTY_FRAME; MAX_FRAMES],
+    };
+
+    fn write(&self, size: usize, frames: &[usize]) {
+        let seq = self.seq.load(Ordering::Relaxed);
+        // Another thread is writing this slot; drop the sample.
+        if seq % 2 == 1
+            || self
+                .seq
+                .compare_exchange(seq, seq + 1, Ordering::Relaxed, Ordering::Relaxed)
+                .is_err()
+        {
+            return;
+        }
+        fence(Ordering::Release);
+        self.size.store(size, Ordering::Relaxed);
+        self.len.store(frames.len(), Ordering::Relaxed);
+        for (slot, &frame) in self.frames.iter().zip(frames) {
+            slot.store(frame, Ordering::Relaxed);
+        }
+        self.seq.store(seq + 2, Ordering::Release);
+    }
+
+    fn read(&self) -> Option<RawSample> {
+        let seq = self.seq.load(Ordering::Acquire);
+        if seq == 0 || seq % 2 == 1 {
+            return None;
+        }
+        let mut sample = RawSample {
+            size: self.size.load(Ordering::Relaxed),
+            len: self.len.load(Ordering::Relaxed).min(MAX_FRAMES),
+            frames: [0; MAX_FRAMES],
+        };
+        for (frame, slot) in sample.frames.iter_mut().zip(&self.frames) {
+            *frame = slot.load(Ordering::Relaxed);
+        }
+        fence(Ordering::Acquire);
+        // Overwritten while it was being read.
+        if self.seq.load(Ordering::Relaxed) != seq {
+            return None;
+        }
+        Some(sample)
+    }
+}
+
+// A copy of a slot that doesn't allocate, for the signal handler.
+struct RawSample {
+    size: usize,
+    len: usize,
+    frames: [usize; MAX_FRAMES],
+}
+
+static SLOTS: [Slot; CAPACITY] = [Slot::EMPTY; CAPACITY];
+// The index of the slot the next sample goes into, modulo `CAPACITY`.
+static NEXT: AtomicUsize = AtomicUsize::new(0);
+// Every how many allocations a sample is taken; 0 when not sampling.
+static EVERY: AtomicUsize = AtomicUsize::new(0);
+static ALLOCATIONS: AtomicUsize = AtomicUsize::new(0);
+// Where `dump_on_signal` writes to, or -1.
+static SIGNAL_FD: AtomicI32 = AtomicI32::new(-1);
+
+// Called by the global allocator before every allocation, so it must not
+// allocate.
+fn on_alloc(size: usize) {
+    let every = EVERY.load(Ordering::Relaxed);
+    if every == 0 || ALLOCATIONS.fetch_add(1, Ordering::Relaxed) % every != 0 {
+        return;
+    }
+    let mut frames = [0; SKIPPED_FRAMES + MAX_FRAMES];
+    let len = trace_frame_pointers(&mut frames).max(SKIPPED_FRAMES);
+    let slot = NEXT.fetch_add(1, Ordering::Relaxed) % CAPACITY;
+    SLOTS[slot].write(size, &frames[SKIPPED_FRAMES..len]);
+}
+
+// Calls `f` with every sample, oldest first.
+fn for_each_sample(mut f: impl FnMut(&RawSample)) {
+    let next = NEXT.load(Ordering::Relaxed);
+    for i in 0..CAPACITY {
+        if let Some(sample) = SLOTS[(next + i) % CAPACITY].read() {
+            f(&sample);
+        }
+    }
+}
+
+/// Starts sampling one in every `every` allocations, or changes how often
+/// samples are taken if sampling is already on.
+///
+/// Samples taken before are kept.
+pub fn start_sampling(every: NonZeroUsize) {
+    EVERY.store(every.get(), Ordering::Relaxed);
+    dlibc::set_alloc_hook(Some(on_alloc));
+}
+
+/// Stops taking samples. The samples taken so far are kept.
+pub fn stop_sampling() {
+    dlibc::set_alloc_hook(None);
+    EVERY.store(0, Ordering::Relaxed);
+}
+
+/// A sampled allocation, as returned by [`samples`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Sample {
+    size: usize,
+    frames: Vec<usize>,
+}
+
+impl Sample {
+    /// Returns the number of bytes allocated, rounded up to the alignment.
+    pub fn size(&self) -> usize {
+        self.size
+    }
+
+    /// Returns the return addresses of the callers that made the
+    /// allocation, innermost first.
+    pub fn frames(&self) -> &[usize] {
+        &self.frames
+    }
+}
+
+/// Returns the samples taken so far, oldest first.
+///
+/// Samples being overwritten at the time are left out.
+pub fn samples() -> Vec<Sample> {
+    let mut samples = Vec::new();
+    for_each_sample(|sample| {
+        samples.push(Sample {
+            size: sample.size,
+            frames: sample.frames[..sample.len].to_vec(),
+        })
+    });
+    samples
+}
+
+/// Writes the samples taken so far to `out`, one per line, oldest first.
+///
+/// Each line holds the size of the allocation in decimal, then the
+/// addresses of its callers in hexadecimal, innermost first, for example
+/// `48 0x5555555a1c2e 0x5555555a03f1`.
+pub fn dump(out: &mut dyn Write) -> io::Result<()> {
+    let mut result = Ok(());
+    for_each_sample(|sample| {
+        if result.is_ok() {
+            let mut line = [0; LINE_LEN];
+            let len = format_sample(sample, &mut line);
+            result = out.write_all(&line[..len]);
+        }
+    });
+    result
+}
+
+/// Makes the process [`dump`] its samples to `out` whenever it gets
+/// `signal`, replacing the file set by an earlier call.
+///
+/// `out` is written to from the signal handler, so it is best opened for
+/// appending. Fails with [`InvalidInput`] for signals that can't be caught.
+///
+/// [`InvalidInput`]: io::ErrorKind::InvalidInput
+pub fn dump_on_signal(signal: i32, out: OwnedFd) -> io::Result<()> {
+    let fd = out.into_raw_fd();
+    let old = SIGNAL_FD.swap(fd, Ordering::Relaxed);
+    if old != -1 {
+        unsafe { dlibc::close(old) };
+    }
+    unsafe {
+        let mut action: dlibc::sigaction = mem::zeroed();
+        action.sa_sigaction = dump_signal_handler as dlibc::sighandler_t;
+        action.sa_flags = dlibc::SA_RESTART;
+        cvt(dlibc::sigaction(signal, &action, ptr::null_mut()))?;
+    }
+    Ok(())
+}
+
+extern "C" fn dump_signal_handler(_signal: dlibc::c_int) {
+    let fd = SIGNAL_FD.load(Ordering::Relaxed);
+    if fd == -1 {
+        return;
+    }
+    let errno = crate::std::sys::os::errno();
+    for_each_sample(|sample| {
+        let mut line = [0; LINE_LEN];
+        let len = format_sample(sample, &mut line);
+        let mut line = &line[..len];
+        while !line.is_empty() {
+            let n = unsafe { dlibc::write(fd, line.as_ptr().cast(), line.len()) };
+            if n <= 0 {
+                return;
+            }
+            line = &line[n as usize..];
+        }
+    });
+    crate::std::sys::os::set_errno(errno);
+}
+
+// Room for a size and `MAX_FRAMES` addresses, with their separators.
+const LINE_LEN: usize = 20 + MAX_FRAMES * 19 + 1;
+
+// Formats `sample` as a line of `dump` without allocating, since this also
+// runs in the signal handler.
+fn format_sample(sample: &RawSample, line: &mut [u8; LINE_LEN]) -> usize {
+    let mut len = 0;
+    let mut digits = [0; 20];
+
+    let mut n = sample.size;
+    let mut start = digits.len();
+    loop {
+        start -= 1;
+        digits[start] = b'0' + (n % 10) as u8;
+        n /= 10;
+        if n == 0 {
+            break;
+        }
+    }
+    line[..digits.len() - start].copy_from_slice(&digits[start..]);
+    len += digits.len() - start;
+
+    for &frame in &sample.frames[..sample.len] {
+        line[len..len + 3].copy_from_slice(b" 0x");
+        len += 3;
+        let mut n = frame;
+        let mut start = 16;
+        loop {
+            start -= 1;
+            digits[start] = b"0123456789abcdef"[n % 16];
+            n /= 16;
+            if n == 0 {
+                break;
+            }
+        }
+        line[len..len + 16 - start].copy_from_slice(&digits[start..16]);
+        len += 16 - start;
+    }
+    line[len] = b'\n';
+    len + 1
+}
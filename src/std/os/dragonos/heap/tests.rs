@@ -0,0 +1,30 @@
+use super::{dump, samples, start_sampling, stop_sampling, LINE_LEN, MAX_FRAMES};
+use crate::std::hint::black_box;
+use crate::std::num::NonZeroUsize;
+
+#[test]
+fn samples_every_allocation() {
+    start_sampling(NonZeroUsize::new(1).unwrap());
+    let allocation = black_box(vec![0u8; 123_457]);
+    stop_sampling();
+    drop(allocation);
+
+    let samples = samples();
+    let sample = samples
+        .iter()
+        .rev()
+        .find(|sample| sample.size() >= 123_457)
+        .unwrap();
+    assert!(sample.frames().len() <= MAX_FRAMES);
+
+    let mut out = Vec::new();
+    dump(&mut out).unwrap();
+    let out = String::from_utf8(out).unwrap();
+    assert_eq!(out.lines().count(), samples.len());
+    for line in out.lines() {
+        assert!(line.len() < LINE_LEN);
+        let mut fields = line.split(' ');
+        fields.next().unwrap().parse::<usize>().unwrap();
+        assert!(fields.all(|frame| frame.starts_with("0x")));
+    }
+}
@@ -0,0 +1,1710 @@
+//! DragonOS-specific extensions to general I/O primitives.
+
+#[cfg(test)]
+mod tests;
+
+use crate::std::cmp;
+use crate::std::fmt;
+use crate::std::fs;
+use crate::std::io::{self, BorrowedCursor, BufRead, IoSlice, IoSliceMut, Read, Write};
+use crate::std::marker::PhantomData;
+use crate::std::mem::{self, forget, ManuallyDrop};
+use crate::std::os::unix::io::{AsFd, AsRawFd, BorrowedFd, FromRawFd, OwnedFd, RawFd};
+use crate::std::path::{Path, PathBuf};
+use crate::std::sealed::Sealed;
+use crate::std::sync::atomic::{AtomicI32, Ordering};
+use crate::std::sync::{Arc, Mutex, MutexGuard, PoisonError};
+use crate::std::sys::cvt;
+use crate::std::thread;
+use crate::std::time::{Duration, Instant};
+use dlibc;
+
+/// Returns whether `fd` is inherited by programs started with `exec`, i.e.
+/// whether its `FD_CLOEXEC` flag is clear.
+///
+/// Every descriptor created by the standard library is opened with
+/// `FD_CLOEXEC` set, so this returns `false` for them unless
+/// [`set_inheritable`] was used to opt out.
+pub fn is_inheritable(fd: BorrowedFd<'_>) -> io::Result<bool> {
+    let flags = cvt(unsafe { dlibc::fcntl(fd.as_raw_fd(), dlibc::F_GETFD) })?;
+    Ok(flags & dlibc::FD_CLOEXEC == 0)
+}
+
+/// Sets whether `fd` is inherited by programs started with `exec`.
+///
+/// This is the per-descriptor opt-out from the standard library's
+/// close-on-exec default. Prefer [`CommandExt::fd_mapping`] when handing a
+/// descriptor to a single child, since an inheritable descriptor also leaks
+/// into every other process spawned while it is open.
+///
+/// [`CommandExt::fd_mapping`]: crate::std::os::dragonos::process::CommandExt::fd_mapping
+pub fn set_inheritable(fd: BorrowedFd<'_>, inheritable: bool) -> io::Result<()> {
+    unsafe {
+        let previous = cvt(dlibc::fcntl(fd.as_raw_fd(), dlibc::F_GETFD))?;
+        let new = if inheritable {
+            previous & !dlibc::FD_CLOEXEC
+        } else {
+            previous | dlibc::FD_CLOEXEC
+        };
+        if new != previous {
+            cvt(dlibc::fcntl(fd.as_raw_fd(), dlibc::F_SETFD, new))?;
+        }
+        Ok(())
+    }
+}
+
+/// A file descriptor open in the calling process, as listed by
+/// [`list_open_fds`].
+#[derive(Clone, Debug)]
+pub struct OpenFd {
+    fd: RawFd,
+    file_type: fs::FileType,
+    flags: i32,
+    inheritable: bool,
+    target: Option<PathBuf>,
+}
+
+impl OpenFd {
+    fn inspect(fd: RawFd) -> Option<OpenFd> {
+        // Both fail only if `fd` was closed since it was listed.
+        let fd_flags = unsafe { dlibc::fcntl(fd, dlibc::F_GETFD) };
+        let flags = unsafe { dlibc::fcntl(fd, dlibc::F_GETFL) };
+        if fd_flags < 0 || flags < 0 {
+            return None;
+        }
+        let file = ManuallyDrop::new(unsafe { fs::File::from_raw_fd(fd) });
+        let file_type = file.metadata().ok()?.file_type();
+        Some(OpenFd {
+            fd,
+            file_type,
+            flags,
+            inheritable: fd_flags & dlibc::FD_CLOEXEC == 0,
+            target: fs::read_link(format!("/proc/self/fd/{fd}")).ok(),
+        })
+    }
+
+    /// Returns the descriptor number.
+    pub fn fd(&self) -> RawFd {
+        self.fd
+    }
+
+    /// Returns the type of what the descriptor refers to.
+    ///
+    /// [`FileTypeExt`] tells sockets, pipes and devices apart.
+    ///
+    /// [`FileTypeExt`]: crate::std::os::unix::fs::FileTypeExt
+    pub fn file_type(&self) -> fs::FileType {
+        self.file_type
+    }
+
+    /// Returns the file status flags, as returned by `fcntl(F_GETFL)`: the
+    /// access mode together with flags such as `O_APPEND` and `O_NONBLOCK`.
+    pub fn flags(&self) -> i32 {
+        self.flags
+    }
+
+    /// Returns whether the descriptor is inherited by programs started with
+    /// `exec`, like [`is_inheritable`].
+    pub fn is_inheritable(&self) -> bool {
+        self.inheritable
+    }
+
+    /// Returns the path the descriptor was opened with, or a description
+    /// such as `socket:[1234]` for descriptors that have none.
+    ///
+    /// This is `None` if the kernel didn't say.
+    pub fn target(&self) -> Option<&Path> {
+        self.target.as_deref()
+    }
+}
+
+/// Lists the file descriptors open in the calling process, in ascending
+/// order.
+///
+/// This reads `/proc/self/fd`. The list is a snapshot: other threads may
+/// open and close descriptors while, and after, it is taken.
+///
+/// ```no_run
+/// use std::os::dragonos::io::list_open_fds;
+///
+/// for fd in list_open_fds()? {
+///     if fd.is_inheritable() {
+///         eprintln!("{} leaks into children: {:?}", fd.fd(), fd.target());
+///     }
+/// }
+/// # Ok::<(), std::io::Error>(())
+/// ```
+pub fn list_open_fds() -> io::Result<Vec<OpenFd>> {
+    let mut fds = Vec::new();
+    for entry in fs::read_dir("/proc/self/fd")? {
+        if let Some(fd) = entry?.file_name().to_str().and_then(|s| s.parse().ok()) {
+            fds.push(fd);
+        }
+    }
+    fds.sort_unstable();
+    // This drops the descriptor of the directory stream itself, which is
+    // closed by now.
+    Ok(fds.into_iter().filter_map(OpenFd::inspect).collect())
+}
+
+/// Closes every file descriptor from `first` to `last`, inclusive, such as
+/// all those above the standard streams before a program is started with
+/// `exec`.
+///
+/// Descriptors in the range that aren't open are skipped. This makes one
+/// system call and doesn't allocate, so it may be used in a
+/// [`pre_exec`] closure. On kernels without `close_range`, it closes the
+/// descriptors one by one, up to the `RLIMIT_NOFILE` limit, and fails with
+/// [`ErrorKind::Unsupported`] without closing any if that would be more
+/// than 65536 of them.
+///
+/// # Safety
+///
+/// No descriptor in the range may be owned by anything that is used
+/// afterwards, since that would then operate on a closed descriptor, or on
+/// an unrelated one opened later with the same number.
+///
+/// [`pre_exec`]: crate::std::os::unix::process::CommandExt::pre_exec
+/// [`ErrorKind::Unsupported`]: io::ErrorKind::Unsupported
+pub unsafe fn close_range(first: RawFd, last: RawFd) -> io::Result<()> {
+    match close_range_raw(first, last, 0) {
+        Err(e) if e.raw_os_error() == Some(dlibc::ENOSYS) => {
+            for fd in first..=fallback_last(first, last)? {
+                dlibc::close(fd);
+            }
+            Ok(())
+        }
+        result => result,
+    }
+}
+
+/// Sets `FD_CLOEXEC` on every file descriptor from `first` to `last`,
+/// inclusive, so that none of them is inherited by programs started with
+/// `exec`.
+///
+/// Unlike [`close_range`], this leaves the descriptors usable in the calling
+/// process, which makes it safe. Like it, this makes one system call where
+/// the kernel has `close_range`, and otherwise goes through at most 65536
+/// descriptors one by one.
+pub fn set_cloexec_range(first: RawFd, last: RawFd) -> io::Result<()> {
+    match close_range_raw(first, last, dlibc::CLOSE_RANGE_CLOEXEC) {
+        Err(e) if matches!(e.raw_os_error(), Some(dlibc::ENOSYS | dlibc::EINVAL)) => {
+            for fd in first..=fallback_last(first, last)? {
+                let flags = unsafe { dlibc::fcntl(fd, dlibc::F_GETFD) };
+                if flags >= 0 && flags & dlibc::FD_CLOEXEC == 0 {
+                    unsafe { dlibc::fcntl(fd, dlibc::F_SETFD, flags | dlibc::FD_CLOEXEC) };
+                }
+            }
+            Ok(())
+        }
+        result => result,
+    }
+}
+
+fn close_range_raw(first: RawFd, last: RawFd, flags: dlibc::c_uint) -> io::Result<()> {
+    if first < 0 || last < first {
+        return Err(io::const_io_error!(
+            io::ErrorKind::InvalidInput,
+            "invalid descriptor range",
+        ));
+    }
+    cvt(unsafe {
+        dlibc::syscall(
+            dlibc::SYS_close_range,
+            first as dlibc::c_uint,
+            last as dlibc::c_uint,
+            flags,
+        )
+    })?;
+    Ok(())
+}
+
+// The most descriptors that the fallbacks for kernels without `close_range`
+// go through one by one. Where `RLIMIT_NOFILE` allows more, they fail rather
+// than make millions of system calls.
+const MAX_FALLBACK_FDS: RawFd = 1 << 16;
+
+// The end of `first..=last` for the fallbacks: the highest descriptor number
+// in it that can be open. This doesn't allocate, since it may run in a
+// child process after `fork`.
+fn fallback_last(first: RawFd, last: RawFd) -> io::Result<RawFd> {
+    let last = cmp::min(last, open_max());
+    if last >= first && last - first >= MAX_FALLBACK_FDS {
+        return Err(io::const_io_error!(
+            io::ErrorKind::Unsupported,
+            "close_range is not supported, and RLIMIT_NOFILE allows too many descriptors to go through them one by one",
+        ));
+    }
+    Ok(last)
+}
+
+// The highest descriptor number the process can have.
+fn open_max() -> RawFd {
+    let mut limit: dlibc::rlimit = unsafe { mem::zeroed() };
+    if unsafe { dlibc::getrlimit(dlibc::RLIMIT_NOFILE, &mut limit) } != 0 {
+        return 1023;
+    }
+    cmp::min(limit.rlim_cur, RawFd::MAX as dlibc::rlim_t) as RawFd - 1
+}
+
+/// Options controlling how a file descriptor is duplicated by
+/// [`OwnedFd::duplicate_with`] and [`BorrowedFd::duplicate_with`].
+///
+/// By default the new descriptor is the lowest free number that is at least
+/// 3, so that it never lands on a standard stream, and has `FD_CLOEXEC` set,
+/// like every other descriptor the standard library creates. This is what
+/// [`OwnedFd::try_clone`] does.
+///
+/// ```no_run
+/// use std::fs::File;
+/// use std::os::dragonos::io::DuplicateOptions;
+/// use std::os::fd::AsFd;
+///
+/// let log = File::create("/var/log/daemon.log")?;
+/// // Keep the log open well out of the way of descriptors the daemon
+/// // hands to its children, and let exec'd helpers inherit it.
+/// let fd = log.as_fd().duplicate_with(DuplicateOptions::new().min_fd(100).cloexec(false))?;
+/// # Ok::<(), std::io::Error>(())
+/// ```
+#[derive(Clone, Debug)]
+pub struct DuplicateOptions {
+    pub(crate) target: Option<RawFd>,
+    pub(crate) min_fd: RawFd,
+    pub(crate) cloexec: bool,
+}
+
+impl DuplicateOptions {
+    /// Creates a blank set of options with the defaults described above.
+    pub fn new() -> Self {
+        DuplicateOptions {
+            target: None,
+            min_fd: 3,
+            cloexec: true,
+        }
+    }
+
+    /// Sets whether the new descriptor has the `FD_CLOEXEC` flag set, i.e.
+    /// whether it is closed in programs started with `exec`.
+    ///
+    /// This option defaults to `true`.
+    pub fn cloexec(&mut self, cloexec: bool) -> &mut Self {
+        self.cloexec = cloexec;
+        self
+    }
+
+    /// Sets the lowest number the new descriptor may have, using
+    /// `F_DUPFD`/`F_DUPFD_CLOEXEC`.
+    ///
+    /// This option defaults to 3 and is ignored if a [`target`] is set.
+    ///
+    /// [`target`]: DuplicateOptions::target
+    pub fn min_fd(&mut self, min_fd: RawFd) -> &mut Self {
+        self.min_fd = min_fd;
+        self
+    }
+
+    /// Sets the exact number the new descriptor will have, using `dup2`.
+    ///
+    /// If `target` is already open it is silently closed first. Duplicating
+    /// fails with [`InvalidInput`] if `target` is the descriptor being
+    /// duplicated. When [`cloexec`] is set, the flag is applied after the
+    /// `dup2` call, so a concurrent `fork` may briefly observe the new
+    /// descriptor without it.
+    ///
+    /// # Safety
+    ///
+    /// If `target` is open, nothing else may own it or rely on it remaining
+    /// open, since it is closed and replaced by the new descriptor.
+    ///
+    /// [`InvalidInput`]: crate::std::io::ErrorKind::InvalidInput
+    /// [`cloexec`]: DuplicateOptions::cloexec
+    pub unsafe fn target(&mut self, target: RawFd) -> &mut Self {
+        self.target = Some(target);
+        self
+    }
+}
+
+impl Default for DuplicateOptions {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// FFI type for file descriptors returned by raw DragonOS system calls, which
+/// report failure by returning `-errno` instead of a descriptor. This uses
+/// `repr(transparent)` and has the representation of a syscall return value,
+/// so that it can be used in such FFI declarations.
+///
+/// The only thing you can usefully do with a `FdOrErrno` is to convert it into
+/// an [`OwnedFd`] using its [`TryFrom`] implementation; this conversion takes
+/// care of decoding the error. This ensures that such FFI calls cannot start
+/// using the descriptor without checking for an error first.
+///
+/// If this holds a descriptor, it will close the descriptor on drop.
+#[repr(transparent)]
+#[derive(Debug)]
+pub struct FdOrErrno(isize);
+
+impl FdOrErrno {
+    /// Constructs a new instance of `Self` from the value returned by a raw
+    /// system call that either creates a descriptor or returns `-errno`.
+    ///
+    /// # Safety
+    ///
+    /// If `ret` is nonnegative, it must satisfy the safety requirements of
+    /// [`FromRawFd::from_raw_fd`]. Note that C library wrappers such as
+    /// `open` return `-1` and set `errno` instead; use [`OwnedFd`] directly
+    /// with those.
+    #[inline]
+    pub unsafe fn from_raw_ret(ret: isize) -> Self {
+        Self(ret)
+    }
+}
+
+impl TryFrom<FdOrErrno> for OwnedFd {
+    type Error = io::Error;
+
+    #[inline]
+    fn try_from(fd_or_errno: FdOrErrno) -> Result<Self, io::Error> {
+        let ret = fd_or_errno.0;
+        // Ownership moves to the returned `OwnedFd`, or there is nothing to
+        // close, so don't run `FdOrErrno`'s destructor.
+        forget(fd_or_errno);
+        if ret < 0 {
+            Err(io::Error::from_raw_os_error(ret.wrapping_neg() as i32))
+        } else {
+            Ok(unsafe { OwnedFd::from_raw_fd(ret as RawFd) })
+        }
+    }
+}
+
+impl Drop for FdOrErrno {
+    #[inline]
+    fn drop(&mut self) {
+        if self.0 >= 0 {
+            // Errors are ignored when closing, as in `OwnedFd`'s destructor.
+            let _ = unsafe { dlibc::close(self.0 as RawFd) };
+        }
+    }
+}
+
+/// How [`Stdout`] buffers what is written to it, as set with
+/// [`StdoutExt::set_buffer_mode`].
+///
+/// [`Stdout`]: io::Stdout
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum BufferMode {
+    /// Write everything out as soon as it is written.
+    Unbuffered,
+    /// Write out every complete line, and everything else once the buffer is
+    /// full or flushed. This is the default.
+    Line,
+    /// Write out only once the buffer is full or flushed, which takes the
+    /// fewest system calls.
+    Block,
+}
+
+/// Os-specific extensions for [`Stdout`].
+///
+/// [`Stdout`]: io::Stdout
+pub trait StdoutExt: Sealed {
+    /// Returns how standard output is buffered.
+    fn buffer_mode(&self) -> BufferMode;
+
+    /// Changes how standard output is buffered, for every handle to it.
+    ///
+    /// Anything already buffered is written out first; if that fails, the
+    /// error is returned and the mode stays as it was.
+    ///
+    /// Output that is block buffered is still written out when `main`
+    /// returns and by [`process::exit`], but not if the process aborts or is
+    /// killed by a signal. A tool that writes a lot to a pipe or a file
+    /// usually wants block buffering, and someone watching a terminal wants
+    /// to see lines as they come:
+    ///
+    /// ```no_run
+    /// use std::io::{self, IsTerminal};
+    /// use std::os::dragonos::io::{BufferMode, StdoutExt};
+    ///
+    /// let stdout = io::stdout();
+    /// if !stdout.is_terminal() {
+    ///     stdout.set_buffer_mode(BufferMode::Block)?;
+    /// }
+    /// # Ok::<(), io::Error>(())
+    /// ```
+    ///
+    /// [`process::exit`]: crate::std::process::exit
+    fn set_buffer_mode(&self, mode: BufferMode) -> io::Result<()>;
+
+    /// Moves standard output into or out of non-blocking mode, in which
+    /// writes fail with [`WouldBlock`] instead of waiting for a slow reader.
+    ///
+    /// Output that couldn't be written stays buffered and goes out with the
+    /// next write or flush, so an event loop can [`poll`] for standard output
+    /// to become writable and then flush it. The `print!` family of macros
+    /// panics on errors, `WouldBlock` included, so write to a [`lock`] of
+    /// standard output instead.
+    ///
+    /// The mode belongs to the open file, which is usually shared with the
+    /// shell that started the program, so it should be switched back before
+    /// exiting.
+    ///
+    /// [`WouldBlock`]: io::ErrorKind::WouldBlock
+    /// [`lock`]: io::Stdout::lock
+    fn set_nonblocking(&self, nonblocking: bool) -> io::Result<()>;
+}
+
+impl StdoutExt for io::Stdout {
+    fn buffer_mode(&self) -> BufferMode {
+        io::Stdout::buffer_mode(self)
+    }
+
+    fn set_buffer_mode(&self, mode: BufferMode) -> io::Result<()> {
+        io::Stdout::set_buffer_mode(self, mode)
+    }
+
+    fn set_nonblocking(&self, nonblocking: bool) -> io::Result<()> {
+        set_nonblocking_flag(self.as_raw_fd(), nonblocking).map(drop)
+    }
+}
+
+// Sets or clears `O_NONBLOCK` on `fd`, returning the flags it had before.
+fn set_nonblocking_flag(fd: RawFd, nonblocking: bool) -> io::Result<dlibc::c_int> {
+    let flags = cvt(unsafe { dlibc::fcntl(fd, dlibc::F_GETFL) })?;
+    let new = if nonblocking {
+        flags | dlibc::O_NONBLOCK
+    } else {
+        flags & !dlibc::O_NONBLOCK
+    };
+    if new != flags {
+        cvt(unsafe { dlibc::fcntl(fd, dlibc::F_SETFL, new) })?;
+    }
+    Ok(flags)
+}
+
+// The mode standard input was in before `enable_raw_mode`, if it is on.
+static ORIGINAL_MODE: Mutex<Option<dlibc::termios>> = Mutex::new(None);
+
+/// Puts the terminal on standard input into raw mode, so that reads return
+/// every key press as soon as it is made, without echoing it.
+///
+/// In raw mode the terminal does no line editing, doesn't turn `Ctrl-C` and
+/// the like into signals, and doesn't translate `\n` into `\r\n` on output,
+/// which is what full-screen programs and line editors want. The previous
+/// mode is saved, and restored by [`disable_raw_mode`]; enabling raw mode
+/// again while it is on keeps the mode saved the first time.
+///
+/// Raw mode outlives the process, so it should be disabled again before
+/// exiting, panics included.
+///
+/// ```no_run
+/// use std::io::{self, Read};
+/// use std::os::dragonos::io::{disable_raw_mode, enable_raw_mode};
+///
+/// enable_raw_mode()?;
+/// let mut key = [0];
+/// let read = io::stdin().read(&mut key);
+/// disable_raw_mode()?;
+/// println!("pressed {:?}", read.map(|_| key[0] as char)?);
+/// # Ok::<(), io::Error>(())
+/// ```
+pub fn enable_raw_mode() -> io::Result<()> {
+    set_raw_mode().map(drop)
+}
+
+// Enables raw mode, returning whether it was off before.
+fn set_raw_mode() -> io::Result<bool> {
+    let fd = io::stdin().as_raw_fd();
+    let mut original = ORIGINAL_MODE.lock().unwrap_or_else(PoisonError::into_inner);
+    let mut mode: dlibc::termios = unsafe { mem::zeroed() };
+    cvt(unsafe { dlibc::tcgetattr(fd, &mut mode) })?;
+    let saved = mode;
+
+    // What `cfmakeraw` does.
+    mode.c_iflag &= !(dlibc::IGNBRK
+        | dlibc::BRKINT
+        | dlibc::PARMRK
+        | dlibc::ISTRIP
+        | dlibc::INLCR
+        | dlibc::IGNCR
+        | dlibc::ICRNL
+        | dlibc::IXON);
+    mode.c_oflag &= !dlibc::OPOST;
+    mode.c_lflag &= !(dlibc::ECHO | dlibc::ECHONL | dlibc::ICANON | dlibc::ISIG | dlibc::IEXTEN);
+    mode.c_cflag &= !(dlibc::CSIZE | dlibc::PARENB);
+    mode.c_cflag |= dlibc::CS8;
+    mode.c_cc[dlibc::VMIN] = 1;
+    mode.c_cc[dlibc::VTIME] = 0;
+    cvt(unsafe { dlibc::tcsetattr(fd, dlibc::TCSAFLUSH, &mode) })?;
+
+    if original.is_some() {
+        return Ok(false);
+    }
+    *original = Some(saved);
+    dlibc::set_panic_cleanup(restore_stdin);
+    Ok(true)
+}
+
+/// Restores the mode the terminal on standard input was in before
+/// [`enable_raw_mode`].
+///
+/// Does nothing if raw mode isn't on.
+pub fn disable_raw_mode() -> io::Result<()> {
+    let mut original = ORIGINAL_MODE.lock().unwrap_or_else(PoisonError::into_inner);
+    if let Some(mode) = *original {
+        cvt(unsafe { dlibc::tcsetattr(io::stdin().as_raw_fd(), dlibc::TCSAFLUSH, &mode) })?;
+        *original = None;
+    }
+    Ok(())
+}
+
+/// Returns whether [`enable_raw_mode`] put standard input into raw mode.
+pub fn is_raw_mode_enabled() -> bool {
+    ORIGINAL_MODE
+        .lock()
+        .unwrap_or_else(PoisonError::into_inner)
+        .is_some()
+}
+
+// The file status flags standard input had before
+// `RawModeGuard::set_nonblocking`, or -1.
+static ORIGINAL_FLAGS: AtomicI32 = AtomicI32::new(-1);
+
+// Run by the panic handler, since panics abort without dropping the guards.
+fn restore_stdin() {
+    let fd = io::stdin().as_raw_fd();
+    let flags = ORIGINAL_FLAGS.swap(-1, Ordering::Relaxed);
+    if flags != -1 {
+        unsafe { dlibc::fcntl(fd, dlibc::F_SETFL, flags) };
+    }
+    // The panic may have happened with the lock held.
+    if let Ok(mut original) = ORIGINAL_MODE.try_lock() {
+        if let Some(mode) = original.take() {
+            unsafe { dlibc::tcsetattr(fd, dlibc::TCSAFLUSH, &mode) };
+        }
+    }
+}
+
+/// Os-specific extensions for [`Stdin`].
+///
+/// [`Stdin`]: io::Stdin
+pub trait StdinExt: Sealed {
+    /// Puts the terminal on standard input into raw mode, like
+    /// [`enable_raw_mode`], until the returned guard is dropped.
+    ///
+    /// The terminal is also put back if the program panics while the guard
+    /// is alive, so that a crashing REPL or full-screen program doesn't leave
+    /// the console unusable. If raw mode was already on, it stays on when the
+    /// guard is dropped.
+    ///
+    /// ```no_run
+    /// use std::io::{self, Read};
+    /// use std::os::dragonos::io::StdinExt;
+    ///
+    /// let stdin = io::stdin();
+    /// let mut guard = stdin.raw_mode()?;
+    /// guard.set_nonblocking(true)?;
+    ///
+    /// let mut key = [0];
+    /// match stdin.lock().read(&mut key) {
+    ///     Ok(_) => println!("pressed {:?}\r", key[0] as char),
+    ///     Err(e) if e.kind() == io::ErrorKind::WouldBlock => println!("no key yet\r"),
+    ///     Err(e) => return Err(e),
+    /// }
+    /// # Ok::<(), io::Error>(())
+    /// ```
+    fn raw_mode(&self) -> io::Result<RawModeGuard>;
+
+    /// Moves standard input into or out of non-blocking mode, in which reads
+    /// fail with [`WouldBlock`] instead of waiting for input.
+    ///
+    /// Together with [`poll`], this lets a program wait for standard input
+    /// and sockets at the same time:
+    ///
+    /// ```no_run
+    /// use std::io::{self, BufRead};
+    /// use std::net::TcpStream;
+    /// use std::os::dragonos::io::{poll, PollFd, StdinExt};
+    /// use std::os::fd::AsFd;
+    ///
+    /// let stdin = io::stdin();
+    /// stdin.set_nonblocking(true)?;
+    /// let server = TcpStream::connect("10.0.2.2:7000")?;
+    ///
+    /// let mut fds = [PollFd::readable(stdin.as_fd()), PollFd::readable(server.as_fd())];
+    /// poll(&mut fds, None)?;
+    /// if fds[0].is_readable() {
+    ///     let mut line = String::new();
+    ///     match stdin.lock().read_line(&mut line) {
+    ///         Ok(_) => print!("typed {line}"),
+    ///         Err(e) if e.kind() == io::ErrorKind::WouldBlock => {}
+    ///         Err(e) => return Err(e),
+    ///     }
+    /// }
+    /// stdin.set_nonblocking(false)?;
+    /// # Ok::<(), io::Error>(())
+    /// ```
+    ///
+    /// Nothing that has been read is lost to `WouldBlock`: [`read_line`]
+    /// leaves the start of a line that arrives in pieces in the string it
+    /// was given, and calling it again with the same string adds the rest.
+    /// The mode belongs to the open file, which is usually shared with the
+    /// shell that started the program, so it should be switched back before
+    /// exiting; [`RawModeGuard::set_nonblocking`] does that on its own.
+    ///
+    /// [`WouldBlock`]: io::ErrorKind::WouldBlock
+    /// [`read_line`]: io::Stdin::read_line
+    fn set_nonblocking(&self, nonblocking: bool) -> io::Result<()>;
+}
+
+impl StdinExt for io::Stdin {
+    fn raw_mode(&self) -> io::Result<RawModeGuard> {
+        Ok(RawModeGuard {
+            restore_mode: set_raw_mode()?,
+            restore_flags: false,
+        })
+    }
+
+    fn set_nonblocking(&self, nonblocking: bool) -> io::Result<()> {
+        set_nonblocking_flag(self.as_raw_fd(), nonblocking).map(drop)
+    }
+}
+
+/// Keeps standard input in raw mode until it is dropped, as returned by
+/// [`StdinExt::raw_mode`].
+#[must_use = "raw mode is turned off again as soon as the guard is dropped"]
+#[derive(Debug)]
+pub struct RawModeGuard {
+    restore_mode: bool,
+    restore_flags: bool,
+}
+
+impl RawModeGuard {
+    /// Moves standard input into or out of non-blocking mode, in which reads
+    /// fail with [`WouldBlock`] instead of waiting for a key press.
+    ///
+    /// The mode standard input was in before is restored along with the
+    /// terminal when the guard is dropped.
+    ///
+    /// [`WouldBlock`]: io::ErrorKind::WouldBlock
+    pub fn set_nonblocking(&mut self, nonblocking: bool) -> io::Result<()> {
+        let flags = set_nonblocking_flag(io::stdin().as_raw_fd(), nonblocking)?;
+        if !self.restore_flags {
+            self.restore_flags = ORIGINAL_FLAGS
+                .compare_exchange(-1, flags, Ordering::Relaxed, Ordering::Relaxed)
+                .is_ok();
+        }
+        Ok(())
+    }
+}
+
+impl Drop for RawModeGuard {
+    fn drop(&mut self) {
+        if self.restore_flags {
+            let flags = ORIGINAL_FLAGS.swap(-1, Ordering::Relaxed);
+            if flags != -1 {
+                unsafe { dlibc::fcntl(io::stdin().as_raw_fd(), dlibc::F_SETFL, flags) };
+            }
+        }
+        if self.restore_mode {
+            let _ = disable_raw_mode();
+        }
+    }
+}
+
+/// The size of a terminal window, as returned by [`window_size`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct WindowSize {
+    /// The number of rows of characters.
+    pub rows: u16,
+    /// The number of columns of characters.
+    pub columns: u16,
+}
+
+/// Returns the size of the terminal window `tty` is open on.
+///
+/// Programs that lay out the screen call this again when they get
+/// `SIGWINCH`, which is sent whenever the window is resized.
+///
+/// ```no_run
+/// use std::io;
+/// use std::os::dragonos::io::window_size;
+/// use std::os::fd::AsFd;
+///
+/// let size = window_size(io::stdout().as_fd())?;
+/// println!("{}", "-".repeat(size.columns.into()));
+/// # Ok::<(), io::Error>(())
+/// ```
+pub fn window_size(tty: BorrowedFd<'_>) -> io::Result<WindowSize> {
+    let mut size: dlibc::winsize = unsafe { mem::zeroed() };
+    cvt(unsafe {
+        dlibc::ioctl(
+            tty.as_raw_fd(),
+            dlibc::TIOCGWINSZ,
+            &mut size as *mut dlibc::winsize,
+        )
+    })?;
+    Ok(WindowSize {
+        rows: size.ws_row,
+        columns: size.ws_col,
+    })
+}
+
+/// A file descriptor to wait on with [`poll`], and what to wait for.
+///
+/// After [`poll`] returns, the `is_*` methods tell what the descriptor is
+/// ready for.
+#[derive(Clone, Copy)]
+#[repr(transparent)]
+pub struct PollFd<'fd> {
+    raw: dlibc::pollfd,
+    _fd: PhantomData<BorrowedFd<'fd>>,
+}
+
+impl<'fd> PollFd<'fd> {
+    fn new(fd: BorrowedFd<'fd>, events: dlibc::c_short) -> PollFd<'fd> {
+        PollFd {
+            raw: dlibc::pollfd {
+                fd: fd.as_raw_fd(),
+                events,
+                revents: 0,
+            },
+            _fd: PhantomData,
+        }
+    }
+
+    /// Waits for `fd` to have something to read.
+    pub fn readable(fd: BorrowedFd<'fd>) -> PollFd<'fd> {
+        PollFd::new(fd, dlibc::POLLIN)
+    }
+
+    /// Waits for `fd` to have room to write.
+    pub fn writable(fd: BorrowedFd<'fd>) -> PollFd<'fd> {
+        PollFd::new(fd, dlibc::POLLOUT)
+    }
+
+    /// Waits for `fd` to have something to read or room to write.
+    pub fn readable_or_writable(fd: BorrowedFd<'fd>) -> PollFd<'fd> {
+        PollFd::new(fd, dlibc::POLLIN | dlibc::POLLOUT)
+    }
+
+    /// Returns whether a read won't block: there is something to read, the
+    /// other end is closed, or reading fails.
+    pub fn is_readable(&self) -> bool {
+        self.raw.revents & (dlibc::POLLIN | dlibc::POLLHUP | dlibc::POLLERR) != 0
+    }
+
+    /// Returns whether a write won't block: there is room to write, or
+    /// writing fails.
+    pub fn is_writable(&self) -> bool {
+        self.raw.revents & (dlibc::POLLOUT | dlibc::POLLERR) != 0
+    }
+
+    /// Returns whether the other end, such as the writing end of a pipe or
+    /// the peer of a socket, is closed.
+    pub fn is_hung_up(&self) -> bool {
+        self.raw.revents & dlibc::POLLHUP != 0
+    }
+
+    /// Returns whether an error is pending on the descriptor, such as a
+    /// failed connection on a socket. This is reported whether or not it
+    /// was waited for.
+    pub fn is_error(&self) -> bool {
+        self.raw.revents & (dlibc::POLLERR | dlibc::POLLNVAL) != 0
+    }
+
+    /// Returns the descriptor.
+    pub fn fd(&self) -> BorrowedFd<'fd> {
+        // SAFETY: `fd` was borrowed for `'fd` when this was created.
+        unsafe { BorrowedFd::borrow_raw(self.raw.fd) }
+    }
+}
+
+impl fmt::Debug for PollFd<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("PollFd")
+            .field("fd", &self.raw.fd)
+            .field("events", &self.raw.events)
+            .field("revents", &self.raw.revents)
+            .finish()
+    }
+}
+
+/// Waits until one of `fds` is ready, or until `timeout` has passed.
+///
+/// Returns how many of `fds` are ready, which is 0 if the time ran out.
+/// Waits with no limit if `timeout` is `None`, and doesn't wait at all if it
+/// is zero. Being interrupted by a signal doesn't end the wait early.
+pub fn poll(fds: &mut [PollFd<'_>], timeout: Option<Duration>) -> io::Result<usize> {
+    let nfds = dlibc::nfds_t::try_from(fds.len()).map_err(|_| {
+        io::const_io_error!(io::ErrorKind::InvalidInput, "too many file descriptors")
+    })?;
+    for fd in fds.iter_mut() {
+        fd.raw.revents = 0;
+    }
+    let deadline = timeout.and_then(|timeout| Instant::now().checked_add(timeout));
+    loop {
+        let ms = timeout_ms(deadline, timeout);
+        // `PollFd` is a transparent wrapper around `pollfd`.
+        let ret = unsafe { dlibc::poll(fds.as_mut_ptr().cast(), nfds, ms) };
+        match cvt(ret) {
+            Ok(ready) => return Ok(ready as usize),
+            Err(e) if e.is_interrupted() => {}
+            Err(e) => return Err(e),
+        }
+    }
+}
+
+/// Returns the milliseconds left until `deadline`, computed from `timeout`,
+/// as `poll` and `epoll_wait` take them.
+fn timeout_ms(deadline: Option<Instant>, timeout: Option<Duration>) -> dlibc::c_int {
+    match deadline {
+        // Rounded up, so that the wait isn't cut short.
+        Some(deadline) => {
+            let left = deadline.saturating_duration_since(Instant::now());
+            let ms = (left.as_nanos() + 999_999) / 1_000_000;
+            ms.min(dlibc::c_int::MAX as u128) as dlibc::c_int
+        }
+        None if timeout.is_some() => dlibc::c_int::MAX,
+        None => -1,
+    }
+}
+
+/// What a [`Poller`] or an [`EventQueue`] waits for a descriptor to be ready
+/// for.
+///
+/// Interests combine with `|`. Errors and hang-ups are reported with any
+/// interest.
+#[derive(Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Interest(dlibc::c_short);
+
+impl Interest {
+    /// Something to read.
+    pub const READABLE: Interest = Interest(dlibc::POLLIN);
+    /// Room to write.
+    pub const WRITABLE: Interest = Interest(dlibc::POLLOUT);
+    /// Nothing but errors and hang-ups.
+    pub const ERROR: Interest = Interest(0);
+}
+
+impl crate::std::ops::BitOr for Interest {
+    type Output = Interest;
+
+    fn bitor(self, other: Interest) -> Interest {
+        Interest(self.0 | other.0)
+    }
+}
+
+impl fmt::Debug for Interest {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match (self.0 & dlibc::POLLIN != 0, self.0 & dlibc::POLLOUT != 0) {
+            (true, true) => f.write_str("READABLE | WRITABLE"),
+            (true, false) => f.write_str("READABLE"),
+            (false, true) => f.write_str("WRITABLE"),
+            (false, false) => f.write_str("ERROR"),
+        }
+    }
+}
+
+/// A set of descriptors to wait on together, with [`poll`] underneath.
+///
+/// Unlike calling [`poll`] directly, the set is kept between waits and
+/// grows as descriptors are added. Waiting on a connection and on stdin at
+/// once:
+///
+/// ```no_run
+/// use std::io::{self, Read};
+/// use std::net::TcpStream;
+/// use std::os::dragonos::io::{Interest, Poller};
+/// use std::os::fd::{AsFd, AsRawFd};
+///
+/// let mut stream = TcpStream::connect("10.0.2.2:7000")?;
+/// let stdin = io::stdin();
+/// let (stream_fd, stdin_fd) = (stream.try_clone()?, stdin.as_fd());
+///
+/// let mut poller = Poller::with_capacity(2);
+/// poller.add(stream_fd.as_fd(), Interest::READABLE)?;
+/// poller.add(stdin_fd, Interest::READABLE)?;
+/// loop {
+///     poller.wait(None)?;
+///     for fd in poller.ready() {
+///         let mut buf = [0; 1024];
+///         let n = if fd.fd().as_raw_fd() == stdin_fd.as_raw_fd() {
+///             stdin.lock().read(&mut buf)?
+///         } else {
+///             stream.read(&mut buf)?
+///         };
+///         if n == 0 {
+///             return Ok(());
+///         }
+///     }
+/// }
+/// # Ok::<(), io::Error>(())
+/// ```
+///
+/// A descriptor can be in the set only once, and must stay open while it
+/// is, which the `'fd` lifetime makes sure of.
+#[derive(Debug, Default)]
+pub struct Poller<'fd> {
+    fds: Vec<PollFd<'fd>>,
+}
+
+impl<'fd> Poller<'fd> {
+    /// Creates an empty set.
+    pub fn new() -> Poller<'fd> {
+        Poller { fds: Vec::new() }
+    }
+
+    /// Creates an empty set with room for `capacity` descriptors before it
+    /// has to grow.
+    pub fn with_capacity(capacity: usize) -> Poller<'fd> {
+        Poller {
+            fds: Vec::with_capacity(capacity),
+        }
+    }
+
+    /// Returns how many descriptors are in the set.
+    pub fn len(&self) -> usize {
+        self.fds.len()
+    }
+
+    /// Returns whether the set is empty.
+    pub fn is_empty(&self) -> bool {
+        self.fds.is_empty()
+    }
+
+    fn position(&self, fd: BorrowedFd<'_>) -> Option<usize> {
+        self.fds
+            .iter()
+            .position(|poll_fd| poll_fd.raw.fd == fd.as_raw_fd())
+    }
+
+    /// Adds `fd` to the set, to wait for `interest`.
+    ///
+    /// Fails with [`AlreadyExists`] if `fd` is in the set already; use
+    /// [`modify`] to change what it is waited for.
+    ///
+    /// [`AlreadyExists`]: io::ErrorKind::AlreadyExists
+    /// [`modify`]: Poller::modify
+    pub fn add(&mut self, fd: BorrowedFd<'fd>, interest: Interest) -> io::Result<()> {
+        if self.position(fd).is_some() {
+            return Err(io::const_io_error!(
+                io::ErrorKind::AlreadyExists,
+                "file descriptor is already being polled",
+            ));
+        }
+        self.fds.push(PollFd::new(fd, interest.0));
+        Ok(())
+    }
+
+    /// Changes what `fd` is waited for.
+    ///
+    /// Fails with [`NotFound`] if `fd` is not in the set.
+    ///
+    /// [`NotFound`]: io::ErrorKind::NotFound
+    pub fn modify(&mut self, fd: BorrowedFd<'_>, interest: Interest) -> io::Result<()> {
+        let i = self.position(fd).ok_or_else(not_polled)?;
+        self.fds[i].raw.events = interest.0;
+        self.fds[i].raw.revents = 0;
+        Ok(())
+    }
+
+    /// Removes `fd` from the set.
+    ///
+    /// Fails with [`NotFound`] if `fd` is not in the set.
+    ///
+    /// [`NotFound`]: io::ErrorKind::NotFound
+    pub fn remove(&mut self, fd: BorrowedFd<'_>) -> io::Result<()> {
+        let i = self.position(fd).ok_or_else(not_polled)?;
+        self.fds.swap_remove(i);
+        Ok(())
+    }
+
+    /// Waits until a descriptor in the set is ready, or until `timeout` has
+    /// passed, like [`poll`].
+    ///
+    /// Returns how many descriptors are ready, which is 0 if the time ran
+    /// out; [`ready`] lists them.
+    ///
+    /// [`ready`]: Poller::ready
+    pub fn wait(&mut self, timeout: Option<Duration>) -> io::Result<usize> {
+        poll(&mut self.fds, timeout)
+    }
+
+    /// Returns the descriptors found ready by the last [`wait`].
+    ///
+    /// [`wait`]: Poller::wait
+    pub fn ready(&self) -> Ready<'_, 'fd> {
+        Ready {
+            fds: self.fds.iter(),
+        }
+    }
+}
+
+fn not_polled() -> io::Error {
+    io::const_io_error!(
+        io::ErrorKind::NotFound,
+        "file descriptor is not being polled",
+    )
+}
+
+/// An iterator over the descriptors that are ready, returned by
+/// [`Poller::ready`].
+#[derive(Debug)]
+pub struct Ready<'a, 'fd> {
+    fds: crate::std::slice::Iter<'a, PollFd<'fd>>,
+}
+
+impl<'a, 'fd> Iterator for Ready<'a, 'fd> {
+    type Item = &'a PollFd<'fd>;
+
+    fn next(&mut self) -> Option<&'a PollFd<'fd>> {
+        self.fds.find(|fd| fd.raw.revents != 0)
+    }
+}
+
+/// How an [`EventQueue`] reports a descriptor that stays ready.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Trigger {
+    /// Reported by every wait for as long as it is ready, like [`poll`].
+    Level,
+    /// Reported once each time it becomes ready, so it must be read or
+    /// written until that fails with [`WouldBlock`] before it is reported
+    /// again.
+    ///
+    /// [`WouldBlock`]: io::ErrorKind::WouldBlock
+    Edge,
+    /// Reported once, after which it stays registered but isn't reported
+    /// again until it is [modified](EventQueue::modify).
+    Oneshot,
+}
+
+/// A kernel queue of readiness events, for waiting on many descriptors at
+/// once without handing all of them to the kernel on every wait.
+///
+/// Descriptors are registered with a token, a number of the caller's
+/// choosing that comes back with their events. Unlike [`Poller`], the
+/// queue doesn't borrow the descriptors: the kernel removes one from the
+/// queue once it and every duplicate of it are closed.
+///
+/// ```no_run
+/// use std::net::TcpListener;
+/// use std::os::dragonos::io::{EventQueue, Events, Interest, Trigger};
+/// use std::os::fd::AsFd;
+/// use std::time::Duration;
+///
+/// let listener = TcpListener::bind("0.0.0.0:7000")?;
+/// listener.set_nonblocking(true)?;
+///
+/// let queue = EventQueue::new()?;
+/// queue.add(listener.as_fd(), 0, Interest::READABLE, Trigger::Edge)?;
+/// let mut events = Events::with_capacity(64);
+/// loop {
+///     queue.wait(&mut events, Some(Duration::from_secs(1)))?;
+///     for event in events.iter() {
+///         if event.token() == 0 {
+///             while let Ok((_stream, addr)) = listener.accept() {
+///                 println!("connection from {addr}");
+///             }
+///         }
+///     }
+/// }
+/// # Ok::<(), std::io::Error>(())
+/// ```
+#[derive(Debug)]
+pub struct EventQueue {
+    fd: OwnedFd,
+}
+
+impl EventQueue {
+    /// Creates an empty queue.
+    pub fn new() -> io::Result<EventQueue> {
+        let fd = cvt(unsafe { dlibc::epoll_create1(dlibc::EPOLL_CLOEXEC) })?;
+        // SAFETY: `epoll_create1` returned a new descriptor.
+        Ok(EventQueue {
+            fd: unsafe { OwnedFd::from_raw_fd(fd) },
+        })
+    }
+
+    fn ctl(
+        &self,
+        op: dlibc::c_int,
+        fd: BorrowedFd<'_>,
+        token: u64,
+        interest: Interest,
+        trigger: Trigger,
+    ) -> io::Result<()> {
+        let mut events = 0;
+        if interest.0 & dlibc::POLLIN != 0 {
+            events |= dlibc::EPOLLIN;
+        }
+        if interest.0 & dlibc::POLLOUT != 0 {
+            events |= dlibc::EPOLLOUT;
+        }
+        events |= match trigger {
+            Trigger::Level => 0,
+            Trigger::Edge => dlibc::EPOLLET,
+            Trigger::Oneshot => dlibc::EPOLLONESHOT,
+        };
+        let mut event = dlibc::epoll_event {
+            events,
+            data: dlibc::epoll_data { u64: token },
+        };
+        cvt(unsafe { dlibc::epoll_ctl(self.fd.as_raw_fd(), op, fd.as_raw_fd(), &mut event) })?;
+        Ok(())
+    }
+
+    /// Registers `fd`, to report when it is ready for `interest` with
+    /// `token`.
+    ///
+    /// Fails with [`AlreadyExists`] if `fd` is registered already; use
+    /// [`modify`] to change how.
+    ///
+    /// [`AlreadyExists`]: io::ErrorKind::AlreadyExists
+    /// [`modify`]: EventQueue::modify
+    pub fn add(
+        &self,
+        fd: BorrowedFd<'_>,
+        token: u64,
+        interest: Interest,
+        trigger: Trigger,
+    ) -> io::Result<()> {
+        self.ctl(dlibc::EPOLL_CTL_ADD, fd, token, interest, trigger)
+    }
+
+    /// Changes the token, interest and trigger `fd` is registered with.
+    ///
+    /// Fails with [`NotFound`] if `fd` is not registered.
+    ///
+    /// [`NotFound`]: io::ErrorKind::NotFound
+    pub fn modify(
+        &self,
+        fd: BorrowedFd<'_>,
+        token: u64,
+        interest: Interest,
+        trigger: Trigger,
+    ) -> io::Result<()> {
+        self.ctl(dlibc::EPOLL_CTL_MOD, fd, token, interest, trigger)
+    }
+
+    /// Unregisters `fd`.
+    ///
+    /// Fails with [`NotFound`] if `fd` is not registered.
+    ///
+    /// [`NotFound`]: io::ErrorKind::NotFound
+    pub fn delete(&self, fd: BorrowedFd<'_>) -> io::Result<()> {
+        self.ctl(dlibc::EPOLL_CTL_DEL, fd, 0, Interest::ERROR, Trigger::Level)
+    }
+
+    /// Waits until a registered descriptor is ready, or until `timeout` has
+    /// passed, and puts the events into `events`, replacing those of the
+    /// last wait.
+    ///
+    /// Returns how many events there are, at most the capacity of
+    /// `events`; the rest are kept for the next wait. Waits with no limit
+    /// if `timeout` is `None`, and doesn't wait at all if it is zero. Being
+    /// interrupted by a signal doesn't end the wait early.
+    pub fn wait(&self, events: &mut Events, timeout: Option<Duration>) -> io::Result<usize> {
+        events.len = 0;
+        let max = cmp::min(events.buf.len(), dlibc::c_int::MAX as usize) as dlibc::c_int;
+        let deadline = timeout.and_then(|timeout| Instant::now().checked_add(timeout));
+        loop {
+            let ms = timeout_ms(deadline, timeout);
+            let ret =
+                unsafe { dlibc::epoll_wait(self.fd.as_raw_fd(), events.buf.as_mut_ptr(), max, ms) };
+            match cvt(ret) {
+                Ok(n) => {
+                    events.len = n as usize;
+                    return Ok(events.len);
+                }
+                Err(e) if e.is_interrupted() => {}
+                Err(e) => return Err(e),
+            }
+        }
+    }
+}
+
+impl AsFd for EventQueue {
+    fn as_fd(&self) -> BorrowedFd<'_> {
+        self.fd.as_fd()
+    }
+}
+
+impl AsRawFd for EventQueue {
+    fn as_raw_fd(&self) -> RawFd {
+        self.fd.as_raw_fd()
+    }
+}
+
+/// A buffer for the events of an [`EventQueue::wait`].
+pub struct Events {
+    buf: Vec<dlibc::epoll_event>,
+    len: usize,
+}
+
+impl Events {
+    /// Creates a buffer with room for `capacity` events per wait, or for
+    /// one if `capacity` is 0.
+    pub fn with_capacity(capacity: usize) -> Events {
+        Events {
+            buf: vec![dlibc::epoll_event::default(); cmp::max(capacity, 1)],
+            len: 0,
+        }
+    }
+
+    /// Returns how many events fit in the buffer.
+    pub fn capacity(&self) -> usize {
+        self.buf.len()
+    }
+
+    /// Returns how many events the last wait put in the buffer.
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Returns whether the last wait put no events in the buffer.
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Returns the events of the last wait.
+    pub fn iter(&self) -> impl Iterator<Item = Event> + '_ {
+        self.buf[..self.len].iter().map(|event| Event {
+            // SAFETY: every registration sets the token.
+            token: unsafe { event.data.u64 },
+            events: event.events,
+        })
+    }
+}
+
+impl fmt::Debug for Events {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_list().entries(self.iter()).finish()
+    }
+}
+
+/// A descriptor found ready by [`EventQueue::wait`].
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub struct Event {
+    token: u64,
+    events: u32,
+}
+
+impl Event {
+    /// Returns the token the descriptor was registered with.
+    pub fn token(&self) -> u64 {
+        self.token
+    }
+
+    /// Returns whether a read won't block: there is something to read, the
+    /// other end is closed, or reading fails.
+    pub fn is_readable(&self) -> bool {
+        self.events & (dlibc::EPOLLIN | dlibc::EPOLLHUP | dlibc::EPOLLERR) != 0
+    }
+
+    /// Returns whether a write won't block: there is room to write, or
+    /// writing fails.
+    pub fn is_writable(&self) -> bool {
+        self.events & (dlibc::EPOLLOUT | dlibc::EPOLLERR) != 0
+    }
+
+    /// Returns whether the other end, such as the writing end of a pipe or
+    /// the peer of a socket, is closed.
+    pub fn is_hung_up(&self) -> bool {
+        self.events & dlibc::EPOLLHUP != 0
+    }
+
+    /// Returns whether an error is pending on the descriptor. This is
+    /// reported whatever the interest.
+    pub fn is_error(&self) -> bool {
+        self.events & dlibc::EPOLLERR != 0
+    }
+}
+
+impl fmt::Debug for Event {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Event")
+            .field("token", &self.token)
+            .field("events", &self.events)
+            .finish()
+    }
+}
+
+/// A reader that counts the bytes read through it.
+///
+/// Bytes consumed through [`BufRead`] count as well, once they are consumed.
+///
+/// ```no_run
+/// use std::fs::File;
+/// use std::io;
+/// use std::os::dragonos::io::CountingReader;
+///
+/// let mut reader = CountingReader::new(File::open("/var/log/daemon.log")?);
+/// io::copy(&mut reader, &mut io::sink())?;
+/// println!("read {} bytes", reader.count());
+/// # Ok::<(), io::Error>(())
+/// ```
+#[derive(Debug, Default)]
+pub struct CountingReader<R> {
+    inner: R,
+    count: u64,
+}
+
+impl<R> CountingReader<R> {
+    /// Wraps `inner`, starting the count at 0.
+    pub fn new(inner: R) -> CountingReader<R> {
+        CountingReader { inner, count: 0 }
+    }
+
+    /// Returns the number of bytes read so far.
+    pub fn count(&self) -> u64 {
+        self.count
+    }
+
+    /// Sets the count back to 0, returning what it was.
+    pub fn reset(&mut self) -> u64 {
+        crate::std::mem::take(&mut self.count)
+    }
+
+    /// Returns a reference to the underlying reader.
+    pub fn get_ref(&self) -> &R {
+        &self.inner
+    }
+
+    /// Returns a mutable reference to the underlying reader. Bytes read
+    /// through it directly are not counted.
+    pub fn get_mut(&mut self) -> &mut R {
+        &mut self.inner
+    }
+
+    /// Returns the underlying reader.
+    pub fn into_inner(self) -> R {
+        self.inner
+    }
+}
+
+impl<R: Read> Read for CountingReader<R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let n = self.inner.read(buf)?;
+        self.count += n as u64;
+        Ok(n)
+    }
+
+    fn read_vectored(&mut self, bufs: &mut [IoSliceMut<'_>]) -> io::Result<usize> {
+        let n = self.inner.read_vectored(bufs)?;
+        self.count += n as u64;
+        Ok(n)
+    }
+
+    fn is_read_vectored(&self) -> bool {
+        self.inner.is_read_vectored()
+    }
+
+    fn read_buf(&mut self, mut cursor: BorrowedCursor<'_>) -> io::Result<()> {
+        let before = cursor.written();
+        // Count what was read even if the read then failed.
+        let result = self.inner.read_buf(cursor.reborrow());
+        self.count += (cursor.written() - before) as u64;
+        result
+    }
+}
+
+impl<R: BufRead> BufRead for CountingReader<R> {
+    fn fill_buf(&mut self) -> io::Result<&[u8]> {
+        self.inner.fill_buf()
+    }
+
+    fn consume(&mut self, amt: usize) {
+        self.inner.consume(amt);
+        self.count += amt as u64;
+    }
+}
+
+/// A writer that counts the bytes written through it.
+///
+/// Only bytes the underlying writer accepted count, so after an error the
+/// count says how far the output got.
+#[derive(Debug, Default)]
+pub struct CountingWriter<W> {
+    inner: W,
+    count: u64,
+}
+
+impl<W> CountingWriter<W> {
+    /// Wraps `inner`, starting the count at 0.
+    pub fn new(inner: W) -> CountingWriter<W> {
+        CountingWriter { inner, count: 0 }
+    }
+
+    /// Returns the number of bytes written so far.
+    pub fn count(&self) -> u64 {
+        self.count
+    }
+
+    /// Sets the count back to 0, returning what it was.
+    pub fn reset(&mut self) -> u64 {
+        crate::std::mem::take(&mut self.count)
+    }
+
+    /// Returns a reference to the underlying writer.
+    pub fn get_ref(&self) -> &W {
+        &self.inner
+    }
+
+    /// Returns a mutable reference to the underlying writer. Bytes written
+    /// through it directly are not counted.
+    pub fn get_mut(&mut self) -> &mut W {
+        &mut self.inner
+    }
+
+    /// Returns the underlying writer.
+    pub fn into_inner(self) -> W {
+        self.inner
+    }
+}
+
+impl<W: Write> Write for CountingWriter<W> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let n = self.inner.write(buf)?;
+        self.count += n as u64;
+        Ok(n)
+    }
+
+    fn write_vectored(&mut self, bufs: &[IoSlice<'_>]) -> io::Result<usize> {
+        let n = self.inner.write_vectored(bufs)?;
+        self.count += n as u64;
+        Ok(n)
+    }
+
+    fn is_write_vectored(&self) -> bool {
+        self.inner.is_write_vectored()
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.inner.flush()
+    }
+}
+
+/// A reader or writer that moves at most a given number of bytes per second.
+///
+/// The limit is a token bucket refilled from the monotonic clock: bytes may
+/// go through in bursts of up to `burst` bytes, which defaults to one
+/// second's worth, and a read or write that finds the bucket empty sleeps
+/// until it has refilled enough. Each read or write is shortened to what the
+/// bucket holds, so it never goes over the limit.
+///
+/// ```no_run
+/// use std::io::{self, Read};
+/// use std::net::TcpStream;
+/// use std::os::dragonos::io::RateLimited;
+///
+/// // Upload at most 1 MiB/s.
+/// let mut upload = RateLimited::new(TcpStream::connect("192.0.2.1:9000")?, 1 << 20);
+/// io::copy(&mut io::repeat(0).take(10 << 20), &mut upload)?;
+/// # Ok::<(), io::Error>(())
+/// ```
+#[derive(Debug)]
+pub struct RateLimited<T> {
+    inner: T,
+    rate: u64,
+    burst: u64,
+    available: u64,
+    // When `available` was last brought up to date.
+    updated: Instant,
+}
+
+const NANOS_PER_SEC: u128 = 1_000_000_000;
+
+impl<T> RateLimited<T> {
+    /// Wraps `inner`, allowing `bytes_per_second` bytes per second, with a
+    /// full bucket to start with.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `bytes_per_second` is 0.
+    pub fn new(inner: T, bytes_per_second: u64) -> RateLimited<T> {
+        assert!(bytes_per_second > 0, "rate limit must be positive");
+        RateLimited {
+            inner,
+            rate: bytes_per_second,
+            burst: bytes_per_second,
+            available: bytes_per_second,
+            updated: Instant::now(),
+        }
+    }
+
+    /// Sets how many bytes may go through at once after a pause, which is
+    /// also the most a single read or write moves.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `burst` is 0.
+    pub fn set_burst(&mut self, burst: u64) {
+        assert!(burst > 0, "burst size must be positive");
+        self.burst = burst;
+        self.available = self.available.min(burst);
+    }
+
+    /// Returns the allowed bytes per second.
+    pub fn rate(&self) -> u64 {
+        self.rate
+    }
+
+    /// Returns a reference to the underlying reader or writer.
+    pub fn get_ref(&self) -> &T {
+        &self.inner
+    }
+
+    /// Returns a mutable reference to the underlying reader or writer. Bytes
+    /// moved through it directly are not limited.
+    pub fn get_mut(&mut self) -> &mut T {
+        &mut self.inner
+    }
+
+    /// Returns the underlying reader or writer.
+    pub fn into_inner(self) -> T {
+        self.inner
+    }
+
+    fn refill(&mut self, now: Instant) {
+        let elapsed = now.saturating_duration_since(self.updated).as_nanos();
+        let earned = elapsed * self.rate as u128 / NANOS_PER_SEC;
+        if earned == 0 {
+            return;
+        }
+        let available = (self.available as u128 + earned).min(self.burst as u128);
+        self.available = available as u64;
+        self.updated = if available == self.burst as u128 {
+            now
+        } else {
+            // Keep the time the fraction of a byte took for later.
+            let spent = earned * NANOS_PER_SEC / self.rate as u128;
+            self.updated + Duration::from_nanos(spent as u64)
+        };
+    }
+
+    /// Waits until `wanted` bytes, or the burst size if that is less, may go
+    /// through, and returns how many may.
+    fn acquire(&mut self, wanted: usize) -> usize {
+        let wanted = (wanted as u64).min(self.burst);
+        loop {
+            self.refill(Instant::now());
+            if self.available >= wanted {
+                return cmp::min(self.available, usize::MAX as u64) as usize;
+            }
+            let missing = (wanted - self.available) as u128;
+            let rate = self.rate as u128;
+            let wait = (missing * NANOS_PER_SEC + rate - 1) / rate;
+            thread::sleep(Duration::from_nanos(wait.min(u64::MAX as u128) as u64));
+        }
+    }
+}
+
+impl<R: Read> Read for RateLimited<R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        if buf.is_empty() {
+            return self.inner.read(buf);
+        }
+        let len = cmp::min(buf.len(), self.acquire(buf.len()));
+        let n = self.inner.read(&mut buf[..len])?;
+        self.available -= n as u64;
+        Ok(n)
+    }
+}
+
+impl<W: Write> Write for RateLimited<W> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        if buf.is_empty() {
+            return self.inner.write(buf);
+        }
+        let len = cmp::min(buf.len(), self.acquire(buf.len()));
+        let n = self.inner.write(&buf[..len])?;
+        self.available -= n as u64;
+        Ok(n)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.inner.flush()
+    }
+}
+
+/// Captures what the current thread prints with [`print!`], [`eprint!`]
+/// and their `ln` variants into memory, for a test harness to show only
+/// for the tests that failed.
+///
+/// Threads spawned while the capture is active print into it as well.
+/// Writes to [`io::stdout`] and [`io::stderr`] other than through the
+/// macros are not captured, nor is anything written by other processes.
+///
+/// Captures nest: dropping one, or calling [`finish`], goes back to the
+/// capture that was active when it started, if any. Since that is a
+/// setting of the thread that started it, a capture can't be sent to
+/// another thread.
+///
+/// ```
+/// use std::os::dragonos::io::OutputCapture;
+///
+/// let capture = OutputCapture::start();
+/// println!("hello");
+/// eprintln!("world");
+/// assert_eq!(capture.finish(), b"hello\nworld\n");
+/// ```
+///
+/// [`finish`]: OutputCapture::finish
+pub struct OutputCapture {
+    buffer: Arc<Mutex<Vec<u8>>>,
+    previous: Option<Arc<Mutex<Vec<u8>>>>,
+    // Dropping restores the capture of the current thread, so keep this on
+    // the thread that started it.
+    _not_send: PhantomData<*const ()>,
+}
+
+impl OutputCapture {
+    /// Starts capturing the output of the current thread.
+    pub fn start() -> OutputCapture {
+        let buffer = Arc::new(Mutex::new(Vec::new()));
+        let previous = io::set_output_capture(Some(buffer.clone()));
+        OutputCapture {
+            buffer,
+            previous,
+            _not_send: PhantomData,
+        }
+    }
+
+    fn lock(&self) -> MutexGuard<'_, Vec<u8>> {
+        self.buffer.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    /// Returns a copy of what was captured so far.
+    pub fn contents(&self) -> Vec<u8> {
+        self.lock().clone()
+    }
+
+    /// Returns what was captured so far, and empties the buffer.
+    pub fn take(&self) -> Vec<u8> {
+        mem::take(&mut *self.lock())
+    }
+
+    /// Stops capturing, and returns what was captured since the last
+    /// [`take`](OutputCapture::take).
+    pub fn finish(self) -> Vec<u8> {
+        // Threads spawned during the capture may still hold the buffer, so
+        // take its contents instead of unwrapping it.
+        self.take()
+    }
+}
+
+impl Drop for OutputCapture {
+    fn drop(&mut self) {
+        io::set_output_capture(self.previous.take());
+    }
+}
+
+impl fmt::Debug for OutputCapture {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("OutputCapture")
+            .field("len", &self.lock().len())
+            .finish()
+    }
+}
@@ -0,0 +1,364 @@
+use super::{CountingReader, CountingWriter, FdOrErrno, OutputCapture, RateLimited};
+use crate::std::io::{self, BufRead, IoSlice, Read, Write};
+use crate::std::os::unix::io::{AsRawFd, IntoRawFd, OwnedFd};
+use crate::std::time::{Duration, Instant};
+
+#[test]
+fn fd_or_errno_decodes_errno() {
+    let fd_or_errno = unsafe { FdOrErrno::from_raw_ret(-(dlibc::EBADF as isize)) };
+    let err = OwnedFd::try_from(fd_or_errno).unwrap_err();
+    assert_eq!(err.raw_os_error(), Some(dlibc::EBADF));
+}
+
+#[test]
+fn fd_or_errno_takes_ownership() {
+    let fd = io::stdout().as_raw_fd();
+    let dup = unsafe { dlibc::dup(fd) };
+    assert!(dup >= 0);
+
+    let fd_or_errno = unsafe { FdOrErrno::from_raw_ret(dup as isize) };
+    let owned = OwnedFd::try_from(fd_or_errno).unwrap();
+    assert_eq!(owned.as_raw_fd(), dup);
+}
+
+#[test]
+fn duplicate_with_options() {
+    use super::{is_inheritable, DuplicateOptions};
+    use crate::std::os::unix::io::AsFd;
+
+    let stdout = io::stdout();
+    let fd = stdout.as_fd();
+
+    let dup = fd
+        .duplicate_with(DuplicateOptions::new().min_fd(64))
+        .unwrap();
+    assert!(dup.as_raw_fd() >= 64);
+    assert!(!is_inheritable(dup.as_fd()).unwrap());
+
+    let inheritable = dup
+        .duplicate_with(DuplicateOptions::new().cloexec(false))
+        .unwrap();
+    assert!(is_inheritable(inheritable.as_fd()).unwrap());
+
+    // Reserve a descriptor number that is then handed over to `moved`.
+    let target = fd.try_clone_to_owned().unwrap().into_raw_fd();
+    let moved = inheritable.duplicate_with(unsafe { DuplicateOptions::new().target(target) });
+    let moved = moved.unwrap();
+    assert_eq!(moved.as_raw_fd(), target);
+    assert!(!is_inheritable(moved.as_fd()).unwrap());
+
+    let err = moved
+        .duplicate_with(unsafe { DuplicateOptions::new().target(target) })
+        .unwrap_err();
+    assert_eq!(err.kind(), io::ErrorKind::InvalidInput);
+}
+
+#[test]
+fn counting_reader() {
+    let mut reader = CountingReader::new(io::BufReader::with_capacity(4, &b"hello world"[..]));
+    let mut buf = [0; 3];
+    reader.read_exact(&mut buf).unwrap();
+    assert_eq!(reader.count(), 3);
+    // Filling the buffer doesn't count until the bytes are consumed.
+    assert_eq!(reader.fill_buf().unwrap(), b"l");
+    assert_eq!(reader.count(), 3);
+    reader.consume(1);
+    let mut rest = String::new();
+    reader.read_to_string(&mut rest).unwrap();
+    assert_eq!(rest, "o world");
+    assert_eq!(reader.reset(), 11);
+    assert_eq!(reader.count(), 0);
+}
+
+#[test]
+fn counting_writer() {
+    let mut writer = CountingWriter::new(Vec::new());
+    writer.write_all(b"hello").unwrap();
+    writer
+        .write_vectored(&[IoSlice::new(b" "), IoSlice::new(b"world")])
+        .unwrap();
+    assert_eq!(writer.count(), 11);
+    assert_eq!(writer.into_inner(), b"hello world");
+}
+
+#[test]
+fn rate_limited() {
+    // A full bucket of 1000 bytes, then 500 bytes at 10000 bytes/s.
+    let mut writer = RateLimited::new(Vec::new(), 10_000);
+    writer.set_burst(1000);
+    let start = Instant::now();
+    writer.write_all(&[0; 1500]).unwrap();
+    let elapsed = start.elapsed();
+    assert_eq!(writer.get_ref().len(), 1500);
+    assert!(elapsed >= Duration::from_millis(45), "{elapsed:?}");
+
+    // Single reads never exceed the burst size.
+    let mut reader = RateLimited::new(&[0u8; 100][..], 1_000_000);
+    reader.set_burst(10);
+    let mut buf = [0; 100];
+    assert_eq!(reader.read(&mut buf).unwrap(), 10);
+}
+
+#[test]
+fn list_open_fds_sees_pipe() {
+    use super::{list_open_fds, set_inheritable};
+    use crate::std::os::unix::fs::FileTypeExt;
+    use crate::std::os::unix::io::AsFd;
+
+    let (reader, writer) = io::pipe().unwrap();
+    set_inheritable(writer.as_fd(), true).unwrap();
+
+    let fds = list_open_fds().unwrap();
+    assert!(fds.windows(2).all(|w| w[0].fd() < w[1].fd()));
+
+    let read_end = fds.iter().find(|fd| fd.fd() == reader.as_raw_fd()).unwrap();
+    assert!(read_end.file_type().is_fifo());
+    assert!(!read_end.is_inheritable());
+    assert_eq!(read_end.flags() & dlibc::O_ACCMODE, dlibc::O_RDONLY);
+
+    let write_end = fds.iter().find(|fd| fd.fd() == writer.as_raw_fd()).unwrap();
+    assert!(write_end.is_inheritable());
+    assert_eq!(write_end.flags() & dlibc::O_ACCMODE, dlibc::O_WRONLY);
+}
+
+#[test]
+fn close_and_cloexec_range() {
+    use super::{close_range, is_inheritable, set_cloexec_range, set_inheritable};
+    use crate::std::os::unix::io::AsFd;
+
+    let (reader, writer) = io::pipe().unwrap();
+    set_inheritable(writer.as_fd(), true).unwrap();
+    let fd = writer.as_raw_fd();
+    set_cloexec_range(fd, fd).unwrap();
+    assert!(!is_inheritable(writer.as_fd()).unwrap());
+
+    let fd = writer.into_raw_fd();
+    unsafe { close_range(fd, fd).unwrap() };
+    assert_eq!(unsafe { dlibc::fcntl(fd, dlibc::F_GETFD) }, -1);
+    drop(reader);
+
+    assert!(set_cloexec_range(5, 4).is_err());
+}
+
+#[test]
+fn stdout_buffer_mode() {
+    use super::{BufferMode, StdoutExt};
+
+    let stdout = io::stdout();
+    assert_eq!(stdout.buffer_mode(), BufferMode::Line);
+
+    stdout.set_buffer_mode(BufferMode::Block).unwrap();
+    assert_eq!(io::stdout().buffer_mode(), BufferMode::Block);
+    stdout.lock().write_all(b"").unwrap();
+
+    stdout.set_buffer_mode(BufferMode::Unbuffered).unwrap();
+    assert_eq!(stdout.buffer_mode(), BufferMode::Unbuffered);
+
+    stdout.set_buffer_mode(BufferMode::Line).unwrap();
+    assert_eq!(stdout.buffer_mode(), BufferMode::Line);
+}
+
+#[test]
+fn terminal_queries_on_pipe() {
+    use super::{disable_raw_mode, is_raw_mode_enabled, window_size};
+    use crate::std::os::unix::io::AsFd;
+
+    let (reader, _writer) = io::pipe().unwrap();
+    let err = window_size(reader.as_fd()).unwrap_err();
+    assert_eq!(err.raw_os_error(), Some(dlibc::ENOTTY));
+
+    // Standard input may be a real terminal here, so only the harmless
+    // direction is tried.
+    assert!(!is_raw_mode_enabled());
+    disable_raw_mode().unwrap();
+}
+
+#[test]
+fn raw_mode_guard_restores_stdin() {
+    use super::{is_raw_mode_enabled, StdinExt};
+    use crate::std::io::IsTerminal;
+
+    let stdin = io::stdin();
+    if !stdin.is_terminal() {
+        let err = stdin.raw_mode().unwrap_err();
+        assert_eq!(err.raw_os_error(), Some(dlibc::ENOTTY));
+        assert!(!is_raw_mode_enabled());
+        return;
+    }
+
+    let fd = stdin.as_raw_fd();
+    let flags = unsafe { dlibc::fcntl(fd, dlibc::F_GETFL) };
+    {
+        let mut guard = stdin.raw_mode().unwrap();
+        assert!(is_raw_mode_enabled());
+        guard.set_nonblocking(true).unwrap();
+        let now = unsafe { dlibc::fcntl(fd, dlibc::F_GETFL) };
+        assert_eq!(now & dlibc::O_NONBLOCK, dlibc::O_NONBLOCK);
+    }
+    assert!(!is_raw_mode_enabled());
+    assert_eq!(unsafe { dlibc::fcntl(fd, dlibc::F_GETFL) }, flags);
+}
+
+#[test]
+fn poll_pipe_and_nonblocking_reads() {
+    use super::{poll, set_nonblocking_flag, PollFd};
+    use crate::std::os::unix::io::AsFd;
+
+    let (mut reader, mut writer) = io::pipe().unwrap();
+    let mut fds = [
+        PollFd::readable(reader.as_fd()),
+        PollFd::writable(writer.as_fd()),
+    ];
+    assert_eq!(poll(&mut fds, Some(Duration::ZERO)).unwrap(), 1);
+    assert!(!fds[0].is_readable());
+    assert!(fds[1].is_writable());
+
+    set_nonblocking_flag(reader.as_raw_fd(), true).unwrap();
+    let mut buf = [0; 4];
+    assert_eq!(
+        reader.read(&mut buf).unwrap_err().kind(),
+        io::ErrorKind::WouldBlock
+    );
+
+    writer.write_all(b"hi").unwrap();
+    let mut fds = [PollFd::readable(reader.as_fd())];
+    assert_eq!(poll(&mut fds, None).unwrap(), 1);
+    assert!(fds[0].is_readable());
+    assert_eq!(reader.read(&mut buf).unwrap(), 2);
+
+    drop(writer);
+    let mut fds = [PollFd::readable(reader.as_fd())];
+    let start = Instant::now();
+    assert_eq!(poll(&mut fds, Some(Duration::from_secs(5))).unwrap(), 1);
+    assert!(start.elapsed() < Duration::from_secs(5));
+    assert!(fds[0].is_hung_up());
+    assert_eq!(reader.read(&mut buf).unwrap(), 0);
+}
+
+#[test]
+fn poller_tracks_many_pipes() {
+    use super::{Interest, Poller};
+    use crate::std::os::unix::io::AsFd;
+
+    let pipes: Vec<_> = (0..20).map(|_| io::pipe().unwrap()).collect();
+    let mut poller = Poller::new();
+    for (reader, _) in &pipes {
+        poller.add(reader.as_fd(), Interest::READABLE).unwrap();
+    }
+    assert_eq!(poller.len(), 20);
+    assert_eq!(
+        poller
+            .add(pipes[0].0.as_fd(), Interest::WRITABLE)
+            .unwrap_err()
+            .kind(),
+        io::ErrorKind::AlreadyExists
+    );
+    assert_eq!(poller.wait(Some(Duration::ZERO)).unwrap(), 0);
+    assert_eq!(poller.ready().count(), 0);
+
+    (&pipes[3].1).write_all(b"x").unwrap();
+    (&pipes[17].1).write_all(b"x").unwrap();
+    assert_eq!(poller.wait(None).unwrap(), 2);
+    let ready: Vec<_> = poller.ready().map(|fd| fd.fd().as_raw_fd()).collect();
+    assert_eq!(ready, [pipes[3].0.as_raw_fd(), pipes[17].0.as_raw_fd()]);
+    assert!(poller.ready().all(|fd| fd.is_readable() && !fd.is_error()));
+
+    poller.remove(pipes[3].0.as_fd()).unwrap();
+    poller.modify(pipes[17].0.as_fd(), Interest::ERROR).unwrap();
+    assert_eq!(poller.wait(Some(Duration::from_millis(10))).unwrap(), 0);
+    assert_eq!(
+        poller.remove(pipes[3].0.as_fd()).unwrap_err().kind(),
+        io::ErrorKind::NotFound
+    );
+}
+
+#[test]
+fn output_capture_nests_and_reaches_spawned_threads() {
+    // The print macros go through these.
+    use crate::std::io::{_eprint, _print};
+
+    let outer = OutputCapture::start();
+    _print(format_args!("outer "));
+
+    let inner = OutputCapture::start();
+    _eprint(format_args!("inner "));
+    crate::std::thread::spawn(|| _print(format_args!("spawned ")))
+        .join()
+        .unwrap();
+    assert_eq!(inner.take(), b"inner spawned ");
+    _print(format_args!("again"));
+    assert_eq!(inner.finish(), b"again");
+
+    _print(format_args!("after"));
+    assert_eq!(outer.contents(), b"outer after");
+    assert_eq!(outer.finish(), b"outer after");
+}
+
+#[test]
+fn event_queue_triggers() {
+    use super::{EventQueue, Events, Interest, Trigger};
+    use crate::std::os::unix::io::AsFd;
+
+    let queue = EventQueue::new().unwrap();
+    let mut events = Events::with_capacity(8);
+    assert_eq!(
+        queue
+            .wait(&mut events, Some(Duration::from_millis(10)))
+            .unwrap(),
+        0
+    );
+    assert!(events.is_empty());
+
+    let (level, level_tx) = io::pipe().unwrap();
+    let (edge, edge_tx) = io::pipe().unwrap();
+    let (oneshot, oneshot_tx) = io::pipe().unwrap();
+    queue
+        .add(level.as_fd(), 1, Interest::READABLE, Trigger::Level)
+        .unwrap();
+    queue
+        .add(edge.as_fd(), 2, Interest::READABLE, Trigger::Edge)
+        .unwrap();
+    queue
+        .add(oneshot.as_fd(), 3, Interest::READABLE, Trigger::Oneshot)
+        .unwrap();
+    assert_eq!(
+        queue
+            .add(level.as_fd(), 4, Interest::READABLE, Trigger::Level)
+            .unwrap_err()
+            .kind(),
+        io::ErrorKind::AlreadyExists
+    );
+
+    for mut tx in [&level_tx, &edge_tx, &oneshot_tx] {
+        tx.write_all(b"x").unwrap();
+    }
+    let tokens = |events: &Events| {
+        let mut tokens: Vec<_> = events.iter().map(|event| event.token()).collect();
+        tokens.sort();
+        tokens
+    };
+    assert_eq!(queue.wait(&mut events, None).unwrap(), 3);
+    assert_eq!(tokens(&events), [1, 2, 3]);
+    assert!(events
+        .iter()
+        .all(|event| event.is_readable() && !event.is_error()));
+
+    // Nothing was read, but only the level-triggered pipe is reported again.
+    queue.wait(&mut events, Some(Duration::ZERO)).unwrap();
+    assert_eq!(tokens(&events), [1]);
+
+    // New data makes the edge-triggered pipe ready again, and modifying the
+    // oneshot one rearms it.
+    (&edge_tx).write_all(b"y").unwrap();
+    queue
+        .modify(oneshot.as_fd(), 5, Interest::READABLE, Trigger::Oneshot)
+        .unwrap();
+    queue.delete(level.as_fd()).unwrap();
+    queue.wait(&mut events, Some(Duration::ZERO)).unwrap();
+    assert_eq!(tokens(&events), [2, 5]);
+
+    assert_eq!(
+        queue.delete(level.as_fd()).unwrap_err().kind(),
+        io::ErrorKind::NotFound
+    );
+}
@@ -0,0 +1,325 @@
+//! Communication between processes.
+
+use crate::std::fmt;
+use crate::std::fs::{File, OpenOptions};
+use crate::std::io;
+use crate::std::marker::PhantomData;
+use crate::std::mem;
+use crate::std::os::unix::fs::OpenOptionsExt;
+use crate::std::os::unix::io::AsRawFd;
+use crate::std::path::Path;
+use crate::std::ptr::{self, NonNull};
+use crate::std::sync::atomic::{
+    AtomicU32,
+    Ordering::{Acquire, Relaxed, Release, SeqCst},
+};
+use crate::std::sys::futex::{futex_wait_shared, futex_wake_all_shared};
+use crate::std::time::{Duration, Instant};
+
+/// Marks a file as an initialized channel. Written last by `create`.
+const MAGIC: u32 = u32::from_le_bytes(*b"DShm");
+
+/// The start of the shared mapping, followed by the slots.
+///
+/// `head` and `tail` count the values sent and received so far, wrapping at
+/// `u32::MAX`, which works out because the capacity is a power of two. Each is
+/// written by one side only and doubles as the futex the other side waits on.
+#[repr(C, align(64))]
+struct Header {
+    magic: AtomicU32,
+    elem_size: u32,
+    elem_align: u32,
+    capacity: u32,
+    head: AtomicU32,
+    tail: AtomicU32,
+    // Set by a receiver about to wait on `head`, and by a sender about to
+    // wait on `tail`, so the other side only makes a system call when
+    // someone is waiting.
+    receiver_waiting: AtomicU32,
+    sender_waiting: AtomicU32,
+}
+
+/// A bounded single-producer, single-consumer queue in a file mapped by two
+/// processes.
+///
+/// One process [`create`]s the channel and the other [`open`]s it by path.
+/// Values are copied into memory both processes share, so sending doesn't
+/// serialize anything or make a system call unless the other side is
+/// blocked waiting. Since only the bytes of a value are copied, `T` must be
+/// plain data: pointers and file descriptors in it mean nothing to the other
+/// process. Values are read back from memory that other processes can
+/// write, so both [`create`] and [`open`] are unsafe.
+///
+/// The channel doesn't notice when the other process exits. Use
+/// [`recv_timeout`] where that matters.
+///
+/// ```no_run
+/// use std::os::dragonos::ipc::ShmChannel;
+///
+/// // In the service:
+/// let requests = unsafe { ShmChannel::<[u64; 4]>::create("/tmp/service.chan", 1024)? };
+/// let request = requests.recv();
+///
+/// // In the client:
+/// let requests = unsafe { ShmChannel::<[u64; 4]>::open("/tmp/service.chan")? };
+/// requests.send([1, 2, 3, 4]);
+/// # Ok::<(), std::io::Error>(())
+/// ```
+///
+/// [`create`]: ShmChannel::create
+/// [`open`]: ShmChannel::open
+/// [`recv_timeout`]: ShmChannel::recv_timeout
+pub struct ShmChannel<T: Copy> {
+    header: NonNull<Header>,
+    len: usize,
+    _marker: PhantomData<T>,
+}
+
+unsafe impl<T: Copy + Send> Send for ShmChannel<T> {}
+
+impl<T: Copy> ShmChannel<T> {
+    /// Creates the file at `path` and maps a channel with room for at least
+    /// `capacity` values in it.
+    ///
+    /// The capacity is rounded up to a power of two. The file is created
+    /// with mode `0o600` and fails to be created if it already exists; remove
+    /// it once the other process has opened it, or when the channel is no
+    /// longer needed.
+    ///
+    /// # Safety
+    ///
+    /// Every bit pattern must be a valid `T`, since any process that can
+    /// write the file can put arbitrary bytes in the slots; this rules out
+    /// references, `bool`, `char`, enums and the `NonZero` integers, among
+    /// others. At most one process may send and at most one may receive on
+    /// the channel.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `T` needs to be aligned to more than 64 bytes.
+    pub unsafe fn create<P: AsRef<Path>>(path: P, capacity: usize) -> io::Result<ShmChannel<T>> {
+        assert!(
+            mem::align_of::<T>() <= mem::align_of::<Header>(),
+            "ShmChannel values may be aligned to at most 64 bytes"
+        );
+        let capacity = u32::try_from(capacity.max(1))
+            .ok()
+            .and_then(u32::checked_next_power_of_two)
+            .ok_or(io::const_io_error!(
+                io::ErrorKind::InvalidInput,
+                "channel capacity is too large",
+            ))?;
+        let len = Self::mapping_len(capacity).ok_or(io::const_io_error!(
+            io::ErrorKind::InvalidInput,
+            "channel capacity is too large",
+        ))?;
+
+        let file = OpenOptions::new()
+            .read(true)
+            .write(true)
+            .create_new(true)
+            .mode(0o600)
+            .open(path)?;
+        file.set_len(len as u64)?;
+        let channel = unsafe { Self::map(&file, len)? };
+        // The file is new, so everything else is zero already.
+        unsafe {
+            let header = channel.header.as_ptr();
+            ptr::addr_of_mut!((*header).elem_size).write(mem::size_of::<T>() as u32);
+            ptr::addr_of_mut!((*header).elem_align).write(mem::align_of::<T>() as u32);
+            ptr::addr_of_mut!((*header).capacity).write(capacity);
+        }
+        channel.header().magic.store(MAGIC, Release);
+        Ok(channel)
+    }
+
+    /// Maps the channel that another process [`create`]d at `path`.
+    ///
+    /// Fails with [`InvalidData`] if the file doesn't hold a channel for
+    /// values of the same size and alignment as `T`, which includes a
+    /// channel that is still being created.
+    ///
+    /// # Safety
+    ///
+    /// Every bit pattern must be a valid `T`, as for [`create`]. The channel
+    /// must have been created for the same type `T`, at most one process may
+    /// send and at most one may receive on it, and nothing else may modify
+    /// the file while it is mapped.
+    ///
+    /// [`create`]: ShmChannel::create
+    /// [`InvalidData`]: io::ErrorKind::InvalidData
+    pub unsafe fn open<P: AsRef<Path>>(path: P) -> io::Result<ShmChannel<T>> {
+        let file = OpenOptions::new().read(true).write(true).open(path)?;
+        let len = usize::try_from(file.metadata()?.len())
+            .ok()
+            .filter(|&len| len >= mem::size_of::<Header>())
+            .ok_or(io::const_io_error!(
+                io::ErrorKind::InvalidData,
+                "not a channel",
+            ))?;
+        let channel = Self::map(&file, len)?;
+        let header = channel.header();
+        if header.magic.load(Acquire) != MAGIC
+            || header.elem_size as usize != mem::size_of::<T>()
+            || header.elem_align as usize != mem::align_of::<T>()
+            || !header.capacity.is_power_of_two()
+            || Self::mapping_len(header.capacity) != Some(len)
+        {
+            return Err(io::const_io_error!(
+                io::ErrorKind::InvalidData,
+                "not a channel for values of this type",
+            ));
+        }
+        Ok(channel)
+    }
+
+    fn mapping_len(capacity: u32) -> Option<usize> {
+        (capacity as usize)
+            .checked_mul(mem::size_of::<T>())?
+            .checked_add(mem::size_of::<Header>())
+    }
+
+    unsafe fn map(file: &File, len: usize) -> io::Result<ShmChannel<T>> {
+        let addr = dlibc::mmap(
+            ptr::null_mut(),
+            len,
+            dlibc::PROT_READ | dlibc::PROT_WRITE,
+            dlibc::MAP_SHARED,
+            file.as_raw_fd(),
+            0,
+        );
+        if addr == dlibc::MAP_FAILED {
+            return Err(io::Error::last_os_error());
+        }
+        Ok(ShmChannel {
+            header: NonNull::new_unchecked(addr.cast()),
+            len,
+            _marker: PhantomData,
+        })
+    }
+
+    fn header(&self) -> &Header {
+        unsafe { self.header.as_ref() }
+    }
+
+    fn slot(&self, index: u32) -> *mut T {
+        let index = index & (self.header().capacity - 1);
+        unsafe { self.header.as_ptr().add(1).cast::<T>().add(index as usize) }
+    }
+
+    /// Returns the number of values the channel can hold.
+    pub fn capacity(&self) -> usize {
+        self.header().capacity as usize
+    }
+
+    /// Returns the number of values sent but not yet received.
+    pub fn len(&self) -> usize {
+        let header = self.header();
+        header
+            .head
+            .load(Relaxed)
+            .wrapping_sub(header.tail.load(Relaxed)) as usize
+    }
+
+    /// Returns whether there are no values waiting to be received.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Sends `value` if there is room for it, or returns it if the channel
+    /// is full.
+    pub fn try_send(&self, value: T) -> Result<(), T> {
+        let header = self.header();
+        let head = header.head.load(Relaxed);
+        if head.wrapping_sub(header.tail.load(Acquire)) == header.capacity {
+            return Err(value);
+        }
+        unsafe { self.slot(head).write(value) };
+        header.head.store(head.wrapping_add(1), SeqCst);
+        if header.receiver_waiting.swap(0, SeqCst) != 0 {
+            futex_wake_all_shared(&header.head);
+        }
+        Ok(())
+    }
+
+    /// Sends `value`, waiting for room if the channel is full.
+    pub fn send(&self, mut value: T) {
+        let header = self.header();
+        loop {
+            let tail = header.tail.load(Relaxed);
+            value = match self.try_send(value) {
+                Ok(()) => return,
+                Err(value) => value,
+            };
+            header.sender_waiting.store(1, SeqCst);
+            if header.tail.load(SeqCst) == tail {
+                futex_wait_shared(&header.tail, tail, None);
+            }
+        }
+    }
+
+    /// Receives a value if one is waiting.
+    pub fn try_recv(&self) -> Option<T> {
+        let header = self.header();
+        let tail = header.tail.load(Relaxed);
+        if header.head.load(Acquire) == tail {
+            return None;
+        }
+        let value = unsafe { self.slot(tail).read() };
+        header.tail.store(tail.wrapping_add(1), SeqCst);
+        if header.sender_waiting.swap(0, SeqCst) != 0 {
+            futex_wake_all_shared(&header.tail);
+        }
+        Some(value)
+    }
+
+    /// Receives a value, waiting for one to be sent if the channel is empty.
+    pub fn recv(&self) -> T {
+        // Without a deadline, this only returns once there is a value.
+        self.recv_until(None).unwrap()
+    }
+
+    /// Receives a value, waiting at most `timeout` for one to be sent if the
+    /// channel is empty.
+    pub fn recv_timeout(&self, timeout: Duration) -> Option<T> {
+        self.recv_until(Instant::now().checked_add(timeout))
+    }
+
+    fn recv_until(&self, deadline: Option<Instant>) -> Option<T> {
+        let header = self.header();
+        loop {
+            let head = header.head.load(Relaxed);
+            if let Some(value) = self.try_recv() {
+                return Some(value);
+            }
+            let timeout = match deadline {
+                Some(deadline) => Some(deadline.checked_duration_since(Instant::now())?),
+                None => None,
+            };
+            header.receiver_waiting.store(1, SeqCst);
+            if header.head.load(SeqCst) == head && !futex_wait_shared(&header.head, head, timeout) {
+                return self.try_recv();
+            }
+        }
+    }
+}
+
+impl<T: Copy> Drop for ShmChannel<T> {
+    fn drop(&mut self) {
+        unsafe {
+            dlibc::munmap(self.header.as_ptr().cast(), self.len);
+        }
+    }
+}
+
+impl<T: Copy> fmt::Debug for ShmChannel<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ShmChannel")
+            .field("capacity", &self.capacity())
+            .field("len", &self.len())
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod tests;
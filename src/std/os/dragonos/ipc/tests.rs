@@ -0,0 +1,56 @@
+use super::ShmChannel;
+use crate::std::io::ErrorKind;
+use crate::std::thread;
+use crate::std::time::Duration;
+
+#[test]
+fn send_and_recv() {
+    let dir = crate::std::sys_common::io::test::tmpdir();
+    let path = dir.join("chan");
+    let tx = unsafe { ShmChannel::<u64>::create(&path, 3).unwrap() };
+    // A second mapping of the file stands in for the other process.
+    let rx = unsafe { ShmChannel::<u64>::open(&path).unwrap() };
+    assert_eq!(tx.capacity(), 4);
+
+    for i in 0..4 {
+        tx.try_send(i).unwrap();
+    }
+    assert_eq!(tx.try_send(4), Err(4));
+    assert_eq!(rx.len(), 4);
+    assert_eq!(rx.try_recv(), Some(0));
+    tx.try_send(4).unwrap();
+    for i in 1..5 {
+        assert_eq!(rx.recv(), i);
+    }
+    assert!(rx.is_empty());
+    assert_eq!(rx.recv_timeout(Duration::from_millis(10)), None);
+}
+
+#[test]
+fn blocking_transfer() {
+    let dir = crate::std::sys_common::io::test::tmpdir();
+    let path = dir.join("chan");
+    let tx = unsafe { ShmChannel::<[u32; 2]>::create(&path, 8).unwrap() };
+    let rx = unsafe { ShmChannel::<[u32; 2]>::open(&path).unwrap() };
+
+    let sender = thread::spawn(move || {
+        for i in 0..10_000 {
+            tx.send([i, !i]);
+        }
+    });
+    for i in 0..10_000 {
+        assert_eq!(rx.recv(), [i, !i]);
+    }
+    sender.join().unwrap();
+}
+
+#[test]
+fn open_checks_type() {
+    let dir = crate::std::sys_common::io::test::tmpdir();
+    let path = dir.join("chan");
+    let _tx = unsafe { ShmChannel::<u64>::create(&path, 1).unwrap() };
+    let err = unsafe { ShmChannel::<u32>::open(&path).unwrap_err() };
+    assert_eq!(err.kind(), ErrorKind::InvalidData);
+    let err = unsafe { ShmChannel::<u64>::create(&path, 1).unwrap_err() };
+    assert_eq!(err.kind(), ErrorKind::AlreadyExists);
+}
@@ -0,0 +1,32 @@
+//! DragonOS-specific definitions.
+//!
+//! DragonOS also exposes the [`std::os::unix`] and [`std::os::linux`]
+//! extension modules; this module holds the extensions that have no Linux
+//! equivalent or whose behavior is specific to the DragonOS kernel.
+//!
+//! [`std::os::unix`]: crate::std::os::unix
+//! [`std::os::linux`]: crate::std::os::linux
+
+#![doc(cfg(target_os = "dragonos"))]
+
+pub mod caps;
+pub mod cgroup;
+pub mod dl;
+pub mod dns;
+pub mod fs;
+pub mod futex;
+#[cfg(feature = "heap-profiling")]
+pub mod heap;
+pub mod io;
+pub mod ipc;
+pub mod net;
+pub mod process;
+pub mod rt;
+pub mod seccomp;
+pub mod service;
+pub mod sync;
+pub mod system;
+pub mod thread;
+pub mod time;
+#[cfg(feature = "wsa-compat")]
+pub mod wsa;
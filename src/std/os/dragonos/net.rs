@@ -0,0 +1,491 @@
+//! DragonOS-specific extensions to primitives in the [`std::net`] module.
+//!
+//! [`std::net`]: crate::std::net
+
+use crate::std::ffi::{OsStr, OsString};
+use crate::std::fmt;
+use crate::std::io;
+use crate::std::mem;
+use crate::std::net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr, TcpListener, TcpStream, UdpSocket};
+use crate::std::os::unix::ffi::{OsStrExt, OsStringExt};
+use crate::std::os::unix::io::{AsFd, AsRawFd, BorrowedFd, RawFd};
+use crate::std::sealed::Sealed;
+use crate::std::sys::net::{init, Socket};
+use crate::std::sys::{cvt, cvt_r};
+use crate::std::sys_common::net::{self as net_imp, setsockopt, sockaddr_to_addr};
+use crate::std::sys_common::{AsInner, FromInner, IntoInner};
+
+mod packet;
+
+pub use self::packet::{interface_index, BpfInstruction, LinkAddr, PacketSocket};
+
+/// Creates a [`TcpListener`] in steps, so that socket options can be set
+/// before the socket is bound.
+///
+/// [`TcpListener::bind`] creates, binds and starts listening on a socket in
+/// one call, which leaves no room for options that only take effect before
+/// `bind`, such as `SO_REUSEPORT` and `IPV6_V6ONLY`, or before `listen`,
+/// such as `TCP_FASTOPEN`. With a builder, the socket is created by
+/// [`new_v4`] or [`new_v6`], the options are set, and then it is bound and
+/// turned into a listener.
+///
+/// Unlike [`TcpListener::bind`], a builder sets no options on its own, not
+/// even `SO_REUSEADDR`.
+///
+/// ```no_run
+/// use std::os::dragonos::net::TcpListenerBuilder;
+///
+/// // Let several processes accept connections on the same port.
+/// let listener = TcpListenerBuilder::new_v6()?
+///     .only_v6(false)?
+///     .reuse_address(true)?
+///     .reuse_port(true)?
+///     .bind("[::]:8080".parse().unwrap())?
+///     .listen(1024)?;
+/// # Ok::<(), std::io::Error>(())
+/// ```
+///
+/// [`new_v4`]: TcpListenerBuilder::new_v4
+/// [`new_v6`]: TcpListenerBuilder::new_v6
+pub struct TcpListenerBuilder {
+    socket: Socket,
+    family: dlibc::c_int,
+}
+
+impl TcpListenerBuilder {
+    /// Creates an unbound IPv4 TCP socket.
+    pub fn new_v4() -> io::Result<TcpListenerBuilder> {
+        Self::new(dlibc::AF_INET)
+    }
+
+    /// Creates an unbound IPv6 TCP socket.
+    pub fn new_v6() -> io::Result<TcpListenerBuilder> {
+        Self::new(dlibc::AF_INET6)
+    }
+
+    fn new(family: dlibc::c_int) -> io::Result<TcpListenerBuilder> {
+        init();
+        Ok(TcpListenerBuilder {
+            socket: Socket::new_raw(family, dlibc::SOCK_STREAM)?,
+            family,
+        })
+    }
+
+    /// Sets `SO_REUSEADDR`, which allows binding to a local address that a
+    /// recently closed socket still lingers on.
+    pub fn reuse_address(self, reuse: bool) -> io::Result<TcpListenerBuilder> {
+        self.set(
+            dlibc::SOL_SOCKET,
+            dlibc::SO_REUSEADDR,
+            reuse as dlibc::c_int,
+        )
+    }
+
+    /// Sets `SO_REUSEPORT`, which allows several sockets that all set it to
+    /// bind to the same address, with the kernel spreading the incoming
+    /// connections among them.
+    pub fn reuse_port(self, reuse: bool) -> io::Result<TcpListenerBuilder> {
+        self.set(
+            dlibc::SOL_SOCKET,
+            dlibc::SO_REUSEPORT,
+            reuse as dlibc::c_int,
+        )
+    }
+
+    /// Sets `IPV6_V6ONLY` on an IPv6 socket. When `false`, a socket bound to
+    /// the unspecified address `::` also accepts IPv4 connections, which
+    /// then appear to come from IPv4-mapped IPv6 addresses.
+    pub fn only_v6(self, only_v6: bool) -> io::Result<TcpListenerBuilder> {
+        self.set(
+            dlibc::IPPROTO_IPV6,
+            dlibc::IPV6_V6ONLY,
+            only_v6 as dlibc::c_int,
+        )
+    }
+
+    /// Sets `TCP_FASTOPEN`, like [`TcpListenerExt::set_fastopen`].
+    pub fn fastopen(self, queue_len: u32) -> io::Result<TcpListenerBuilder> {
+        set_fastopen(&self.socket, queue_len)?;
+        Ok(self)
+    }
+
+    /// Sets `IP_FREEBIND` or `IPV6_FREEBIND`, which allows binding to an
+    /// address that isn't assigned to any interface yet, such as the address
+    /// of an interface that a VPN daemon is still bringing up.
+    pub fn freebind(self, freebind: bool) -> io::Result<TcpListenerBuilder> {
+        let (level, name) = match self.family {
+            dlibc::AF_INET => (dlibc::IPPROTO_IP, dlibc::IP_FREEBIND),
+            _ => (dlibc::IPPROTO_IPV6, dlibc::IPV6_FREEBIND),
+        };
+        self.set(level, name, freebind as dlibc::c_int)
+    }
+
+    /// Binds the socket to a network interface, like
+    /// [`SocketExt::bind_device`].
+    pub fn bind_device(self, interface: Option<&OsStr>) -> io::Result<TcpListenerBuilder> {
+        bind_device(&self.socket, interface)?;
+        Ok(self)
+    }
+
+    fn set(
+        self,
+        level: dlibc::c_int,
+        name: dlibc::c_int,
+        value: dlibc::c_int,
+    ) -> io::Result<TcpListenerBuilder> {
+        setsockopt(&self.socket, level, name, value)?;
+        Ok(self)
+    }
+
+    /// Binds the socket to `addr`, which must be of the address family the
+    /// builder was created for.
+    pub fn bind(self, addr: SocketAddr) -> io::Result<TcpListenerBuilder> {
+        let (addr, len) = (&addr).into_inner();
+        cvt(unsafe { dlibc::bind(self.socket.as_raw_fd(), addr.as_ptr(), len as _) })?;
+        Ok(self)
+    }
+
+    /// Starts listening for connections, with room for `backlog` of them to
+    /// wait for [`accept`].
+    ///
+    /// If the socket hasn't been bound, it is bound to an unused port first.
+    ///
+    /// [`accept`]: TcpListener::accept
+    pub fn listen(self, backlog: i32) -> io::Result<TcpListener> {
+        cvt(unsafe { dlibc::listen(self.socket.as_raw_fd(), backlog) })?;
+        Ok(TcpListener::from_inner(net_imp::TcpListener::from_inner(
+            self.socket,
+        )))
+    }
+}
+
+impl AsFd for TcpListenerBuilder {
+    fn as_fd(&self) -> BorrowedFd<'_> {
+        self.socket.as_fd()
+    }
+}
+
+impl AsRawFd for TcpListenerBuilder {
+    fn as_raw_fd(&self) -> RawFd {
+        self.socket.as_raw_fd()
+    }
+}
+
+impl fmt::Debug for TcpListenerBuilder {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("TcpListenerBuilder")
+            .field("fd", &self.socket.as_raw_fd())
+            .finish()
+    }
+}
+
+/// Os-specific extensions for sockets in the [`std::net`] module: for
+/// [`TcpStream`], [`TcpListener`] and [`UdpSocket`].
+///
+/// [`std::net`]: crate::std::net
+pub trait SocketExt: Sealed {
+    /// Binds the socket to a network interface with `SO_BINDTODEVICE`, or
+    /// removes the binding if `interface` is `None`.
+    ///
+    /// A socket bound to an interface only receives packets that arrived on
+    /// it, and sends packets out through it regardless of the routing table.
+    /// Binding needs the `NetRaw` capability, while removing the binding
+    /// may not be allowed without it.
+    ///
+    /// ```no_run
+    /// use std::ffi::OsStr;
+    /// use std::net::UdpSocket;
+    /// use std::os::dragonos::net::SocketExt;
+    ///
+    /// // Keep the tunnel's traffic on the physical uplink.
+    /// let socket = UdpSocket::bind("0.0.0.0:51820")?;
+    /// socket.bind_device(Some(OsStr::new("eth0")))?;
+    /// # Ok::<(), std::io::Error>(())
+    /// ```
+    fn bind_device(&self, interface: Option<&OsStr>) -> io::Result<()>;
+
+    /// Returns the interface the socket is bound to with
+    /// [`bind_device`](SocketExt::bind_device), if any.
+    fn device(&self) -> io::Result<Option<OsString>>;
+}
+
+impl Sealed for UdpSocket {}
+
+macro_rules! impl_socket_ext {
+    ($($t:ty),*) => {$(
+        impl SocketExt for $t {
+            fn bind_device(&self, interface: Option<&OsStr>) -> io::Result<()> {
+                bind_device(self.as_inner().socket(), interface)
+            }
+
+            fn device(&self) -> io::Result<Option<OsString>> {
+                device(self.as_inner().socket())
+            }
+        }
+    )*};
+}
+
+impl_socket_ext!(TcpStream, TcpListener, UdpSocket);
+
+fn bind_device(socket: &Socket, interface: Option<&OsStr>) -> io::Result<()> {
+    let name = interface.map_or(&[][..], OsStr::as_bytes);
+    if name.len() >= dlibc::IFNAMSIZ {
+        return Err(io::const_io_error!(
+            io::ErrorKind::InvalidInput,
+            "interface name is too long",
+        ));
+    }
+    cvt(unsafe {
+        dlibc::setsockopt(
+            socket.as_raw_fd(),
+            dlibc::SOL_SOCKET,
+            dlibc::SO_BINDTODEVICE,
+            name.as_ptr().cast(),
+            name.len() as dlibc::socklen_t,
+        )
+    })?;
+    Ok(())
+}
+
+fn device(socket: &Socket) -> io::Result<Option<OsString>> {
+    let mut name = [0u8; dlibc::IFNAMSIZ];
+    let mut len = name.len() as dlibc::socklen_t;
+    cvt(unsafe {
+        dlibc::getsockopt(
+            socket.as_raw_fd(),
+            dlibc::SOL_SOCKET,
+            dlibc::SO_BINDTODEVICE,
+            name.as_mut_ptr().cast(),
+            &mut len,
+        )
+    })?;
+    let name = &name[..len as usize];
+    let name = name.split(|&b| b == 0).next().unwrap_or(name);
+    Ok((!name.is_empty()).then(|| OsString::from_vec(name.to_vec())))
+}
+
+/// Os-specific extensions for [`TcpListener`]
+pub trait TcpListenerExt: Sealed {
+    /// Sets `TCP_FASTOPEN`, which lets clients that connected before send
+    /// data along with their first segment, such as with
+    /// [`connect_fastopen`], saving a round trip before the server sees the
+    /// request.
+    ///
+    /// `queue_len` limits how many such connections may be waiting for
+    /// [`accept`] at a time; 0 turns fast open off.
+    ///
+    /// [`accept`]: TcpListener::accept
+    fn set_fastopen(&self, queue_len: u32) -> io::Result<()>;
+}
+
+impl Sealed for TcpListener {}
+
+impl TcpListenerExt for TcpListener {
+    fn set_fastopen(&self, queue_len: u32) -> io::Result<()> {
+        set_fastopen(self.as_inner().socket(), queue_len)
+    }
+}
+
+fn set_fastopen(socket: &Socket, queue_len: u32) -> io::Result<()> {
+    let queue_len = dlibc::c_int::try_from(queue_len).unwrap_or(dlibc::c_int::MAX);
+    setsockopt(socket, dlibc::IPPROTO_TCP, dlibc::TCP_FASTOPEN, queue_len)
+}
+
+/// Opens a TCP connection to `addr` and sends `data` along with the
+/// connection request, if the server allows it.
+///
+/// With TCP fast open, a client that connected to a server before holds a
+/// cookie from it, and can send data in the very first segment of a new
+/// connection, so the server can answer a round trip earlier. The first
+/// connection to a server, and connections to servers that don't enable it
+/// with [`TcpListenerExt::set_fastopen`], fall back to a regular handshake
+/// with the data sent afterwards, as does everything on kernels without
+/// fast open.
+///
+/// Returns the stream and how many bytes of `data` were sent, which, as with
+/// [`Write::write`], may be fewer than all of them.
+///
+/// ```no_run
+/// use std::io::{Read, Write};
+/// use std::os::dragonos::net::connect_fastopen;
+///
+/// let request = b"GET / HTTP/1.0\r\n\r\n";
+/// let (mut stream, sent) = connect_fastopen("192.0.2.1:80".parse().unwrap(), request)?;
+/// stream.write_all(&request[sent..])?;
+/// let mut response = Vec::new();
+/// stream.read_to_end(&mut response)?;
+/// # Ok::<(), std::io::Error>(())
+/// ```
+///
+/// [`Write::write`]: crate::std::io::Write::write
+pub fn connect_fastopen(addr: SocketAddr, data: &[u8]) -> io::Result<(TcpStream, usize)> {
+    init();
+    let socket = Socket::new(&addr, dlibc::SOCK_STREAM)?;
+    let (raw_addr, len) = (&addr).into_inner();
+    let sent = cvt_r(|| unsafe {
+        dlibc::sendto(
+            socket.as_raw_fd(),
+            data.as_ptr().cast(),
+            data.len(),
+            dlibc::MSG_FASTOPEN | dlibc::MSG_NOSIGNAL,
+            raw_addr.as_ptr(),
+            len,
+        )
+    });
+    let sent = match sent {
+        Ok(sent) => sent as usize,
+        // The kernel doesn't know `MSG_FASTOPEN`.
+        Err(e) if matches!(e.raw_os_error(), Some(dlibc::EOPNOTSUPP | dlibc::EINVAL)) => {
+            cvt_r(|| unsafe { dlibc::connect(socket.as_raw_fd(), raw_addr.as_ptr(), len) })?;
+            socket.write(data)?
+        }
+        Err(e) => return Err(e),
+    };
+    let stream = TcpStream::from_inner(net_imp::TcpStream::from_inner(socket));
+    Ok((stream, sent))
+}
+
+/// Os-specific extensions for [`UdpSocket`]
+pub trait UdpSocketExt: Sealed {
+    /// Sets whether the kernel reports where each datagram was sent to, with
+    /// `IP_PKTINFO` for IPv4 sockets and `IPV6_RECVPKTINFO` for IPv6 ones.
+    ///
+    /// Once set, [`recv_from_pktinfo`] returns the destination of every
+    /// datagram.
+    ///
+    /// [`recv_from_pktinfo`]: UdpSocketExt::recv_from_pktinfo
+    fn set_recv_pktinfo(&self, recv: bool) -> io::Result<()>;
+
+    /// Receives a datagram like [`UdpSocket::recv_from`], and also returns
+    /// the address it was sent to and the interface it arrived on.
+    ///
+    /// A server bound to an unspecified address, such as `0.0.0.0`, has to
+    /// know which of its addresses a request was sent to in order to reply
+    /// from the same one. The information is `None` unless
+    /// [`set_recv_pktinfo`] was turned on before the datagram arrived.
+    ///
+    /// ```no_run
+    /// use std::net::UdpSocket;
+    /// use std::os::dragonos::net::UdpSocketExt;
+    ///
+    /// let socket = UdpSocket::bind("0.0.0.0:53")?;
+    /// socket.set_recv_pktinfo(true)?;
+    /// let mut buf = [0; 512];
+    /// let (len, from, info) = socket.recv_from_pktinfo(&mut buf)?;
+    /// if let Some(info) = info {
+    ///     println!("{len} bytes from {from} to {}", info.destination());
+    /// }
+    /// # Ok::<(), std::io::Error>(())
+    /// ```
+    ///
+    /// [`set_recv_pktinfo`]: UdpSocketExt::set_recv_pktinfo
+    fn recv_from_pktinfo(
+        &self,
+        buf: &mut [u8],
+    ) -> io::Result<(usize, SocketAddr, Option<PacketInfo>)>;
+}
+
+/// Where a datagram was sent to, as returned by
+/// [`UdpSocketExt::recv_from_pktinfo`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct PacketInfo {
+    destination: IpAddr,
+    interface: u32,
+}
+
+impl PacketInfo {
+    /// Returns the destination address in the header of the datagram.
+    ///
+    /// For a datagram that an IPv6 socket received over IPv4, this is the
+    /// IPv4 address rather than the IPv4-mapped IPv6 one.
+    pub fn destination(&self) -> IpAddr {
+        self.destination
+    }
+
+    /// Returns the index of the interface the datagram arrived on.
+    pub fn interface_index(&self) -> u32 {
+        self.interface
+    }
+}
+
+impl UdpSocketExt for UdpSocket {
+    fn set_recv_pktinfo(&self, recv: bool) -> io::Result<()> {
+        let socket = self.as_inner().socket();
+        if self.local_addr()?.is_ipv4() {
+            setsockopt(
+                socket,
+                dlibc::IPPROTO_IP,
+                dlibc::IP_PKTINFO,
+                recv as dlibc::c_int,
+            )
+        } else {
+            setsockopt(
+                socket,
+                dlibc::IPPROTO_IPV6,
+                dlibc::IPV6_RECVPKTINFO,
+                recv as dlibc::c_int,
+            )
+        }
+    }
+
+    fn recv_from_pktinfo(
+        &self,
+        buf: &mut [u8],
+    ) -> io::Result<(usize, SocketAddr, Option<PacketInfo>)> {
+        let mut storage: dlibc::sockaddr_storage = unsafe { mem::zeroed() };
+        let mut iov = dlibc::iovec {
+            iov_base: buf.as_mut_ptr().cast(),
+            iov_len: buf.len(),
+        };
+        // Room for either kind of packet information, and a little more that
+        // is cut off if other options add their own messages.
+        let mut control = [0usize; 16];
+        let mut msg: dlibc::msghdr = unsafe { mem::zeroed() };
+        msg.msg_name = (&mut storage as *mut dlibc::sockaddr_storage).cast();
+        msg.msg_namelen = mem::size_of_val(&storage) as dlibc::socklen_t;
+        msg.msg_iov = &mut iov;
+        msg.msg_iovlen = 1;
+        msg.msg_control = control.as_mut_ptr().cast();
+        msg.msg_controllen = mem::size_of_val(&control) as _;
+
+        let n = cvt_r(|| unsafe { dlibc::recvmsg(self.as_raw_fd(), &mut msg, 0) })?;
+        let from = sockaddr_to_addr(&storage, msg.msg_namelen as usize)?;
+        Ok((n as usize, from, unsafe { packet_info(&msg) }))
+    }
+}
+
+/// Finds the packet information among the control messages `recvmsg` filled
+/// in.
+unsafe fn packet_info(msg: &dlibc::msghdr) -> Option<PacketInfo> {
+    let mut cmsg = dlibc::CMSG_FIRSTHDR(msg);
+    while let Some(header) = cmsg.as_ref() {
+        let data = dlibc::CMSG_DATA(cmsg);
+        let len = (header.cmsg_len as usize).saturating_sub(dlibc::CMSG_LEN(0) as usize);
+        match (header.cmsg_level, header.cmsg_type) {
+            (dlibc::IPPROTO_IP, dlibc::IP_PKTINFO)
+                if len >= mem::size_of::<dlibc::in_pktinfo>() =>
+            {
+                let info = data.cast::<dlibc::in_pktinfo>().read_unaligned();
+                return Some(PacketInfo {
+                    destination: IpAddr::V4(Ipv4Addr::from(u32::from_be(info.ipi_addr.s_addr))),
+                    interface: info.ipi_ifindex as u32,
+                });
+            }
+            (dlibc::IPPROTO_IPV6, dlibc::IPV6_PKTINFO)
+                if len >= mem::size_of::<dlibc::in6_pktinfo>() =>
+            {
+                let info = data.cast::<dlibc::in6_pktinfo>().read_unaligned();
+                let addr = Ipv6Addr::from(info.ipi6_addr.s6_addr);
+                return Some(PacketInfo {
+                    destination: addr.to_ipv4_mapped().map_or(IpAddr::V6(addr), IpAddr::V4),
+                    interface: info.ipi6_ifindex,
+                });
+            }
+            _ => cmsg = dlibc::CMSG_NXTHDR(msg, cmsg),
+        }
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests;
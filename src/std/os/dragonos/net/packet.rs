@@ -0,0 +1,326 @@
+use crate::std::ffi::OsStr;
+use crate::std::fmt;
+use crate::std::io;
+use crate::std::mem;
+use crate::std::os::unix::ffi::OsStrExt;
+use crate::std::os::unix::io::{AsFd, AsRawFd, BorrowedFd, RawFd};
+use crate::std::sys::net::{init, Socket};
+use crate::std::sys::{cvt, cvt_r};
+use crate::std::sys_common::net::setsockopt;
+use crate::std::time::Duration;
+
+/// A socket that sends and receives whole link-layer frames on one network
+/// interface, with `AF_PACKET`.
+///
+/// Frames are sent and received with their link-layer header, such as the
+/// Ethernet header, which is what programs that configure the network need
+/// before the interface has an address: a DHCP client, for one. Creating a
+/// packet socket needs the `NetRaw` capability.
+///
+/// A socket receives nothing until it is [`bind`]ed, so a [filter] attached
+/// before binding applies to every frame it sees:
+///
+/// ```no_run
+/// use std::ffi::OsStr;
+/// use std::os::dragonos::net::{interface_index, BpfInstruction, PacketSocket};
+///
+/// const ETH_P_IP: u16 = 0x0800;
+///
+/// // Only UDP packets for the DHCP client port, from `tcpdump -dd udp dst port 68`.
+/// # let program: [BpfInstruction; 0] = [];
+/// let socket = PacketSocket::new()?;
+/// socket.attach_filter(&program)?;
+/// socket.bind(interface_index(OsStr::new("eth0"))?, ETH_P_IP)?;
+///
+/// let mut frame = [0; 1514];
+/// let (len, from) = socket.recv_from(&mut frame)?;
+/// println!("{len} bytes from {:02x?}", from.address());
+/// # Ok::<(), std::io::Error>(())
+/// ```
+///
+/// [`bind`]: PacketSocket::bind
+/// [filter]: PacketSocket::attach_filter
+pub struct PacketSocket {
+    socket: Socket,
+}
+
+/// One instruction of a classic BPF program, as attached with
+/// [`PacketSocket::attach_filter`].
+///
+/// The layout is that of `struct sock_filter`, so the output of
+/// `tcpdump -dd` can be used as is.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[repr(C)]
+pub struct BpfInstruction {
+    /// The operation.
+    pub code: u16,
+    /// How many instructions to skip if a jump is taken.
+    pub jt: u8,
+    /// How many instructions to skip if a jump is not taken.
+    pub jf: u8,
+    /// The operand.
+    pub k: u32,
+}
+
+impl BpfInstruction {
+    /// Creates an instruction from its fields.
+    pub const fn new(code: u16, jt: u8, jf: u8, k: u32) -> BpfInstruction {
+        BpfInstruction { code, jt, jf, k }
+    }
+}
+
+/// The link-layer address of the other end of a frame, as returned by
+/// [`PacketSocket::recv_from`], or of the interface a socket is bound to, as
+/// returned by [`PacketSocket::local_addr`].
+#[derive(Clone, Copy, PartialEq, Eq, Hash)]
+pub struct LinkAddr {
+    interface: u32,
+    protocol: u16,
+    packet_type: u8,
+    len: u8,
+    address: [u8; 8],
+}
+
+impl LinkAddr {
+    fn from_raw(addr: &dlibc::sockaddr_ll) -> LinkAddr {
+        LinkAddr {
+            interface: addr.sll_ifindex as u32,
+            protocol: u16::from_be(addr.sll_protocol),
+            packet_type: addr.sll_pkttype,
+            len: addr.sll_halen.min(8),
+            address: addr.sll_addr,
+        }
+    }
+
+    /// Returns the index of the interface.
+    pub fn interface_index(&self) -> u32 {
+        self.interface
+    }
+
+    /// Returns the protocol of the frame, such as `0x0800` for IPv4.
+    pub fn protocol(&self) -> u16 {
+        self.protocol
+    }
+
+    /// Returns the hardware address, such as a 6-byte MAC address for
+    /// Ethernet.
+    pub fn address(&self) -> &[u8] {
+        &self.address[..self.len as usize]
+    }
+
+    /// Returns whether the frame was sent to the whole link, rather than to
+    /// this host or to a multicast group.
+    pub fn is_broadcast(&self) -> bool {
+        self.packet_type == PACKET_BROADCAST
+    }
+
+    /// Returns whether the frame was sent by this host, rather than received
+    /// by it.
+    pub fn is_outgoing(&self) -> bool {
+        self.packet_type == PACKET_OUTGOING
+    }
+}
+
+impl fmt::Debug for LinkAddr {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("LinkAddr")
+            .field("interface_index", &self.interface_index())
+            .field("protocol", &self.protocol())
+            .field("address", &self.address())
+            .finish()
+    }
+}
+
+// Values of `sll_pkttype`.
+const PACKET_BROADCAST: u8 = 1;
+const PACKET_OUTGOING: u8 = 4;
+
+impl PacketSocket {
+    /// Creates a packet socket that receives nothing until it is bound.
+    ///
+    /// Fails with [`Unsupported`] if the kernel has no packet sockets.
+    ///
+    /// [`Unsupported`]: io::ErrorKind::Unsupported
+    pub fn new() -> io::Result<PacketSocket> {
+        init();
+        match Socket::new_raw(dlibc::AF_PACKET, dlibc::SOCK_RAW) {
+            Ok(socket) => Ok(PacketSocket { socket }),
+            Err(e) if e.raw_os_error() == Some(dlibc::EAFNOSUPPORT) => Err(io::const_io_error!(
+                io::ErrorKind::Unsupported,
+                "the kernel doesn't support packet sockets",
+            )),
+            Err(e) => Err(e),
+        }
+    }
+
+    /// Binds the socket to the interface with index `interface`, to send and
+    /// receive frames of `protocol`, such as `0x0800` for IPv4 or `0x0003`
+    /// for all of them.
+    ///
+    /// [`interface_index`] looks up the index of an interface by name.
+    pub fn bind(&self, interface: u32, protocol: u16) -> io::Result<()> {
+        let mut addr: dlibc::sockaddr_ll = unsafe { mem::zeroed() };
+        addr.sll_family = dlibc::AF_PACKET as dlibc::c_ushort;
+        addr.sll_protocol = protocol.to_be();
+        addr.sll_ifindex = interface as dlibc::c_int;
+        cvt(unsafe {
+            dlibc::bind(
+                self.as_raw_fd(),
+                &addr as *const dlibc::sockaddr_ll as *const dlibc::sockaddr,
+                mem::size_of_val(&addr) as dlibc::socklen_t,
+            )
+        })?;
+        Ok(())
+    }
+
+    /// Returns the interface the socket is bound to, with its hardware
+    /// address.
+    pub fn local_addr(&self) -> io::Result<LinkAddr> {
+        let mut addr: dlibc::sockaddr_ll = unsafe { mem::zeroed() };
+        let mut len = mem::size_of_val(&addr) as dlibc::socklen_t;
+        cvt(unsafe {
+            dlibc::getsockname(
+                self.as_raw_fd(),
+                &mut addr as *mut dlibc::sockaddr_ll as *mut dlibc::sockaddr,
+                &mut len,
+            )
+        })?;
+        Ok(LinkAddr::from_raw(&addr))
+    }
+
+    /// Sends a frame, including its link-layer header, out of the interface
+    /// the socket is bound to.
+    pub fn send(&self, frame: &[u8]) -> io::Result<usize> {
+        self.socket.write(frame)
+    }
+
+    /// Receives a frame, including its link-layer header.
+    ///
+    /// A frame too long for `buf` is cut off.
+    pub fn recv(&self, buf: &mut [u8]) -> io::Result<usize> {
+        self.socket.read(buf)
+    }
+
+    /// Receives a frame like [`recv`], and also returns where it came from.
+    ///
+    /// [`recv`]: PacketSocket::recv
+    pub fn recv_from(&self, buf: &mut [u8]) -> io::Result<(usize, LinkAddr)> {
+        let mut addr: dlibc::sockaddr_ll = unsafe { mem::zeroed() };
+        let mut len = mem::size_of_val(&addr) as dlibc::socklen_t;
+        let n = cvt_r(|| unsafe {
+            dlibc::recvfrom(
+                self.as_raw_fd(),
+                buf.as_mut_ptr().cast(),
+                buf.len(),
+                0,
+                &mut addr as *mut dlibc::sockaddr_ll as *mut dlibc::sockaddr,
+                &mut len,
+            )
+        })?;
+        Ok((n as usize, LinkAddr::from_raw(&addr)))
+    }
+
+    /// Attaches a classic BPF program that decides which frames the socket
+    /// receives, replacing any program attached before.
+    ///
+    /// The program runs on every frame and returns how many of its bytes to
+    /// keep; 0 drops the frame.
+    pub fn attach_filter(&self, program: &[BpfInstruction]) -> io::Result<()> {
+        let prog = dlibc::sock_fprog {
+            len: u16::try_from(program.len()).map_err(|_| {
+                io::const_io_error!(io::ErrorKind::InvalidInput, "filter program is too long")
+            })?,
+            filter: program.as_ptr() as *mut dlibc::sock_filter,
+        };
+        setsockopt(
+            &self.socket,
+            dlibc::SOL_SOCKET,
+            dlibc::SO_ATTACH_FILTER,
+            prog,
+        )
+    }
+
+    /// Removes the program attached with [`attach_filter`], so that the
+    /// socket receives every frame again.
+    ///
+    /// [`attach_filter`]: PacketSocket::attach_filter
+    pub fn detach_filter(&self) -> io::Result<()> {
+        setsockopt(
+            &self.socket,
+            dlibc::SOL_SOCKET,
+            dlibc::SO_DETACH_FILTER,
+            0 as dlibc::c_int,
+        )
+    }
+
+    /// Sets how long [`recv`] and [`recv_from`] wait for a frame, or removes
+    /// the limit if `timeout` is `None`.
+    ///
+    /// [`recv`]: PacketSocket::recv
+    /// [`recv_from`]: PacketSocket::recv_from
+    pub fn set_read_timeout(&self, timeout: Option<Duration>) -> io::Result<()> {
+        self.socket.set_timeout(timeout, dlibc::SO_RCVTIMEO)
+    }
+
+    /// Moves the socket into or out of non-blocking mode.
+    pub fn set_nonblocking(&self, nonblocking: bool) -> io::Result<()> {
+        self.socket.set_nonblocking(nonblocking)
+    }
+}
+
+impl AsFd for PacketSocket {
+    fn as_fd(&self) -> BorrowedFd<'_> {
+        self.socket.as_fd()
+    }
+}
+
+impl AsRawFd for PacketSocket {
+    fn as_raw_fd(&self) -> RawFd {
+        self.socket.as_raw_fd()
+    }
+}
+
+impl fmt::Debug for PacketSocket {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("PacketSocket")
+            .field("fd", &self.as_raw_fd())
+            .finish()
+    }
+}
+
+/// Returns the index of the network interface called `name`, as taken by
+/// [`PacketSocket::bind`].
+pub fn interface_index(name: &OsStr) -> io::Result<u32> {
+    // `struct ifreq` with the index member of its union.
+    #[repr(C)]
+    struct IfIndexReq {
+        name: [u8; dlibc::IFNAMSIZ],
+        index: dlibc::c_int,
+        _rest: [u8; 20],
+    }
+
+    let name = name.as_bytes();
+    if name.len() >= dlibc::IFNAMSIZ || name.contains(&0) {
+        return Err(io::const_io_error!(
+            io::ErrorKind::InvalidInput,
+            "invalid interface name",
+        ));
+    }
+    let mut req = IfIndexReq {
+        name: [0; dlibc::IFNAMSIZ],
+        index: 0,
+        _rest: [0; 20],
+    };
+    req.name[..name.len()].copy_from_slice(name);
+
+    init();
+    let socket = Socket::new_raw(dlibc::AF_INET, dlibc::SOCK_DGRAM)?;
+    cvt(unsafe {
+        dlibc::ioctl(
+            socket.as_raw_fd(),
+            dlibc::SIOCGIFINDEX as dlibc::c_int,
+            &mut req as *mut IfIndexReq as *mut dlibc::c_void,
+        )
+    })?;
+    Ok(req.index as u32)
+}
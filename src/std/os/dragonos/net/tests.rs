@@ -0,0 +1,204 @@
+use super::{
+    connect_fastopen, interface_index, BpfInstruction, PacketSocket, SocketExt, TcpListenerBuilder,
+    TcpListenerExt, UdpSocketExt,
+};
+use crate::std::ffi::OsStr;
+use crate::std::io::{self, Read, Write};
+use crate::std::net::{IpAddr, Ipv4Addr, Ipv6Addr, TcpListener, TcpStream, UdpSocket};
+use crate::std::thread;
+
+#[test]
+fn listen() {
+    let listener = TcpListenerBuilder::new_v4()
+        .unwrap()
+        .reuse_address(true)
+        .unwrap()
+        .bind("127.0.0.1:0".parse().unwrap())
+        .unwrap()
+        .listen(16)
+        .unwrap();
+    let addr = listener.local_addr().unwrap();
+    let t = thread::spawn(move || {
+        let mut stream = TcpStream::connect(addr).unwrap();
+        stream.write_all(b"hello").unwrap();
+    });
+    let mut buf = Vec::new();
+    listener.accept().unwrap().0.read_to_end(&mut buf).unwrap();
+    assert_eq!(buf, b"hello");
+    t.join().unwrap();
+}
+
+#[test]
+fn reuse_port() {
+    let builder = || {
+        TcpListenerBuilder::new_v4()
+            .unwrap()
+            .reuse_port(true)
+            .unwrap()
+    };
+    let first = builder()
+        .bind("127.0.0.1:0".parse().unwrap())
+        .unwrap()
+        .listen(16)
+        .unwrap();
+    let addr = first.local_addr().unwrap();
+    let second = builder().bind(addr).unwrap().listen(16).unwrap();
+    assert_eq!(second.local_addr().unwrap(), addr);
+}
+
+#[test]
+fn wrong_family() {
+    assert!(TcpListenerBuilder::new_v4()
+        .unwrap()
+        .bind("[::1]:0".parse().unwrap())
+        .is_err());
+}
+
+#[test]
+fn fastopen() {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    listener.set_fastopen(16).unwrap();
+    let addr = listener.local_addr().unwrap();
+    // The first connection only gets a cookie, later ones may use it; either
+    // way, all of the data arrives.
+    for _ in 0..2 {
+        let t = thread::spawn(move || {
+            let (mut stream, sent) = connect_fastopen(addr, b"hello").unwrap();
+            stream.write_all(&b"hello"[sent..]).unwrap();
+        });
+        let mut buf = Vec::new();
+        listener.accept().unwrap().0.read_to_end(&mut buf).unwrap();
+        assert_eq!(buf, b"hello");
+        t.join().unwrap();
+    }
+}
+
+#[test]
+fn bind_device() {
+    let socket = UdpSocket::bind("127.0.0.1:0").unwrap();
+    assert_eq!(socket.device().unwrap(), None);
+    let err = socket
+        .bind_device(Some(OsStr::new("an-interface-name-too-long")))
+        .unwrap_err();
+    assert_eq!(err.kind(), io::ErrorKind::InvalidInput);
+    // Binding needs a capability the tests may run without.
+    match socket.bind_device(Some(OsStr::new("lo"))) {
+        Ok(()) => assert_eq!(socket.device().unwrap().as_deref(), Some(OsStr::new("lo"))),
+        Err(e) => assert_eq!(e.kind(), io::ErrorKind::PermissionDenied),
+    }
+}
+
+#[test]
+fn freebind() {
+    // An address from a documentation range, which no interface has.
+    let result = TcpListenerBuilder::new_v4()
+        .unwrap()
+        .freebind(true)
+        .unwrap()
+        .bind("192.0.2.1:0".parse().unwrap());
+    assert!(result.is_ok());
+}
+
+#[test]
+fn recv_pktinfo_v4() {
+    let server = UdpSocket::bind("0.0.0.0:0").unwrap();
+    let port = server.local_addr().unwrap().port();
+    let client = UdpSocket::bind("127.0.0.1:0").unwrap();
+
+    client.send_to(b"before", ("127.0.0.1", port)).unwrap();
+    let mut buf = [0; 16];
+    let (n, from, info) = server.recv_from_pktinfo(&mut buf).unwrap();
+    assert_eq!(
+        (&buf[..n], from, info),
+        (&b"before"[..], client.local_addr().unwrap(), None)
+    );
+
+    server.set_recv_pktinfo(true).unwrap();
+    client.send_to(b"after", ("127.0.0.1", port)).unwrap();
+    let (n, _, info) = server.recv_from_pktinfo(&mut buf).unwrap();
+    assert_eq!(&buf[..n], b"after");
+    let info = info.unwrap();
+    assert_eq!(info.destination(), IpAddr::V4(Ipv4Addr::LOCALHOST));
+    assert_ne!(info.interface_index(), 0);
+}
+
+#[test]
+fn recv_pktinfo_v6() {
+    let server = match UdpSocket::bind("[::]:0") {
+        Ok(server) => server,
+        // No IPv6 support.
+        Err(_) => return,
+    };
+    let port = server.local_addr().unwrap().port();
+    server.set_recv_pktinfo(true).unwrap();
+    let client = UdpSocket::bind("[::1]:0").unwrap();
+    client.send_to(b"hello", ("::1", port)).unwrap();
+
+    let mut buf = [0; 16];
+    let (n, from, info) = server.recv_from_pktinfo(&mut buf).unwrap();
+    assert_eq!(&buf[..n], b"hello");
+    assert_eq!(from, client.local_addr().unwrap());
+    assert_eq!(info.unwrap().destination(), IpAddr::V6(Ipv6Addr::LOCALHOST));
+}
+
+#[test]
+fn bpf_instruction_layout() {
+    assert_eq!(crate::std::mem::size_of::<BpfInstruction>(), 8);
+    assert_eq!(crate::std::mem::align_of::<BpfInstruction>(), 4);
+}
+
+#[test]
+fn interface_index_errors() {
+    assert_eq!(
+        interface_index(OsStr::new("a-name-far-too-long"))
+            .unwrap_err()
+            .kind(),
+        io::ErrorKind::InvalidInput
+    );
+    assert!(interface_index(OsStr::new("no-such-if")).is_err());
+}
+
+#[test]
+fn packet_socket_loopback() {
+    const PROTOCOL: u16 = 0x88b5; // For local experiments.
+    let socket = match PacketSocket::new() {
+        Ok(socket) => socket,
+        // Needs `NetRaw`, and packet sockets.
+        Err(e)
+            if matches!(
+                e.kind(),
+                io::ErrorKind::PermissionDenied | io::ErrorKind::Unsupported
+            ) =>
+        {
+            return;
+        }
+        Err(e) => panic!("{e}"),
+    };
+    // Keep only frames with the experimental protocol and the right payload.
+    let program = [
+        BpfInstruction::new(0x28, 0, 0, 12),              // ldh [12]
+        BpfInstruction::new(0x15, 0, 3, PROTOCOL as u32), // jeq PROTOCOL
+        BpfInstruction::new(0x20, 0, 0, 14),              // ld [14]
+        BpfInstruction::new(0x15, 0, 1, 0x7061636b),      // jeq "pack"
+        BpfInstruction::new(0x06, 0, 0, 0xffff),          // ret 0xffff
+        BpfInstruction::new(0x06, 0, 0, 0),               // ret 0
+    ];
+    socket.attach_filter(&program).unwrap();
+    let lo = interface_index(OsStr::new("lo")).unwrap();
+    socket.bind(lo, PROTOCOL).unwrap();
+    assert_eq!(socket.local_addr().unwrap().interface_index(), lo);
+    socket
+        .set_read_timeout(Some(crate::std::time::Duration::from_secs(5)))
+        .unwrap();
+
+    let mut frame = [0u8; 64];
+    frame[12..14].copy_from_slice(&PROTOCOL.to_be_bytes());
+    frame[14..18].copy_from_slice(b"pack");
+    assert_eq!(socket.send(&frame).unwrap(), frame.len());
+
+    let mut buf = [0; 128];
+    let (n, from) = socket.recv_from(&mut buf).unwrap();
+    assert_eq!(&buf[..n], &frame[..]);
+    assert_eq!(from.protocol(), PROTOCOL);
+    assert_eq!(from.interface_index(), lo);
+}
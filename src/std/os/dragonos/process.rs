@@ -0,0 +1,385 @@
+//! DragonOS-specific extensions to primitives in the [`std::process`] module.
+//!
+//! [`std::process`]: crate::std::process
+
+use crate::std::env;
+use crate::std::ffi::{OsStr, OsString};
+use crate::std::fmt;
+use crate::std::io;
+use crate::std::mem;
+use crate::std::os::dragonos::seccomp::Filter;
+use crate::std::os::unix::io::{AsRawFd, BorrowedFd, OwnedFd, RawFd};
+use crate::std::path::{Path, PathBuf};
+use crate::std::process;
+use crate::std::ptr;
+use crate::std::sealed::Sealed;
+use crate::std::sys;
+use crate::std::sys::{cvt, cvt_nz};
+use crate::std::sys_common::AsInnerMut;
+
+/// Os-specific extensions for [`Command`]
+///
+/// [`Command`]: process::Command
+pub trait CommandExt: Sealed {
+    /// Makes `parent_fd` available in the child process as descriptor
+    /// number `child_fd`.
+    ///
+    /// The descriptor is installed after the standard streams have been set
+    /// up and before any [`pre_exec`] closures run. `child_fd` may be equal to
+    /// the current number of `parent_fd`, which simply lets it be inherited.
+    /// `parent_fd` is closed in the parent once the `Command` is dropped.
+    ///
+    /// Spawning fails with [`InvalidInput`] if `child_fd` is one of the
+    /// standard stream descriptors (use [`Command::stdin`] and friends for
+    /// those) or if two mappings name the same `child_fd`.
+    ///
+    /// ```no_run
+    /// use std::os::dragonos::process::CommandExt;
+    /// use std::os::fd::OwnedFd;
+    /// use std::process::Command;
+    ///
+    /// # fn listener() -> OwnedFd { unimplemented!() }
+    /// // Hand a listening socket to a socket-activated service as fd 3.
+    /// Command::new("/bin/service").fd_mapping(listener(), 3).spawn()?;
+    /// # Ok::<(), std::io::Error>(())
+    /// ```
+    ///
+    /// [`pre_exec`]: crate::std::os::unix::process::CommandExt::pre_exec
+    /// [`InvalidInput`]: crate::std::io::ErrorKind::InvalidInput
+    /// [`Command::stdin`]: process::Command::stdin
+    fn fd_mapping(&mut self, parent_fd: OwnedFd, child_fd: RawFd) -> &mut process::Command;
+
+    /// Spawns the child with the environment, working directory, umask and
+    /// signal mask recorded in `context`, instead of inheriting the current
+    /// ones.
+    ///
+    /// This replaces any environment variables and working directory set on
+    /// the `Command` so far; later calls to [`Command::env`] and
+    /// [`Command::current_dir`] still apply on top of `context`.
+    ///
+    /// [`Command::env`]: process::Command::env
+    /// [`Command::current_dir`]: process::Command::current_dir
+    fn with_context(&mut self, context: &SpawnContext) -> &mut process::Command;
+
+    /// Restricts the system calls the child may make to those `filter`
+    /// allows, as [`apply_filter`] would in the child.
+    ///
+    /// The filter is installed right before the program is executed, after
+    /// everything else has been set up and any [`pre_exec`] closures have
+    /// run, so it must let through the `execve` system call and whatever the
+    /// program needs to start. Spawning fails with [`Unsupported`] if the
+    /// kernel doesn't filter system calls.
+    ///
+    /// [`apply_filter`]: super::seccomp::apply_filter
+    /// [`pre_exec`]: crate::std::os::unix::process::CommandExt::pre_exec
+    /// [`Unsupported`]: crate::std::io::ErrorKind::Unsupported
+    fn syscall_filter(&mut self, filter: Filter) -> &mut process::Command;
+
+    /// Keeps every descriptor from being inherited by the child except its
+    /// standard streams and those given with [`fd_mapping`].
+    ///
+    /// Descriptors the standard library creates are never inherited anyway,
+    /// but ones opened by C libraries or made inheritable with
+    /// [`set_inheritable`] are. With this set, the child marks all others
+    /// close-on-exec with a single `close_range` call after installing the
+    /// mappings, so they are closed when the program starts, while
+    /// [`pre_exec`] closures can still use them. On kernels without
+    /// `close_range`, spawning fails as described for [`set_cloexec_range`]
+    /// if `RLIMIT_NOFILE` is too high.
+    ///
+    /// [`fd_mapping`]: CommandExt::fd_mapping
+    /// [`set_inheritable`]: super::io::set_inheritable
+    /// [`set_cloexec_range`]: super::io::set_cloexec_range
+    /// [`pre_exec`]: crate::std::os::unix::process::CommandExt::pre_exec
+    fn close_other_fds(&mut self, close: bool) -> &mut process::Command;
+
+    /// Runs the program open as `program` instead of looking up the one
+    /// passed to [`Command::new`], which only becomes the child's `argv[0]`.
+    ///
+    /// The child executes the very file that `program` refers to, with
+    /// `execveat` and `AT_EMPTY_PATH`, so a binary can be checked and then
+    /// run without anyone replacing it in between:
+    ///
+    /// ```no_run
+    /// use std::fs::File;
+    /// use std::io::Read;
+    /// use std::os::dragonos::process::CommandExt;
+    /// use std::process::Command;
+    ///
+    /// # fn verify(_: &[u8]) -> bool { true }
+    /// let mut file = File::open("/usr/lib/plugins/convert")?;
+    /// let mut contents = Vec::new();
+    /// file.read_to_end(&mut contents)?;
+    /// assert!(verify(&contents));
+    ///
+    /// Command::new("convert").exec_fd(file.into()).spawn()?;
+    /// # Ok::<(), std::io::Error>(())
+    /// ```
+    ///
+    /// `program` is closed in the parent once the `Command` is dropped, and
+    /// is close-on-exec in the child. Scripts starting with `#!` therefore
+    /// fail to run this way, since their interpreter can't reopen them.
+    ///
+    /// [`Command::new`]: process::Command::new
+    fn exec_fd(&mut self, program: OwnedFd) -> &mut process::Command;
+}
+
+impl CommandExt for process::Command {
+    fn fd_mapping(&mut self, parent_fd: OwnedFd, child_fd: RawFd) -> &mut process::Command {
+        self.as_inner_mut().fd_mapping(parent_fd, child_fd);
+        self
+    }
+
+    fn with_context(&mut self, context: &SpawnContext) -> &mut process::Command {
+        self.env_clear()
+            .envs(context.envs())
+            .current_dir(&context.current_dir);
+        self.as_inner_mut().umask(context.umask as dlibc::mode_t);
+        self.as_inner_mut().sigmask(context.sigmask);
+        self
+    }
+
+    fn syscall_filter(&mut self, filter: Filter) -> &mut process::Command {
+        self.as_inner_mut().syscall_filter(filter.compile());
+        self
+    }
+
+    fn close_other_fds(&mut self, close: bool) -> &mut process::Command {
+        self.as_inner_mut().close_other_fds(close);
+        self
+    }
+
+    fn exec_fd(&mut self, program: OwnedFd) -> &mut process::Command {
+        self.as_inner_mut().exec_fd(program);
+        self
+    }
+}
+
+/// The parts of the process state a child inherits when it is spawned: the
+/// environment, the working directory, the umask and the signal mask of the
+/// calling thread.
+///
+/// Other threads may change any of these at any time, so a child spawned
+/// with a plain [`Command`] gets whatever they happen to be at that moment.
+/// A `SpawnContext` captures them once, and [`CommandExt::with_context`]
+/// spawns any number of children with exactly that state, which keeps, for
+/// example, the children of a parallel test runner reproducible.
+///
+/// Each part is captured atomically, but other threads may change one part
+/// between the captures of two others.
+///
+/// ```no_run
+/// use std::os::dragonos::process::{CommandExt, SpawnContext};
+/// use std::process::Command;
+///
+/// let context = SpawnContext::capture()?;
+/// for test in ["a", "b", "c"] {
+///     Command::new("/bin/run-test").arg(test).with_context(&context).spawn()?;
+/// }
+/// # Ok::<(), std::io::Error>(())
+/// ```
+///
+/// [`Command`]: process::Command
+#[derive(Clone)]
+pub struct SpawnContext {
+    envs: Vec<(OsString, OsString)>,
+    current_dir: PathBuf,
+    umask: u32,
+    sigmask: dlibc::sigset_t,
+}
+
+impl SpawnContext {
+    /// Captures the current state of the process and the calling thread.
+    ///
+    /// Fails if the working directory can't be determined; see
+    /// [`env::current_dir`].
+    pub fn capture() -> io::Result<SpawnContext> {
+        let envs = env::vars_os().collect();
+        let current_dir = env::current_dir()?;
+        // The umask can only be read by replacing it. Replacing it with the
+        // most restrictive mask means a file that another thread creates in
+        // the meantime gets fewer permissions, never more.
+        let umask = unsafe {
+            let umask = dlibc::umask(0o777);
+            dlibc::umask(umask);
+            umask as u32
+        };
+        let mut sigmask = unsafe { mem::zeroed() };
+        cvt_nz(unsafe { dlibc::pthread_sigmask(dlibc::SIG_SETMASK, ptr::null(), &mut sigmask) })?;
+        Ok(SpawnContext {
+            envs,
+            current_dir,
+            umask,
+            sigmask,
+        })
+    }
+
+    /// Returns the captured environment variables.
+    pub fn envs(&self) -> impl Iterator<Item = (&OsStr, &OsStr)> {
+        self.envs.iter().map(|(k, v)| (&**k, &**v))
+    }
+
+    /// Returns the captured working directory.
+    pub fn current_dir(&self) -> &Path {
+        &self.current_dir
+    }
+
+    /// Returns the captured umask.
+    pub fn umask(&self) -> u32 {
+        self.umask
+    }
+
+    /// Returns whether `signal` was blocked in the captured signal mask.
+    pub fn is_blocked(&self, signal: i32) -> bool {
+        unsafe { dlibc::sigismember(&self.sigmask, signal) == 1 }
+    }
+}
+
+impl fmt::Debug for SpawnContext {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("SpawnContext")
+            .field("envs", &self.envs)
+            .field("current_dir", &self.current_dir)
+            .field("umask", &format_args!("{:#o}", self.umask))
+            .finish_non_exhaustive()
+    }
+}
+
+/// Os-specific extensions for [`Child`]
+///
+/// [`Child`]: process::Child
+pub trait ChildExt: Sealed {
+    /// Sends `signal` to the child process.
+    ///
+    /// [`Child::kill`] is equivalent to calling this with `SIGKILL`. Other
+    /// signals such as `SIGTERM`, `SIGHUP` or `SIGUSR1` let the child shut
+    /// down gracefully or reload its configuration.
+    ///
+    /// If the child has already been waited on, this does nothing and
+    /// returns `Ok(())`, since its pid may have been reused.
+    ///
+    /// ```no_run
+    /// use std::os::dragonos::process::ChildExt;
+    /// use std::process::Command;
+    ///
+    /// let mut child = Command::new("/bin/daemon").spawn()?;
+    /// child.send_signal(dlibc::SIGTERM)?;
+    /// child.wait()?;
+    /// # Ok::<(), std::io::Error>(())
+    /// ```
+    ///
+    /// [`Child::kill`]: process::Child::kill
+    fn send_signal(&mut self, signal: i32) -> io::Result<()>;
+
+    /// Sends `signal` to every process in the child's process group.
+    ///
+    /// The child must lead its own process group, which is set up by
+    /// spawning it with [`process_group(0)`]. Otherwise there is no group
+    /// with the child's id and this fails with an `ESRCH` error.
+    ///
+    /// Like [`send_signal`], this does nothing once the child has been
+    /// waited on.
+    ///
+    /// [`process_group(0)`]: crate::std::os::unix::process::CommandExt::process_group
+    /// [`send_signal`]: ChildExt::send_signal
+    fn send_signal_group(&mut self, signal: i32) -> io::Result<()>;
+
+    /// Forces the child's whole process group to exit by sending it
+    /// `SIGKILL`.
+    ///
+    /// This is the group counterpart of [`Child::kill`]; see
+    /// [`send_signal_group`] for the requirements on the child.
+    ///
+    /// [`Child::kill`]: process::Child::kill
+    /// [`send_signal_group`]: ChildExt::send_signal_group
+    fn kill_group(&mut self) -> io::Result<()>;
+}
+
+/// Registers `hook` to run when the process exits normally.
+///
+/// Hooks run when [`process::exit`] is called or `main` returns, together
+/// with the handlers C code registered through `atexit`, and before C stdio
+/// buffers are flushed. They run in reverse order of registration and are
+/// skipped by [`process::abort`] and by signals that terminate the process.
+///
+/// Returns an error if the C library has no room left to register the
+/// runtime's exit handler; this can only happen on the first call.
+///
+/// ```no_run
+/// use std::os::dragonos::process::at_exit;
+///
+/// at_exit(|| eprintln!("shutting down"))?;
+/// std::process::exit(0);
+/// # Ok::<(), std::io::Error>(())
+/// ```
+pub fn at_exit<F>(hook: F) -> io::Result<()>
+where
+    F: FnOnce() + Send + 'static,
+{
+    sys::os::at_exit(Box::new(hook))
+}
+
+/// Returns the build ID of the running executable.
+///
+/// This is the contents of the executable's `NT_GNU_BUILD_ID` note, which
+/// linkers add when passed `--build-id`. Including it in crash reports lets
+/// them be matched with the debug information of exactly this build. Returns
+/// `None` if the executable has no build ID.
+///
+/// ```no_run
+/// use std::os::dragonos::process::build_id;
+///
+/// if let Some(id) = build_id() {
+///     let hex: String = id.iter().map(|b| format!("{b:02x}")).collect();
+///     eprintln!("build id: {hex}");
+/// }
+/// ```
+pub fn build_id() -> Option<&'static [u8]> {
+    sys::os::build_id()
+}
+
+/// Returns the id of the foreground process group of the terminal `tty`.
+///
+/// `tty` must be the controlling terminal of the calling process. This is
+/// `tcgetpgrp`; an interactive shell uses it to find out which job the
+/// terminal currently belongs to.
+pub fn foreground_process_group(tty: BorrowedFd<'_>) -> io::Result<u32> {
+    let pgrp = cvt(unsafe { dlibc::tcgetpgrp(tty.as_raw_fd()) })?;
+    Ok(pgrp as u32)
+}
+
+/// Makes `pgrp` the foreground process group of the terminal `tty`.
+///
+/// `tty` must be the controlling terminal of the calling process and `pgrp`
+/// a process group in the same session. This is `tcsetpgrp`; a job-control
+/// shell calls it to hand the terminal to a job spawned with
+/// [`process_group`], and again with its own group once the job stops or
+/// exits.
+///
+/// A process that is not in the foreground group is sent `SIGTTOU` when it
+/// calls this, which stops it by default. Shells therefore ignore `SIGTTOU`
+/// before reclaiming the terminal.
+///
+/// ```no_run
+/// use std::os::dragonos::process::set_foreground_process_group;
+/// use std::os::fd::AsFd;
+/// use std::os::unix::process::CommandExt;
+/// use std::process::{self, Command};
+///
+/// let tty = std::io::stdin();
+/// let mut job = Command::new("/bin/vi").process_group(0).spawn()?;
+/// set_foreground_process_group(tty.as_fd(), job.id())?;
+/// job.wait()?;
+/// // Assumes the shell leads its own group and ignores SIGTTOU.
+/// set_foreground_process_group(tty.as_fd(), process::id())?;
+/// # Ok::<(), std::io::Error>(())
+/// ```
+///
+/// [`process_group`]: crate::std::os::unix::process::CommandExt::process_group
+pub fn set_foreground_process_group(tty: BorrowedFd<'_>, pgrp: u32) -> io::Result<()> {
+    cvt(unsafe { dlibc::tcsetpgrp(tty.as_raw_fd(), pgrp as dlibc::pid_t) })?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests;
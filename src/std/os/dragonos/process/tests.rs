@@ -0,0 +1,79 @@
+use super::{CommandExt, SpawnContext};
+use crate::std::env;
+use crate::std::path::Path;
+use crate::std::process::Command;
+
+#[test]
+fn capture_restores_umask() {
+    let old = unsafe { dlibc::umask(0o027) };
+    let context = SpawnContext::capture().unwrap();
+    let now = unsafe { dlibc::umask(old) };
+    assert_eq!(context.umask(), 0o027);
+    assert_eq!(now, 0o027);
+    assert_eq!(context.current_dir(), env::current_dir().unwrap());
+    assert!(!context.is_blocked(dlibc::SIGUSR1));
+}
+
+#[test]
+fn spawn_with_context() {
+    if !Path::new("/bin/sh").exists() {
+        return;
+    }
+    let context = SpawnContext::capture().unwrap();
+
+    // Variables set before `with_context` are replaced by the captured
+    // environment, later ones apply on top of it.
+    let output = Command::new("/bin/sh")
+        .args([
+            "-c",
+            "echo \"$SPAWN_CONTEXT_BEFORE|$SPAWN_CONTEXT_AFTER\"; pwd",
+        ])
+        .env("SPAWN_CONTEXT_BEFORE", "dropped")
+        .with_context(&context)
+        .env("SPAWN_CONTEXT_AFTER", "applied")
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    let expected = format!("|applied\n{}\n", context.current_dir().display());
+    assert_eq!(String::from_utf8(output.stdout).unwrap(), expected);
+}
+
+#[test]
+fn close_other_fds_keeps_mappings() {
+    use crate::std::io;
+    use crate::std::os::dragonos::io::DuplicateOptions;
+    use crate::std::os::unix::io::{AsFd, AsRawFd, OwnedFd};
+
+    // High enough that `ls` doesn't reuse the number for its own descriptors.
+    let (reader, mapped) = io::pipe().unwrap();
+    let leaked = reader
+        .as_fd()
+        .duplicate_with(DuplicateOptions::new().min_fd(100).cloexec(false))
+        .unwrap();
+
+    let output = Command::new("/bin/ls")
+        .arg("/proc/self/fd")
+        .fd_mapping(OwnedFd::from(mapped), 9)
+        .close_other_fds(true)
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    let fds = String::from_utf8(output.stdout).unwrap();
+    assert!(fds.lines().any(|fd| fd == "9"));
+    let leaked = leaked.as_raw_fd().to_string();
+    assert!(!fds.lines().any(|fd| fd == leaked));
+}
+
+#[test]
+fn exec_fd_runs_open_file() {
+    use crate::std::fs::File;
+
+    let file = File::open("/bin/sh").unwrap();
+    let output = Command::new("not-a-program")
+        .args(["-c", "echo $0"])
+        .exec_fd(file.into())
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    assert_eq!(output.stdout, b"not-a-program\n");
+}
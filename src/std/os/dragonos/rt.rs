@@ -0,0 +1,67 @@
+//! Starting the runtime by hand, for the first processes of the system.
+//!
+//! Before `fn main` runs, the runtime opens `/dev/null` in place of any
+//! standard stream that is closed and stores the program's arguments. The
+//! first userspace process, and the helpers it runs early, may start before
+//! `/dev` is mounted and before the console exists, and would abort there.
+//!
+//! Such programs use `#![no_main]` and define the C `main` themselves.
+//! They call [`init_early`] first, which sets up only what works without
+//! the file system: the `SIGPIPE` handling, the stack overflow handler and
+//! the main thread. Once `/dev` and the console are there, [`init_late`]
+//! finishes the initialization with the standard streams and the
+//! arguments.
+//!
+//! In between, the environment is whatever the kernel passed, possibly
+//! nothing, and [`std::env::args`] as well as the standard input and output
+//! handles must not be used; debug builds abort if they are. Everything
+//! else in `std` is available.
+//!
+//! ```no_run
+//! #![no_main]
+//!
+//! use std::os::dragonos::rt;
+//!
+//! # fn mount_filesystems() {}
+//! #[no_mangle]
+//! extern "C" fn main(argc: isize, argv: *const *const u8) -> i32 {
+//!     unsafe { rt::init_early() };
+//!     mount_filesystems();
+//!     unsafe { rt::init_late(argc, argv) };
+//!
+//!     println!("init started with {:?}", std::env::args().collect::<Vec<_>>());
+//!     // Flushes stdout, which returning from `main` wouldn't.
+//!     std::process::exit(0)
+//! }
+//! ```
+//!
+//! [`std::env::args`]: crate::std::env::args
+
+/// Sets up the parts of the runtime that need neither the file system nor
+/// the console.
+///
+/// `SIGPIPE` is ignored, as it would be for `fn main`.
+///
+/// # Safety
+///
+/// Must be called once, at the start of the C `main`, in a program that
+/// doesn't have a Rust `fn main`.
+pub unsafe fn init_early() {
+    unsafe { crate::std::rt::init_early() }
+}
+
+/// Finishes the runtime initialization started by [`init_early`].
+///
+/// Standard streams that are closed are replaced with `/dev/null`, and
+/// `argc` and `argv` become the arguments [`std::env::args`] returns. The
+/// process aborts if `/dev/null` is needed but can't be opened.
+///
+/// # Safety
+///
+/// Must be called once, after [`init_early`], with the `argc` and `argv`
+/// the C `main` was called with.
+///
+/// [`std::env::args`]: crate::std::env::args
+pub unsafe fn init_late(argc: isize, argv: *const *const u8) {
+    unsafe { crate::std::rt::init_late(argc, argv) }
+}
@@ -0,0 +1,257 @@
+//! Restricting the system calls a thread may make.
+//!
+//! A [`Filter`] maps system call numbers to what should happen when a thread
+//! makes that call: let it through, fail it with an error code, or kill the
+//! caller. Once installed, with [`apply_filter`] or
+//! [`CommandExt::syscall_filter`], a filter can't be removed, and it is
+//! inherited by every thread and process created afterwards. Installing
+//! another filter adds to the restrictions; the kernel runs all of them and
+//! picks the most severe action.
+//!
+//! The kernel checks filters in the seccomp format of Linux, so this module
+//! builds one from the rules and hands it to `prctl`. Calls made through
+//! another system call ABI than the native one, such as the x32 ABI on
+//! x86-64, kill the process, since their numbers would not match the rules.
+//!
+//! Run an untrusted plugin so that it can't open files or sockets:
+//!
+//! ```no_run
+//! use std::os::dragonos::process::CommandExt;
+//! use std::os::dragonos::seccomp::{Action, Filter};
+//! use std::process::Command;
+//!
+//! # const SYS_OPEN: u32 = 2; const SYS_OPENAT: u32 = 257; const SYS_SOCKET: u32 = 41;
+//! let mut filter = Filter::new(Action::Allow);
+//! for syscall in [SYS_OPEN, SYS_OPENAT, SYS_SOCKET] {
+//!     filter.rule(syscall, Action::Errno(1));
+//! }
+//! Command::new("/bin/plugin").syscall_filter(filter).spawn()?;
+//! # Ok::<(), std::io::Error>(())
+//! ```
+//!
+//! [`CommandExt::syscall_filter`]: super::process::CommandExt::syscall_filter
+
+use crate::std::io;
+use crate::std::sys::cvt;
+
+/// What happens when a thread makes a system call.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum Action {
+    /// Make the call as usual.
+    Allow,
+    /// Make the call as usual, but log it.
+    Log,
+    /// Fail the call with the given error number, without making it.
+    Errno(u16),
+    /// Send `SIGSYS` to the thread instead of making the call.
+    Trap,
+    /// Kill the thread.
+    KillThread,
+    /// Kill the whole process.
+    KillProcess,
+}
+
+impl Action {
+    fn ret(self) -> u32 {
+        match self {
+            Action::Allow => dlibc::SECCOMP_RET_ALLOW,
+            Action::Log => dlibc::SECCOMP_RET_LOG,
+            Action::Errno(errno) => dlibc::SECCOMP_RET_ERRNO | errno as u32,
+            Action::Trap => dlibc::SECCOMP_RET_TRAP,
+            Action::KillThread => dlibc::SECCOMP_RET_KILL_THREAD,
+            Action::KillProcess => dlibc::SECCOMP_RET_KILL_PROCESS,
+        }
+    }
+}
+
+/// A set of rules for which system calls a thread may make.
+///
+/// System calls are identified by their number, as found in the `SYS_*`
+/// constants of a libc.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Filter {
+    default: Action,
+    rules: Vec<(u32, Action)>,
+}
+
+impl Filter {
+    /// The largest number of rules a filter can have, which keeps the
+    /// program built from it within the kernel's limits.
+    pub const MAX_RULES: usize = 1024;
+
+    /// Creates a filter that takes `default` for every system call without
+    /// a rule of its own.
+    pub fn new(default: Action) -> Filter {
+        Filter {
+            default,
+            rules: Vec::new(),
+        }
+    }
+
+    /// Takes `action` whenever `syscall` is made, replacing any earlier rule
+    /// for it.
+    ///
+    /// # Panics
+    ///
+    /// Panics if this adds a rule to a filter that already has
+    /// [`Filter::MAX_RULES`] rules.
+    pub fn rule(&mut self, syscall: u32, action: Action) -> &mut Filter {
+        match self.rules.iter_mut().find(|(nr, _)| *nr == syscall) {
+            Some(rule) => rule.1 = action,
+            None => {
+                assert!(
+                    self.rules.len() < Self::MAX_RULES,
+                    "too many rules in a syscall filter"
+                );
+                self.rules.push((syscall, action));
+            }
+        }
+        self
+    }
+
+    /// Lets `syscall` through, like `rule(syscall, Action::Allow)`.
+    pub fn allow(&mut self, syscall: u32) -> &mut Filter {
+        self.rule(syscall, Action::Allow)
+    }
+
+    /// Returns the action taken for `syscall`.
+    pub fn action(&self, syscall: u32) -> Action {
+        self.rules
+            .iter()
+            .find(|(nr, _)| *nr == syscall)
+            .map_or(self.default, |&(_, action)| action)
+    }
+
+    /// Builds the program the kernel runs on every system call.
+    pub(crate) fn compile(&self) -> Box<[dlibc::sock_filter]> {
+        let mut program = Vec::with_capacity(2 * self.rules.len() + 7);
+        // Numbers only mean something for the native ABI.
+        program.push(stmt(
+            dlibc::BPF_LD | dlibc::BPF_W | dlibc::BPF_ABS,
+            ARCH_OFFSET,
+        ));
+        program.push(jump(
+            dlibc::BPF_JMP | dlibc::BPF_JEQ | dlibc::BPF_K,
+            AUDIT_ARCH,
+            1,
+            0,
+        ));
+        program.push(stmt(
+            dlibc::BPF_RET | dlibc::BPF_K,
+            dlibc::SECCOMP_RET_KILL_PROCESS,
+        ));
+        program.push(stmt(
+            dlibc::BPF_LD | dlibc::BPF_W | dlibc::BPF_ABS,
+            NR_OFFSET,
+        ));
+        #[cfg(target_arch = "x86_64")]
+        {
+            program.push(jump(
+                dlibc::BPF_JMP | dlibc::BPF_JSET | dlibc::BPF_K,
+                X32_SYSCALL_BIT,
+                0,
+                1,
+            ));
+            program.push(stmt(
+                dlibc::BPF_RET | dlibc::BPF_K,
+                dlibc::SECCOMP_RET_KILL_PROCESS,
+            ));
+        }
+        for &(nr, action) in &self.rules {
+            program.push(jump(
+                dlibc::BPF_JMP | dlibc::BPF_JEQ | dlibc::BPF_K,
+                nr,
+                0,
+                1,
+            ));
+            program.push(stmt(dlibc::BPF_RET | dlibc::BPF_K, action.ret()));
+        }
+        program.push(stmt(dlibc::BPF_RET | dlibc::BPF_K, self.default.ret()));
+        program.into_boxed_slice()
+    }
+}
+
+#[cfg(target_arch = "x86_64")]
+const AUDIT_ARCH: u32 = 0xc000_003e;
+#[cfg(target_arch = "aarch64")]
+const AUDIT_ARCH: u32 = 0xc000_00b7;
+#[cfg(target_arch = "riscv64")]
+const AUDIT_ARCH: u32 = 0xc000_00f3;
+
+/// Set in the numbers of x32 system calls on x86-64.
+#[cfg(target_arch = "x86_64")]
+const X32_SYSCALL_BIT: u32 = 0x4000_0000;
+
+// Offsets of `nr` and `arch` in `seccomp_data`.
+const NR_OFFSET: u32 = 0;
+const ARCH_OFFSET: u32 = 4;
+
+fn stmt(code: u32, k: u32) -> dlibc::sock_filter {
+    jump(code, k, 0, 0)
+}
+
+fn jump(code: u32, k: u32, jt: u8, jf: u8) -> dlibc::sock_filter {
+    dlibc::sock_filter {
+        code: code as u16,
+        jt,
+        jf,
+        k,
+    }
+}
+
+/// Restricts the system calls of the calling thread to those `filter` allows.
+///
+/// The filter also applies to the threads and processes the calling thread
+/// creates afterwards, but not to other threads that already run. To keep a
+/// filter from being undone by executing a set-user-ID program, this also
+/// sets the thread's *no new privileges* flag, so such programs no longer
+/// gain privileges.
+///
+/// # Errors
+///
+/// Fails with [`Unsupported`] if the kernel doesn't filter system calls.
+///
+/// [`Unsupported`]: io::ErrorKind::Unsupported
+pub fn apply_filter(filter: &Filter) -> io::Result<()> {
+    unsafe { install(&filter.compile()) }
+}
+
+/// Installs a program built by [`Filter::compile`].
+///
+/// This makes two system calls and doesn't allocate, so it may be called in
+/// a child between `fork` and `exec`.
+pub(crate) unsafe fn install(program: &[dlibc::sock_filter]) -> io::Result<()> {
+    let prog = dlibc::sock_fprog {
+        len: program.len() as dlibc::c_ushort,
+        filter: program.as_ptr() as *mut dlibc::sock_filter,
+    };
+    cvt(dlibc::prctl(
+        dlibc::PR_SET_NO_NEW_PRIVS,
+        1 as dlibc::c_ulong,
+        0 as dlibc::c_ulong,
+        0 as dlibc::c_ulong,
+        0 as dlibc::c_ulong,
+    ))?;
+    match cvt(dlibc::prctl(
+        dlibc::PR_SET_SECCOMP,
+        dlibc::SECCOMP_MODE_FILTER as dlibc::c_ulong,
+        &prog as *const dlibc::sock_fprog as dlibc::c_ulong,
+        0 as dlibc::c_ulong,
+        0 as dlibc::c_ulong,
+    )) {
+        Ok(_) => Ok(()),
+        // The program is valid, so these mean the kernel doesn't know the
+        // filter mode.
+        Err(e) if matches!(e.raw_os_error(), Some(dlibc::EINVAL | dlibc::ENOSYS)) => {
+            Err(io::const_io_error!(
+                io::ErrorKind::Unsupported,
+                "the kernel doesn't support syscall filters",
+            ))
+        }
+        Err(e) => Err(e),
+    }
+}
+
+#[cfg(test)]
+mod tests;
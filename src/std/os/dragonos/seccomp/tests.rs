@@ -0,0 +1,52 @@
+use super::{apply_filter, Action, Filter};
+use crate::std::io;
+use crate::std::thread;
+
+#[test]
+fn rules() {
+    let mut filter = Filter::new(Action::KillProcess);
+    filter
+        .allow(1)
+        .rule(2, Action::Errno(13))
+        .rule(1, Action::Log);
+    assert_eq!(filter.action(1), Action::Log);
+    assert_eq!(filter.action(2), Action::Errno(13));
+    assert_eq!(filter.action(3), Action::KillProcess);
+
+    let program = filter.compile();
+    let last = program.last().unwrap();
+    assert_eq!(last.code as u32, dlibc::BPF_RET | dlibc::BPF_K);
+    assert_eq!(last.k, dlibc::SECCOMP_RET_KILL_PROCESS);
+    // One comparison and one return for each of the two rules.
+    let rules = &program[program.len() - 5..program.len() - 1];
+    assert_eq!((rules[0].k, rules[0].jf), (1, 1));
+    assert_eq!(rules[1].k, dlibc::SECCOMP_RET_LOG);
+    assert_eq!((rules[2].k, rules[2].jf), (2, 1));
+    assert_eq!(rules[3].k, dlibc::SECCOMP_RET_ERRNO | 13);
+}
+
+#[test]
+fn errno_in_filtered_thread() {
+    // Filters only affect the thread that installs them, so this leaves the
+    // rest of the test process alone.
+    let errno = thread::spawn(|| {
+        let mut filter = Filter::new(Action::Allow);
+        filter.rule(
+            dlibc::SYS_getppid as u32,
+            Action::Errno(dlibc::EACCES as u16),
+        );
+        match apply_filter(&filter) {
+            Ok(()) => {}
+            Err(e) if e.kind() == io::ErrorKind::Unsupported => return None,
+            Err(e) => panic!("{e}"),
+        }
+        let ret = unsafe { dlibc::syscall(dlibc::SYS_getppid) };
+        assert_eq!(ret, -1);
+        io::Error::last_os_error().raw_os_error()
+    })
+    .join()
+    .unwrap();
+    if let Some(errno) = errno {
+        assert_eq!(errno, dlibc::EACCES);
+    }
+}
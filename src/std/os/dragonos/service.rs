@@ -0,0 +1,129 @@
+//! Integration with the service manager that supervises a daemon.
+//!
+//! This speaks the notification protocol of systemd's `sd_notify`: the
+//! manager passes the path of a datagram socket in the `NOTIFY_SOCKET`
+//! environment variable, and the service sends it newline-separated
+//! `KEY=VALUE` assignments such as `READY=1`. Nothing here depends on a
+//! particular manager, but whether one on DragonOS listens for these
+//! notifications is up to that manager.
+//!
+//! Every function here returns `Ok(false)` when the process wasn't started
+//! by a manager that listens for notifications, so daemons can call them
+//! unconditionally. If the kernel lacks the Unix datagram sockets the
+//! protocol needs, they fail with [`Unsupported`].
+//!
+//! [`Unsupported`]: io::ErrorKind::Unsupported
+//!
+//! ```no_run
+//! use std::os::dragonos::service;
+//! use std::thread;
+//!
+//! // ... bind sockets, load configuration ...
+//! service::notify_ready()?;
+//! if let Some(interval) = service::watchdog_interval() {
+//!     thread::spawn(move || loop {
+//!         thread::sleep(interval / 2);
+//!         let _ = service::watchdog_ping();
+//!     });
+//! }
+//! # Ok::<(), std::io::Error>(())
+//! ```
+
+use crate::std::env;
+use crate::std::ffi::OsStr;
+use crate::std::io;
+use crate::std::os::unix::ffi::OsStrExt;
+use crate::std::os::unix::net::UnixDatagram;
+use crate::std::process;
+use crate::std::time::Duration;
+use dlibc;
+
+/// Sends `state`, one or more newline-separated `KEY=VALUE` assignments, to
+/// the service manager.
+///
+/// Returns `Ok(false)` if `NOTIFY_SOCKET` is not set. Sockets in the
+/// abstract namespace (names starting with `@`) are not supported and fail
+/// with [`Unsupported`], as does a kernel without Unix datagram sockets.
+///
+/// [`Unsupported`]: io::ErrorKind::Unsupported
+pub fn notify(state: &str) -> io::Result<bool> {
+    match env::var_os("NOTIFY_SOCKET") {
+        Some(socket) => send(&socket, state).map(|()| true),
+        None => Ok(false),
+    }
+}
+
+/// Tells the service manager that startup has finished, with `READY=1`.
+///
+/// Managers that wait for this hold back services that depend on this one
+/// until it is sent.
+pub fn notify_ready() -> io::Result<bool> {
+    notify("READY=1")
+}
+
+/// Tells the service manager that the service is shutting down, with
+/// `STOPPING=1`.
+pub fn notify_stopping() -> io::Result<bool> {
+    notify("STOPPING=1")
+}
+
+/// Tells the service manager that the service is still alive, with
+/// `WATCHDOG=1`.
+///
+/// If [`watchdog_interval`] returns an interval, the manager considers the
+/// service hung and restarts it when this isn't called at least that often.
+pub fn watchdog_ping() -> io::Result<bool> {
+    notify("WATCHDOG=1")
+}
+
+/// Returns how often the service manager expects [`watchdog_ping`], or
+/// `None` if it doesn't watch this process.
+///
+/// The interval comes from `WATCHDOG_USEC`. If `WATCHDOG_PID` is set as
+/// well, the interval only applies to the process with that id, so that
+/// children which inherit the environment aren't mistaken for the service.
+pub fn watchdog_interval() -> Option<Duration> {
+    parse_watchdog(
+        env::var_os("WATCHDOG_USEC").as_deref(),
+        env::var_os("WATCHDOG_PID").as_deref(),
+        process::id(),
+    )
+}
+
+fn send(socket: &OsStr, state: &str) -> io::Result<()> {
+    if socket.as_bytes().starts_with(b"@") {
+        return Err(io::const_io_error!(
+            io::ErrorKind::Unsupported,
+            "abstract notification sockets are not supported",
+        ));
+    }
+    let sent = UnixDatagram::unbound()
+        .and_then(|sock| sock.send_to(state.as_bytes(), socket))
+        .map_err(|e| match e.raw_os_error() {
+            Some(dlibc::ENOSYS) => io::const_io_error!(
+                io::ErrorKind::Unsupported,
+                "Unix datagram sockets are not supported by the kernel",
+            ),
+            _ => e,
+        })?;
+    if sent != state.len() {
+        return Err(io::const_io_error!(
+            io::ErrorKind::WriteZero,
+            "failed to send the whole notification",
+        ));
+    }
+    Ok(())
+}
+
+fn parse_watchdog(usec: Option<&OsStr>, pid: Option<&OsStr>, own_pid: u32) -> Option<Duration> {
+    if let Some(pid) = pid {
+        if pid.to_str()?.parse::<u32>().ok()? != own_pid {
+            return None;
+        }
+    }
+    let usec = usec?.to_str()?.parse::<u64>().ok()?;
+    (usec > 0).then(|| Duration::from_micros(usec))
+}
+
+#[cfg(test)]
+mod tests;
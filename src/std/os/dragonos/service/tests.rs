@@ -0,0 +1,34 @@
+use super::{parse_watchdog, send};
+use crate::std::ffi::OsStr;
+use crate::std::io::ErrorKind;
+use crate::std::os::unix::net::UnixDatagram;
+use crate::std::time::Duration;
+
+#[test]
+fn send_notification() {
+    let dir = crate::std::sys_common::io::test::tmpdir();
+    let path = dir.join("notify");
+    let manager = UnixDatagram::bind(&path).unwrap();
+
+    send(path.as_os_str(), "READY=1\nSTATUS=serving").unwrap();
+    let mut buf = [0; 64];
+    let n = manager.recv(&mut buf).unwrap();
+    assert_eq!(&buf[..n], b"READY=1\nSTATUS=serving");
+
+    let err = send(OsStr::new("@manager"), "READY=1").unwrap_err();
+    assert_eq!(err.kind(), ErrorKind::Unsupported);
+}
+
+#[test]
+fn watchdog_settings() {
+    let usec = Some(OsStr::new("3000000"));
+    assert_eq!(parse_watchdog(usec, None, 7), Some(Duration::from_secs(3)));
+    assert_eq!(
+        parse_watchdog(usec, Some(OsStr::new("7")), 7),
+        Some(Duration::from_secs(3))
+    );
+    assert_eq!(parse_watchdog(usec, Some(OsStr::new("8")), 7), None);
+    assert_eq!(parse_watchdog(Some(OsStr::new("0")), None, 7), None);
+    assert_eq!(parse_watchdog(Some(OsStr::new("soon")), None, 7), None);
+    assert_eq!(parse_watchdog(None, None, 7), None);
+}
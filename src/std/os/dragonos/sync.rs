@@ -0,0 +1,198 @@
+//! Synchronization between processes through shared memory.
+//!
+//! The locks in [`std::sync`] wait on futexes private to the process, so a
+//! process blocked on one is never woken by another. The types here wait on
+//! shared futexes instead, and can be placed in memory that several
+//! processes map with `MAP_SHARED`. They hold no pointers and need no
+//! initialization beyond zeroed memory: all-zero bytes are an unlocked
+//! [`SharedMutex`] and a [`SharedCondvar`] nobody waits on, so they can be
+//! used straight from a freshly created file.
+//!
+//! ```no_run
+//! use std::os::dragonos::sync::{SharedCondvar, SharedMutex};
+//! use std::sync::atomic::{AtomicU32, Ordering::Relaxed};
+//!
+//! #[repr(C)]
+//! struct Shared {
+//!     lock: SharedMutex,
+//!     ready: SharedCondvar,
+//!     jobs: AtomicU32,
+//! }
+//!
+//! # fn map_shared() -> &'static Shared { unimplemented!() }
+//! // The same file, mapped with `MAP_SHARED` by every process.
+//! let shared: &Shared = map_shared();
+//!
+//! let mut guard = shared.lock.lock();
+//! while shared.jobs.load(Relaxed) == 0 {
+//!     guard = shared.ready.wait(guard);
+//! }
+//! shared.jobs.fetch_sub(1, Relaxed);
+//! ```
+//!
+//! A process that exits while holding a [`SharedMutex`] leaves it locked;
+//! there is no owner recorded that could be checked.
+//!
+//! [`std::sync`]: crate::std::sync
+
+use crate::std::fmt;
+use crate::std::sync::atomic::{AtomicU32, Ordering::Relaxed};
+use crate::std::sys::futex::{futex_wait_shared, futex_wake_all_shared, futex_wake_shared};
+use crate::std::sys::locks;
+use crate::std::time::Duration;
+
+/// A mutual exclusion lock that works across processes.
+///
+/// Unlike [`Mutex`], it doesn't hold the data it protects, since that data
+/// lives in shared memory next to it, and it isn't poisoned when a thread
+/// panics while holding it.
+///
+/// [`Mutex`]: crate::std::sync::Mutex
+#[repr(C)]
+pub struct SharedMutex {
+    // The futex mutex behind `std::sync::Mutex`, waiting on a shared futex.
+    // It is a single `AtomicU32` that is zero when unlocked.
+    inner: locks::SharedMutex,
+}
+
+/// Keeps a [`SharedMutex`] locked until it is dropped.
+#[must_use = "if unused the SharedMutex will immediately unlock"]
+#[clippy::has_significant_drop]
+pub struct SharedMutexGuard<'a> {
+    mutex: &'a SharedMutex,
+}
+
+impl SharedMutex {
+    /// Creates an unlocked mutex.
+    pub const fn new() -> SharedMutex {
+        SharedMutex {
+            inner: locks::SharedMutex::new(),
+        }
+    }
+
+    /// Locks the mutex, waiting until no other thread or process holds it.
+    ///
+    /// Locking a mutex that the calling thread already holds deadlocks.
+    pub fn lock(&self) -> SharedMutexGuard<'_> {
+        self.inner.lock();
+        SharedMutexGuard { mutex: self }
+    }
+
+    /// Locks the mutex if nobody holds it.
+    pub fn try_lock(&self) -> Option<SharedMutexGuard<'_>> {
+        self.inner
+            .try_lock()
+            .then(|| SharedMutexGuard { mutex: self })
+    }
+}
+
+impl Default for SharedMutex {
+    fn default() -> SharedMutex {
+        SharedMutex::new()
+    }
+}
+
+impl fmt::Debug for SharedMutex {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("SharedMutex")
+            .field("locked", &self.inner.is_locked())
+            .finish()
+    }
+}
+
+impl Drop for SharedMutexGuard<'_> {
+    fn drop(&mut self) {
+        // SAFETY: the guard proves that this thread holds the mutex.
+        unsafe { self.mutex.inner.unlock() }
+    }
+}
+
+impl fmt::Debug for SharedMutexGuard<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("SharedMutexGuard").finish_non_exhaustive()
+    }
+}
+
+/// A condition variable that works across processes, used together with a
+/// [`SharedMutex`].
+///
+/// As with [`Condvar`], waits may wake up spuriously, so the condition must
+/// be checked again in a loop. All waits on one condition variable must use
+/// the same mutex.
+///
+/// [`Condvar`]: crate::std::sync::Condvar
+#[repr(C)]
+pub struct SharedCondvar {
+    // Incremented on every notification, so that a waiter doesn't miss one
+    // between unlocking the mutex and going to sleep.
+    futex: AtomicU32,
+}
+
+impl SharedCondvar {
+    /// Creates a condition variable nobody waits on.
+    pub const fn new() -> SharedCondvar {
+        SharedCondvar {
+            futex: AtomicU32::new(0),
+        }
+    }
+
+    /// Unlocks the mutex of `guard`, waits for a notification, and locks the
+    /// mutex again.
+    pub fn wait<'a>(&self, guard: SharedMutexGuard<'a>) -> SharedMutexGuard<'a> {
+        self.wait_optional_timeout(guard, None).0
+    }
+
+    /// Like [`wait`], but gives up after `timeout`. The returned flag is
+    /// `false` if the wait timed out.
+    ///
+    /// [`wait`]: SharedCondvar::wait
+    pub fn wait_timeout<'a>(
+        &self,
+        guard: SharedMutexGuard<'a>,
+        timeout: Duration,
+    ) -> (SharedMutexGuard<'a>, bool) {
+        self.wait_optional_timeout(guard, Some(timeout))
+    }
+
+    fn wait_optional_timeout<'a>(
+        &self,
+        guard: SharedMutexGuard<'a>,
+        timeout: Option<Duration>,
+    ) -> (SharedMutexGuard<'a>, bool) {
+        // Synchronization is done by the mutex, so `Relaxed` is enough.
+        let futex_value = self.futex.load(Relaxed);
+        let mutex = guard.mutex;
+        drop(guard);
+        let notified = futex_wait_shared(&self.futex, futex_value, timeout);
+        (mutex.lock(), notified)
+    }
+
+    /// Wakes up one thread waiting on this condition variable, in any
+    /// process.
+    pub fn notify_one(&self) {
+        self.futex.fetch_add(1, Relaxed);
+        futex_wake_shared(&self.futex);
+    }
+
+    /// Wakes up all threads waiting on this condition variable, in any
+    /// process.
+    pub fn notify_all(&self) {
+        self.futex.fetch_add(1, Relaxed);
+        futex_wake_all_shared(&self.futex);
+    }
+}
+
+impl Default for SharedCondvar {
+    fn default() -> SharedCondvar {
+        SharedCondvar::new()
+    }
+}
+
+impl fmt::Debug for SharedCondvar {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("SharedCondvar").finish_non_exhaustive()
+    }
+}
+
+#[cfg(test)]
+mod tests;
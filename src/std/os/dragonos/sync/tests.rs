@@ -0,0 +1,98 @@
+use super::{SharedCondvar, SharedMutex};
+use crate::std::mem;
+use crate::std::ptr;
+use crate::std::sync::atomic::{AtomicU32, Ordering::Relaxed};
+use crate::std::sync::Arc;
+use crate::std::thread;
+use crate::std::time::Duration;
+
+#[repr(C)]
+struct Shared {
+    lock: SharedMutex,
+    ready: SharedCondvar,
+    value: AtomicU32,
+}
+
+#[test]
+fn lock() {
+    let mutex = SharedMutex::new();
+    let guard = mutex.lock();
+    assert!(mutex.try_lock().is_none());
+    drop(guard);
+    assert!(mutex.try_lock().is_some());
+}
+
+#[test]
+fn threads() {
+    let shared = Arc::new(Shared {
+        lock: SharedMutex::new(),
+        ready: SharedCondvar::new(),
+        value: AtomicU32::new(0),
+    });
+    let threads: Vec<_> = (0..4)
+        .map(|_| {
+            let shared = shared.clone();
+            thread::spawn(move || {
+                for _ in 0..1000 {
+                    let _guard = shared.lock.lock();
+                    // Not atomic as a whole, so the lock must exclude others.
+                    let value = shared.value.load(Relaxed);
+                    shared.value.store(value + 1, Relaxed);
+                }
+                shared.ready.notify_all();
+            })
+        })
+        .collect();
+    let mut guard = shared.lock.lock();
+    while shared.value.load(Relaxed) < 4000 {
+        guard = shared.ready.wait(guard);
+    }
+    drop(guard);
+    for thread in threads {
+        thread.join().unwrap();
+    }
+}
+
+#[test]
+fn wait_timeout() {
+    let mutex = SharedMutex::new();
+    let condvar = SharedCondvar::new();
+    let (_guard, notified) = condvar.wait_timeout(mutex.lock(), Duration::from_millis(10));
+    assert!(!notified);
+}
+
+#[test]
+fn across_fork() {
+    unsafe {
+        // Zeroed memory, as a new shared file would be.
+        let addr = dlibc::mmap(
+            ptr::null_mut(),
+            mem::size_of::<Shared>(),
+            dlibc::PROT_READ | dlibc::PROT_WRITE,
+            dlibc::MAP_SHARED | dlibc::MAP_ANONYMOUS,
+            -1,
+            0,
+        );
+        assert_ne!(addr, dlibc::MAP_FAILED);
+        let shared = &*(addr as *const Shared);
+
+        let mut guard = shared.lock.lock();
+        let pid = dlibc::fork();
+        assert!(pid >= 0);
+        if pid == 0 {
+            // The parent holds the lock, so this waits for it to wait.
+            let _guard = shared.lock.lock();
+            shared.value.store(1, Relaxed);
+            shared.ready.notify_one();
+            dlibc::_exit(0);
+        }
+        while shared.value.load(Relaxed) == 0 {
+            guard = shared.ready.wait(guard);
+        }
+        drop(guard);
+
+        let mut status = 0;
+        assert_eq!(dlibc::waitpid(pid, &mut status, 0), pid);
+        dlibc::munmap(addr, mem::size_of::<Shared>());
+    }
+}
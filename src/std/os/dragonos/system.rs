@@ -0,0 +1,178 @@
+//! How busy the system as a whole is.
+//!
+//! [`available_parallelism`] says how many CPUs a program may use, but not
+//! how many of them other programs are already using. A server that sizes
+//! its work by the former alone keeps accepting requests while the
+//! scheduler is thrashing. [`cpu_pressure`] measures how much runnable
+//! tasks have been waiting for a CPU, so that a server can shrink its
+//! concurrency before that happens:
+//!
+//! ```no_run
+//! use std::os::dragonos::system;
+//! use std::thread;
+//!
+//! // Recomputed every few seconds, and not for every request: reading the
+//! // pressure costs a file read.
+//! let pressure = system::cpu_pressure()?;
+//! let limit = pressure.limit(thread::available_parallelism()?);
+//! println!("taking up to {limit} requests at a time");
+//! # Ok::<(), std::io::Error>(())
+//! ```
+//!
+//! Requests beyond the limit are best turned away early, with a "busy"
+//! reply that lets the client retry elsewhere, rather than queued.
+//!
+//! [`available_parallelism`]: crate::std::thread::available_parallelism
+
+#[cfg(test)]
+mod tests;
+
+use crate::std::fs;
+use crate::std::io;
+use crate::std::num::NonZeroUsize;
+use crate::std::thread;
+
+/// The average number of runnable tasks over the last 1, 5 and 15
+/// minutes, as returned by [`load_average`].
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct LoadAverage {
+    one: f64,
+    five: f64,
+    fifteen: f64,
+}
+
+impl LoadAverage {
+    /// Returns the average over the last minute.
+    pub fn one(&self) -> f64 {
+        self.one
+    }
+
+    /// Returns the average over the last 5 minutes.
+    pub fn five(&self) -> f64 {
+        self.five
+    }
+
+    /// Returns the average over the last 15 minutes.
+    pub fn fifteen(&self) -> f64 {
+        self.fifteen
+    }
+}
+
+/// Returns the system load average, from `/proc/loadavg`.
+pub fn load_average() -> io::Result<LoadAverage> {
+    parse_loadavg(&fs::read_to_string("/proc/loadavg")?).ok_or(io::const_io_error!(
+        io::ErrorKind::InvalidData,
+        "invalid /proc/loadavg",
+    ))
+}
+
+/// The share of time that runnable tasks waited for a CPU, from 0.0 to
+/// 1.0, as returned by [`cpu_pressure`].
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct CpuPressure {
+    short: f64,
+    medium: f64,
+    long: f64,
+    estimated: bool,
+}
+
+impl CpuPressure {
+    /// Returns the pressure over the last 10 seconds, or the last minute
+    /// if [estimated](CpuPressure::is_estimated).
+    pub fn short(&self) -> f64 {
+        self.short
+    }
+
+    /// Returns the pressure over the last minute, or the last 5 minutes if
+    /// [estimated](CpuPressure::is_estimated).
+    pub fn medium(&self) -> f64 {
+        self.medium
+    }
+
+    /// Returns the pressure over the last 5 minutes, or the last 15
+    /// minutes if [estimated](CpuPressure::is_estimated).
+    pub fn long(&self) -> f64 {
+        self.long
+    }
+
+    /// Returns whether the kernel doesn't track pressure, so that it was
+    /// estimated from the [`load_average`] instead.
+    pub fn is_estimated(&self) -> bool {
+        self.estimated
+    }
+
+    /// Scales `max` tasks at a time down by the [short](CpuPressure::short)
+    /// pressure, leaving at least one.
+    ///
+    /// With no pressure this is `max`; when tasks wait for a CPU half of
+    /// the time, it is half of `max`.
+    pub fn limit(&self, max: NonZeroUsize) -> NonZeroUsize {
+        let limit = (max.get() as f64 * (1.0 - self.short)) as usize;
+        NonZeroUsize::new(limit.max(1)).unwrap()
+    }
+}
+
+/// Returns how much runnable tasks have been waiting for a CPU.
+///
+/// This is read from `/proc/pressure/cpu` where the kernel provides it.
+/// Otherwise it is estimated from the [`load_average`] and the number of
+/// CPUs, as the share of runnable tasks that don't fit on them: a load of
+/// 6 on 4 CPUs gives a pressure of 1/3, and any load up to 4 gives none.
+pub fn cpu_pressure() -> io::Result<CpuPressure> {
+    match fs::read_to_string("/proc/pressure/cpu") {
+        Ok(psi) => parse_psi(&psi).ok_or(io::const_io_error!(
+            io::ErrorKind::InvalidData,
+            "invalid /proc/pressure/cpu",
+        )),
+        Err(e) if e.kind() == io::ErrorKind::NotFound => {
+            let cpus = thread::available_parallelism()?;
+            Ok(estimate(load_average()?, cpus))
+        }
+        Err(e) => Err(e),
+    }
+}
+
+/// Parses `/proc/loadavg`, which reads `0.52 0.58 0.59 1/345 12345`.
+fn parse_loadavg(loadavg: &str) -> Option<LoadAverage> {
+    let mut fields = loadavg.split_whitespace().map(str::parse);
+    Some(LoadAverage {
+        one: fields.next()?.ok()?,
+        five: fields.next()?.ok()?,
+        fifteen: fields.next()?.ok()?,
+    })
+}
+
+/// Parses the `some` line of `/proc/pressure/cpu`, which reads
+/// `some avg10=1.53 avg60=0.87 avg300=0.21 total=58761459`, in percent.
+fn parse_psi(psi: &str) -> Option<CpuPressure> {
+    let line = psi.lines().find_map(|line| line.strip_prefix("some "))?;
+    let avg = |key: &str| -> Option<f64> {
+        let value = line
+            .split_whitespace()
+            .find_map(|field| field.strip_prefix(key)?.strip_prefix('='))?;
+        Some(value.parse::<f64>().ok()? / 100.0)
+    };
+    Some(CpuPressure {
+        short: avg("avg10")?,
+        medium: avg("avg60")?,
+        long: avg("avg300")?,
+        estimated: false,
+    })
+}
+
+fn estimate(load: LoadAverage, cpus: NonZeroUsize) -> CpuPressure {
+    let cpus = cpus.get() as f64;
+    let waiting = |load: f64| {
+        if load > cpus {
+            (load - cpus) / load
+        } else {
+            0.0
+        }
+    };
+    CpuPressure {
+        short: waiting(load.one),
+        medium: waiting(load.five),
+        long: waiting(load.fifteen),
+        estimated: true,
+    }
+}
@@ -0,0 +1,47 @@
+use super::{estimate, parse_loadavg, parse_psi};
+use crate::std::num::NonZeroUsize;
+
+#[test]
+fn loadavg() {
+    let load = parse_loadavg("0.52 1.58 2.00 1/345 12345\n").unwrap();
+    assert_eq!((load.one(), load.five(), load.fifteen()), (0.52, 1.58, 2.0));
+    assert_eq!(parse_loadavg("0.52 x 2.00\n"), None);
+    assert_eq!(parse_loadavg(""), None);
+}
+
+#[test]
+fn psi() {
+    let pressure = parse_psi(
+        "some avg10=50.00 avg60=25.00 avg300=0.00 total=58761459\n\
+         full avg10=0.00 avg60=0.00 avg300=0.00 total=0\n",
+    )
+    .unwrap();
+    assert!(!pressure.is_estimated());
+    assert_eq!(
+        (pressure.short(), pressure.medium(), pressure.long()),
+        (0.5, 0.25, 0.0)
+    );
+    assert_eq!(pressure.limit(NonZeroUsize::new(8).unwrap()).get(), 4);
+    assert_eq!(
+        parse_psi("full avg10=0.00 avg60=0.00 avg300=0.00 total=0\n"),
+        None
+    );
+}
+
+#[test]
+fn estimated_from_load() {
+    let load = parse_loadavg("6.00 4.00 2.00 1/100 1\n").unwrap();
+    let pressure = estimate(load, NonZeroUsize::new(4).unwrap());
+    assert!(pressure.is_estimated());
+    assert!((pressure.short() - 1.0 / 3.0).abs() < 1e-9);
+    assert_eq!((pressure.medium(), pressure.long()), (0.0, 0.0));
+    // Never down to no tasks at all.
+    assert_eq!(pressure.limit(NonZeroUsize::new(1).unwrap()).get(), 1);
+}
+
+#[test]
+fn current() {
+    // Either source works on a running system.
+    let pressure = super::cpu_pressure().unwrap();
+    assert!((0.0..=1.0).contains(&pressure.short()));
+}
@@ -0,0 +1,295 @@
+//! DragonOS-specific extensions to primitives in the [`std::thread`] module.
+//!
+//! [`std::thread`]: crate::std::thread
+
+use crate::std::cell::Cell;
+use crate::std::fmt;
+use crate::std::io;
+use crate::std::mem;
+use crate::std::sealed::Sealed;
+use crate::std::sys::cvt;
+use crate::std::sys_common::AsInner;
+use crate::std::thread::JoinHandle;
+
+mod pool;
+
+pub use self::pool::{PoolScope, Task, ThreadPool};
+
+/// A set of CPUs, used as a thread's affinity mask.
+///
+/// CPUs are numbered from 0 up to, but not including, [`CpuSet::MAX_CPUS`].
+///
+/// ```no_run
+/// use std::os::dragonos::thread::{self, CpuSet};
+///
+/// // Pin the calling thread to the first CPU it may currently run on.
+/// let first = thread::affinity()?.iter().next().unwrap();
+/// thread::set_affinity(&CpuSet::from_iter([first]))?;
+/// # Ok::<(), std::io::Error>(())
+/// ```
+#[derive(Clone, Copy)]
+pub struct CpuSet {
+    set: dlibc::cpu_set_t,
+}
+
+impl CpuSet {
+    /// The number of CPUs a `CpuSet` can hold.
+    pub const MAX_CPUS: usize = 8 * mem::size_of::<dlibc::cpu_set_t>();
+
+    /// Creates an empty set.
+    pub fn new() -> CpuSet {
+        CpuSet {
+            set: unsafe { mem::zeroed() },
+        }
+    }
+
+    /// Adds `cpu` to the set.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `cpu` is not less than [`CpuSet::MAX_CPUS`].
+    pub fn insert(&mut self, cpu: usize) {
+        assert!(
+            cpu < Self::MAX_CPUS,
+            "CPU {cpu} is out of range for a CpuSet"
+        );
+        unsafe { dlibc::CPU_SET(cpu, &mut self.set) }
+    }
+
+    /// Removes `cpu` from the set.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `cpu` is not less than [`CpuSet::MAX_CPUS`].
+    pub fn remove(&mut self, cpu: usize) {
+        assert!(
+            cpu < Self::MAX_CPUS,
+            "CPU {cpu} is out of range for a CpuSet"
+        );
+        unsafe { dlibc::CPU_CLR(cpu, &mut self.set) }
+    }
+
+    /// Returns whether `cpu` is in the set.
+    pub fn contains(&self, cpu: usize) -> bool {
+        cpu < Self::MAX_CPUS && unsafe { dlibc::CPU_ISSET(cpu, &self.set) }
+    }
+
+    /// Returns the number of CPUs in the set.
+    pub fn len(&self) -> usize {
+        unsafe { dlibc::CPU_COUNT(&self.set) as usize }
+    }
+
+    /// Returns whether the set is empty.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Returns the CPUs in the set, in ascending order.
+    pub fn iter(&self) -> impl Iterator<Item = usize> + '_ {
+        (0..Self::MAX_CPUS).filter(|&cpu| self.contains(cpu))
+    }
+}
+
+impl Default for CpuSet {
+    fn default() -> CpuSet {
+        CpuSet::new()
+    }
+}
+
+impl PartialEq for CpuSet {
+    fn eq(&self, other: &CpuSet) -> bool {
+        unsafe { dlibc::CPU_EQUAL(&self.set, &other.set) }
+    }
+}
+
+impl Eq for CpuSet {}
+
+impl FromIterator<usize> for CpuSet {
+    fn from_iter<I: IntoIterator<Item = usize>>(iter: I) -> CpuSet {
+        let mut set = CpuSet::new();
+        set.extend(iter);
+        set
+    }
+}
+
+impl Extend<usize> for CpuSet {
+    fn extend<I: IntoIterator<Item = usize>>(&mut self, iter: I) {
+        for cpu in iter {
+            self.insert(cpu);
+        }
+    }
+}
+
+impl fmt::Debug for CpuSet {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_set().entries(self.iter()).finish()
+    }
+}
+
+/// Returns the set of CPUs the calling thread may run on.
+pub fn affinity() -> io::Result<CpuSet> {
+    let mut set = CpuSet::new();
+    cvt(unsafe { dlibc::sched_getaffinity(0, mem::size_of_val(&set.set), &mut set.set) })?;
+    Ok(set)
+}
+
+/// Restricts the calling thread to the CPUs in `set`.
+///
+/// If the thread is running on a CPU outside `set`, it is migrated before
+/// this returns. Fails with [`InvalidInput`] if `set` contains no CPU that
+/// is online.
+///
+/// [`InvalidInput`]: io::ErrorKind::InvalidInput
+pub fn set_affinity(set: &CpuSet) -> io::Result<()> {
+    cvt(unsafe { dlibc::sched_setaffinity(0, mem::size_of_val(&set.set), &set.set) })?;
+    Ok(())
+}
+
+/// Moves the calling thread into the idle scheduling class, or back into
+/// the normal one if `idle` is `false`.
+///
+/// A thread in the idle class only runs on CPUs that nothing else wants,
+/// whatever its nice value. This suits background work such as indexing or
+/// compaction, which then doesn't take CPU time from the threads serving
+/// requests. Threads spawned by an idle thread start in the idle class as
+/// well.
+///
+/// Going back to the normal class may fail with [`PermissionDenied`] if
+/// the thread's nice value is above what its `RLIMIT_NICE` allows.
+///
+/// [`PermissionDenied`]: io::ErrorKind::PermissionDenied
+pub fn set_idle_class(idle: bool) -> io::Result<()> {
+    set_scheduler(0, idle)
+}
+
+/// Returns whether the calling thread is in the idle scheduling class. See
+/// [`set_idle_class`].
+pub fn is_idle_class() -> io::Result<bool> {
+    is_idle(0)
+}
+
+fn set_scheduler(tid: dlibc::pid_t, idle: bool) -> io::Result<()> {
+    let policy = if idle {
+        dlibc::SCHED_IDLE
+    } else {
+        dlibc::SCHED_OTHER
+    };
+    // Both classes only take a priority of 0.
+    let param = dlibc::sched_param { sched_priority: 0 };
+    cvt(unsafe { dlibc::sched_setscheduler(tid, policy, &param) })?;
+    Ok(())
+}
+
+fn is_idle(tid: dlibc::pid_t) -> io::Result<bool> {
+    let policy = cvt(unsafe { dlibc::sched_getscheduler(tid) })?;
+    Ok(policy & !dlibc::SCHED_RESET_ON_FORK == dlibc::SCHED_IDLE)
+}
+
+thread_local! {
+    // The kernel ID of the current thread, or 0 until it is first asked for.
+    static TID: Cell<u32> = const { Cell::new(0) };
+}
+
+/// Returns the kernel task ID of the calling thread.
+///
+/// This is the ID the kernel uses for the thread in its logs and in
+/// `/proc`, and that tracing tools report. The main thread's ID is the
+/// process ID.
+///
+/// Only the first call on each thread asks the kernel. A child forked by
+/// calling `fork` directly rather than through [`Command`] must not rely
+/// on this before it calls `exec`.
+///
+/// [`Command`]: crate::std::process::Command
+pub fn gettid() -> u32 {
+    let tid = TID.get();
+    if tid != 0 {
+        return tid;
+    }
+    let tid = unsafe { dlibc::gettid() as u32 };
+    TID.set(tid);
+    tid
+}
+
+/// Forgets the cached ID, in a child that was forked from this thread.
+pub(crate) fn forget_tid() {
+    TID.set(0);
+}
+
+/// Os-specific extensions for [`JoinHandle`]
+pub trait JoinHandleExt: Sealed {
+    /// Returns the kernel task ID of the thread, like [`gettid`] does for the
+    /// calling thread.
+    ///
+    /// If the thread hasn't started running yet, this waits until it has.
+    /// The ID stays available after the thread has exited, but the kernel
+    /// may then reuse it for another task.
+    fn as_raw_tid(&self) -> u32;
+
+    /// Returns the set of CPUs the thread may run on.
+    ///
+    /// See [`affinity`] for the calling thread.
+    fn affinity(&self) -> io::Result<CpuSet>;
+
+    /// Restricts the thread to the CPUs in `set`.
+    ///
+    /// This takes effect even if the thread is running at the time. See
+    /// [`set_affinity`] for the calling thread and the errors it can return.
+    fn set_affinity(&self, set: &CpuSet) -> io::Result<()>;
+
+    /// Moves the thread into the idle scheduling class, or back into the
+    /// normal one if `idle` is `false`.
+    ///
+    /// See [`set_idle_class`] for the calling thread and the errors it can
+    /// return.
+    fn set_idle_class(&self, idle: bool) -> io::Result<()>;
+
+    /// Returns whether the thread is in the idle scheduling class.
+    fn is_idle_class(&self) -> io::Result<bool>;
+}
+
+impl<T> JoinHandleExt for JoinHandle<T> {
+    fn as_raw_tid(&self) -> u32 {
+        self.as_inner().tid()
+    }
+
+    fn affinity(&self) -> io::Result<CpuSet> {
+        let mut set = CpuSet::new();
+        let ret = unsafe {
+            dlibc::pthread_getaffinity_np(
+                self.as_inner().id(),
+                mem::size_of_val(&set.set),
+                &mut set.set,
+            )
+        };
+        if ret != 0 {
+            return Err(io::Error::from_raw_os_error(ret));
+        }
+        Ok(set)
+    }
+
+    fn set_affinity(&self, set: &CpuSet) -> io::Result<()> {
+        let ret = unsafe {
+            dlibc::pthread_setaffinity_np(
+                self.as_inner().id(),
+                mem::size_of_val(&set.set),
+                &set.set,
+            )
+        };
+        if ret != 0 {
+            return Err(io::Error::from_raw_os_error(ret));
+        }
+        Ok(())
+    }
+
+    fn set_idle_class(&self, idle: bool) -> io::Result<()> {
+        set_scheduler(self.as_raw_tid() as dlibc::pid_t, idle)
+    }
+
+    fn is_idle_class(&self) -> io::Result<bool> {
+        is_idle(self.as_raw_tid() as dlibc::pid_t)
+    }
+}
+
+#[cfg(test)]
+mod tests;
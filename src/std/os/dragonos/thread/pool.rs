@@ -0,0 +1,361 @@
+#[cfg(test)]
+mod tests;
+
+use crate::std::collections::VecDeque;
+use crate::std::fmt;
+use crate::std::io;
+use crate::std::marker::PhantomData;
+use crate::std::mem;
+use crate::std::num::NonZeroUsize;
+use crate::std::panic::{catch_unwind, resume_unwind, AssertUnwindSafe};
+use crate::std::sync::{Arc, Condvar, Mutex, MutexGuard, PoisonError};
+use crate::std::thread::{self, Builder, JoinHandle};
+
+type Job = Box<dyn FnOnce() + Send + 'static>;
+
+/// A fixed number of threads that run closures handed to them, in the order
+/// they were handed over.
+///
+/// Starting a thread for every short task costs more than the task itself;
+/// a pool starts its threads once and reuses them.
+///
+/// ```no_run
+/// use std::num::NonZeroUsize;
+/// use std::os::dragonos::thread::ThreadPool;
+///
+/// let pool = ThreadPool::new(NonZeroUsize::new(4).unwrap())?;
+///
+/// // Closures that own their data can run on their own, and be waited on
+/// // one by one.
+/// let task = pool.spawn(|| (1..=100).sum::<u32>());
+/// assert_eq!(task.join().unwrap(), 5050);
+///
+/// // Closures that borrow run in a scope, which waits for all of them.
+/// let mut chunks = vec![vec![1, 2], vec![3, 4], vec![5, 6]];
+/// pool.scope(|s| {
+///     for chunk in &mut chunks {
+///         s.submit(move || chunk.iter_mut().for_each(|x| *x *= 10));
+///     }
+/// });
+/// assert_eq!(chunks, [[10, 20], [30, 40], [50, 60]]);
+/// # Ok::<(), std::io::Error>(())
+/// ```
+///
+/// A closure that panics doesn't take its thread down; [`Task::join`]
+/// returns the panic, and [`ThreadPool::scope`] panics once all of its
+/// closures have run. Dropping the pool runs the closures still queued and
+/// then stops its threads.
+pub struct ThreadPool {
+    shared: Arc<Shared>,
+    workers: Vec<JoinHandle<()>>,
+}
+
+struct Shared {
+    state: Mutex<State>,
+    // Signaled when a job is queued, or the pool is dropped.
+    work: Condvar,
+    // Signaled when the last running job finishes with none queued.
+    idle: Condvar,
+}
+
+struct State {
+    jobs: VecDeque<Job>,
+    running: usize,
+    stopping: bool,
+}
+
+impl Shared {
+    fn lock(&self) -> MutexGuard<'_, State> {
+        // Jobs run without the lock held, so it is never poisoned.
+        self.state.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+}
+
+impl ThreadPool {
+    /// Starts a pool of `threads` threads, named `pool-0`, `pool-1` and so
+    /// on.
+    ///
+    /// Fails if a thread can't be started, after stopping the ones that
+    /// were.
+    pub fn new(threads: NonZeroUsize) -> io::Result<ThreadPool> {
+        let shared = Arc::new(Shared {
+            state: Mutex::new(State {
+                jobs: VecDeque::new(),
+                running: 0,
+                stopping: false,
+            }),
+            work: Condvar::new(),
+            idle: Condvar::new(),
+        });
+        let mut pool = ThreadPool {
+            shared,
+            workers: Vec::with_capacity(threads.get()),
+        };
+        for i in 0..threads.get() {
+            let shared = pool.shared.clone();
+            // On error, dropping `pool` stops the threads started so far.
+            let worker = Builder::new()
+                .name(format!("pool-{i}"))
+                .spawn(move || work(&shared))?;
+            pool.workers.push(worker);
+        }
+        Ok(pool)
+    }
+
+    /// Returns the number of threads in the pool.
+    pub fn threads(&self) -> usize {
+        self.workers.len()
+    }
+
+    fn push(&self, job: Job) {
+        self.shared.lock().jobs.push_back(job);
+        self.shared.work.notify_one();
+    }
+
+    /// Queues `f` to run on one of the threads, without a way to get its
+    /// result.
+    pub fn execute<F>(&self, f: F)
+    where
+        F: FnOnce() + Send + 'static,
+    {
+        self.push(Box::new(f));
+    }
+
+    /// Queues `f` to run on one of the threads, and returns a [`Task`] to
+    /// wait for its result with.
+    pub fn spawn<F, T>(&self, f: F) -> Task<T>
+    where
+        F: FnOnce() -> T + Send + 'static,
+        T: Send + 'static,
+    {
+        let result = Arc::new(TaskResult {
+            value: Mutex::new(None),
+            done: Condvar::new(),
+        });
+        let their_result = result.clone();
+        self.push(Box::new(move || {
+            let value = catch_unwind(AssertUnwindSafe(f));
+            *their_result.lock() = Some(value);
+            their_result.done.notify_all();
+        }));
+        Task { result }
+    }
+
+    /// Runs `f` with a [`PoolScope`] to queue closures that borrow from the
+    /// caller, and waits until they have all run.
+    ///
+    /// If one of the closures panics, this panics as well once all of them
+    /// have run.
+    ///
+    /// Like [`join`], this must not be called from the pool's own threads,
+    /// which would end up waiting for themselves.
+    ///
+    /// [`join`]: ThreadPool::join
+    pub fn scope<'env, F, R>(&self, f: F) -> R
+    where
+        F: for<'scope> FnOnce(&'scope PoolScope<'scope, 'env>) -> R,
+    {
+        let scope = PoolScope {
+            pool: self,
+            data: Arc::new(ScopeData {
+                state: Mutex::new(ScopeState {
+                    pending: 0,
+                    panicked: false,
+                }),
+                done: Condvar::new(),
+            }),
+            scope: PhantomData,
+            env: PhantomData,
+        };
+        let result = catch_unwind(AssertUnwindSafe(|| f(&scope)));
+        // The closures may borrow from the caller, so they have to be done
+        // before returning, even if `f` panicked.
+        let mut state = scope.data.lock();
+        while state.pending > 0 {
+            state = scope
+                .data
+                .done
+                .wait(state)
+                .unwrap_or_else(PoisonError::into_inner);
+        }
+        let panicked = state.panicked;
+        drop(state);
+        match result {
+            Err(e) => resume_unwind(e),
+            Ok(_) if panicked => panic!("a closure in a pool scope panicked"),
+            Ok(result) => result,
+        }
+    }
+
+    /// Waits until every closure queued so far, and any queued by them, has
+    /// run.
+    ///
+    /// Called from one of the pool's own threads, this never returns.
+    pub fn join(&self) {
+        let mut state = self.shared.lock();
+        while !state.jobs.is_empty() || state.running > 0 {
+            state = self
+                .shared
+                .idle
+                .wait(state)
+                .unwrap_or_else(PoisonError::into_inner);
+        }
+    }
+}
+
+fn work(shared: &Shared) {
+    let mut state = shared.lock();
+    loop {
+        if let Some(job) = state.jobs.pop_front() {
+            state.running += 1;
+            drop(state);
+            // Panics are reported by the job itself, if at all; the thread
+            // goes on with the next one.
+            let _ = catch_unwind(AssertUnwindSafe(job));
+            state = shared.lock();
+            state.running -= 1;
+            if state.running == 0 && state.jobs.is_empty() {
+                shared.idle.notify_all();
+            }
+        } else if state.stopping {
+            return;
+        } else {
+            state = shared
+                .work
+                .wait(state)
+                .unwrap_or_else(PoisonError::into_inner);
+        }
+    }
+}
+
+impl Drop for ThreadPool {
+    fn drop(&mut self) {
+        self.shared.lock().stopping = true;
+        self.shared.work.notify_all();
+        for worker in self.workers.drain(..) {
+            let _ = worker.join();
+        }
+    }
+}
+
+impl fmt::Debug for ThreadPool {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let state = self.shared.lock();
+        f.debug_struct("ThreadPool")
+            .field("threads", &self.workers.len())
+            .field("queued", &state.jobs.len())
+            .field("running", &state.running)
+            .finish()
+    }
+}
+
+/// The result of a closure queued with [`ThreadPool::spawn`].
+pub struct Task<T> {
+    result: Arc<TaskResult<T>>,
+}
+
+struct TaskResult<T> {
+    value: Mutex<Option<thread::Result<T>>>,
+    done: Condvar,
+}
+
+impl<T> TaskResult<T> {
+    fn lock(&self) -> MutexGuard<'_, Option<thread::Result<T>>> {
+        self.value.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+}
+
+impl<T> Task<T> {
+    /// Waits for the closure to run, and returns what it returned, or the
+    /// value it panicked with.
+    pub fn join(self) -> thread::Result<T> {
+        let mut value = self.result.lock();
+        loop {
+            if let Some(value) = value.take() {
+                return value;
+            }
+            value = self
+                .result
+                .done
+                .wait(value)
+                .unwrap_or_else(PoisonError::into_inner);
+        }
+    }
+
+    /// Returns whether the closure has run, so that [`join`] won't wait.
+    ///
+    /// [`join`]: Task::join
+    pub fn is_finished(&self) -> bool {
+        self.result.lock().is_some()
+    }
+}
+
+impl<T> fmt::Debug for Task<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Task")
+            .field("finished", &self.is_finished())
+            .finish()
+    }
+}
+
+/// A scope to queue closures that borrow from the caller in, created by
+/// [`ThreadPool::scope`].
+pub struct PoolScope<'scope, 'env: 'scope> {
+    pool: &'scope ThreadPool,
+    data: Arc<ScopeData>,
+    // Invariant over both, as for `std::thread::Scope`, so that a closure
+    // can't outlive what it borrows.
+    scope: PhantomData<&'scope mut &'scope ()>,
+    env: PhantomData<&'env mut &'env ()>,
+}
+
+struct ScopeData {
+    state: Mutex<ScopeState>,
+    // Signaled when the last pending closure has run.
+    done: Condvar,
+}
+
+struct ScopeState {
+    pending: usize,
+    panicked: bool,
+}
+
+impl ScopeData {
+    fn lock(&self) -> MutexGuard<'_, ScopeState> {
+        self.state.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+}
+
+impl<'scope, 'env> PoolScope<'scope, 'env> {
+    /// Queues `f` to run on one of the pool's threads before the scope
+    /// ends.
+    pub fn submit<F>(&'scope self, f: F)
+    where
+        F: FnOnce() + Send + 'scope,
+    {
+        self.data.lock().pending += 1;
+        let data = self.data.clone();
+        let job: Box<dyn FnOnce() + Send + 'scope> = Box::new(move || {
+            let panicked = catch_unwind(AssertUnwindSafe(f)).is_err();
+            let mut state = data.lock();
+            state.panicked |= panicked;
+            state.pending -= 1;
+            if state.pending == 0 {
+                data.done.notify_all();
+            }
+        });
+        // SAFETY: `ThreadPool::scope` doesn't return until `pending` is
+        // back to 0, that is until this job has run, so nothing it borrows
+        // for `'scope` is gone while it runs.
+        let job = unsafe { mem::transmute::<Box<dyn FnOnce() + Send + 'scope>, Job>(job) };
+        self.pool.push(job);
+    }
+}
+
+impl fmt::Debug for PoolScope<'_, '_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("PoolScope")
+            .field("pending", &self.data.lock().pending)
+            .finish_non_exhaustive()
+    }
+}
@@ -0,0 +1,108 @@
+use super::ThreadPool;
+use crate::std::num::NonZeroUsize;
+use crate::std::panic::{catch_unwind, AssertUnwindSafe};
+use crate::std::sync::atomic::{AtomicUsize, Ordering};
+use crate::std::sync::{Arc, Barrier};
+use crate::std::thread;
+
+fn pool(threads: usize) -> ThreadPool {
+    ThreadPool::new(NonZeroUsize::new(threads).unwrap()).unwrap()
+}
+
+#[test]
+fn spawn_returns_results_and_panics() {
+    let pool = pool(2);
+    assert_eq!(pool.threads(), 2);
+    let tasks: Vec<_> = (0..10).map(|i| pool.spawn(move || i * i)).collect();
+    let results: Vec<_> = tasks.into_iter().map(|task| task.join().unwrap()).collect();
+    assert_eq!(results, [0, 1, 4, 9, 16, 25, 36, 49, 64, 81]);
+
+    let task = pool.spawn(|| panic!("job panicked"));
+    assert!(task.join().is_err());
+    // The thread that ran it is still there.
+    assert_eq!(pool.spawn(|| 1).join().unwrap(), 1);
+}
+
+#[test]
+fn threads_run_jobs_concurrently() {
+    let pool = pool(3);
+    let barrier = Arc::new(Barrier::new(3));
+    let tasks: Vec<_> = (0..3)
+        .map(|_| {
+            let barrier = barrier.clone();
+            pool.spawn(move || {
+                barrier.wait();
+                thread::current().name().unwrap().to_string()
+            })
+        })
+        .collect();
+    let mut names: Vec<_> = tasks.into_iter().map(|task| task.join().unwrap()).collect();
+    names.sort();
+    assert_eq!(names, ["pool-0", "pool-1", "pool-2"]);
+}
+
+#[test]
+fn join_waits_for_execute() {
+    let pool = pool(2);
+    let count = Arc::new(AtomicUsize::new(0));
+    for _ in 0..100 {
+        let count = count.clone();
+        pool.execute(move || {
+            count.fetch_add(1, Ordering::Relaxed);
+        });
+    }
+    pool.join();
+    assert_eq!(count.load(Ordering::Relaxed), 100);
+}
+
+#[test]
+fn drop_runs_queued_jobs() {
+    let count = Arc::new(AtomicUsize::new(0));
+    let pool = pool(1);
+    for _ in 0..10 {
+        let count = count.clone();
+        pool.execute(move || {
+            count.fetch_add(1, Ordering::Relaxed);
+        });
+    }
+    drop(pool);
+    assert_eq!(count.load(Ordering::Relaxed), 10);
+}
+
+#[test]
+fn scope_borrows_and_waits() {
+    let pool = pool(4);
+    let mut data = [1, 2, 3, 4, 5, 6, 7, 8];
+    let total = AtomicUsize::new(0);
+    pool.scope(|s| {
+        for chunk in data.chunks_mut(3) {
+            let total = &total;
+            s.submit(move || {
+                for x in chunk.iter_mut() {
+                    *x *= 10;
+                }
+                total.fetch_add(chunk.iter().sum::<usize>(), Ordering::Relaxed);
+            });
+        }
+    });
+    assert_eq!(data, [10, 20, 30, 40, 50, 60, 70, 80]);
+    assert_eq!(total.into_inner(), 360);
+}
+
+#[test]
+fn scope_panics_after_all_jobs_ran() {
+    let pool = pool(2);
+    let count = AtomicUsize::new(0);
+    let result = catch_unwind(AssertUnwindSafe(|| {
+        pool.scope(|s| {
+            s.submit(|| panic!("job panicked"));
+            for _ in 0..10 {
+                s.submit(|| {
+                    count.fetch_add(1, Ordering::Relaxed);
+                });
+            }
+        })
+    }));
+    assert!(result.is_err());
+    assert_eq!(count.load(Ordering::Relaxed), 10);
+}
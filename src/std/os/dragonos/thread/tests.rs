@@ -0,0 +1,122 @@
+use super::{affinity, gettid, is_idle_class, set_affinity, set_idle_class, CpuSet, JoinHandleExt};
+use crate::std::sync::mpsc;
+use crate::std::thread;
+
+#[test]
+fn cpu_set_ops() {
+    let mut set = CpuSet::new();
+    assert!(set.is_empty());
+    set.insert(0);
+    set.insert(3);
+    assert!(set.contains(3));
+    assert!(!set.contains(CpuSet::MAX_CPUS));
+    assert_eq!(set.len(), 2);
+    set.remove(0);
+    assert_eq!(set.iter().collect::<Vec<_>>(), [3]);
+    assert_eq!(set, CpuSet::from_iter([3]));
+    assert_eq!(format!("{set:?}"), "{3}");
+}
+
+#[test]
+fn current_thread_affinity_round_trips() {
+    let original = affinity().unwrap();
+    assert!(!original.is_empty());
+
+    let first = CpuSet::from_iter(original.iter().take(1));
+    set_affinity(&first).unwrap();
+    assert_eq!(affinity().unwrap(), first);
+
+    set_affinity(&original).unwrap();
+    assert_eq!(affinity().unwrap(), original);
+}
+
+#[test]
+fn join_handle_affinity() {
+    let (tx, rx) = mpsc::channel::<()>();
+    let handle = thread::spawn(move || {
+        rx.recv().unwrap();
+        affinity().unwrap()
+    });
+
+    let first = CpuSet::from_iter(handle.affinity().unwrap().iter().take(1));
+    handle.set_affinity(&first).unwrap();
+    assert_eq!(handle.affinity().unwrap(), first);
+
+    tx.send(()).unwrap();
+    assert_eq!(handle.join().unwrap(), first);
+}
+
+#[test]
+fn join_handle_tid() {
+    let (tx, rx) = mpsc::channel();
+    let handle = thread::spawn(move || tx.send(gettid()).unwrap());
+    let tid = handle.as_raw_tid();
+    assert_ne!(tid, gettid());
+    assert_eq!(rx.recv().unwrap(), tid);
+    // Still known once the thread has exited.
+    while !handle.is_finished() {
+        thread::yield_now();
+    }
+    assert_eq!(handle.as_raw_tid(), tid);
+    handle.join().unwrap();
+}
+
+#[test]
+fn idle_class_round_trips() {
+    thread::spawn(|| {
+        assert!(!is_idle_class().unwrap());
+        set_idle_class(true).unwrap();
+        assert!(is_idle_class().unwrap());
+        // Inherited by threads spawned from here.
+        assert!(thread::spawn(|| is_idle_class().unwrap()).join().unwrap());
+        set_idle_class(false).unwrap();
+        assert!(!is_idle_class().unwrap());
+    })
+    .join()
+    .unwrap();
+}
+
+#[test]
+fn join_handle_idle_class() {
+    let (tx, rx) = mpsc::channel::<()>();
+    let handle = thread::spawn(move || {
+        rx.recv().unwrap();
+        is_idle_class().unwrap()
+    });
+    handle.set_idle_class(true).unwrap();
+    assert!(handle.is_idle_class().unwrap());
+    tx.send(()).unwrap();
+    assert!(handle.join().unwrap());
+}
+
+#[test]
+fn gettid_is_reset_in_forked_children() {
+    use crate::std::io;
+    use crate::std::os::unix::process::CommandExt;
+    use crate::std::process::Command;
+
+    // Spawned from a thread other than the main one, so that its cached ID
+    // differs from the child's.
+    thread::spawn(|| {
+        let tid = gettid();
+        assert_eq!(tid, unsafe { dlibc::gettid() as u32 });
+        assert_ne!(tid, crate::std::process::id());
+        let status = unsafe {
+            Command::new("/bin/sh")
+                .args(["-c", "exit 0"])
+                .pre_exec(|| {
+                    if gettid() == crate::std::process::id() {
+                        Ok(())
+                    } else {
+                        Err(io::Error::from(io::ErrorKind::Other))
+                    }
+                })
+                .status()
+                .unwrap()
+        };
+        assert!(status.success());
+        assert_eq!(gettid(), tid);
+    })
+    .join()
+    .unwrap();
+}
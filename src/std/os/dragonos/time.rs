@@ -0,0 +1,344 @@
+//! DragonOS-specific extensions to primitives in the [`std::time`] module.
+//!
+//! [`Instant::now`] and [`SystemTime::now`] read the kernel's clocks to the
+//! nanosecond, which costs a system call each time. The coarse variants
+//! here read clocks that the kernel only advances once per timer tick, a
+//! few milliseconds apart, and are much cheaper. They suit timestamps on
+//! hot paths, like log lines, where that precision is not needed:
+//!
+//! ```no_run
+//! use std::os::dragonos::time::SystemTimeExt;
+//! use std::time::SystemTime;
+//!
+//! let now = SystemTime::now_coarse();
+//! println!("[{:?}] request accepted", now);
+//! ```
+//!
+//! [`TimerFd`] is a timer that an event loop can wait on along with its
+//! other descriptors, instead of dedicating a sleeping thread to it.
+//! [`Clock`] tells which clocks the kernel supports, and how fine their
+//! readings are.
+//!
+//! [`std::time`]: crate::std::time
+
+#[cfg(test)]
+mod tests;
+
+use crate::std::cmp;
+use crate::std::io;
+use crate::std::os::unix::io::{AsFd, AsRawFd, BorrowedFd, FromRawFd, RawFd};
+use crate::std::ptr;
+use crate::std::sealed::Sealed;
+use crate::std::sys::cvt;
+use crate::std::sys::fd::FileDesc;
+use crate::std::sys::time;
+use crate::std::sys_common::{FromInner, IntoInner};
+use crate::std::time::{Duration, Instant, SystemTime};
+use dlibc;
+
+/// DragonOS-specific extensions to [`Instant`].
+pub trait InstantExt: Sealed {
+    /// Returns an instant corresponding to "now", as of the last timer
+    /// tick.
+    ///
+    /// This can be behind [`Instant::now`] by up to a tick, so it can be
+    /// earlier than an instant that function returned just before. Measure
+    /// durations between instants from the same function.
+    fn now_coarse() -> Self;
+}
+
+/// DragonOS-specific extensions to [`SystemTime`].
+pub trait SystemTimeExt: Sealed {
+    /// Returns the system time as of the last timer tick.
+    ///
+    /// This can be behind [`SystemTime::now`] by up to a tick.
+    fn now_coarse() -> Self;
+}
+
+impl Sealed for Instant {}
+
+impl InstantExt for Instant {
+    fn now_coarse() -> Instant {
+        Instant::from_inner(time::Instant::now_coarse())
+    }
+}
+
+impl Sealed for SystemTime {}
+
+impl SystemTimeExt for SystemTime {
+    fn now_coarse() -> SystemTime {
+        SystemTime::from_inner(time::SystemTime::now_coarse())
+    }
+}
+
+/// One of the kernel's clocks, to query with [`resolution`] and
+/// [`is_supported`].
+///
+/// [`resolution`]: Clock::resolution
+/// [`is_supported`]: Clock::is_supported
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum Clock {
+    /// The clock [`Instant::now`] reads, `CLOCK_MONOTONIC`.
+    Monotonic,
+    /// The clock [`InstantExt::now_coarse`] reads, `CLOCK_MONOTONIC_COARSE`.
+    MonotonicCoarse,
+    /// The monotonic clock without frequency adjustments from NTP,
+    /// `CLOCK_MONOTONIC_RAW`.
+    MonotonicRaw,
+    /// The monotonic clock including the time the system was suspended,
+    /// `CLOCK_BOOTTIME`.
+    Boottime,
+    /// The clock [`SystemTime::now`] reads, `CLOCK_REALTIME`.
+    Realtime,
+    /// The clock [`SystemTimeExt::now_coarse`] reads,
+    /// `CLOCK_REALTIME_COARSE`.
+    RealtimeCoarse,
+    /// The CPU time used by the process, `CLOCK_PROCESS_CPUTIME_ID`.
+    ProcessCpuTime,
+    /// The CPU time used by the calling thread, `CLOCK_THREAD_CPUTIME_ID`.
+    ThreadCpuTime,
+}
+
+impl Clock {
+    /// Every clock, to find the supported ones among.
+    ///
+    /// ```no_run
+    /// use std::os::dragonos::time::Clock;
+    ///
+    /// for clock in Clock::ALL.iter().filter(|clock| clock.is_supported()) {
+    ///     println!("{clock:?}: {:?}", clock.resolution()?);
+    /// }
+    /// # Ok::<(), std::io::Error>(())
+    /// ```
+    pub const ALL: &'static [Clock] = &[
+        Clock::Monotonic,
+        Clock::MonotonicCoarse,
+        Clock::MonotonicRaw,
+        Clock::Boottime,
+        Clock::Realtime,
+        Clock::RealtimeCoarse,
+        Clock::ProcessCpuTime,
+        Clock::ThreadCpuTime,
+    ];
+
+    fn id(self) -> dlibc::clockid_t {
+        match self {
+            Clock::Monotonic => dlibc::CLOCK_MONOTONIC,
+            Clock::MonotonicCoarse => dlibc::CLOCK_MONOTONIC_COARSE,
+            Clock::MonotonicRaw => dlibc::CLOCK_MONOTONIC_RAW,
+            Clock::Boottime => dlibc::CLOCK_BOOTTIME,
+            Clock::Realtime => dlibc::CLOCK_REALTIME,
+            Clock::RealtimeCoarse => dlibc::CLOCK_REALTIME_COARSE,
+            Clock::ProcessCpuTime => dlibc::CLOCK_PROCESS_CPUTIME_ID,
+            Clock::ThreadCpuTime => dlibc::CLOCK_THREAD_CPUTIME_ID,
+        }
+    }
+
+    /// Returns the resolution of the clock, from `clock_getres`: the
+    /// smallest step in which its readings advance.
+    ///
+    /// For the coarse clocks this is the timer tick. Measuring something
+    /// that takes less than the resolution of a clock returns either zero
+    /// or the resolution itself.
+    ///
+    /// Fails with [`InvalidInput`] if the kernel doesn't support the clock.
+    ///
+    /// [`InvalidInput`]: io::ErrorKind::InvalidInput
+    pub fn resolution(self) -> io::Result<Duration> {
+        let mut res = timespec(Duration::ZERO);
+        cvt(unsafe { dlibc::clock_getres(self.id(), &mut res) })?;
+        Ok(Duration::new(res.tv_sec as u64, res.tv_nsec as u32))
+    }
+
+    /// Returns whether the kernel supports the clock.
+    pub fn is_supported(self) -> bool {
+        self.resolution().is_ok()
+    }
+}
+
+/// A timer on the monotonic clock whose expirations are read from a file
+/// descriptor.
+///
+/// The descriptor becomes readable when the timer expires, so it can be
+/// registered with a [`Poller`] or an [`EventQueue`] like a socket. Reading
+/// it with [`wait`](TimerFd::wait) returns how many times the timer
+/// expired since the last read, so a periodic timer whose reader fell
+/// behind reports the ticks it missed rather than drifting.
+///
+/// ```no_run
+/// use std::os::dragonos::io::{EventQueue, Events, Interest, Trigger};
+/// use std::os::dragonos::time::TimerFd;
+/// use std::os::fd::AsFd;
+/// use std::time::Duration;
+///
+/// let timer = TimerFd::new()?;
+/// timer.set_periodic(Duration::from_millis(100))?;
+///
+/// let queue = EventQueue::new()?;
+/// queue.add(timer.as_fd(), 0, Interest::READABLE, Trigger::Level)?;
+/// let mut events = Events::with_capacity(16);
+/// loop {
+///     queue.wait(&mut events, None)?;
+///     for event in events.iter() {
+///         if event.token() == 0 {
+///             let ticks = timer.wait()?;
+///             println!("{ticks} tick(s)");
+///         }
+///     }
+/// }
+/// # Ok::<(), std::io::Error>(())
+/// ```
+///
+/// [`Poller`]: crate::std::os::dragonos::io::Poller
+/// [`EventQueue`]: crate::std::os::dragonos::io::EventQueue
+#[derive(Debug)]
+pub struct TimerFd {
+    fd: FileDesc,
+}
+
+impl TimerFd {
+    /// Creates a timer that is not armed.
+    pub fn new() -> io::Result<TimerFd> {
+        let fd = cvt(unsafe { dlibc::timerfd_create(dlibc::CLOCK_MONOTONIC, dlibc::TFD_CLOEXEC) })?;
+        // SAFETY: `timerfd_create` returned a new descriptor.
+        Ok(TimerFd {
+            fd: unsafe { FileDesc::from_raw_fd(fd) },
+        })
+    }
+
+    fn set(
+        &self,
+        flags: dlibc::c_int,
+        value: dlibc::timespec,
+        interval: Duration,
+    ) -> io::Result<()> {
+        let spec = dlibc::itimerspec {
+            it_interval: timespec(interval),
+            it_value: value,
+        };
+        cvt(unsafe { dlibc::timerfd_settime(self.fd.as_raw_fd(), flags, &spec, ptr::null_mut()) })?;
+        Ok(())
+    }
+
+    /// Arms the timer to expire once, after `delay`, replacing any earlier
+    /// setting. A zero `delay` expires right away.
+    pub fn set_oneshot(&self, delay: Duration) -> io::Result<()> {
+        // A zero value would disarm the timer instead.
+        self.set(
+            0,
+            timespec(cmp::max(delay, Duration::from_nanos(1))),
+            Duration::ZERO,
+        )
+    }
+
+    /// Arms the timer to expire every `interval`, first after `interval`,
+    /// replacing any earlier setting.
+    ///
+    /// Fails with [`InvalidInput`] if `interval` is zero.
+    ///
+    /// [`InvalidInput`]: io::ErrorKind::InvalidInput
+    pub fn set_periodic(&self, interval: Duration) -> io::Result<()> {
+        if interval.is_zero() {
+            return Err(io::const_io_error!(
+                io::ErrorKind::InvalidInput,
+                "a periodic timer needs a non-zero interval",
+            ));
+        }
+        self.set(0, timespec(interval), interval)
+    }
+
+    /// Arms the timer to expire at `deadline`, and then every `interval`
+    /// if there is one, replacing any earlier setting.
+    ///
+    /// Unlike [`set_oneshot`](TimerFd::set_oneshot), the expiration
+    /// doesn't move later by the time it took to compute the delay. A
+    /// deadline that has passed expires right away.
+    pub fn set_deadline(&self, deadline: Instant, interval: Option<Duration>) -> io::Result<()> {
+        let interval = interval.unwrap_or(Duration::ZERO);
+        match deadline.into_inner().to_timespec() {
+            // A zero value would disarm the timer, and the monotonic clock
+            // is past it anyway.
+            Some(ts) if ts.tv_sec != 0 || ts.tv_nsec != 0 => {
+                self.set(dlibc::TFD_TIMER_ABSTIME, ts, interval)
+            }
+            Some(_) => self.set(0, timespec(Duration::from_nanos(1)), interval),
+            None => Err(io::const_io_error!(
+                io::ErrorKind::InvalidInput,
+                "deadline is too far in the future",
+            )),
+        }
+    }
+
+    /// Disarms the timer. Expirations that weren't read yet are discarded,
+    /// as with every other change to the setting.
+    pub fn disarm(&self) -> io::Result<()> {
+        self.set(0, timespec(Duration::ZERO), Duration::ZERO)
+    }
+
+    /// Returns the time until the timer next expires, or `None` if it is not
+    /// armed.
+    pub fn remaining(&self) -> io::Result<Option<Duration>> {
+        let mut spec = dlibc::itimerspec {
+            it_interval: timespec(Duration::ZERO),
+            it_value: timespec(Duration::ZERO),
+        };
+        cvt(unsafe { dlibc::timerfd_gettime(self.fd.as_raw_fd(), &mut spec) })?;
+        let value = spec.it_value;
+        if value.tv_sec == 0 && value.tv_nsec == 0 {
+            return Ok(None);
+        }
+        Ok(Some(Duration::new(
+            value.tv_sec as u64,
+            value.tv_nsec as u32,
+        )))
+    }
+
+    /// Waits for the timer to expire, and returns how many times it
+    /// expired since the last wait.
+    ///
+    /// Returns at once if it has expired already. In
+    /// [non-blocking](TimerFd::set_nonblocking) mode, fails with
+    /// [`WouldBlock`] instead of waiting. Waits forever if the timer is not
+    /// armed.
+    ///
+    /// [`WouldBlock`]: io::ErrorKind::WouldBlock
+    pub fn wait(&self) -> io::Result<u64> {
+        let mut buf = [0; 8];
+        loop {
+            match self.fd.read(&mut buf) {
+                Ok(_) => return Ok(u64::from_ne_bytes(buf)),
+                Err(e) if e.is_interrupted() => {}
+                Err(e) => return Err(e),
+            }
+        }
+    }
+
+    /// Moves the timer into or out of non-blocking mode, in which
+    /// [`wait`](TimerFd::wait) fails with [`WouldBlock`] if the timer
+    /// hasn't expired.
+    ///
+    /// [`WouldBlock`]: io::ErrorKind::WouldBlock
+    pub fn set_nonblocking(&self, nonblocking: bool) -> io::Result<()> {
+        self.fd.set_nonblocking(nonblocking)
+    }
+}
+
+impl AsFd for TimerFd {
+    fn as_fd(&self) -> BorrowedFd<'_> {
+        self.fd.as_fd()
+    }
+}
+
+impl AsRawFd for TimerFd {
+    fn as_raw_fd(&self) -> RawFd {
+        self.fd.as_raw_fd()
+    }
+}
+
+fn timespec(duration: Duration) -> dlibc::timespec {
+    dlibc::timespec {
+        tv_sec: cmp::min(duration.as_secs(), dlibc::time_t::MAX as u64) as dlibc::time_t,
+        tv_nsec: duration.subsec_nanos() as _,
+    }
+}
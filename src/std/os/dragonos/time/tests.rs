@@ -0,0 +1,115 @@
+use super::{Clock, InstantExt, SystemTimeExt, TimerFd};
+use crate::std::io;
+use crate::std::os::dragonos::io::{poll, PollFd};
+use crate::std::os::unix::io::AsFd;
+use crate::std::thread;
+use crate::std::time::{Duration, Instant, SystemTime};
+
+#[test]
+fn coarse_instants_are_close() {
+    let mut last = Instant::now_coarse();
+    for _ in 0..1000 {
+        let now = Instant::now_coarse();
+        assert!(now >= last);
+        last = now;
+    }
+    // The coarse clock lags behind by at most a tick.
+    let precise = Instant::now();
+    let coarse = Instant::now_coarse();
+    let lag = precise.saturating_duration_since(coarse);
+    assert!(lag < Duration::from_secs(1), "{lag:?}");
+}
+
+#[test]
+fn coarse_system_time_is_close() {
+    let precise = SystemTime::now();
+    let coarse = SystemTime::now_coarse();
+    let skew = match coarse.duration_since(precise) {
+        Ok(ahead) => ahead,
+        Err(behind) => behind.duration(),
+    };
+    assert!(skew < Duration::from_secs(1), "{skew:?}");
+}
+
+#[test]
+fn timer_fd_oneshot() {
+    let timer = TimerFd::new().unwrap();
+    assert_eq!(timer.remaining().unwrap(), None);
+    timer.set_nonblocking(true).unwrap();
+    assert_eq!(timer.wait().unwrap_err().kind(), io::ErrorKind::WouldBlock);
+
+    let start = Instant::now();
+    timer.set_oneshot(Duration::from_millis(20)).unwrap();
+    assert!(timer.remaining().unwrap().unwrap() <= Duration::from_millis(20));
+
+    let mut fds = [PollFd::readable(timer.as_fd())];
+    assert_eq!(poll(&mut fds, Some(Duration::from_secs(5))).unwrap(), 1);
+    assert!(start.elapsed() >= Duration::from_millis(20));
+    assert_eq!(timer.wait().unwrap(), 1);
+    assert_eq!(timer.remaining().unwrap(), None);
+    assert_eq!(timer.wait().unwrap_err().kind(), io::ErrorKind::WouldBlock);
+}
+
+#[test]
+fn timer_fd_periodic() {
+    let timer = TimerFd::new().unwrap();
+    assert_eq!(
+        timer.set_periodic(Duration::ZERO).unwrap_err().kind(),
+        io::ErrorKind::InvalidInput
+    );
+
+    // Missed ticks add up rather than getting lost.
+    timer.set_periodic(Duration::from_millis(10)).unwrap();
+    thread::sleep(Duration::from_millis(55));
+    assert!(timer.wait().unwrap() >= 5);
+    assert!(timer.wait().unwrap() >= 1);
+
+    timer.disarm().unwrap();
+    assert_eq!(timer.remaining().unwrap(), None);
+}
+
+#[test]
+fn timer_fd_disarm_discards_expirations() {
+    let timer = TimerFd::new().unwrap();
+    timer.set_nonblocking(true).unwrap();
+    timer.set_oneshot(Duration::ZERO).unwrap();
+    let mut fds = [PollFd::readable(timer.as_fd())];
+    assert_eq!(poll(&mut fds, Some(Duration::from_secs(5))).unwrap(), 1);
+
+    timer.disarm().unwrap();
+    assert_eq!(timer.wait().unwrap_err().kind(), io::ErrorKind::WouldBlock);
+}
+
+#[test]
+fn timer_fd_deadline() {
+    let timer = TimerFd::new().unwrap();
+    let deadline = Instant::now() + Duration::from_millis(20);
+    timer.set_deadline(deadline, None).unwrap();
+    assert_eq!(timer.wait().unwrap(), 1);
+    assert!(Instant::now() >= deadline);
+
+    // A deadline in the past expires right away.
+    timer.set_deadline(deadline, None).unwrap();
+    assert_eq!(timer.wait().unwrap(), 1);
+}
+
+#[test]
+fn clock_resolutions() {
+    for clock in [Clock::Monotonic, Clock::Realtime] {
+        assert!(clock.is_supported());
+        let res = clock.resolution().unwrap();
+        assert!(
+            !res.is_zero() && res <= Duration::from_secs(1),
+            "{clock:?}: {res:?}"
+        );
+    }
+    for clock in Clock::ALL {
+        assert_eq!(clock.is_supported(), clock.resolution().is_ok());
+    }
+    // The coarse clocks trade resolution for speed.
+    if Clock::MonotonicCoarse.is_supported() {
+        assert!(
+            Clock::MonotonicCoarse.resolution().unwrap() >= Clock::Monotonic.resolution().unwrap()
+        );
+    }
+}
@@ -0,0 +1,118 @@
+//! Windows Sockets error codes for code ported from Windows.
+//!
+//! Each `WSAE*` constant here has the value of the DragonOS errno that is
+//! reported in the same situation, so code that compares
+//! [`io::Error::raw_os_error`] against Windows Sockets error names keeps
+//! working without a `cfg` block at every comparison. [`errno_from_wsa`] and
+//! [`wsa_from_errno`] translate the numeric codes for code that stores or
+//! transmits them.
+//!
+//! This module is only available with the `wsa-compat` feature.
+//!
+//! ```no_run
+//! use std::net::TcpStream;
+//! use std::os::dragonos::wsa::WSAECONNREFUSED;
+//!
+//! match TcpStream::connect("127.0.0.1:9") {
+//!     Err(e) if e.raw_os_error() == Some(WSAECONNREFUSED) => println!("nobody listening"),
+//!     other => drop(other),
+//! }
+//! ```
+//!
+//! [`io::Error::raw_os_error`]: crate::std::io::Error::raw_os_error
+
+#[cfg(test)]
+mod tests;
+
+use crate::std::sys;
+use dlibc;
+
+macro_rules! wsa_errors {
+    ($($name:ident = $wsa:literal => $errno:ident,)*) => {
+        $(
+            #[doc = concat!(
+                "The errno reported where Windows reports `", stringify!($name),
+                "` (", stringify!($wsa), "), i.e. `", stringify!($errno), "`."
+            )]
+            pub const $name: i32 = dlibc::$errno;
+        )*
+
+        /// Translates a numeric Windows Sockets error code into the DragonOS
+        /// errno reported in the same situation.
+        ///
+        /// Returns `None` for codes with no DragonOS equivalent.
+        pub fn errno_from_wsa(code: i32) -> Option<i32> {
+            match code {
+                $($wsa => Some(dlibc::$errno),)*
+                _ => None,
+            }
+        }
+
+        /// Translates a DragonOS errno into the numeric Windows Sockets error
+        /// code reported in the same situation.
+        ///
+        /// Returns `None` for errnos with no Windows Sockets equivalent.
+        pub fn wsa_from_errno(errno: i32) -> Option<i32> {
+            $(
+                if errno == dlibc::$errno {
+                    return Some($wsa);
+                }
+            )*
+            None
+        }
+    };
+}
+
+wsa_errors! {
+    WSAEINTR = 10004 => EINTR,
+    WSAEBADF = 10009 => EBADF,
+    WSAEACCES = 10013 => EACCES,
+    WSAEFAULT = 10014 => EFAULT,
+    WSAEINVAL = 10022 => EINVAL,
+    WSAEMFILE = 10024 => EMFILE,
+    WSAEWOULDBLOCK = 10035 => EWOULDBLOCK,
+    WSAEINPROGRESS = 10036 => EINPROGRESS,
+    WSAEALREADY = 10037 => EALREADY,
+    WSAENOTSOCK = 10038 => ENOTSOCK,
+    WSAEDESTADDRREQ = 10039 => EDESTADDRREQ,
+    WSAEMSGSIZE = 10040 => EMSGSIZE,
+    WSAEPROTOTYPE = 10041 => EPROTOTYPE,
+    WSAENOPROTOOPT = 10042 => ENOPROTOOPT,
+    WSAEPROTONOSUPPORT = 10043 => EPROTONOSUPPORT,
+    WSAESOCKTNOSUPPORT = 10044 => ESOCKTNOSUPPORT,
+    WSAEOPNOTSUPP = 10045 => EOPNOTSUPP,
+    WSAEPFNOSUPPORT = 10046 => EPFNOSUPPORT,
+    WSAEAFNOSUPPORT = 10047 => EAFNOSUPPORT,
+    WSAEADDRINUSE = 10048 => EADDRINUSE,
+    WSAEADDRNOTAVAIL = 10049 => EADDRNOTAVAIL,
+    WSAENETDOWN = 10050 => ENETDOWN,
+    WSAENETUNREACH = 10051 => ENETUNREACH,
+    WSAENETRESET = 10052 => ENETRESET,
+    WSAECONNABORTED = 10053 => ECONNABORTED,
+    WSAECONNRESET = 10054 => ECONNRESET,
+    WSAENOBUFS = 10055 => ENOBUFS,
+    WSAEISCONN = 10056 => EISCONN,
+    WSAENOTCONN = 10057 => ENOTCONN,
+    WSAESHUTDOWN = 10058 => ESHUTDOWN,
+    WSAETOOMANYREFS = 10059 => ETOOMANYREFS,
+    WSAETIMEDOUT = 10060 => ETIMEDOUT,
+    WSAECONNREFUSED = 10061 => ECONNREFUSED,
+    WSAELOOP = 10062 => ELOOP,
+    WSAENAMETOOLONG = 10063 => ENAMETOOLONG,
+    WSAEHOSTDOWN = 10064 => EHOSTDOWN,
+    WSAEHOSTUNREACH = 10065 => EHOSTUNREACH,
+    WSAENOTEMPTY = 10066 => ENOTEMPTY,
+    WSAEUSERS = 10068 => EUSERS,
+    WSAEDQUOT = 10069 => EDQUOT,
+    WSAESTALE = 10070 => ESTALE,
+    WSAEREMOTE = 10071 => EREMOTE,
+}
+
+/// Returns the error of the last failed socket call on this thread, like
+/// `WSAGetLastError`.
+///
+/// This is the thread's errno, so it can be compared against the constants
+/// in this module directly.
+pub fn last_error() -> i32 {
+    sys::os::errno()
+}
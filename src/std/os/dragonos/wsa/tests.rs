@@ -0,0 +1,13 @@
+use super::*;
+
+#[test]
+fn wsa_codes_round_trip() {
+    for code in 10004..=10071 {
+        if let Some(errno) = errno_from_wsa(code) {
+            assert_eq!(wsa_from_errno(errno), Some(code));
+        }
+    }
+    assert_eq!(errno_from_wsa(WSAECONNREFUSED), None);
+    assert_eq!(errno_from_wsa(10061), Some(WSAECONNREFUSED));
+    assert_eq!(wsa_from_errno(dlibc::ENOENT), None);
+}
@@ -20,3 +20,7 @@ mod tests;
 // Export the types and traits for the public API.
 pub use owned::*;
 pub use raw::*;
+
+// Internal helper for descriptor-backed newtypes such as `PidFd`.
+#[allow(unused_imports)]
+pub(crate) use owned::impl_fd_newtype;
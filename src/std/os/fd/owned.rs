@@ -6,8 +6,12 @@ use super::raw::{AsRawFd, FromRawFd, IntoRawFd, RawFd};
 use crate::std::fmt;
 use crate::std::fs;
 use crate::std::io;
+#[cfg(target_os = "dragonos")]
+use crate::std::io::{Read, Seek, Write};
 use crate::std::marker::PhantomData;
 use crate::std::mem::forget;
+#[cfg(target_os = "dragonos")]
+use crate::std::mem::ManuallyDrop;
 #[cfg(not(any(target_arch = "wasm32", target_env = "sgx", target_os = "hermit")))]
 use crate::std::sys::cvt;
 use crate::std::sys_common::{AsInner, FromInner, IntoInner};
@@ -84,6 +88,21 @@ impl OwnedFd {
     pub fn try_clone(&self) -> crate::std::io::Result<Self> {
         self.as_fd().try_clone_to_owned()
     }
+
+    /// Creates a new `OwnedFd` instance that shares the same underlying file
+    /// description as the existing `OwnedFd` instance, with control over the
+    /// new descriptor's number and close-on-exec flag.
+    ///
+    /// See [`DuplicateOptions`] for the available options.
+    ///
+    /// [`DuplicateOptions`]: crate::std::os::dragonos::io::DuplicateOptions
+    #[cfg(target_os = "dragonos")]
+    pub fn duplicate_with(
+        &self,
+        options: &crate::std::os::dragonos::io::DuplicateOptions,
+    ) -> crate::std::io::Result<Self> {
+        self.as_fd().duplicate_with(options)
+    }
 }
 
 impl BorrowedFd<'_> {
@@ -109,6 +128,55 @@ impl BorrowedFd<'_> {
         Ok(unsafe { OwnedFd::from_raw_fd(fd) })
     }
 
+    /// Creates a new `OwnedFd` instance that shares the same underlying file
+    /// description as the existing `BorrowedFd` instance, with control over
+    /// the new descriptor's number and close-on-exec flag.
+    ///
+    /// See [`DuplicateOptions`] for the available options.
+    ///
+    /// [`DuplicateOptions`]: crate::std::os::dragonos::io::DuplicateOptions
+    #[cfg(target_os = "dragonos")]
+    pub fn duplicate_with(
+        &self,
+        options: &crate::std::os::dragonos::io::DuplicateOptions,
+    ) -> crate::std::io::Result<OwnedFd> {
+        self.duplicate(options.target, options.min_fd, options.cloexec)
+    }
+
+    #[cfg(target_os = "dragonos")]
+    pub(crate) fn duplicate(
+        &self,
+        target: Option<RawFd>,
+        min_fd: RawFd,
+        cloexec: bool,
+    ) -> io::Result<OwnedFd> {
+        let fd = self.as_raw_fd();
+        let Some(target) = target else {
+            let cmd = if cloexec {
+                dlibc::F_DUPFD_CLOEXEC
+            } else {
+                dlibc::F_DUPFD
+            };
+            let new = cvt(unsafe { dlibc::fcntl(fd, cmd, min_fd) })?;
+            return Ok(unsafe { OwnedFd::from_raw_fd(new) });
+        };
+
+        // `dup3` rejects duplicating a descriptor onto itself with a bare
+        // `EINVAL`; report it with a message that says why.
+        if target == fd {
+            return Err(io::const_io_error!(
+                io::ErrorKind::InvalidInput,
+                "cannot duplicate a file descriptor onto itself",
+            ));
+        }
+
+        // `dup3` sets close-on-exec atomically with the duplication, so the
+        // new descriptor can never leak into a concurrently spawned child.
+        let flags = if cloexec { dlibc::O_CLOEXEC } else { 0 };
+        let new = cvt(unsafe { dlibc::dup3(fd, target, flags) })?;
+        Ok(unsafe { OwnedFd::from_raw_fd(new) })
+    }
+
     /// Creates a new `OwnedFd` instance that shares the same underlying file
     /// description as the existing `BorrowedFd` instance.
     #[cfg(any(target_arch = "wasm32", target_os = "hermit"))]
@@ -187,11 +255,11 @@ impl fmt::Debug for OwnedFd {
     }
 }
 
+// Only implements the trait; the types are sealed separately, since some of
+// them are sealed for other extension traits as well.
 macro_rules! impl_is_terminal {
     ($($t:ty),*$(,)?) => {$(
-                impl crate::std::sealed::Sealed for $t {}
-
-                impl crate::std::io::IsTerminal for $t {
+        impl crate::std::io::IsTerminal for $t {
             #[inline]
             fn is_terminal(&self) -> bool {
                 crate::std::sys::io::is_terminal(self)
@@ -200,8 +268,120 @@ macro_rules! impl_is_terminal {
     )*}
 }
 
+impl crate::std::sealed::Sealed for BorrowedFd<'_> {}
+impl crate::std::sealed::Sealed for OwnedFd {}
+
 impl_is_terminal!(BorrowedFd<'_>, OwnedFd);
 
+// On DragonOS every type that owns a descriptor can be asked, not just stdio.
+// `TcpStream`, `TcpListener` and `UdpSocket` are sealed by `os::net` and
+// `os::dragonos::net`.
+#[cfg(target_os = "dragonos")]
+mod dragonos_sealed {
+    use crate::std::io::{PipeReader, PipeWriter};
+    use crate::std::os::unix::net::{UnixDatagram, UnixListener, UnixStream};
+    use crate::std::process::{ChildStderr, ChildStdin, ChildStdout};
+    use crate::std::sealed::Sealed;
+
+    impl Sealed for ChildStdin {}
+    impl Sealed for ChildStdout {}
+    impl Sealed for ChildStderr {}
+    impl Sealed for PipeReader {}
+    impl Sealed for PipeWriter {}
+    impl Sealed for UnixStream {}
+    impl Sealed for UnixListener {}
+    impl Sealed for UnixDatagram {}
+}
+
+#[cfg(target_os = "dragonos")]
+impl_is_terminal!(
+    crate::std::process::ChildStdin,
+    crate::std::process::ChildStdout,
+    crate::std::process::ChildStderr,
+    crate::std::io::PipeReader,
+    crate::std::io::PipeWriter,
+    crate::std::net::TcpStream,
+    crate::std::net::TcpListener,
+    crate::std::net::UdpSocket,
+    crate::std::os::unix::net::UnixStream,
+    crate::std::os::unix::net::UnixListener,
+    crate::std::os::unix::net::UnixDatagram,
+);
+
+/// Reads, writes and seeks on the descriptor directly, as `File` would,
+/// without taking it over: dropping a `BorrowedFd` never closes it, and an
+/// `OwnedFd` is closed only once, when it is dropped itself.
+#[cfg(target_os = "dragonos")]
+macro_rules! impl_io_for_fd {
+    ($($t:ty),*) => {$(
+        impl Read for $t {
+            #[inline]
+            fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+                (&*as_file(self.as_raw_fd())).read(buf)
+            }
+
+            #[inline]
+            fn read_vectored(&mut self, bufs: &mut [io::IoSliceMut<'_>]) -> io::Result<usize> {
+                (&*as_file(self.as_raw_fd())).read_vectored(bufs)
+            }
+
+            #[inline]
+            fn read_buf(&mut self, cursor: io::BorrowedCursor<'_>) -> io::Result<()> {
+                (&*as_file(self.as_raw_fd())).read_buf(cursor)
+            }
+
+            #[inline]
+            fn is_read_vectored(&self) -> bool {
+                as_file(self.as_raw_fd()).is_read_vectored()
+            }
+
+            fn read_to_end(&mut self, buf: &mut Vec<u8>) -> io::Result<usize> {
+                (&*as_file(self.as_raw_fd())).read_to_end(buf)
+            }
+        }
+
+        impl Write for $t {
+            #[inline]
+            fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+                (&*as_file(self.as_raw_fd())).write(buf)
+            }
+
+            #[inline]
+            fn write_vectored(&mut self, bufs: &[io::IoSlice<'_>]) -> io::Result<usize> {
+                (&*as_file(self.as_raw_fd())).write_vectored(bufs)
+            }
+
+            #[inline]
+            fn is_write_vectored(&self) -> bool {
+                as_file(self.as_raw_fd()).is_write_vectored()
+            }
+
+            #[inline]
+            fn flush(&mut self) -> io::Result<()> {
+                Ok(())
+            }
+        }
+
+        impl Seek for $t {
+            #[inline]
+            fn seek(&mut self, pos: io::SeekFrom) -> io::Result<u64> {
+                (&*as_file(self.as_raw_fd())).seek(pos)
+            }
+        }
+    )*};
+}
+
+// A `File` on `fd` that is never dropped, so that `fd` stays open.
+#[cfg(target_os = "dragonos")]
+fn as_file(fd: RawFd) -> ManuallyDrop<fs::File> {
+    // SAFETY: the callers own or borrow `fd`, so it stays open while the
+    // `File` is used, and the `File` never closes it.
+    ManuallyDrop::new(unsafe { fs::File::from_raw_fd(fd) })
+}
+
+#[cfg(target_os = "dragonos")]
+impl_io_for_fd!(BorrowedFd<'_>, OwnedFd);
+
 /// A trait to borrow the file descriptor from an underlying object.
 ///
 /// This is only available on unix platforms and must be imported in order to
@@ -278,89 +458,79 @@ impl From<OwnedFd> for fs::File {
     }
 }
 
-impl AsFd for crate::std::net::TcpStream {
-    #[inline]
-    fn as_fd(&self) -> BorrowedFd<'_> {
-        self.as_inner().socket().as_fd()
-    }
+impl_owned_conversions! {
+    fd from: crate::std::net::TcpStream, crate::std::net::TcpListener, crate::std::net::UdpSocket
 }
 
-impl From<crate::std::net::TcpStream> for OwnedFd {
-    #[inline]
-    fn from(tcp_stream: crate::std::net::TcpStream) -> OwnedFd {
-        tcp_stream
-            .into_inner()
-            .into_socket()
-            .into_inner()
-            .into_inner()
-            .into()
-    }
-}
-
-impl From<OwnedFd> for crate::std::net::TcpStream {
-    #[inline]
-    fn from(owned_fd: OwnedFd) -> Self {
-        Self::from_inner(FromInner::from_inner(FromInner::from_inner(
-            FromInner::from_inner(owned_fd),
-        )))
-    }
-}
+/// Implements the whole descriptor conversion matrix for a newtype around
+/// `sys::fd::FileDesc`: `AsRawFd`, `FromRawFd` and `IntoRawFd`, and on top
+/// of those `AsFd`, `From<OwnedFd>` and `From<$t> for OwnedFd`.
+///
+/// The type only has to implement `AsInner`, `FromInner` and `IntoInner` for
+/// `FileDesc`, so descriptor-backed types such as `PidFd` stay consistent with
+/// each other without repeating the impls by hand.
+macro_rules! impl_fd_newtype {
+    ($($t:ty),* $(,)?) => {$(
+        impl $crate::std::os::fd::AsRawFd for $t {
+            #[inline]
+            fn as_raw_fd(&self) -> $crate::std::os::fd::RawFd {
+                $crate::std::os::fd::AsRawFd::as_raw_fd(
+                    $crate::std::sys_common::AsInner::as_inner(self),
+                )
+            }
+        }
 
-impl AsFd for crate::std::net::TcpListener {
-    #[inline]
-    fn as_fd(&self) -> BorrowedFd<'_> {
-        self.as_inner().socket().as_fd()
-    }
-}
+        impl $crate::std::os::fd::FromRawFd for $t {
+            #[inline]
+            unsafe fn from_raw_fd(fd: $crate::std::os::fd::RawFd) -> Self {
+                // SAFETY: the caller passes ownership of `fd`.
+                $crate::std::sys_common::FromInner::from_inner(unsafe {
+                    $crate::std::os::fd::FromRawFd::from_raw_fd(fd)
+                })
+            }
+        }
 
-impl From<crate::std::net::TcpListener> for OwnedFd {
-    #[inline]
-    fn from(tcp_listener: crate::std::net::TcpListener) -> OwnedFd {
-        tcp_listener
-            .into_inner()
-            .into_socket()
-            .into_inner()
-            .into_inner()
-            .into()
-    }
-}
+        impl $crate::std::os::fd::IntoRawFd for $t {
+            #[inline]
+            fn into_raw_fd(self) -> $crate::std::os::fd::RawFd {
+                $crate::std::os::fd::IntoRawFd::into_raw_fd(
+                    $crate::std::sys_common::IntoInner::into_inner(self),
+                )
+            }
+        }
 
-impl From<OwnedFd> for crate::std::net::TcpListener {
-    #[inline]
-    fn from(owned_fd: OwnedFd) -> Self {
-        Self::from_inner(FromInner::from_inner(FromInner::from_inner(
-            FromInner::from_inner(owned_fd),
-        )))
-    }
+        impl_owned_conversions! { fd from: $t }
+    )*};
 }
+#[allow(unused_imports)]
+pub(crate) use impl_fd_newtype;
 
-impl AsFd for crate::std::net::UdpSocket {
-    #[inline]
-    fn as_fd(&self) -> BorrowedFd<'_> {
-        self.as_inner().socket().as_fd()
-    }
-}
+/// Gives the `std::io` pipe ends the `FileDesc` accessors that
+/// `impl_fd_newtype!` builds the descriptor conversions on.
+macro_rules! impl_inner_for_pipe {
+    ($($t:ident)*) => {$(
+        impl AsInner<crate::std::sys::fd::FileDesc> for crate::std::io::$t {
+            #[inline]
+            fn as_inner(&self) -> &crate::std::sys::fd::FileDesc {
+                self.0.as_inner()
+            }
+        }
 
-impl From<crate::std::net::UdpSocket> for OwnedFd {
-    #[inline]
-    fn from(udp_socket: crate::std::net::UdpSocket) -> OwnedFd {
-        udp_socket
-            .into_inner()
-            .into_socket()
-            .into_inner()
-            .into_inner()
-            .into()
-    }
-}
+        impl FromInner<crate::std::sys::fd::FileDesc> for crate::std::io::$t {
+            fn from_inner(fd: crate::std::sys::fd::FileDesc) -> Self {
+                Self(FromInner::from_inner(fd))
+            }
+        }
 
-impl From<OwnedFd> for crate::std::net::UdpSocket {
-    #[inline]
-    fn from(owned_fd: OwnedFd) -> Self {
-        Self::from_inner(FromInner::from_inner(FromInner::from_inner(
-            FromInner::from_inner(owned_fd),
-        )))
-    }
+        impl IntoInner<crate::std::sys::fd::FileDesc> for crate::std::io::$t {
+            fn into_inner(self) -> crate::std::sys::fd::FileDesc {
+                self.0.into_inner()
+            }
+        }
+    )*};
 }
+impl_inner_for_pipe! { PipeReader PipeWriter }
+impl_fd_newtype!(crate::std::io::PipeReader, crate::std::io::PipeWriter);
 
 /// This impl allows implementing traits that require `AsFd` on Arc.
 /// ```
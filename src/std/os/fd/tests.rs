@@ -68,3 +68,105 @@ fn test_niche_optimizations() {
         );
     }
 }
+
+#[cfg(target_os = "dragonos")]
+#[test]
+fn test_is_terminal_for_fd_holders() {
+    use crate::std::io::IsTerminal;
+    use crate::std::os::unix::io::{AsFd, OwnedFd};
+    use crate::std::process::{Command, Stdio};
+
+    let mut child = Command::new("cat")
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .unwrap();
+    let stdin = child.stdin.take().unwrap();
+    let stdout = child.stdout.take().unwrap();
+    let stderr = child.stderr.take().unwrap();
+
+    assert!(!stdin.is_terminal());
+    assert!(!stdout.is_terminal());
+    assert!(!stderr.is_terminal());
+    assert!(!stdout.as_fd().is_terminal());
+
+    let owned = OwnedFd::from(stdin);
+    assert!(!owned.is_terminal());
+    drop(owned);
+    child.wait().unwrap();
+}
+
+#[cfg(target_os = "dragonos")]
+#[test]
+fn test_is_terminal_for_sockets() {
+    use crate::std::io::IsTerminal;
+    use crate::std::net::{TcpListener, TcpStream, UdpSocket};
+    use crate::std::os::unix::net::{UnixDatagram, UnixStream};
+
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let stream = TcpStream::connect(listener.local_addr().unwrap()).unwrap();
+    let socket = UdpSocket::bind("127.0.0.1:0").unwrap();
+    assert!(!listener.is_terminal());
+    assert!(!stream.is_terminal());
+    assert!(!socket.is_terminal());
+
+    let (a, _b) = UnixStream::pair().unwrap();
+    let datagram = UnixDatagram::unbound().unwrap();
+    assert!(!a.is_terminal());
+    assert!(!datagram.is_terminal());
+}
+
+#[cfg(target_os = "dragonos")]
+#[test]
+fn test_is_terminal_for_pty_and_pipe() {
+    use crate::std::fs::OpenOptions;
+    use crate::std::io::{self, IsTerminal};
+
+    let (reader, writer) = io::pipe().unwrap();
+    assert!(!reader.is_terminal());
+    assert!(!writer.is_terminal());
+
+    let dir = crate::std::sys_common::io::test::tmpdir();
+    let file = crate::std::fs::File::create(dir.join("file")).unwrap();
+    assert!(!file.is_terminal());
+
+    // The master side of a pseudo-terminal answers `TCGETS` like the slave.
+    match OpenOptions::new().read(true).write(true).open("/dev/ptmx") {
+        Ok(master) => assert!(master.is_terminal()),
+        Err(e) => assert_eq!(e.kind(), io::ErrorKind::NotFound),
+    }
+}
+
+#[cfg(target_os = "dragonos")]
+#[test]
+fn test_io_on_owned_and_borrowed_fd() {
+    use crate::std::fs::OpenOptions;
+    use crate::std::io::{Read, Seek, SeekFrom, Write};
+    use crate::std::os::unix::io::{AsFd, AsRawFd, OwnedFd};
+
+    let dir = crate::std::sys_common::io::test::tmpdir();
+    let file = OpenOptions::new()
+        .read(true)
+        .write(true)
+        .create(true)
+        .open(dir.join("file"))
+        .unwrap();
+    let mut owned = OwnedFd::from(file);
+    owned.write_all(b"hello world").unwrap();
+    assert_eq!(owned.seek(SeekFrom::Start(6)).unwrap(), 6);
+
+    let mut borrowed = owned.as_fd();
+    let mut buf = String::new();
+    borrowed.read_to_string(&mut buf).unwrap();
+    assert_eq!(buf, "world");
+    assert_eq!(borrowed.stream_position().unwrap(), 11);
+
+    // Neither read nor the borrowed descriptor closed it.
+    let flags = unsafe { dlibc::fcntl(owned.as_raw_fd(), dlibc::F_GETFD) };
+    assert!(flags >= 0);
+    borrowed.rewind().unwrap();
+    let mut buf = [0; 5];
+    owned.read_exact(&mut buf).unwrap();
+    assert_eq!(&buf, b"hello");
+}
@@ -314,30 +314,7 @@ impl MetadataExt for Metadata {
     fn as_raw_stat(&self) -> &raw::stat {
         unsafe { &*(self.as_inner().as_inner() as *const dlibc::stat as *const raw::stat) }
     }
-    fn st_dev(&self) -> u64 {
-        self.as_inner().as_inner().st_dev as u64
-    }
-    fn st_ino(&self) -> u64 {
-        self.as_inner().as_inner().st_ino as u64
-    }
-    fn st_mode(&self) -> u32 {
-        self.as_inner().as_inner().st_mode as u32
-    }
-    fn st_nlink(&self) -> u64 {
-        self.as_inner().as_inner().st_nlink as u64
-    }
-    fn st_uid(&self) -> u32 {
-        self.as_inner().as_inner().st_uid as u32
-    }
-    fn st_gid(&self) -> u32 {
-        self.as_inner().as_inner().st_gid as u32
-    }
-    fn st_rdev(&self) -> u64 {
-        self.as_inner().as_inner().st_rdev as u64
-    }
-    fn st_size(&self) -> u64 {
-        self.as_inner().as_inner().st_size as u64
-    }
+    unix_stat_common!();
     fn st_atime(&self) -> i64 {
         let file_attr = self.as_inner();
         #[cfg(all(target_env = "gnu", target_pointer_width = "32"))]
@@ -346,9 +323,6 @@ impl MetadataExt for Metadata {
         }
         file_attr.as_inner().st_atime as i64
     }
-    fn st_atime_nsec(&self) -> i64 {
-        self.as_inner().as_inner().st_atime_nsec as i64
-    }
     fn st_mtime(&self) -> i64 {
         let file_attr = self.as_inner();
         #[cfg(all(target_env = "gnu", target_pointer_width = "32"))]
@@ -357,9 +331,6 @@ impl MetadataExt for Metadata {
         }
         file_attr.as_inner().st_mtime as i64
     }
-    fn st_mtime_nsec(&self) -> i64 {
-        self.as_inner().as_inner().st_mtime_nsec as i64
-    }
     fn st_ctime(&self) -> i64 {
         let file_attr = self.as_inner();
         #[cfg(all(target_env = "gnu", target_pointer_width = "32"))]
@@ -368,13 +339,4 @@ impl MetadataExt for Metadata {
         }
         file_attr.as_inner().st_ctime as i64
     }
-    fn st_ctime_nsec(&self) -> i64 {
-        self.as_inner().as_inner().st_ctime_nsec as i64
-    }
-    fn st_blksize(&self) -> u64 {
-        self.as_inner().as_inner().st_blksize as u64
-    }
-    fn st_blocks(&self) -> u64 {
-        self.as_inner().as_inner().st_blocks as u64
-    }
 }
@@ -3,7 +3,6 @@
 //! [`std::process`]: crate::std::process
 
 use crate::std::io::Result;
-use crate::std::os::unix::io::{AsFd, AsRawFd, BorrowedFd, FromRawFd, IntoRawFd, OwnedFd, RawFd};
 use crate::std::process;
 use crate::std::sealed::Sealed;
 #[cfg(not(doc))]
@@ -68,42 +67,7 @@ impl IntoInner<FileDesc> for PidFd {
     }
 }
 
-impl AsRawFd for PidFd {
-    #[inline]
-    fn as_raw_fd(&self) -> RawFd {
-        self.as_inner().as_raw_fd()
-    }
-}
-
-impl FromRawFd for PidFd {
-    unsafe fn from_raw_fd(fd: RawFd) -> Self {
-        Self::from_inner(FileDesc::from_raw_fd(fd))
-    }
-}
-
-impl IntoRawFd for PidFd {
-    fn into_raw_fd(self) -> RawFd {
-        self.into_inner().into_raw_fd()
-    }
-}
-
-impl AsFd for PidFd {
-    fn as_fd(&self) -> BorrowedFd<'_> {
-        self.as_inner().as_fd()
-    }
-}
-
-impl From<OwnedFd> for PidFd {
-    fn from(fd: OwnedFd) -> Self {
-        Self::from_inner(FileDesc::from_inner(fd))
-    }
-}
-
-impl From<PidFd> for OwnedFd {
-    fn from(pid_fd: PidFd) -> Self {
-        pid_fd.into_inner().into_inner()
-    }
-}
+crate::std::os::fd::impl_fd_newtype!(PidFd);
 
 /// Os-specific extensions for [`Child`]
 ///
@@ -4,6 +4,13 @@
 
 pub mod raw;
 
+#[cfg(any(target_os = "linux", target_os = "redox", target_os = "dragonos", doc))]
+#[macro_use]
+mod stat_common;
+
+#[macro_use]
+mod owned_common;
+
 // The code below could be written clearer using `cfg_if!`. However, the items below are
 // publicly exported by `std` and external tools can have trouble analysing them because of the use
 // of a macro that is not vendored by Rust and included in the toolchain.
@@ -147,6 +154,8 @@ pub mod fd;
 #[cfg(any(target_os = "linux", target_os = "android", doc))]
 mod net;
 
+#[cfg(target_os = "dragonos")]
+pub mod dragonos;
 #[cfg(target_os = "dragonos")]
 pub mod fd;
 #[cfg(target_os = "dragonos")]
@@ -0,0 +1,104 @@
+//! Shared implementation of the owned and borrowed I/O object conversions.
+//!
+//! Unix file descriptors, Windows handles and Windows sockets each have an
+//! `AsX` trait to borrow the object and an `OwnedX` type to own it, and the
+//! standard library types that wrap one implement the same conversions on
+//! top of their raw ones. Generating those from one place keeps the three
+//! platforms from drifting apart.
+
+/// Implements the borrowed and owned conversions of I/O object types in
+/// terms of their raw ones.
+///
+/// `fd: T, U` implements `AsFd` and `From<T> for OwnedFd` for the types,
+/// which must implement `AsRawFd` and `IntoRawFd`. `fd from: T, U` also
+/// implements `From<OwnedFd> for T`, which needs `FromRawFd`. `handle` and
+/// `socket` do the same with the Windows handle and socket traits.
+#[allow(unused_macros)]
+macro_rules! impl_owned_conversions {
+    (fd $($from:ident)?: $($t:ty),+ $(,)?) => {
+        impl_owned_conversions! {
+            @kind [
+                crate::std::os::fd::AsFd, as_fd,
+                crate::std::os::fd::BorrowedFd<'_>, crate::std::os::fd::OwnedFd,
+                crate::std::os::fd::AsRawFd, as_raw_fd,
+                crate::std::os::fd::IntoRawFd, into_raw_fd,
+                crate::std::os::fd::FromRawFd, from_raw_fd
+            ]
+            $($from)?: $($t),+
+        }
+    };
+    (handle $($from:ident)?: $($t:ty),+ $(,)?) => {
+        impl_owned_conversions! {
+            @kind [
+                crate::std::os::windows::io::AsHandle, as_handle,
+                crate::std::os::windows::io::BorrowedHandle<'_>,
+                crate::std::os::windows::io::OwnedHandle,
+                crate::std::os::windows::io::AsRawHandle, as_raw_handle,
+                crate::std::os::windows::io::IntoRawHandle, into_raw_handle,
+                crate::std::os::windows::io::FromRawHandle, from_raw_handle
+            ]
+            $($from)?: $($t),+
+        }
+    };
+    (socket $($from:ident)?: $($t:ty),+ $(,)?) => {
+        impl_owned_conversions! {
+            @kind [
+                crate::std::os::windows::io::AsSocket, as_socket,
+                crate::std::os::windows::io::BorrowedSocket<'_>,
+                crate::std::os::windows::io::OwnedSocket,
+                crate::std::os::windows::io::AsRawSocket, as_raw_socket,
+                crate::std::os::windows::io::IntoRawSocket, into_raw_socket,
+                crate::std::os::windows::io::FromRawSocket, from_raw_socket
+            ]
+            $($from)?: $($t),+
+        }
+    };
+    (@kind $kind:tt : $($t:ty),+) => {
+        impl_owned_conversions! { @borrow $kind $($t),+ }
+    };
+    (@kind $kind:tt from: $($t:ty),+) => {
+        impl_owned_conversions! { @borrow $kind $($t),+ }
+        impl_owned_conversions! { @from $kind $($t),+ }
+    };
+    (
+        @borrow [
+            $As:path, $as:ident, $Borrowed:ty, $Owned:ty,
+            $AsRaw:path, $as_raw:ident, $IntoRaw:path, $into_raw:ident,
+            $FromRaw:path, $from_raw:ident
+        ]
+        $($t:ty),+
+    ) => {$(
+        impl $As for $t {
+            #[inline]
+            fn $as(&self) -> $Borrowed {
+                // SAFETY: the object owns the raw one for as long as it is
+                // borrowed.
+                unsafe { <$Borrowed>::borrow_raw(<$t as $AsRaw>::$as_raw(self)) }
+            }
+        }
+
+        impl From<$t> for $Owned {
+            #[inline]
+            fn from(value: $t) -> $Owned {
+                // SAFETY: `into_raw` hands over ownership of the raw object.
+                unsafe { <$Owned as $FromRaw>::$from_raw(<$t as $IntoRaw>::$into_raw(value)) }
+            }
+        }
+    )+};
+    (
+        @from [
+            $As:path, $as:ident, $Borrowed:ty, $Owned:ty,
+            $AsRaw:path, $as_raw:ident, $IntoRaw:path, $into_raw:ident,
+            $FromRaw:path, $from_raw:ident
+        ]
+        $($t:ty),+
+    ) => {$(
+        impl From<$Owned> for $t {
+            #[inline]
+            fn from(owned: $Owned) -> $t {
+                // SAFETY: `into_raw` hands over ownership of the raw object.
+                unsafe { <$t as $FromRaw>::$from_raw(<$Owned as $IntoRaw>::$into_raw(owned)) }
+            }
+        }
+    )+};
+}
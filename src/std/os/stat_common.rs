@@ -0,0 +1,50 @@
+//! Shared implementation of the platform `MetadataExt` accessors.
+//!
+//! The Redox and Linux (and therefore DragonOS) `MetadataExt` traits expose
+//! the same `st_*` accessor set over their respective `stat` structures.
+//! Generating the plain field reads from one place keeps the two impls from
+//! drifting apart when fields are added or their conversions change.
+
+#[cfg(test)]
+mod tests;
+
+/// Expands to the `st_*` accessors of a `MetadataExt` impl for `Metadata`
+/// that are plain reads of the `stat` field of the same name.
+///
+/// The timestamp seconds accessors are left to the caller by default, since
+/// some targets prefer `statx` data for them. Pass `with_times` to generate
+/// plain field reads for those too.
+macro_rules! unix_stat_common {
+    () => {
+        unix_stat_common! {
+            @fields
+            st_dev: u64,
+            st_ino: u64,
+            st_mode: u32,
+            st_nlink: u64,
+            st_uid: u32,
+            st_gid: u32,
+            st_rdev: u64,
+            st_size: u64,
+            st_atime_nsec: i64,
+            st_mtime_nsec: i64,
+            st_ctime_nsec: i64,
+            st_blksize: u64,
+            st_blocks: u64,
+        }
+    };
+    (with_times) => {
+        unix_stat_common!();
+        unix_stat_common! {
+            @fields
+            st_atime: i64,
+            st_mtime: i64,
+            st_ctime: i64,
+        }
+    };
+    (@fields $($field:ident: $ty:ty,)*) => {$(
+        fn $field(&self) -> $ty {
+            self.as_inner().as_inner().$field as $ty
+        }
+    )*};
+}
@@ -0,0 +1,77 @@
+use crate::std::fs;
+use crate::std::io::Write;
+use crate::std::os::unix::fs::PermissionsExt;
+
+#[cfg(any(target_os = "linux", target_os = "dragonos"))]
+use crate::std::os::linux::fs::MetadataExt;
+#[cfg(target_os = "redox")]
+use crate::std::os::redox::fs::MetadataExt;
+
+#[test]
+fn stat_accessors_match_metadata() {
+    let dir = crate::std::sys_common::io::test::tmpdir();
+    let path = dir.join("stat_common.txt");
+    fs::File::create(&path)
+        .unwrap()
+        .write_all(b"parity")
+        .unwrap();
+
+    let meta = fs::metadata(&path).unwrap();
+    assert_eq!(meta.st_size(), meta.len());
+    assert_eq!(meta.st_mode(), meta.permissions().mode());
+    assert!(meta.st_nlink() >= 1);
+    assert!(meta.st_blksize() > 0);
+    for nsec in [
+        meta.st_atime_nsec(),
+        meta.st_mtime_nsec(),
+        meta.st_ctime_nsec(),
+    ] {
+        assert!((0..1_000_000_000).contains(&nsec));
+    }
+    assert!(meta.st_mtime() > 0);
+}
+
+#[test]
+fn stat_accessors_distinguish_files() {
+    let dir = crate::std::sys_common::io::test::tmpdir();
+    let a = dir.join("a");
+    let b = dir.join("b");
+    fs::File::create(&a).unwrap();
+    fs::File::create(&b).unwrap();
+
+    let (a, b) = (fs::metadata(a).unwrap(), fs::metadata(b).unwrap());
+    assert_eq!(a.st_dev(), b.st_dev());
+    assert_ne!(a.st_ino(), b.st_ino());
+    assert_eq!(a.st_uid(), b.st_uid());
+    assert_eq!(a.st_gid(), b.st_gid());
+}
+
+// A compile test: the accessors keep the same signatures on every target
+// that shares `unix_stat_common!`, including Redox, which has no other test
+// exercising its `MetadataExt`.
+#[test]
+#[cfg(feature = "redox-parity")]
+fn stat_accessor_signatures() {
+    let _: [fn(&fs::Metadata) -> u64; 7] = [
+        MetadataExt::st_dev,
+        MetadataExt::st_ino,
+        MetadataExt::st_nlink,
+        MetadataExt::st_rdev,
+        MetadataExt::st_size,
+        MetadataExt::st_blksize,
+        MetadataExt::st_blocks,
+    ];
+    let _: [fn(&fs::Metadata) -> u32; 3] = [
+        MetadataExt::st_mode,
+        MetadataExt::st_uid,
+        MetadataExt::st_gid,
+    ];
+    let _: [fn(&fs::Metadata) -> i64; 6] = [
+        MetadataExt::st_atime,
+        MetadataExt::st_atime_nsec,
+        MetadataExt::st_mtime,
+        MetadataExt::st_mtime_nsec,
+        MetadataExt::st_ctime,
+        MetadataExt::st_ctime_nsec,
+    ];
+}
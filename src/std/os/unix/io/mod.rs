@@ -77,6 +77,32 @@
 
 pub use crate::std::os::fd::*;
 
+/// Socket names matching `std::os::wasi::io`.
+///
+/// Sockets are plain file descriptors on DragonOS, so these are the same
+/// types as their `Fd` counterparts; they exist so code shared with WASI
+/// preview2 can spell socket handles identically on both targets.
+#[cfg(target_os = "dragonos")]
+pub use self::socket::*;
+
+#[cfg(target_os = "dragonos")]
+mod socket {
+    pub use crate::std::os::fd::{
+        AsFd as AsSocket, AsRawFd as AsRawSocket, FromRawFd as FromRawSocket,
+        IntoRawFd as IntoRawSocket,
+    };
+    use crate::std::os::fd::{BorrowedFd, OwnedFd, RawFd};
+
+    /// A borrowed socket, which is a [`BorrowedFd`] on DragonOS.
+    pub type BorrowedSocket<'socket> = BorrowedFd<'socket>;
+
+    /// An owned socket, which is an [`OwnedFd`] on DragonOS.
+    pub type OwnedSocket = OwnedFd;
+
+    /// A raw socket, which is a [`RawFd`] on DragonOS.
+    pub type RawSocket = RawFd;
+}
+
 // Tests for this module
 #[cfg(test)]
 mod tests;
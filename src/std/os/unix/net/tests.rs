@@ -1,6 +1,7 @@
 use super::*;
 use crate::std::io::prelude::*;
-use crate::std::io::{self, ErrorKind, IoSlice, IoSliceMut};
+use crate::std::io::{self, BorrowedBuf, ErrorKind, IoSlice, IoSliceMut};
+use crate::std::mem::MaybeUninit;
 #[cfg(any(target_os = "android", target_os = "linux"))]
 use crate::std::os::unix::io::AsRawFd;
 use crate::std::sys_common::io::test::tmpdir;
@@ -97,6 +98,19 @@ fn pair() {
     thread.join().unwrap();
 }
 
+#[test]
+fn read_buf() {
+    let (mut s1, mut s2) = or_panic!(UnixStream::pair());
+    or_panic!(s1.write_all(&[1, 2, 3, 4]));
+
+    let mut buf: [MaybeUninit<u8>; 128] = MaybeUninit::uninit_array();
+    let mut buf = BorrowedBuf::from(buf.as_mut_slice());
+    or_panic!(s2.read_buf(buf.unfilled()));
+    assert_eq!(buf.filled(), &[1, 2, 3, 4]);
+    // The rest of the buffer is left uninitialized.
+    assert_eq!(buf.init_len(), 4);
+}
+
 #[test]
 #[cfg_attr(target_os = "android", ignore)] // Android SELinux rules prevent creating Unix sockets
 fn try_clone() {
@@ -4,7 +4,7 @@
 
 use crate::std::ffi::OsStr;
 use crate::std::io;
-use crate::std::os::unix::io::{AsFd, AsRawFd, BorrowedFd, FromRawFd, IntoRawFd, OwnedFd, RawFd};
+use crate::std::os::unix::io::{AsRawFd, FromRawFd, IntoRawFd, OwnedFd, RawFd};
 use crate::std::process;
 use crate::std::sealed::Sealed;
 use crate::std::sys;
@@ -390,47 +390,7 @@ impl IntoRawFd for process::ChildStderr {
     }
 }
 
-impl AsFd for crate::std::process::ChildStdin {
-    #[inline]
-    fn as_fd(&self) -> BorrowedFd<'_> {
-        self.as_inner().as_fd()
-    }
-}
-
-impl From<crate::std::process::ChildStdin> for OwnedFd {
-    #[inline]
-    fn from(child_stdin: crate::std::process::ChildStdin) -> OwnedFd {
-        child_stdin.into_inner().into_inner().into_inner()
-    }
-}
-
-impl AsFd for crate::std::process::ChildStdout {
-    #[inline]
-    fn as_fd(&self) -> BorrowedFd<'_> {
-        self.as_inner().as_fd()
-    }
-}
-
-impl From<crate::std::process::ChildStdout> for OwnedFd {
-    #[inline]
-    fn from(child_stdout: crate::std::process::ChildStdout) -> OwnedFd {
-        child_stdout.into_inner().into_inner().into_inner()
-    }
-}
-
-impl AsFd for crate::std::process::ChildStderr {
-    #[inline]
-    fn as_fd(&self) -> BorrowedFd<'_> {
-        self.as_inner().as_fd()
-    }
-}
-
-impl From<crate::std::process::ChildStderr> for OwnedFd {
-    #[inline]
-    fn from(child_stderr: crate::std::process::ChildStderr) -> OwnedFd {
-        child_stderr.into_inner().into_inner().into_inner()
-    }
-}
+impl_owned_conversions! { fd: process::ChildStdin, process::ChildStdout, process::ChildStderr }
 
 /// Returns the OS-assigned process identifier associated with this process's parent.
 #[must_use]
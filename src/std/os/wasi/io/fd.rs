@@ -1,8 +1,25 @@
 //! Owned and borrowed file descriptors.
 
-
 // Tests for this module
 #[cfg(test)]
 mod tests;
 
-pub use crate::std::os::fd::owned::*;
+pub use crate::std::os::fd::{AsFd, BorrowedFd, OwnedFd};
+
+/// A borrowed socket.
+///
+/// Under WASI preview2 a socket is a file descriptor, so this is the same type
+/// as [`BorrowedFd`].
+pub type BorrowedSocket<'socket> = BorrowedFd<'socket>;
+
+/// An owned socket.
+///
+/// Under WASI preview2 a socket is a file descriptor, so this is the same type
+/// as [`OwnedFd`].
+pub type OwnedSocket = OwnedFd;
+
+/// A trait to borrow the socket from an underlying object.
+///
+/// This is [`AsFd`] under another name, provided for parity with targets that
+/// distinguish sockets from other descriptors.
+pub use crate::std::os::fd::AsFd as AsSocket;
@@ -9,3 +9,12 @@ fn test_raw_fd_layout() {
     // to be updated.
     assert_eq!(size_of::<RawFd>(), 4);
 }
+
+#[test]
+fn test_socket_aliases_are_fds() {
+    use crate::std::os::wasi::io::{BorrowedFd, BorrowedSocket, OwnedFd, OwnedSocket, RawSocket};
+
+    assert_eq!(size_of::<RawSocket>(), size_of::<RawFd>());
+    assert_eq!(size_of::<OwnedSocket>(), size_of::<OwnedFd>());
+    assert_eq!(size_of::<BorrowedSocket<'_>>(), size_of::<BorrowedFd<'_>>());
+}
@@ -1,3 +1,12 @@
 //! WASI-specific extensions to general I/O primitives.
+//!
+//! WASI preview2 models sockets as ordinary descriptors, so the socket
+//! names exported here are the same types as their `Fd` counterparts. They
+//! mirror the surface of `std::os::unix::io` on DragonOS, which lets code
+//! shared between the two targets name sockets without extra `cfg` blocks.
 
-pub use crate::std::os::fd::*;
+mod fd;
+mod raw;
+
+pub use fd::*;
+pub use raw::*;
@@ -1,6 +1,5 @@
 //! WASI-specific extensions to general I/O primitives.
 
-
 // NOTE: despite the fact that this module is unstable,
 // stable Rust had the capability to access the stable
 // re-exported items from os::fd::raw through this
@@ -16,4 +15,13 @@
 // If this module is stabilized,
 // you may want to remove those attributes
 // (assuming no other unstable modules need them).
-pub use crate::std::os::fd::raw::*;
+pub use crate::std::os::fd::{AsRawFd, FromRawFd, IntoRawFd, RawFd};
+
+/// Raw sockets.
+///
+/// Under WASI preview2 a raw socket is a raw file descriptor.
+pub type RawSocket = RawFd;
+
+pub use crate::std::os::fd::{
+    AsRawFd as AsRawSocket, FromRawFd as FromRawSocket, IntoRawFd as IntoRawSocket,
+};
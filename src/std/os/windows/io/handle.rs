@@ -55,10 +55,11 @@ pub struct BorrowedHandle<'handle> {
 ///
 /// `OwnedHandle` uses [`CloseHandle`] to close its handle on drop. As such,
 /// it must not be used with handles to open registry keys which need to be
-/// closed with [`RegCloseKey`] instead.
+/// closed with [`RegCloseKey`] instead; use [`OwnedRegKey`] for those.
 ///
 /// [`CloseHandle`]: https://docs.microsoft.com/en-us/windows/win32/api/handleapi/nf-handleapi-closehandle
 /// [`RegCloseKey`]: https://docs.microsoft.com/en-us/windows/win32/api/winreg/nf-winreg-regclosekey
+/// [`OwnedRegKey`]: super::OwnedRegKey
 ///
 /// [here]: https://devblogs.microsoft.com/oldnewthing/20040302-00/?p=40443
 /// [the current process handle]: https://docs.microsoft.com/en-us/windows/win32/api/processthreadsapi/nf-processthreadsapi-getcurrentprocess#remarks
@@ -516,46 +517,9 @@ impl<'a> AsHandle for crate::std::io::StderrLock<'a> {
     }
 }
 
-impl AsHandle for crate::std::process::ChildStdin {
-    #[inline]
-    fn as_handle(&self) -> BorrowedHandle<'_> {
-        unsafe { BorrowedHandle::borrow_raw(self.as_raw_handle()) }
-    }
-}
-
-impl From<crate::std::process::ChildStdin> for OwnedHandle {
-    #[inline]
-    fn from(child_stdin: crate::std::process::ChildStdin) -> OwnedHandle {
-        unsafe { OwnedHandle::from_raw_handle(child_stdin.into_raw_handle()) }
-    }
-}
-
-impl AsHandle for crate::std::process::ChildStdout {
-    #[inline]
-    fn as_handle(&self) -> BorrowedHandle<'_> {
-        unsafe { BorrowedHandle::borrow_raw(self.as_raw_handle()) }
-    }
-}
-
-impl From<crate::std::process::ChildStdout> for OwnedHandle {
-    #[inline]
-    fn from(child_stdout: crate::std::process::ChildStdout) -> OwnedHandle {
-        unsafe { OwnedHandle::from_raw_handle(child_stdout.into_raw_handle()) }
-    }
-}
-
-impl AsHandle for crate::std::process::ChildStderr {
-    #[inline]
-    fn as_handle(&self) -> BorrowedHandle<'_> {
-        unsafe { BorrowedHandle::borrow_raw(self.as_raw_handle()) }
-    }
-}
-
-impl From<crate::std::process::ChildStderr> for OwnedHandle {
-    #[inline]
-    fn from(child_stderr: crate::std::process::ChildStderr) -> OwnedHandle {
-        unsafe { OwnedHandle::from_raw_handle(child_stderr.into_raw_handle()) }
-    }
+impl_owned_conversions! {
+    handle: crate::std::process::ChildStdin, crate::std::process::ChildStdout,
+    crate::std::process::ChildStderr
 }
 
 impl<T> AsHandle for crate::std::thread::JoinHandle<T> {
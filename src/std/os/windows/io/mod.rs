@@ -50,10 +50,12 @@
 
 mod handle;
 mod raw;
+mod reg_key;
 mod socket;
 
 pub use handle::*;
 pub use raw::*;
+pub use reg_key::*;
 pub use socket::*;
 
 #[cfg(test)]
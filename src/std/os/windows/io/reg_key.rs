@@ -0,0 +1,136 @@
+//! Owned OS registry keys.
+
+use super::raw::RawHandle;
+use crate::std::fmt;
+use crate::std::mem::forget;
+use crate::std::sys;
+
+/// An owned registry key.
+///
+/// This closes the key with [`RegCloseKey`] on drop. Registry keys are not
+/// kernel objects, and closing one with `CloseHandle` fails or, worse,
+/// closes an unrelated handle that happens to have the same value. For
+/// that reason there is no conversion between `OwnedRegKey` and
+/// [`OwnedHandle`], and `OwnedRegKey` doesn't implement the traits of
+/// [`raw`] handles.
+///
+/// The predefined keys, such as `HKEY_LOCAL_MACHINE`, are not opened by
+/// the process and must not be owned by an `OwnedRegKey`.
+///
+/// [`RegCloseKey`]: https://docs.microsoft.com/en-us/windows/win32/api/winreg/nf-winreg-regclosekey
+/// [`OwnedHandle`]: super::OwnedHandle
+/// [`raw`]: super::AsRawHandle
+#[repr(transparent)]
+pub struct OwnedRegKey {
+    key: RawHandle,
+}
+
+/// FFI type for registry keys in out parameters, such as the `phkResult`
+/// of `RegOpenKeyExW`, which are null when the call fails. This uses
+/// `repr(transparent)` and has the representation of a host handle, so
+/// that it can be used in such FFI declarations.
+///
+/// The only thing you can usefully do with a `RegKeyOrNull` is to convert
+/// it into an `OwnedRegKey` using its [`TryFrom`] implementation; this
+/// conversion takes care of the check for null.
+///
+/// If this holds a non-null key, it will close the key on drop.
+#[repr(transparent)]
+#[derive(Debug)]
+pub struct RegKeyOrNull(OwnedRegKey);
+
+// Registry keys may be used from any thread, like handles.
+unsafe impl Send for OwnedRegKey {}
+unsafe impl Send for RegKeyOrNull {}
+unsafe impl Sync for OwnedRegKey {}
+unsafe impl Sync for RegKeyOrNull {}
+
+impl OwnedRegKey {
+    /// Constructs a new instance of `Self` from the given raw key.
+    ///
+    /// # Safety
+    ///
+    /// The `key` must be an open registry key, as returned by a function
+    /// like `RegOpenKeyExW`, and not be closed by anything else. It must not
+    /// be a predefined key or a handle to any other kind of object.
+    #[inline]
+    pub unsafe fn from_raw_key(key: RawHandle) -> Self {
+        Self { key }
+    }
+
+    /// Extracts the raw key, without taking any ownership.
+    #[inline]
+    pub fn as_raw_key(&self) -> RawHandle {
+        self.key
+    }
+
+    /// Consumes this object, returning the raw key. The caller becomes
+    /// responsible for closing it with `RegCloseKey`.
+    #[inline]
+    pub fn into_raw_key(self) -> RawHandle {
+        let key = self.key;
+        forget(self);
+        key
+    }
+}
+
+impl RegKeyOrNull {
+    /// Constructs a new instance of `Self` from the given raw key, as set by
+    /// a registry function that leaves its out parameter null on failure.
+    ///
+    /// # Safety
+    ///
+    /// The passed `key` value must either satisfy the safety requirements of
+    /// [`OwnedRegKey::from_raw_key`], or be null.
+    #[inline]
+    pub unsafe fn from_raw_key(key: RawHandle) -> Self {
+        Self(OwnedRegKey::from_raw_key(key))
+    }
+}
+
+impl TryFrom<RegKeyOrNull> for OwnedRegKey {
+    type Error = NullRegKeyError;
+
+    #[inline]
+    fn try_from(key_or_null: RegKeyOrNull) -> Result<Self, NullRegKeyError> {
+        let owned_key = key_or_null.0;
+        if owned_key.key.is_null() {
+            forget(owned_key);
+
+            Err(NullRegKeyError(()))
+        } else {
+            Ok(owned_key)
+        }
+    }
+}
+
+/// This is the error type used by [`RegKeyOrNull`] when attempting to
+/// convert into a registry key, to indicate that the value is null.
+// The empty field prevents constructing this, and allows extending it in the future.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct NullRegKeyError(());
+
+impl fmt::Display for NullRegKeyError {
+    fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
+        "A RegKeyOrNull could not be converted to a registry key because it was null".fmt(fmt)
+    }
+}
+
+impl crate::std::error::Error for NullRegKeyError {}
+
+impl Drop for OwnedRegKey {
+    #[inline]
+    fn drop(&mut self) {
+        unsafe {
+            let _ = sys::c::RegCloseKey(self.key);
+        }
+    }
+}
+
+impl fmt::Debug for OwnedRegKey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("OwnedRegKey")
+            .field("key", &self.key)
+            .finish()
+    }
+}
@@ -9,6 +9,7 @@ use crate::std::mem::forget;
 use crate::std::sys;
 #[cfg(not(target_vendor = "uwp"))]
 use crate::std::sys::cvt;
+use crate::std::time::Duration;
 
 /// A borrowed socket.
 ///
@@ -111,6 +112,78 @@ impl OwnedSocket {
             "Unavailable on UWP"
         ))
     }
+
+    /// Moves the socket into or out of nonblocking mode, with `FIONBIO`.
+    pub fn set_nonblocking(&self, nonblocking: bool) -> io::Result<()> {
+        let mut nonblocking = nonblocking as u32;
+        let result =
+            unsafe { sys::c::ioctlsocket(self.as_raw_socket(), sys::c::FIONBIO, &mut nonblocking) };
+        sys::net::cvt(result).map(drop)
+    }
+
+    /// Sets the `SO_LINGER` option: how long closing the socket waits for
+    /// unsent data to be sent, or `None` to return at once and send it in
+    /// the background. The time is rounded down to whole seconds.
+    pub fn set_linger(&self, linger: Option<Duration>) -> io::Result<()> {
+        let linger = sys::c::linger {
+            l_onoff: linger.is_some() as u16,
+            l_linger: linger.unwrap_or_default().as_secs() as u16,
+        };
+        self.setsockopt(sys::c::SOL_SOCKET, sys::c::SO_LINGER, linger)
+    }
+
+    /// Returns the `SO_LINGER` option, as set by
+    /// [`set_linger`](OwnedSocket::set_linger).
+    pub fn linger(&self) -> io::Result<Option<Duration>> {
+        let linger: sys::c::linger = self.getsockopt(sys::c::SOL_SOCKET, sys::c::SO_LINGER)?;
+        Ok((linger.l_onoff != 0).then(|| Duration::from_secs(linger.l_linger as u64)))
+    }
+
+    /// Sets the `SO_KEEPALIVE` option, which makes a connected TCP socket
+    /// probe an idle peer and fail once it stops answering.
+    pub fn set_keepalive(&self, keepalive: bool) -> io::Result<()> {
+        self.setsockopt(
+            sys::c::SOL_SOCKET,
+            sys::c::SO_KEEPALIVE,
+            keepalive as sys::c::BOOL,
+        )
+    }
+
+    /// Returns the `SO_KEEPALIVE` option, as set by
+    /// [`set_keepalive`](OwnedSocket::set_keepalive).
+    pub fn keepalive(&self) -> io::Result<bool> {
+        let keepalive: sys::c::BOOL = self.getsockopt(sys::c::SOL_SOCKET, sys::c::SO_KEEPALIVE)?;
+        Ok(keepalive != 0)
+    }
+
+    fn setsockopt<T>(&self, level: i32, name: i32, value: T) -> io::Result<()> {
+        let result = unsafe {
+            sys::c::setsockopt(
+                self.as_raw_socket(),
+                level,
+                name,
+                &value as *const T as *const _,
+                mem::size_of::<T>() as i32,
+            )
+        };
+        sys::net::cvt(result).map(drop)
+    }
+
+    fn getsockopt<T: Copy>(&self, level: i32, name: i32) -> io::Result<T> {
+        let mut value = mem::MaybeUninit::<T>::zeroed();
+        let mut len = mem::size_of::<T>() as i32;
+        let result = unsafe {
+            sys::c::getsockopt(
+                self.as_raw_socket(),
+                level,
+                name,
+                value.as_mut_ptr() as *mut _,
+                &mut len,
+            )
+        };
+        sys::net::cvt(result)?;
+        Ok(unsafe { value.assume_init() })
+    }
 }
 
 impl BorrowedSocket<'_> {
@@ -301,65 +374,6 @@ impl AsSocket for OwnedSocket {
     }
 }
 
-impl AsSocket for crate::std::net::TcpStream {
-    #[inline]
-    fn as_socket(&self) -> BorrowedSocket<'_> {
-        unsafe { BorrowedSocket::borrow_raw(self.as_raw_socket()) }
-    }
-}
-
-impl From<crate::std::net::TcpStream> for OwnedSocket {
-    #[inline]
-    fn from(tcp_stream: crate::std::net::TcpStream) -> OwnedSocket {
-        unsafe { OwnedSocket::from_raw_socket(tcp_stream.into_raw_socket()) }
-    }
-}
-
-impl From<OwnedSocket> for crate::std::net::TcpStream {
-    #[inline]
-    fn from(owned: OwnedSocket) -> Self {
-        unsafe { Self::from_raw_socket(owned.into_raw_socket()) }
-    }
-}
-
-impl AsSocket for crate::std::net::TcpListener {
-    #[inline]
-    fn as_socket(&self) -> BorrowedSocket<'_> {
-        unsafe { BorrowedSocket::borrow_raw(self.as_raw_socket()) }
-    }
-}
-
-impl From<crate::std::net::TcpListener> for OwnedSocket {
-    #[inline]
-    fn from(tcp_listener: crate::std::net::TcpListener) -> OwnedSocket {
-        unsafe { OwnedSocket::from_raw_socket(tcp_listener.into_raw_socket()) }
-    }
-}
-
-impl From<OwnedSocket> for crate::std::net::TcpListener {
-    #[inline]
-    fn from(owned: OwnedSocket) -> Self {
-        unsafe { Self::from_raw_socket(owned.into_raw_socket()) }
-    }
-}
-
-impl AsSocket for crate::std::net::UdpSocket {
-    #[inline]
-    fn as_socket(&self) -> BorrowedSocket<'_> {
-        unsafe { BorrowedSocket::borrow_raw(self.as_raw_socket()) }
-    }
-}
-
-impl From<crate::std::net::UdpSocket> for OwnedSocket {
-    #[inline]
-    fn from(udp_socket: crate::std::net::UdpSocket) -> OwnedSocket {
-        unsafe { OwnedSocket::from_raw_socket(udp_socket.into_raw_socket()) }
-    }
-}
-
-impl From<OwnedSocket> for crate::std::net::UdpSocket {
-    #[inline]
-    fn from(owned: OwnedSocket) -> Self {
-        unsafe { Self::from_raw_socket(owned.into_raw_socket()) }
-    }
+impl_owned_conversions! {
+    socket from: crate::std::net::TcpStream, crate::std::net::TcpListener, crate::std::net::UdpSocket
 }
@@ -32,3 +32,34 @@ fn test_niche_optimizations_socket() {
         );
     }
 }
+
+#[test]
+fn test_socket_options() {
+    use crate::std::net::{TcpListener, TcpStream};
+    use crate::std::os::windows::io::OwnedSocket;
+    use crate::std::time::Duration;
+
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let stream = TcpStream::connect(listener.local_addr().unwrap()).unwrap();
+    let socket = OwnedSocket::from(stream);
+
+    socket.set_nonblocking(true).unwrap();
+    socket.set_nonblocking(false).unwrap();
+
+    assert_eq!(socket.linger().unwrap(), None);
+    socket.set_linger(Some(Duration::from_secs(3))).unwrap();
+    assert_eq!(socket.linger().unwrap(), Some(Duration::from_secs(3)));
+
+    assert!(!socket.keepalive().unwrap());
+    socket.set_keepalive(true).unwrap();
+    assert!(socket.keepalive().unwrap());
+}
+
+#[test]
+fn test_reg_key_or_null() {
+    use crate::std::os::windows::io::{OwnedRegKey, RegKeyOrNull};
+    use crate::std::ptr;
+
+    let null = unsafe { RegKeyOrNull::from_raw_key(ptr::null_mut()) };
+    assert!(OwnedRegKey::try_from(null).is_err());
+}
@@ -2788,7 +2788,10 @@ impl Path {
     #[must_use]
     #[inline]
     pub fn exists(&self) -> bool {
-        fs::metadata(self).is_ok()
+        // Not `fs::metadata`, which on DragonOS would allocate to add the
+        // path to the error only for it to be dropped. The same goes for the `is_*`
+        // methods below.
+        sys::fs::stat(self).is_ok()
     }
 
     /// Returns `Ok(true)` if the path points at an existing entity.
@@ -2849,7 +2852,9 @@ impl Path {
     /// [`fs::OpenOptions::open`] for more information.
     #[must_use]
     pub fn is_file(&self) -> bool {
-        fs::metadata(self).map(|m| m.is_file()).unwrap_or(false)
+        sys::fs::stat(self)
+            .map(|attr| attr.file_type().is_file())
+            .unwrap_or(false)
     }
 
     /// Returns `true` if the path exists on disk and is pointing at a directory.
@@ -2875,7 +2880,9 @@ impl Path {
     /// [`fs::Metadata::is_dir`] if it was [`Ok`].
     #[must_use]
     pub fn is_dir(&self) -> bool {
-        fs::metadata(self).map(|m| m.is_dir()).unwrap_or(false)
+        sys::fs::stat(self)
+            .map(|attr| attr.file_type().is_dir())
+            .unwrap_or(false)
     }
 
     /// Returns `true` if the path exists on disk and is pointing at a symbolic link.
@@ -2906,8 +2913,8 @@ impl Path {
     /// [`fs::Metadata::is_symlink`] if it was [`Ok`].
     #[must_use]
     pub fn is_symlink(&self) -> bool {
-        fs::symlink_metadata(self)
-            .map(|m| m.is_symlink())
+        sys::fs::lstat(self)
+            .map(|attr| attr.file_type().is_symlink())
             .unwrap_or(false)
     }
 
@@ -1451,6 +1451,22 @@ impl From<fs::File> for Stdio {
     }
 }
 
+impl From<io::PipeReader> for Stdio {
+    /// Converts the reading end of an [`io::pipe`] into a [`Stdio`], so that
+    /// the child reads what is written to the other end.
+    fn from(pipe: io::PipeReader) -> Stdio {
+        Stdio::from_inner(pipe.0.into())
+    }
+}
+
+impl From<io::PipeWriter> for Stdio {
+    /// Converts the writing end of an [`io::pipe`] into a [`Stdio`], so that
+    /// what the child writes can be read from the other end.
+    fn from(pipe: io::PipeWriter) -> Stdio {
+        Stdio::from_inner(pipe.0.into())
+    }
+}
+
 /// Describes the result of a process after it has terminated.
 ///
 /// This `struct` is used to represent the exit status or other termination of a child process.
@@ -2061,6 +2077,11 @@ impl Child {
 ///
 /// process::exit(0x0100);
 /// ```
+///
+/// **DragonOS**: After flushing Rust's own stdout, this exits through the C
+/// library's `exit`, so handlers registered with `atexit` (and with
+/// `std::os::dragonos::process::at_exit`) run and C stdio buffers are
+/// flushed, which keeps output from mixed Rust/C programs intact.
 pub fn exit(code: i32) -> ! {
     crate::std::rt::cleanup();
     crate::std::sys::os::exit(code)
@@ -2127,6 +2148,11 @@ pub fn exit(code: i32) -> ! {
 /// }
 /// ```
 ///
+/// **DragonOS**: The process is terminated by raising `SIGABRT`, so a
+/// parent waiting on it observes death by that signal. If a `SIGABRT`
+/// handler returns, the default action is restored and the signal raised
+/// again.
+///
 /// [panic hook]: crate::std::panic::set_hook
 #[cold]
 pub fn abort() -> ! {
@@ -16,6 +16,7 @@ use crate::std::ffi::CString;
 pub use crate::std::panicking::{begin_panic, panic_count};
 pub use core::panicking::{panic_display, panic_fmt};
 
+use crate::std::sync::atomic::{AtomicU8, Ordering};
 use crate::std::sync::Once;
 use crate::std::sys;
 use crate::std::sys_common::thread_info;
@@ -25,10 +26,15 @@ use crate::std::thread::Thread;
 // - the standard error output
 // - some dedicated platform specific output
 // - nothing (so this macro is a no-op)
+//
+// With the `minimal-runtime` feature nothing is printed, so that the
+// formatting code can be left out of the binary.
 macro_rules! rtprintpanic {
     ($($t:tt)*) => {
-        if let Some(mut out) = crate::std::sys::stdio::panic_output() {
-            let _ = crate::std::io::Write::write_fmt(&mut out, format_args!($($t)*));
+        if cfg!(not(feature = "minimal-runtime")) {
+            if let Some(mut out) = crate::std::sys::stdio::panic_output() {
+                let _ = crate::std::io::Write::write_fmt(&mut out, format_args!($($t)*));
+            }
         }
     }
 }
@@ -62,6 +68,51 @@ macro_rules! rtunwrap {
     };
 }
 
+/// The steps of runtime initialization and cleanup, in the order `init` and
+/// `cleanup` go through them.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+#[repr(u8)]
+pub(crate) enum Stage {
+    /// Nothing has been set up. Also the case for programs that don't start
+    /// through `lang_start`.
+    Uninit = 0,
+    /// `init_early` has run: like `MainThread`, but without what `Sys` does
+    /// to the standard file descriptors and the arguments. `init_late` goes
+    /// on to `MainThread` from here.
+    Early = 1,
+    /// `sys::init` has run: the standard file descriptors are open,
+    /// `SIGPIPE` is set up, the stack overflow handler is installed and the
+    /// arguments are stored.
+    Sys = 2,
+    /// The main thread's guard page and `Thread` handle are set up.
+    MainThread = 3,
+    /// `cleanup` has run, stdout is flushed and unbuffered.
+    CleanedUp = 4,
+}
+
+static STAGE: AtomicU8 = AtomicU8::new(Stage::Uninit as u8);
+
+/// Records that `to` is done, aborting unless `from` was the last step done.
+fn advance(from: Stage, to: Stage) {
+    let ok = STAGE
+        .compare_exchange(from as u8, to as u8, Ordering::AcqRel, Ordering::Acquire)
+        .is_ok();
+    rtassert!(ok);
+}
+
+/// Aborts in debug builds if `what` is used before runtime initialization
+/// got to `stage`.
+///
+/// Facilities that depend on `init` call this, so that a program that skips
+/// or reorders it fails here rather than crashing somewhere later.
+#[cfg_attr(test, allow(unused_variables))]
+pub(crate) fn debug_assert_init(stage: Stage, what: &str) {
+    // Tests are started by the real std's runtime, which doesn't run `init`.
+    if cfg!(all(debug_assertions, not(test))) && STAGE.load(Ordering::Acquire) < stage as u8 {
+        rtabort!("{what} was used before the runtime was initialized");
+    }
+}
+
 // One-time runtime initialization.
 // Runs before `main`.
 // SAFETY: must be called only once during runtime initialization.
@@ -89,7 +140,15 @@ macro_rules! rtunwrap {
 unsafe fn init(argc: isize, argv: *const *const u8, sigpipe: u8) {
     unsafe {
         sys::init(argc, argv, sigpipe);
+        advance(Stage::Uninit, Stage::Sys);
+        init_main_thread();
+        advance(Stage::Sys, Stage::MainThread);
+    }
+}
 
+#[cfg_attr(test, allow(dead_code))]
+unsafe fn init_main_thread() {
+    unsafe {
         let main_guard = sys::thread::guard::init();
         // Next, set up the current Thread with the guard information we just
         // created. Note that this isn't necessary in general for new threads,
@@ -100,6 +159,28 @@ unsafe fn init(argc: isize, argv: *const *const u8, sigpipe: u8) {
     }
 }
 
+// The first half of `init` for programs that can't run all of it yet, see
+// `os::dragonos::rt`. `SIGPIPE` gets its default treatment.
+// SAFETY: must be called only once, instead of `init`.
+#[cfg(target_os = "dragonos")]
+pub(crate) unsafe fn init_early() {
+    unsafe {
+        sys::init_early(0);
+        init_main_thread();
+        advance(Stage::Uninit, Stage::Early);
+    }
+}
+
+// The rest of `init` after `init_early`.
+// SAFETY: must be called only once, after `init_early`.
+#[cfg(target_os = "dragonos")]
+pub(crate) unsafe fn init_late(argc: isize, argv: *const *const u8) {
+    unsafe {
+        sys::init_late(argc, argv);
+        advance(Stage::Early, Stage::MainThread);
+    }
+}
+
 // One-time runtime cleanup.
 // Runs after `main` or at program exit.
 // NOTE: this is not guaranteed to run, for example when the program aborts.
@@ -110,6 +191,9 @@ pub(crate) fn cleanup() {
         crate::std::io::cleanup();
         // SAFETY: Only called once during runtime cleanup.
         sys::cleanup();
+        // `process::exit` cleans up too, and may be called by programs that
+        // didn't start through `lang_start`, so any earlier step is fine.
+        STAGE.store(Stage::CleanedUp as u8, Ordering::Release);
     });
 }
 
@@ -148,19 +232,23 @@ fn lang_start_internal(
     ret_code
 }
 
-// #[cfg(not(test))]
-// #[lang = "start"]
-// fn lang_start<T: crate::std::process::Termination + 'static>(
-//     main: fn() -> T,
-//     argc: isize,
-//     argv: *const *const u8,
-//     sigpipe: u8,
-// ) -> isize {
-//     let Ok(v) = lang_start_internal(
-//         &move || crate::std::sys_common::backtrace::__rust_begin_short_backtrace(main).report().to_i32(),
-//         argc,
-//         argv,
-//         sigpipe,
-//     );
-//     v
-// }
+#[cfg(not(test))]
+#[lang = "start"]
+fn lang_start<T: crate::std::process::Termination + 'static>(
+    main: fn() -> T,
+    argc: isize,
+    argv: *const *const u8,
+    sigpipe: u8,
+) -> isize {
+    let Ok(v) = lang_start_internal(
+        &move || {
+            crate::std::sys_common::backtrace::__rust_begin_short_backtrace(main)
+                .report()
+                .to_i32()
+        },
+        argc,
+        argv,
+        sigpipe,
+    );
+    v
+}
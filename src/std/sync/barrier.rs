@@ -2,7 +2,7 @@
 mod tests;
 
 use crate::std::fmt;
-use crate::std::sync::{Condvar, Mutex};
+use crate::std::sys_common::barrier as sys;
 
 /// A barrier enables multiple threads to synchronize the beginning
 /// of some computation.
@@ -32,15 +32,7 @@ use crate::std::sync::{Condvar, Mutex};
 /// }
 /// ```
 pub struct Barrier {
-    lock: Mutex<BarrierState>,
-    cvar: Condvar,
-    num_threads: usize,
-}
-
-// The inner state of a double barrier
-struct BarrierState {
-    count: usize,
-    generation_id: usize,
+    inner: sys::Barrier,
 }
 
 /// A `BarrierWaitResult` is returned by [`Barrier::wait()`] when all threads
@@ -78,14 +70,9 @@ impl Barrier {
     /// let barrier = Barrier::new(10);
     /// ```
     #[must_use]
-    pub fn new(n: usize) -> Barrier {
+    pub const fn new(n: usize) -> Barrier {
         Barrier {
-            lock: Mutex::new(BarrierState {
-                count: 0,
-                generation_id: 0,
-            }),
-            cvar: Condvar::new(),
-            num_threads: n,
+            inner: sys::Barrier::new(n),
         }
     }
 
@@ -124,21 +111,7 @@ impl Barrier {
     /// }
     /// ```
     pub fn wait(&self) -> BarrierWaitResult {
-        let mut lock = self.lock.lock().unwrap();
-        let local_gen = lock.generation_id;
-        lock.count += 1;
-        if lock.count < self.num_threads {
-            let _guard = self
-                .cvar
-                .wait_while(lock, |state| local_gen == state.generation_id)
-                .unwrap();
-            BarrierWaitResult(false)
-        } else {
-            lock.count = 0;
-            lock.generation_id = lock.generation_id.wrapping_add(1);
-            self.cvar.notify_all();
-            BarrierWaitResult(true)
-        }
+        BarrierWaitResult(self.inner.wait())
     }
 }
 
@@ -33,3 +33,19 @@ fn test_barrier() {
     }
     assert!(leader_found);
 }
+
+#[test]
+#[cfg_attr(target_os = "emscripten", ignore)]
+fn test_barrier_reuse() {
+    const N: usize = 4;
+    const ROUNDS: usize = 1000;
+
+    static BARRIER: Barrier = Barrier::new(N);
+    let handles: Vec<_> = (0..N)
+        .map(|_| thread::spawn(|| (0..ROUNDS).filter(|_| BARRIER.wait().is_leader()).count()))
+        .collect();
+
+    // Every round has exactly one leader.
+    let leaders: usize = handles.into_iter().map(|h| h.join().unwrap()).sum();
+    assert_eq!(leaders, ROUNDS);
+}
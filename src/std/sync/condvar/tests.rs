@@ -194,3 +194,204 @@ fn wait_timeout_wake() {
         break;
     }
 }
+
+#[test]
+#[cfg(target_os = "dragonos")]
+fn wait_timeout_while_producer() {
+    let pair = Arc::new((Mutex::new(0u32), Condvar::new()));
+    let pair2 = pair.clone();
+
+    let t = thread::spawn(move || {
+        let (lock, cvar) = &*pair2;
+        for _ in 0..10 {
+            *lock.lock().unwrap() += 1;
+            cvar.notify_one();
+        }
+    });
+
+    let (lock, cvar) = &*pair;
+    let (count, result) = cvar
+        .wait_timeout_while(lock.lock().unwrap(), Duration::from_secs(60), |count| {
+            *count < 10
+        })
+        .unwrap();
+    assert!(!result.timed_out());
+    assert_eq!(*count, 10);
+    drop(count);
+    t.join().unwrap();
+}
+
+#[test]
+#[should_panic(expected = "two mutexes")]
+#[cfg(target_os = "dragonos")]
+fn two_mutexes() {
+    let data = Arc::new((Mutex::new(()), Condvar::new()));
+    let (tx, rx) = channel();
+    let data2 = data.clone();
+    thread::spawn(move || {
+        let &(ref m, ref c) = &*data2;
+        let g = m.lock().unwrap();
+        tx.send(()).unwrap();
+        let _ = c.wait_timeout(g, Duration::from_secs(1));
+    });
+    rx.recv().unwrap();
+    // The thread only unlocks the mutex by starting to wait.
+    drop(data.0.lock().unwrap());
+
+    let m2 = Mutex::new(());
+    let _ = data
+        .1
+        .wait_timeout(m2.lock().unwrap(), Duration::from_millis(1));
+}
+
+// The mutex and condition variable may be moved between waits.
+#[test]
+#[cfg(target_os = "dragonos")]
+fn move_between_waits() {
+    let pair = (Mutex::new(()), Condvar::new());
+    let (g, _) = pair
+        .1
+        .wait_timeout(pair.0.lock().unwrap(), Duration::from_millis(1))
+        .unwrap();
+    drop(g);
+
+    let pair = Box::new(pair);
+    let (g, _) = pair
+        .1
+        .wait_timeout(pair.0.lock().unwrap(), Duration::from_millis(1))
+        .unwrap();
+    drop(g);
+}
+
+// `notify_all` moves waiters onto the futex of the mutex, which must be the
+// one at its current address after the pair was moved.
+#[test]
+#[cfg_attr(target_os = "emscripten", ignore)]
+fn notify_all_after_move() {
+    const THREADS: usize = 4;
+
+    let pair = (Mutex::new(0usize), Condvar::new());
+    let (g, _) = pair
+        .1
+        .wait_timeout(pair.0.lock().unwrap(), Duration::from_millis(1))
+        .unwrap();
+    drop(g);
+
+    let data = Arc::new(pair);
+    let threads: Vec<_> = (0..THREADS)
+        .map(|_| {
+            let data = data.clone();
+            thread::spawn(move || {
+                let &(ref lock, ref cond) = &*data;
+                let mut waiting = lock.lock().unwrap();
+                *waiting += 1;
+                let _ = cond.wait_while(waiting, |waiting| *waiting != 0).unwrap();
+            })
+        })
+        .collect();
+
+    let &(ref lock, ref cond) = &*data;
+    loop {
+        let mut waiting = lock.lock().unwrap();
+        if *waiting == THREADS {
+            *waiting = 0;
+            break;
+        }
+        drop(waiting);
+        thread::yield_now();
+    }
+    cond.notify_all();
+    for t in threads {
+        t.join().unwrap();
+    }
+}
+
+// Waiters moved onto the mutex by `notify_all` must all get the lock, whether
+// or not the notifier holds it, and also when some of them time out.
+#[test]
+#[cfg_attr(target_os = "emscripten", ignore)]
+fn notify_all_stress() {
+    const THREADS: usize = 8;
+    const ROUNDS: usize = 200;
+
+    let data = Arc::new((Mutex::new((0usize, 0usize)), Condvar::new()));
+    let threads: Vec<_> = (0..THREADS)
+        .map(|i| {
+            let data = data.clone();
+            thread::spawn(move || {
+                let &(ref lock, ref cond) = &*data;
+                for round in 1..=ROUNDS {
+                    let mut state = lock.lock().unwrap();
+                    state.1 += 1;
+                    while state.0 < round {
+                        state = if i % 2 == 0 {
+                            cond.wait(state).unwrap()
+                        } else {
+                            cond.wait_timeout(state, Duration::from_millis(1))
+                                .unwrap()
+                                .0
+                        };
+                    }
+                }
+            })
+        })
+        .collect();
+
+    let &(ref lock, ref cond) = &*data;
+    for round in 1..=ROUNDS {
+        loop {
+            let mut state = lock.lock().unwrap();
+            if state.1 == THREADS * round {
+                state.0 = round;
+                if round % 2 == 0 {
+                    cond.notify_all();
+                    drop(state);
+                } else {
+                    drop(state);
+                    cond.notify_all();
+                }
+                break;
+            }
+            drop(state);
+            thread::yield_now();
+        }
+    }
+    for t in threads {
+        t.join().unwrap();
+    }
+}
+
+#[test]
+#[cfg(not(feature = "no-poison"))]
+#[cfg_attr(target_os = "emscripten", ignore)]
+fn notify_all_then_poison() {
+    let data = Arc::new((Mutex::new(false), Condvar::new()));
+    let waiters: Vec<_> = (0..4)
+        .map(|_| {
+            let data = data.clone();
+            thread::spawn(move || {
+                let &(ref lock, ref cond) = &*data;
+                let mut ready = lock.lock().unwrap_or_else(|e| e.into_inner());
+                while !*ready {
+                    ready = cond.wait(ready).unwrap_or_else(|e| e.into_inner());
+                }
+            })
+        })
+        .collect();
+
+    let data2 = data.clone();
+    let _ = thread::spawn(move || {
+        let &(ref lock, ref cond) = &*data2;
+        let mut ready = lock.lock().unwrap();
+        *ready = true;
+        cond.notify_all();
+        panic!("poison the mutex");
+    })
+    .join();
+
+    // Every waiter gets the poisoned mutex in turn.
+    for w in waiters {
+        w.join().unwrap();
+    }
+    assert!(data.0.is_poisoned());
+}
@@ -0,0 +1,368 @@
+//! Lock order checking for the `deadlock-detection` feature.
+//!
+//! Whenever a thread blocks on a [`Mutex`] or [`RwLock`] while holding other
+//! locks, the locks it holds are recorded as coming before the one it takes.
+//! Two threads that deadlock on each other took the same locks in opposite
+//! orders, so taking a lock while holding one that it was recorded to come
+//! before panics, naming the locks, the threads that took them in the other
+//! order and where. The check happens before blocking, so it catches the
+//! mistake even on the runs where the threads happen not to deadlock.
+//!
+//! Locks are numbered in the order they are first taken, and the orders
+//! recorded for a lock are forgotten when it is dropped. Where they were
+//! taken is given as return addresses, found by following frame pointers,
+//! which needs code built with `-C force-frame-pointers=yes`.
+//!
+//! The feature only has an effect on DragonOS.
+//!
+//! [`Mutex`]: crate::std::sync::Mutex
+//! [`RwLock`]: crate::std::sync::RwLock
+
+#[cfg(test)]
+mod tests;
+
+use crate::std::cell::{RefCell, UnsafeCell};
+use crate::std::collections::{BTreeMap, VecDeque};
+use crate::std::fmt::{self, Write};
+use crate::std::os::dragonos::thread::gettid;
+use crate::std::sync::atomic::{AtomicUsize, Ordering};
+use crate::std::sys::locks as sys;
+use crate::std::sys_common::backtrace::trace_frame_pointers;
+
+// The most callers recorded for each acquisition.
+const FRAMES: usize = 8;
+// The most locks tracked per thread; any more held at once aren't checked.
+const MAX_HELD: usize = 16;
+
+/// The number of a lock, assigned when it is first taken.
+pub(crate) struct LockId(AtomicUsize);
+
+impl LockId {
+    pub(crate) const fn new() -> LockId {
+        LockId(AtomicUsize::new(0))
+    }
+
+    fn get(&self) -> usize {
+        static NEXT: AtomicUsize = AtomicUsize::new(1);
+
+        let id = self.0.load(Ordering::Relaxed);
+        if id != 0 {
+            return id;
+        }
+        let new = NEXT.fetch_add(1, Ordering::Relaxed);
+        match self
+            .0
+            .compare_exchange(0, new, Ordering::Relaxed, Ordering::Relaxed)
+        {
+            Ok(_) => new,
+            Err(id) => id,
+        }
+    }
+}
+
+impl Drop for LockId {
+    // Forgets the orders recorded for the lock, which can't be taken again,
+    // so that the graph only holds the locks that still exist.
+    fn drop(&mut self) {
+        let id = *self.0.get_mut();
+        if id == 0 {
+            return;
+        }
+        GRAPH.lock.lock();
+        // SAFETY: `GRAPH.lock` is held.
+        let edges = unsafe { &mut *GRAPH.edges.get() };
+        edges.remove(&id);
+        edges.retain(|_, edges| {
+            edges.retain(|edge| edge.to != id);
+            !edges.is_empty()
+        });
+        // SAFETY: locked above.
+        unsafe { GRAPH.lock.unlock() };
+    }
+}
+
+// Where a lock was taken.
+#[derive(Clone, Copy)]
+struct Trace {
+    frames: [usize; FRAMES],
+    len: usize,
+}
+
+impl Trace {
+    const EMPTY: Trace = Trace {
+        frames: [0; FRAMES],
+        len: 0,
+    };
+
+    // Must be inlined into the function called by the lock, whose own frame
+    // is then skipped.
+    #[inline(always)]
+    fn capture() -> Trace {
+        let mut frames = [0; FRAMES + 1];
+        let len = trace_frame_pointers(&mut frames).max(1);
+        let mut trace = Trace::EMPTY;
+        trace.frames[..len - 1].copy_from_slice(&frames[1..len]);
+        trace.len = len - 1;
+        trace
+    }
+}
+
+impl fmt::Display for Trace {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.len == 0 {
+            return f.write_str(" an unknown place");
+        }
+        for frame in &self.frames[..self.len] {
+            write!(f, " {frame:#x}")?;
+        }
+        Ok(())
+    }
+}
+
+#[derive(Clone, Copy)]
+struct Held {
+    id: usize,
+    exclusive: bool,
+    trace: Trace,
+}
+
+// The locks a thread holds, in the order it took them. A fixed array, so
+// that the thread local needs no destructor and works until the thread
+// exits.
+struct HeldLocks {
+    locks: [Held; MAX_HELD],
+    len: usize,
+}
+
+impl HeldLocks {
+    fn iter(&self) -> impl Iterator<Item = &Held> {
+        self.locks[..self.len].iter()
+    }
+
+    fn push(&mut self, held: Held) {
+        if self.len < MAX_HELD {
+            self.locks[self.len] = held;
+            self.len += 1;
+        }
+    }
+
+    fn last_mut(&mut self, id: usize) -> Option<&mut Held> {
+        self.locks[..self.len]
+            .iter_mut()
+            .rev()
+            .find(|held| held.id == id)
+    }
+
+    fn remove(&mut self, id: usize) {
+        if let Some(i) = self.locks[..self.len]
+            .iter()
+            .rposition(|held| held.id == id)
+        {
+            self.locks.copy_within(i + 1..self.len, i);
+            self.len -= 1;
+        }
+    }
+}
+
+thread_local! {
+    static HELD: RefCell<HeldLocks> = const {
+        RefCell::new(HeldLocks {
+            locks: [Held { id: 0, exclusive: false, trace: Trace::EMPTY }; MAX_HELD],
+            len: 0,
+        })
+    };
+}
+
+// That `to` was taken while `from` was held.
+struct Edge {
+    to: usize,
+    thread: u32,
+    from_trace: Trace,
+    to_trace: Trace,
+}
+
+// The recorded orders. Kept free of cycles, since an order that would close
+// one panics instead of being recorded.
+struct Graph {
+    // Not a `Mutex`, which would check itself.
+    lock: sys::Mutex,
+    edges: UnsafeCell<BTreeMap<usize, Vec<Edge>>>,
+}
+
+unsafe impl Sync for Graph {}
+
+static GRAPH: Graph = Graph {
+    lock: sys::Mutex::new(),
+    edges: UnsafeCell::new(BTreeMap::new()),
+};
+
+/// Called before blocking on the lock `lock`, exclusively or shared.
+///
+/// Panics if the thread already holds `lock` in a way that blocks it, or if
+/// `lock` was taken before one of the locks the thread holds.
+#[inline(never)]
+pub(crate) fn acquire(lock: &LockId, exclusive: bool) {
+    let id = lock.get();
+    let trace = Trace::capture();
+    let deadlock = HELD
+        .try_with(|held| {
+            // Borrowed further up the stack if a lock is taken while
+            // recording another, such as by the allocator.
+            let mut held = held.try_borrow_mut().ok()?;
+            // The lock isn't recorded if this panics, so that a caught panic
+            // doesn't leave a lock the thread never took in its held list.
+            let deadlock = check(&held, id, exclusive, &trace);
+            if deadlock.is_none() {
+                held.push(Held {
+                    id,
+                    exclusive,
+                    trace,
+                });
+            }
+            deadlock
+        })
+        .ok()
+        .flatten();
+    if let Some(message) = deadlock {
+        panic!("{message}");
+    }
+}
+
+/// Called after taking `lock` without blocking, with `try_lock` and the
+/// like. The order isn't checked, since this can't deadlock.
+#[inline(never)]
+pub(crate) fn acquired(lock: &LockId, exclusive: bool) {
+    let id = lock.get();
+    let trace = Trace::capture();
+    let _ = HELD.try_with(|held| {
+        if let Ok(mut held) = held.try_borrow_mut() {
+            held.push(Held {
+                id,
+                exclusive,
+                trace,
+            });
+        }
+    });
+}
+
+/// Called when `lock` is released.
+pub(crate) fn release(lock: &LockId) {
+    let id = lock.0.load(Ordering::Relaxed);
+    let _ = HELD.try_with(|held| {
+        if let Ok(mut held) = held.try_borrow_mut() {
+            held.remove(id);
+        }
+    });
+}
+
+/// Called when the exclusive hold on `lock` turns into a shared one.
+pub(crate) fn downgrade(lock: &LockId) {
+    let id = lock.0.load(Ordering::Relaxed);
+    let _ = HELD.try_with(|held| {
+        if let Ok(mut held) = held.try_borrow_mut() {
+            if let Some(held) = held.last_mut(id) {
+                held.exclusive = false;
+            }
+        }
+    });
+}
+
+// Returns the panic message if taking `id` while holding `held` can
+// deadlock, and records the order otherwise.
+fn check(held: &HeldLocks, id: usize, exclusive: bool, trace: &Trace) -> Option<String> {
+    let thread = gettid();
+    if let Some(same) = held.iter().find(|held| held.id == id) {
+        if exclusive || same.exclusive {
+            return Some(format!(
+                "deadlock: thread {thread} is locking lock #{id} at{trace}, \
+                 which it already holds since{}",
+                same.trace
+            ));
+        }
+    }
+
+    GRAPH.lock.lock();
+    // SAFETY: `GRAPH.lock` is held.
+    let edges = unsafe { &mut *GRAPH.edges.get() };
+    let mut message = None;
+    for before in held.iter().filter(|held| held.id != id) {
+        let known = edges
+            .get(&before.id)
+            .map_or(false, |edges| edges.iter().any(|edge| edge.to == id));
+        if known {
+            continue;
+        }
+        if let Some(path) = find_path(edges, id, before.id) {
+            message = Some(describe(thread, before, id, trace, &path));
+            break;
+        }
+        edges.entry(before.id).or_default().push(Edge {
+            to: id,
+            thread,
+            from_trace: before.trace,
+            to_trace: *trace,
+        });
+    }
+    // SAFETY: locked above.
+    unsafe { GRAPH.lock.unlock() };
+    message
+}
+
+// Finds the recorded orders that lead from `from` to `to`, as the lock each
+// one starts from and the order itself.
+fn find_path<'a>(
+    edges: &'a BTreeMap<usize, Vec<Edge>>,
+    from: usize,
+    to: usize,
+) -> Option<Vec<(usize, &'a Edge)>> {
+    let mut parents: BTreeMap<usize, (usize, &Edge)> = BTreeMap::new();
+    let mut queue = VecDeque::from([from]);
+    while let Some(node) = queue.pop_front() {
+        for edge in edges.get(&node).into_iter().flatten() {
+            if edge.to == from || parents.contains_key(&edge.to) {
+                continue;
+            }
+            parents.insert(edge.to, (node, edge));
+            if edge.to == to {
+                let mut path = Vec::new();
+                let mut node = to;
+                while node != from {
+                    let (parent, edge) = parents[&node];
+                    path.push((parent, edge));
+                    node = parent;
+                }
+                path.reverse();
+                return Some(path);
+            }
+            queue.push_back(edge.to);
+        }
+    }
+    None
+}
+
+fn describe(
+    thread: u32,
+    before: &Held,
+    id: usize,
+    trace: &Trace,
+    path: &[(usize, &Edge)],
+) -> String {
+    let mut message = format!(
+        "possible deadlock: thread {thread} is locking lock #{id} while holding lock #{}, \
+         but they have been locked in the opposite order before:",
+        before.id
+    );
+    for (from, edge) in path {
+        let _ = write!(
+            message,
+            "\n  thread {} locked lock #{from} at{}\n    and then lock #{} at{}",
+            edge.thread, edge.from_trace, edge.to, edge.to_trace
+        );
+    }
+    let _ = write!(
+        message,
+        "\nthread {thread} locked lock #{} at{}\n  and is now locking lock #{id} at{trace}",
+        before.id, before.trace
+    );
+    message
+}
@@ -0,0 +1,122 @@
+use crate::std::sync::{Arc, Mutex, RwLock, RwLockWriteGuard};
+use crate::std::thread;
+
+#[test]
+fn same_order_is_fine() {
+    let a = Arc::new(Mutex::new(()));
+    let b = Arc::new(Mutex::new(()));
+    let (a2, b2) = (a.clone(), b.clone());
+    thread::spawn(move || {
+        let _a = a2.lock().unwrap();
+        let _b = b2.lock().unwrap();
+    })
+    .join()
+    .unwrap();
+    let _a = a.lock().unwrap();
+    let _b = b.lock().unwrap();
+}
+
+#[test]
+#[should_panic(expected = "possible deadlock")]
+fn opposite_order_panics() {
+    let a = Arc::new(Mutex::new(()));
+    let b = Arc::new(RwLock::new(()));
+    let (a2, b2) = (a.clone(), b.clone());
+    thread::spawn(move || {
+        let _a = a2.lock().unwrap();
+        let _b = b2.read().unwrap();
+    })
+    .join()
+    .unwrap();
+    let _b = b.write().unwrap();
+    let _a = a.lock().unwrap();
+}
+
+#[test]
+#[should_panic(expected = "possible deadlock")]
+fn longer_cycle_panics() {
+    let a = Mutex::new(());
+    let b = Mutex::new(());
+    let c = Mutex::new(());
+    {
+        let _a = a.lock().unwrap();
+        let _b = b.lock().unwrap();
+    }
+    {
+        let _b = b.lock().unwrap();
+        let _c = c.lock().unwrap();
+    }
+    let _c = c.lock().unwrap();
+    let _a = a.lock().unwrap();
+}
+
+#[test]
+#[should_panic(expected = "which it already holds")]
+fn relocking_panics() {
+    let a = Mutex::new(());
+    let _first = a.lock().unwrap();
+    let _second = a.lock();
+}
+
+#[test]
+fn try_lock_and_shared_reads_are_not_checked() {
+    let a = Mutex::new(());
+    let b = RwLock::new(());
+    {
+        let _a = a.lock().unwrap();
+        let _b = b.read().unwrap();
+        let _again = b.read().unwrap();
+    }
+    let _b = b.write().unwrap();
+    let _a = a.try_lock().unwrap();
+}
+
+#[test]
+fn downgraded_locks_can_be_read_again() {
+    let a = RwLock::new(());
+    let first = RwLockWriteGuard::downgrade(a.write().unwrap());
+    let _second = a.read().unwrap();
+    drop(first);
+}
+
+#[test]
+fn dropped_locks_are_forgotten() {
+    use super::{acquire, release, LockId, GRAPH};
+
+    fn has_edge(from: usize, to: usize) -> bool {
+        GRAPH.lock.lock();
+        // SAFETY: `GRAPH.lock` is held.
+        let edges = unsafe { &*GRAPH.edges.get() };
+        let found = edges
+            .get(&from)
+            .map_or(false, |edges| edges.iter().any(|edge| edge.to == to));
+        // SAFETY: locked above.
+        unsafe { GRAPH.lock.unlock() };
+        found
+    }
+
+    let a = LockId::new();
+    let b = LockId::new();
+    acquire(&a, true);
+    acquire(&b, true);
+    release(&b);
+    release(&a);
+    let (a_id, b_id) = (a.get(), b.get());
+    assert!(has_edge(a_id, b_id));
+    drop(b);
+    assert!(!has_edge(a_id, b_id));
+}
+
+#[test]
+fn caught_panic_does_not_record_the_lock() {
+    use super::{acquire, release, LockId};
+    use crate::std::panic::{self, AssertUnwindSafe};
+
+    let a = LockId::new();
+    acquire(&a, true);
+    assert!(panic::catch_unwind(AssertUnwindSafe(|| acquire(&a, true))).is_err());
+    release(&a);
+    // Would panic if the failed acquisition was still recorded as held.
+    acquire(&a, true);
+    release(&a);
+}
@@ -125,6 +125,9 @@
 //! - [`Condvar`]: Condition Variable, providing the ability to block
 //!   a thread while waiting for an event to occur.
 //!
+//! - [`mpmc`]: Multi-producer, multi-consumer queues, which let several
+//!   threads take work from the same channel. Only available on DragonOS.
+//!
 //! - [`mpsc`]: Multi-producer, single-consumer queues, used for
 //!   message-based communication. Can provide a lightweight
 //!   inter-thread synchronisation mechanism, at the cost of some
@@ -138,6 +141,10 @@
 //! - [`OnceLock`]: Used for thread-safe, one-time initialization of a
 //!   global variable.
 //!
+//! - [`ReentrantLock`]: Like [`Mutex`], but the thread holding the lock
+//!   may lock it again without deadlocking, and only gets shared access to
+//!   the data.
+//!
 //! - [`RwLock`]: Provides a mutual exclusion mechanism which allows
 //!   multiple readers at the same time, while allowing only one
 //!   writer at a time. In some cases, this can be more efficient than
@@ -146,10 +153,12 @@
 //! [`Arc`]: crate::std::sync::Arc
 //! [`Barrier`]: crate::std::sync::Barrier
 //! [`Condvar`]: crate::std::sync::Condvar
+//! [`mpmc`]: crate::std::sync::mpmc
 //! [`mpsc`]: crate::std::sync::mpsc
 //! [`Mutex`]: crate::std::sync::Mutex
 //! [`Once`]: crate::std::sync::Once
 //! [`OnceLock`]: crate::std::sync::OnceLock
+//! [`ReentrantLock`]: crate::std::sync::ReentrantLock
 //! [`RwLock`]: crate::std::sync::RwLock
 
 pub use alloc::sync::{Arc, Weak};
@@ -166,18 +175,22 @@ pub use self::rwlock::{RwLock, RwLockReadGuard, RwLockWriteGuard};
 
 pub use self::lazy_lock::LazyLock;
 pub use self::once_lock::OnceLock;
-
-pub(crate) use self::remutex::{ReentrantMutex, ReentrantMutexGuard};
+pub use self::reentrant_lock::{ReentrantLock, ReentrantLockGuard};
 
 pub mod mpsc;
 
 mod barrier;
 mod condvar;
 mod lazy_lock;
+#[cfg(all(target_os = "dragonos", feature = "deadlock-detection"))]
+pub(crate) mod lock_order;
+#[cfg(target_os = "dragonos")]
+pub mod mpmc;
+#[cfg(not(target_os = "dragonos"))]
 mod mpmc;
 mod mutex;
 pub(crate) mod once;
 mod once_lock;
 mod poison;
-mod remutex;
+mod reentrant_lock;
 mod rwlock;
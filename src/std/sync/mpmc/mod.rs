@@ -1,8 +1,40 @@
 //! Multi-producer multi-consumer channels.
-
-// This module is not currently exposed publicly, but is used
-// as the implementation for the channels in `sync::mpsc`. The
-// implementation comes from the crossbeam-channel crate:
+//!
+//! These work like the channels in [`mpsc`], which are built on them, except
+//! that a [`Receiver`] can be cloned as well. Every message is received by
+//! exactly one of the receivers, so a pool of workers can share one queue of
+//! jobs without a mutex around the receiver serializing them.
+//!
+//! ```
+//! use std::sync::mpmc;
+//! use std::thread;
+//!
+//! let (jobs, queue) = mpmc::channel();
+//! let workers: Vec<_> = (0..4)
+//!     .map(|_| {
+//!         let queue = queue.clone();
+//!         // `recv` fails once every sender is gone and the queue is empty.
+//!         thread::spawn(move || {
+//!             let mut sum = 0;
+//!             while let Ok(job) = queue.recv() {
+//!                 sum += job;
+//!             }
+//!             sum
+//!         })
+//!     })
+//!     .collect();
+//! for job in 1..=100 {
+//!     jobs.send(job).unwrap();
+//! }
+//! drop(jobs);
+//! let total: u32 = workers.into_iter().map(|w| w.join().unwrap()).sum();
+//! assert_eq!(total, 5050);
+//! ```
+//!
+//! [`mpsc`]: crate::std::sync::mpsc
+
+// This module is also the implementation for the channels in
+// `sync::mpsc`. The implementation comes from the crossbeam-channel crate:
 //
 // Copyright (c) 2019 The Crossbeam Project Developers
 //
@@ -147,9 +179,8 @@ impl<T> Sender<T> {
     }
 }
 
-// The methods below are not used by `sync::mpsc`, but
-// are useful and we'll likely want to expose them
-// eventually
+// The methods below are not used by `sync::mpsc`, and
+// are only reachable through `sync::mpmc` on DragonOS.
 #[allow(unused)]
 impl<T> Sender<T> {
     /// Waits for a message to be sent into the channel, but only for a limited time.
@@ -266,6 +297,8 @@ impl<T> fmt::Debug for Sender<T> {
 }
 
 /// The receiving side of a channel.
+///
+/// Receivers can be cloned, and each message is received by only one of them.
 pub struct Receiver<T> {
     flavor: ReceiverFlavor<T>,
 }
@@ -355,9 +388,8 @@ impl<T> Receiver<T> {
     }
 }
 
-// The methods below are not used by `sync::mpsc`, but
-// are useful and we'll likely want to expose them
-// eventually
+// The methods below are not used by `sync::mpsc`, and
+// are only reachable through `sync::mpmc` on DragonOS.
 #[allow(unused)]
 impl<T> Receiver<T> {
     /// Returns `true` if the channel is empty.
@@ -440,3 +472,6 @@ impl<T> fmt::Debug for Receiver<T> {
         f.pad("Receiver { .. }")
     }
 }
+
+#[cfg(test)]
+mod tests;
@@ -0,0 +1,73 @@
+use super::*;
+use crate::std::sync::atomic::{AtomicUsize, Ordering};
+use crate::std::sync::Arc;
+use crate::std::thread;
+use crate::std::time::{Duration, Instant};
+
+#[test]
+fn smoke() {
+    let (tx, rx) = channel::<i32>();
+    tx.send(1).unwrap();
+    assert_eq!(rx.clone().recv().unwrap(), 1);
+}
+
+#[test]
+fn each_message_received_once() {
+    for (tx, rx) in [channel::<usize>(), sync_channel(0), sync_channel(4)] {
+        let received = Arc::new(AtomicUsize::new(0));
+        let workers: Vec<_> = (0..4)
+            .map(|_| {
+                let rx = rx.clone();
+                let received = received.clone();
+                thread::spawn(move || {
+                    let mut sum = 0;
+                    while let Ok(n) = rx.recv() {
+                        received.fetch_add(1, Ordering::Relaxed);
+                        sum += n;
+                    }
+                    sum
+                })
+            })
+            .collect();
+        drop(rx);
+        for n in 0..1000 {
+            tx.send(n).unwrap();
+        }
+        drop(tx);
+        let sum: usize = workers.into_iter().map(|w| w.join().unwrap()).sum();
+        assert_eq!(sum, (0..1000).sum());
+        assert_eq!(received.load(Ordering::Relaxed), 1000);
+    }
+}
+
+#[test]
+fn recv_timeout_with_several_receivers() {
+    let (tx, rx) = sync_channel::<i32>(1);
+    let rx2 = rx.clone();
+    let start = Instant::now();
+    assert_eq!(
+        rx.recv_timeout(Duration::from_millis(10)),
+        Err(RecvTimeoutError::Timeout)
+    );
+    assert!(start.elapsed() >= Duration::from_millis(10));
+
+    let waiter = thread::spawn(move || rx2.recv_timeout(Duration::from_secs(10)));
+    tx.send(7).unwrap();
+    assert_eq!(waiter.join().unwrap(), Ok(7));
+
+    drop(tx);
+    assert_eq!(
+        rx.recv_timeout(Duration::from_secs(10)),
+        Err(RecvTimeoutError::Disconnected)
+    );
+}
+
+#[test]
+fn send_fails_once_every_receiver_is_gone() {
+    let (tx, rx) = channel::<i32>();
+    let rx2 = rx.clone();
+    drop(rx);
+    tx.send(1).unwrap();
+    drop(rx2);
+    assert_eq!(tx.send(2), Err(SendError(2)));
+}
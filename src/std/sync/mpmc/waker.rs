@@ -140,9 +140,11 @@ impl Drop for Waker {
     }
 }
 
-/// A waker that can be shared among threads without locking.
+/// A waker that can be shared among threads.
 ///
-/// This is a simple wrapper around `Waker` that internally uses a mutex for synchronization.
+/// This is a wrapper around `Waker` that uses a mutex for synchronization. Only threads that
+/// block take it to register themselves: `notify`, which every send and receive calls, checks
+/// `is_empty` first and returns without locking when nobody is waiting.
 pub(crate) struct SyncWaker {
     /// The inner `Waker`.
     inner: Mutex<Waker>,
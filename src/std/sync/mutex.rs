@@ -4,6 +4,8 @@ mod tests;
 use crate::std::cell::UnsafeCell;
 use crate::std::fmt;
 use crate::std::ops::{Deref, DerefMut};
+#[cfg(all(target_os = "dragonos", feature = "deadlock-detection"))]
+use crate::std::sync::lock_order;
 use crate::std::sync::{poison, LockResult, TryLockError, TryLockResult};
 use crate::std::sys::locks as sys;
 
@@ -174,6 +176,8 @@ use crate::std::sys::locks as sys;
 pub struct Mutex<T: ?Sized> {
     inner: sys::Mutex,
     poison: poison::Flag,
+    #[cfg(all(target_os = "dragonos", feature = "deadlock-detection"))]
+    id: lock_order::LockId,
     data: UnsafeCell<T>,
 }
 
@@ -222,6 +226,8 @@ impl<T> Mutex<T> {
         Mutex {
             inner: sys::Mutex::new(),
             poison: poison::Flag::new(),
+            #[cfg(all(target_os = "dragonos", feature = "deadlock-detection"))]
+            id: lock_order::LockId::new(),
             data: UnsafeCell::new(t),
         }
     }
@@ -264,6 +270,8 @@ impl<T: ?Sized> Mutex<T> {
     /// assert_eq!(*mutex.lock().unwrap(), 10);
     /// ```
     pub fn lock(&self) -> LockResult<MutexGuard<'_, T>> {
+        #[cfg(all(target_os = "dragonos", feature = "deadlock-detection"))]
+        lock_order::acquire(&self.id, true);
         unsafe {
             self.inner.lock();
             MutexGuard::new(self)
@@ -312,6 +320,8 @@ impl<T: ?Sized> Mutex<T> {
     pub fn try_lock(&self) -> TryLockResult<MutexGuard<'_, T>> {
         unsafe {
             if self.inner.try_lock() {
+                #[cfg(all(target_os = "dragonos", feature = "deadlock-detection"))]
+                lock_order::acquired(&self.id, true);
                 Ok(MutexGuard::new(self)?)
             } else {
                 Err(TryLockError::WouldBlock)
@@ -512,6 +522,8 @@ impl<T: ?Sized> Drop for MutexGuard<'_, T> {
     fn drop(&mut self) {
         unsafe {
             self.lock.poison.done(&self.poison);
+            #[cfg(all(target_os = "dragonos", feature = "deadlock-detection"))]
+            lock_order::release(&self.lock.id);
             self.lock.inner.unlock();
         }
     }
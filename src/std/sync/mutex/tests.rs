@@ -1,6 +1,6 @@
 use crate::std::sync::atomic::{AtomicUsize, Ordering};
 use crate::std::sync::mpsc::channel;
-use crate::std::sync::{Arc, Condvar, Mutex};
+use crate::std::sync::{Arc, Condvar, Mutex, TryLockError};
 use crate::std::thread;
 
 struct Packet<T>(Arc<(Mutex<T>, Condvar)>);
@@ -51,6 +51,28 @@ fn lots_and_lots() {
     assert_eq!(*m.lock().unwrap(), J * K * 2);
 }
 
+#[test]
+fn contended_waiters_are_woken() {
+    // Waiters that find the mutex held long enough give up spinning and
+    // sleep; every unlock must still wake one of them.
+    const N: usize = 8;
+
+    let m = Arc::new(Mutex::new(0));
+    let guard = m.lock().unwrap();
+    let handles: Vec<_> = (0..N)
+        .map(|_| {
+            let m = m.clone();
+            thread::spawn(move || *m.lock().unwrap() += 1)
+        })
+        .collect();
+    thread::sleep(crate::std::time::Duration::from_millis(50));
+    drop(guard);
+    for handle in handles {
+        handle.join().unwrap();
+    }
+    assert_eq!(*m.lock().unwrap(), N);
+}
+
 #[test]
 fn try_lock() {
     let m = Mutex::new(());
@@ -82,6 +104,7 @@ fn test_into_inner_drop() {
 }
 
 #[test]
+#[cfg(not(feature = "no-poison"))]
 fn test_into_inner_poison() {
     let m = Arc::new(Mutex::new(NonCopy(10)));
     let m2 = m.clone();
@@ -106,6 +129,7 @@ fn test_get_mut() {
 }
 
 #[test]
+#[cfg(not(feature = "no-poison"))]
 fn test_get_mut_poison() {
     let m = Arc::new(Mutex::new(NonCopy(10)));
     let m2 = m.clone();
@@ -175,6 +199,7 @@ fn test_arc_condvar_poison() {
 }
 
 #[test]
+#[cfg(not(feature = "no-poison"))]
 fn test_mutex_arc_poison() {
     let arc = Arc::new(Mutex::new(1));
     assert!(!arc.is_poisoned());
@@ -236,3 +261,65 @@ fn test_mutex_unsized() {
     let comp: &[i32] = &[4, 2, 5];
     assert_eq!(&*mutex.lock().unwrap(), comp);
 }
+
+#[test]
+#[cfg(not(feature = "no-poison"))]
+fn try_lock_contended_and_poisoned() {
+    let m = Arc::new(Mutex::new(0));
+    let g = m.lock().unwrap();
+    let m2 = m.clone();
+    let waiter = thread::spawn(move || *m2.lock().unwrap() += 1);
+    // `try_lock` never waits, even with another thread queued on the lock.
+    assert!(matches!(m.try_lock(), Err(TryLockError::WouldBlock)));
+    drop(g);
+    waiter.join().unwrap();
+
+    let m2 = m.clone();
+    let _ = thread::spawn(move || {
+        let _g = m2.lock().unwrap();
+        panic!("poison the mutex");
+    })
+    .join();
+    // A poisoned mutex still locks, and reports the poison.
+    match m.try_lock() {
+        Err(TryLockError::Poisoned(e)) => assert_eq!(*e.into_inner(), 1),
+        _ => panic!("expected a poisoned mutex"),
+    }
+    assert!(matches!(m.lock(), Err(_)));
+}
+
+#[test]
+#[cfg(not(feature = "no-poison"))]
+fn test_clear_poison() {
+    let m = Arc::new(Mutex::new(NonCopy(10)));
+    let m2 = m.clone();
+    let _ = thread::spawn(move || {
+        let _lock = m2.lock().unwrap();
+        panic!("test panic in inner thread to poison mutex");
+    })
+    .join();
+
+    assert!(m.is_poisoned());
+    let mut guard = m.lock().unwrap_or_else(|e| e.into_inner());
+    *guard = NonCopy(20);
+    drop(guard);
+    m.clear_poison();
+    assert!(!m.is_poisoned());
+    assert_eq!(*m.lock().unwrap(), NonCopy(20));
+}
+
+#[test]
+#[cfg(feature = "no-poison")]
+fn test_panic_does_not_poison() {
+    let m = Arc::new(Mutex::new(NonCopy(10)));
+    let m2 = m.clone();
+    let _ = thread::spawn(move || {
+        let _lock = m2.lock().unwrap();
+        panic!("test panic in inner thread");
+    })
+    .join();
+
+    assert!(!m.is_poisoned());
+    assert_eq!(*m.lock().unwrap(), NonCopy(10));
+    assert!(m.try_lock().is_ok());
+}
@@ -201,6 +201,59 @@ impl Once {
         self.inner.call(true, &mut |p| f.take().unwrap()(p));
     }
 
+    /// Blocks the current thread until initialization has completed.
+    ///
+    /// Waiting threads sleep until the initialization finishes instead of
+    /// spinning. Unlike [`call_once`], this doesn't run anything itself, so it
+    /// lets threads that can't initialize the value wait for the one that
+    /// does.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use std::sync::Once;
+    /// use std::thread;
+    ///
+    /// static READY: Once = Once::new();
+    ///
+    /// let thread = thread::spawn(|| {
+    ///     READY.wait();
+    ///     assert!(READY.is_completed());
+    /// });
+    ///
+    /// READY.call_once(|| println!("Complete!"));
+    /// thread.join().unwrap();
+    /// ```
+    ///
+    /// # Panics
+    ///
+    /// If this [`Once`] has been poisoned because an initialization closure
+    /// has panicked, this method will also panic. Use [`wait_force`] if this
+    /// behavior is not desired.
+    ///
+    /// [`call_once`]: Once::call_once
+    /// [`wait_force`]: Once::wait_force
+    #[track_caller]
+    pub fn wait(&self) {
+        if !self.inner.is_completed() {
+            self.inner.wait(false);
+        }
+    }
+
+    /// Blocks the current thread until initialization has completed, ignoring
+    /// poisoning.
+    ///
+    /// A poisoned [`Once`] is waited on until a later [`call_once_force`]
+    /// succeeds.
+    ///
+    /// [`call_once_force`]: Once::call_once_force
+    #[track_caller]
+    pub fn wait_force(&self) {
+        if !self.inner.is_completed() {
+            self.inner.wait(true);
+        }
+    }
+
     /// Returns `true` if some [`call_once()`] call has completed
     /// successfully. Specifically, `is_completed` will return false in
     /// the following situations:
@@ -114,3 +114,42 @@ fn wait_for_force_to_finish() {
     assert!(t1.join().is_ok());
     assert!(t2.join().is_ok());
 }
+
+#[test]
+fn wait() {
+    static O: Once = Once::new();
+
+    let waiters: Vec<_> = (0..4)
+        .map(|_| {
+            thread::spawn(|| {
+                O.wait();
+                assert!(O.is_completed());
+            })
+        })
+        .collect();
+
+    O.call_once(|| {});
+    for waiter in waiters {
+        waiter.join().unwrap();
+    }
+}
+
+#[test]
+fn wait_on_poisoned() {
+    let once = Once::new();
+
+    panic::catch_unwind(|| once.call_once(|| panic!())).unwrap_err();
+    panic::catch_unwind(|| once.wait()).unwrap_err();
+}
+
+#[test]
+fn wait_force_on_poisoned() {
+    static O: Once = Once::new();
+
+    panic::catch_unwind(|| O.call_once(|| panic!())).unwrap_err();
+
+    let waiter = thread::spawn(|| O.wait_force());
+    O.call_once_force(|state| assert!(state.is_poisoned()));
+    waiter.join().unwrap();
+    assert!(O.is_completed());
+}
@@ -202,3 +202,17 @@ fn dropck() {
         cell.set(&s).unwrap();
     }
 }
+
+#[test]
+fn wait() {
+    static CELL: OnceLock<u32> = OnceLock::new();
+
+    let (tx, rx) = channel();
+    let waiter = thread::spawn(move || {
+        tx.send(()).unwrap();
+        *CELL.wait()
+    });
+    rx.recv().unwrap();
+    CELL.set(92).unwrap();
+    assert_eq!(waiter.join().unwrap(), 92);
+}
@@ -1,9 +1,15 @@
 use crate::std::error::Error;
 use crate::std::fmt;
+#[cfg(not(feature = "no-poison"))]
 use crate::std::sync::atomic::{AtomicBool, Ordering};
+#[cfg(not(feature = "no-poison"))]
 use crate::std::thread;
 
+// With the `no-poison` feature, locks are never poisoned: the flag is empty
+// and the checks against it compile away, so that taking a lock doesn't ask
+// whether the thread is panicking.
 pub struct Flag {
+    #[cfg(not(feature = "no-poison"))]
     failed: AtomicBool,
 }
 
@@ -22,6 +28,7 @@ impl Flag {
     #[inline]
     pub const fn new() -> Flag {
         Flag {
+            #[cfg(not(feature = "no-poison"))]
             failed: AtomicBool::new(false),
         }
     }
@@ -40,6 +47,7 @@ impl Flag {
     #[inline]
     pub fn guard(&self) -> LockResult<Guard> {
         let ret = Guard {
+            #[cfg(not(feature = "no-poison"))]
             panicking: thread::panicking(),
         };
         if self.get() {
@@ -50,24 +58,30 @@ impl Flag {
     }
 
     #[inline]
-    pub fn done(&self, guard: &Guard) {
-        if !guard.panicking && thread::panicking() {
+    pub fn done(&self, _guard: &Guard) {
+        #[cfg(not(feature = "no-poison"))]
+        if !_guard.panicking && thread::panicking() {
             self.failed.store(true, Ordering::Relaxed);
         }
     }
 
     #[inline]
     pub fn get(&self) -> bool {
-        self.failed.load(Ordering::Relaxed)
+        #[cfg(not(feature = "no-poison"))]
+        return self.failed.load(Ordering::Relaxed);
+        #[cfg(feature = "no-poison")]
+        return false;
     }
 
     #[inline]
     pub fn clear(&self) {
-        self.failed.store(false, Ordering::Relaxed)
+        #[cfg(not(feature = "no-poison"))]
+        self.failed.store(false, Ordering::Relaxed);
     }
 }
 
 pub struct Guard {
+    #[cfg(not(feature = "no-poison"))]
     panicking: bool,
 }
 
@@ -78,6 +92,9 @@ pub struct Guard {
 /// each lock, but once a lock is poisoned then all future acquisitions will
 /// return this error.
 ///
+/// Built with the `no-poison` feature, locks are never poisoned, so this
+/// error is never returned and [`is_poisoned`] is always `false`.
+///
 /// # Examples
 ///
 /// ```
@@ -104,6 +121,7 @@ pub struct Guard {
 /// ```
 /// [`Mutex`]: crate::std::sync::Mutex
 /// [`RwLock`]: crate::std::sync::RwLock
+/// [`is_poisoned`]: crate::std::sync::Mutex::is_poisoned
 pub struct PoisonError<T> {
     guard: T,
 }
@@ -0,0 +1,326 @@
+#[cfg(all(test, not(target_os = "emscripten")))]
+mod tests;
+
+use crate::std::cell::UnsafeCell;
+use crate::std::fmt;
+use crate::std::num::NonZeroU64;
+use crate::std::ops::Deref;
+use crate::std::panic::{RefUnwindSafe, UnwindSafe};
+use crate::std::sys::locks as sys;
+use crate::std::sys_common::thread_info;
+
+/// A reentrant mutual exclusion lock
+///
+/// This lock will block *other* threads waiting for the lock to become
+/// available. The thread which has already locked the mutex can lock it
+/// multiple times without blocking, preventing a common source of deadlocks.
+/// Since several guards of the same thread may exist at once, they only give
+/// shared access to the data; use a [`Cell`] or [`RefCell`] in the lock to
+/// mutate it.
+///
+/// This is the lock behind [`Stdout::lock`] and [`Stderr::lock`], which is
+/// why a panic hook or a `Drop` implementation may print while the thread is
+/// already printing.
+///
+/// ```
+/// use std::cell::RefCell;
+/// use std::sync::ReentrantLock;
+///
+/// static LOG: ReentrantLock<RefCell<Vec<String>>> = ReentrantLock::new(RefCell::new(Vec::new()));
+///
+/// fn log(message: &str) {
+///     let log = LOG.lock();
+///     if log.borrow().is_empty() {
+///         // Locking again on this thread doesn't deadlock.
+///         log_header();
+///     }
+///     log.borrow_mut().push(message.to_string());
+/// }
+///
+/// fn log_header() {
+///     LOG.lock().borrow_mut().push("start".to_string());
+/// }
+///
+/// log("hello");
+/// assert_eq!(*LOG.lock().borrow(), ["start", "hello"]);
+/// ```
+///
+/// Unlike [`Mutex`], the lock is not poisoned when a thread panics while
+/// holding it.
+///
+/// [`Cell`]: crate::std::cell::Cell
+/// [`RefCell`]: crate::std::cell::RefCell
+/// [`Stdout::lock`]: crate::std::io::Stdout::lock
+/// [`Stderr::lock`]: crate::std::io::Stderr::lock
+/// [`Mutex`]: crate::std::sync::Mutex
+/// [`ThreadId`]: crate::std::thread::ThreadId
+///
+/// ## Implementation details
+///
+/// The 'owner' field tracks which thread has locked the mutex.
+///
+/// We use the [`ThreadId`] of the current thread as the thread identifier.
+/// Unlike the address of a thread local variable, it is never reused by a
+/// later thread, so a thread can't mistake a lock that a thread which has
+/// since exited left locked for its own.
+///
+/// If `owner` is set to the identifier of the current thread,
+/// we assume the mutex is already locked and instead of locking it again,
+/// we increment `lock_count`.
+///
+/// When unlocking, we decrement `lock_count`, and only unlock the mutex when
+/// it reaches zero.
+///
+/// `lock_count` is protected by the mutex and only accessed by the thread that has
+/// locked the mutex, so needs no synchronization.
+///
+/// `owner` can be checked by other threads that want to see if they already
+/// hold the lock, so needs to be atomic. If it compares equal, we're on the
+/// same thread that holds the mutex and memory access can use relaxed ordering
+/// since we're not dealing with multiple threads. If it's not equal,
+/// synchronization is left to the mutex, making relaxed memory ordering for
+/// the `owner` field fine in all cases.
+pub struct ReentrantLock<T> {
+    mutex: sys::Mutex,
+    owner: Tid,
+    lock_count: UnsafeCell<u32>,
+    data: T,
+}
+
+unsafe impl<T: Send> Send for ReentrantLock<T> {}
+unsafe impl<T: Send> Sync for ReentrantLock<T> {}
+
+impl<T> UnwindSafe for ReentrantLock<T> {}
+impl<T> RefUnwindSafe for ReentrantLock<T> {}
+
+/// An RAII implementation of a "scoped lock" of a mutex. When this structure is
+/// dropped (falls out of scope), the lock will be unlocked.
+///
+/// The data protected by the mutex can be accessed through this guard via its
+/// Deref implementation.
+///
+/// # Mutability
+///
+/// Unlike `MutexGuard`, `ReentrantLockGuard` does not implement `DerefMut`,
+/// because implementation of the trait would violate Rust’s reference aliasing
+/// rules. Use interior mutability (usually `RefCell`) in order to mutate the
+/// guarded data.
+#[must_use = "if unused the ReentrantLock will immediately unlock"]
+#[clippy::has_significant_drop]
+pub struct ReentrantLockGuard<'a, T: 'a> {
+    lock: &'a ReentrantLock<T>,
+}
+
+impl<T> !Send for ReentrantLockGuard<'_, T> {}
+
+impl<T> ReentrantLock<T> {
+    /// Creates a new reentrant lock in an unlocked state.
+    pub const fn new(t: T) -> ReentrantLock<T> {
+        ReentrantLock {
+            mutex: sys::Mutex::new(),
+            owner: Tid::new(),
+            lock_count: UnsafeCell::new(0),
+            data: t,
+        }
+    }
+
+    /// Acquires the lock, blocking the current thread until it is able to do so.
+    ///
+    /// This function will block the caller until it is available to acquire the lock.
+    /// Upon returning, the thread is the only thread with the lock held. When the thread
+    /// calling this method already holds the lock, the call shall succeed without
+    /// blocking.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the thread already holds the lock `u32::MAX` times.
+    pub fn lock(&self) -> ReentrantLockGuard<'_, T> {
+        let this_thread = current_thread_id();
+        // Safety: We only touch lock_count when we own the lock.
+        unsafe {
+            if self.owner.contains(this_thread) {
+                self.increment_lock_count();
+            } else {
+                self.mutex.lock();
+                self.owner.set(Some(this_thread));
+                debug_assert_eq!(*self.lock_count.get(), 0);
+                *self.lock_count.get() = 1;
+            }
+        }
+        ReentrantLockGuard { lock: self }
+    }
+
+    /// Attempts to acquire this lock.
+    ///
+    /// If another thread holds the lock, then `None` is returned.
+    /// Otherwise, an RAII guard is returned.
+    ///
+    /// This function does not block.
+    pub fn try_lock(&self) -> Option<ReentrantLockGuard<'_, T>> {
+        let this_thread = current_thread_id();
+        // Safety: We only touch lock_count when we own the lock.
+        unsafe {
+            if self.owner.contains(this_thread) {
+                self.increment_lock_count();
+                Some(ReentrantLockGuard { lock: self })
+            } else if self.mutex.try_lock() {
+                self.owner.set(Some(this_thread));
+                debug_assert_eq!(*self.lock_count.get(), 0);
+                *self.lock_count.get() = 1;
+                Some(ReentrantLockGuard { lock: self })
+            } else {
+                None
+            }
+        }
+    }
+
+    /// Returns a mutable reference to the underlying data.
+    ///
+    /// Since this call borrows the lock mutably, no actual locking needs to
+    /// take place -- the mutable borrow statically guarantees no locks exist.
+    pub fn get_mut(&mut self) -> &mut T {
+        &mut self.data
+    }
+
+    /// Consumes this lock, returning the underlying data.
+    pub fn into_inner(self) -> T {
+        self.data
+    }
+
+    unsafe fn increment_lock_count(&self) {
+        *self.lock_count.get() = (*self.lock_count.get())
+            .checked_add(1)
+            .expect("lock count overflow in reentrant mutex");
+    }
+}
+
+impl<T: fmt::Debug> fmt::Debug for ReentrantLock<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut d = f.debug_struct("ReentrantLock");
+        match self.try_lock() {
+            Some(v) => d.field("data", &&*v),
+            None => d.field("data", &format_args!("<locked>")),
+        };
+        d.finish_non_exhaustive()
+    }
+}
+
+impl<T: Default> Default for ReentrantLock<T> {
+    fn default() -> Self {
+        Self::new(T::default())
+    }
+}
+
+impl<T> From<T> for ReentrantLock<T> {
+    fn from(t: T) -> Self {
+        Self::new(t)
+    }
+}
+
+impl<T> Deref for ReentrantLockGuard<'_, T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        &self.lock.data
+    }
+}
+
+impl<T: fmt::Debug> fmt::Debug for ReentrantLockGuard<'_, T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        (**self).fmt(f)
+    }
+}
+
+impl<T> Drop for ReentrantLockGuard<'_, T> {
+    #[inline]
+    fn drop(&mut self) {
+        // Safety: We own the lock.
+        unsafe {
+            *self.lock.lock_count.get() -= 1;
+            if *self.lock.lock_count.get() == 0 {
+                self.lock.owner.set(None);
+                self.lock.mutex.unlock();
+            }
+        }
+    }
+}
+
+// The ID of the current thread, as the owner of a lock.
+fn current_thread_id() -> NonZeroU64 {
+    thread_info::current_id_or_new().as_u64()
+}
+
+cfg_if::cfg_if! {
+    if #[cfg(target_has_atomic = "64")] {
+        use crate::std::sync::atomic::{AtomicU64, Ordering::Relaxed};
+
+        // The ID of the thread that holds a lock, or 0.
+        struct Tid(AtomicU64);
+
+        impl Tid {
+            const fn new() -> Self {
+                Self(AtomicU64::new(0))
+            }
+
+            #[inline]
+            fn contains(&self, owner: NonZeroU64) -> bool {
+                owner.get() == self.0.load(Relaxed)
+            }
+
+            // Safety: the caller must hold the lock, or be about to release
+            // it when passing `None`.
+            #[inline]
+            unsafe fn set(&self, tid: Option<NonZeroU64>) {
+                self.0.store(tid.map_or(0, NonZeroU64::get), Relaxed);
+            }
+        }
+    } else {
+        use crate::std::sync::atomic::{AtomicUsize, Ordering::Relaxed};
+
+        /// Get an address that is unique per running thread.
+        ///
+        /// This can be used as a non-null usize-sized ID.
+        fn current_thread_unique_ptr() -> usize {
+            // Use a non-drop type to make sure it's still available during thread destruction.
+            thread_local! { static X: u8 = const { 0 } }
+            X.with(|x| <*const _>::addr(x))
+        }
+
+        // Without 64-bit atomics, the ID is only read once the address of a
+        // thread local of the owner, which no other running thread shares,
+        // says that the current thread is the owner. Only the owner writes
+        // the ID, so then it can't change while it is read.
+        struct Tid {
+            tls_addr: AtomicUsize,
+            tid: UnsafeCell<u64>,
+        }
+
+        impl Tid {
+            const fn new() -> Self {
+                Self { tls_addr: AtomicUsize::new(0), tid: UnsafeCell::new(0) }
+            }
+
+            #[inline]
+            fn contains(&self, owner: NonZeroU64) -> bool {
+                // A thread that exited while holding the lock may have left
+                // its address behind, which a later thread can get again; the
+                // ID tells them apart.
+                self.tls_addr.load(Relaxed) == current_thread_unique_ptr()
+                    && unsafe { *self.tid.get() } == owner.get()
+            }
+
+            // Safety: the caller must hold the lock, or be about to release
+            // it when passing `None`.
+            #[inline]
+            unsafe fn set(&self, tid: Option<NonZeroU64>) {
+                match tid {
+                    Some(tid) => {
+                        *self.tid.get() = tid.get();
+                        self.tls_addr.store(current_thread_unique_ptr(), Relaxed);
+                    }
+                    None => self.tls_addr.store(0, Relaxed),
+                }
+            }
+        }
+    }
+}
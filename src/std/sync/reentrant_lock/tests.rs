@@ -0,0 +1,94 @@
+use super::{ReentrantLock, ReentrantLockGuard};
+use crate::std::cell::RefCell;
+use crate::std::sync::Arc;
+use crate::std::thread;
+
+#[test]
+fn smoke() {
+    let m = ReentrantLock::new(());
+    {
+        let a = m.lock();
+        {
+            let b = m.lock();
+            {
+                let c = m.lock();
+                assert_eq!(*c, ());
+            }
+            assert_eq!(*b, ());
+        }
+        assert_eq!(*a, ());
+    }
+}
+
+#[test]
+fn is_mutex() {
+    let m = Arc::new(ReentrantLock::new(RefCell::new(0)));
+    let m2 = m.clone();
+    let lock = m.lock();
+    let child = thread::spawn(move || {
+        let lock = m2.lock();
+        assert_eq!(*lock.borrow(), 4950);
+    });
+    for i in 0..100 {
+        let lock = m.lock();
+        *lock.borrow_mut() += i;
+    }
+    drop(lock);
+    child.join().unwrap();
+}
+
+#[test]
+fn trylock_works() {
+    let m = Arc::new(ReentrantLock::new(()));
+    let m2 = m.clone();
+    let _lock = m.try_lock();
+    let _lock2 = m.try_lock();
+    thread::spawn(move || {
+        let lock = m2.try_lock();
+        assert!(lock.is_none());
+    })
+    .join()
+    .unwrap();
+    let _lock3 = m.try_lock();
+}
+
+pub struct Answer<'a>(pub ReentrantLockGuard<'a, RefCell<u32>>);
+impl Drop for Answer<'_> {
+    fn drop(&mut self) {
+        *self.0.borrow_mut() = 42;
+    }
+}
+
+#[test]
+fn debug_while_locked() {
+    let m = ReentrantLock::new(1);
+    assert_eq!(format!("{m:?}"), "ReentrantLock { data: 1, .. }");
+    let m = Arc::new(m);
+    let _lock = m.lock();
+    let m2 = m.clone();
+    let debug = thread::spawn(move || format!("{m2:?}")).join().unwrap();
+    assert_eq!(debug, "ReentrantLock { data: <locked>, .. }");
+}
+
+#[test]
+fn into_inner() {
+    let mut m = ReentrantLock::new(vec![1]);
+    m.get_mut().push(2);
+    assert_eq!(m.into_inner(), [1, 2]);
+}
+
+#[test]
+fn left_locked_by_exited_thread() {
+    let m = Arc::new(ReentrantLock::new(()));
+    let m2 = m.clone();
+    thread::spawn(move || crate::std::mem::forget(m2.lock()))
+        .join()
+        .unwrap();
+    // Later threads may get the same thread locals, but aren't the owner.
+    for _ in 0..10 {
+        let m = m.clone();
+        assert!(thread::spawn(move || m.try_lock().is_none())
+            .join()
+            .unwrap());
+    }
+}
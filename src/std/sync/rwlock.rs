@@ -3,8 +3,14 @@ mod tests;
 
 use crate::std::cell::UnsafeCell;
 use crate::std::fmt;
+#[cfg(target_os = "dragonos")]
+use crate::std::mem;
 use crate::std::ops::{Deref, DerefMut};
 use crate::std::ptr::NonNull;
+#[cfg(all(target_os = "dragonos", feature = "deadlock-detection"))]
+use crate::std::sync::lock_order;
+#[cfg(target_os = "dragonos")]
+use crate::std::sync::PoisonError;
 use crate::std::sync::{poison, LockResult, TryLockError, TryLockResult};
 use crate::std::sys::locks as sys;
 
@@ -79,6 +85,8 @@ use crate::std::sys::locks as sys;
 pub struct RwLock<T: ?Sized> {
     inner: sys::RwLock,
     poison: poison::Flag,
+    #[cfg(all(target_os = "dragonos", feature = "deadlock-detection"))]
+    id: lock_order::LockId,
     data: UnsafeCell<T>,
 }
 
@@ -106,6 +114,8 @@ pub struct RwLockReadGuard<'a, T: ?Sized + 'a> {
     // is preferable over `const* T` to allow for niche optimization.
     data: NonNull<T>,
     inner_lock: &'a sys::RwLock,
+    #[cfg(all(target_os = "dragonos", feature = "deadlock-detection"))]
+    lock_id: &'a lock_order::LockId,
 }
 
 impl<T: ?Sized> !Send for RwLockReadGuard<'_, T> {}
@@ -150,6 +160,8 @@ impl<T> RwLock<T> {
         RwLock {
             inner: sys::RwLock::new(),
             poison: poison::Flag::new(),
+            #[cfg(all(target_os = "dragonos", feature = "deadlock-detection"))]
+            id: lock_order::LockId::new(),
             data: UnsafeCell::new(t),
         }
     }
@@ -198,6 +210,8 @@ impl<T: ?Sized> RwLock<T> {
     /// ```
     #[inline]
     pub fn read(&self) -> LockResult<RwLockReadGuard<'_, T>> {
+        #[cfg(all(target_os = "dragonos", feature = "deadlock-detection"))]
+        lock_order::acquire(&self.id, false);
         unsafe {
             self.inner.read();
             RwLockReadGuard::new(self)
@@ -244,6 +258,8 @@ impl<T: ?Sized> RwLock<T> {
     pub fn try_read(&self) -> TryLockResult<RwLockReadGuard<'_, T>> {
         unsafe {
             if self.inner.try_read() {
+                #[cfg(all(target_os = "dragonos", feature = "deadlock-detection"))]
+                lock_order::acquired(&self.id, false);
                 Ok(RwLockReadGuard::new(self)?)
             } else {
                 Err(TryLockError::WouldBlock)
@@ -284,6 +300,8 @@ impl<T: ?Sized> RwLock<T> {
     /// ```
     #[inline]
     pub fn write(&self) -> LockResult<RwLockWriteGuard<'_, T>> {
+        #[cfg(all(target_os = "dragonos", feature = "deadlock-detection"))]
+        lock_order::acquire(&self.id, true);
         unsafe {
             self.inner.write();
             RwLockWriteGuard::new(self)
@@ -331,6 +349,8 @@ impl<T: ?Sized> RwLock<T> {
     pub fn try_write(&self) -> TryLockResult<RwLockWriteGuard<'_, T>> {
         unsafe {
             if self.inner.try_write() {
+                #[cfg(all(target_os = "dragonos", feature = "deadlock-detection"))]
+                lock_order::acquired(&self.id, true);
                 Ok(RwLockWriteGuard::new(self)?)
             } else {
                 Err(TryLockError::WouldBlock)
@@ -500,6 +520,8 @@ impl<'rwlock, T: ?Sized> RwLockReadGuard<'rwlock, T> {
         poison::map_result(lock.poison.borrow(), |()| RwLockReadGuard {
             data: NonNull::new_unchecked(lock.data.get()),
             inner_lock: &lock.inner,
+            #[cfg(all(target_os = "dragonos", feature = "deadlock-detection"))]
+            lock_id: &lock.id,
         })
     }
 }
@@ -514,6 +536,45 @@ impl<'rwlock, T: ?Sized> RwLockWriteGuard<'rwlock, T> {
             poison: guard,
         })
     }
+
+    /// Atomically turns this write guard into a read guard, without letting
+    /// another writer acquire the lock in between.
+    ///
+    /// Readers waiting for the lock can acquire it right away, while writers
+    /// keep waiting until all readers, including the returned guard, are
+    /// gone. This lets a thread publish an update and go on reading it
+    /// without blocking other readers, and without the update being
+    /// overwritten before it is read.
+    ///
+    /// This is an associated function that needs to be used as
+    /// `RwLockWriteGuard::downgrade(guard)`, so it doesn't shadow a method on
+    /// `T`.
+    ///
+    /// ```
+    /// use std::sync::{RwLock, RwLockWriteGuard};
+    ///
+    /// let cache = RwLock::new(None);
+    /// let mut entry = cache.write().unwrap();
+    /// *entry = Some(42);
+    /// let entry = RwLockWriteGuard::downgrade(entry);
+    /// assert_eq!(*entry, Some(42));
+    /// assert!(cache.try_read().is_ok());
+    /// ```
+    #[cfg(target_os = "dragonos")]
+    pub fn downgrade(s: Self) -> RwLockReadGuard<'rwlock, T> {
+        let lock = s.lock;
+        // Like dropping the guard, but without unlocking.
+        lock.poison.done(&s.poison);
+        mem::forget(s);
+        // SAFETY: the write guard proves that we hold the write lock, and
+        // after `downgrade` a read lock, as `RwLockReadGuard::new` requires.
+        #[cfg(all(target_os = "dragonos", feature = "deadlock-detection"))]
+        lock_order::downgrade(&lock.id);
+        unsafe {
+            lock.inner.downgrade();
+            RwLockReadGuard::new(lock).unwrap_or_else(PoisonError::into_inner)
+        }
+    }
 }
 
 impl<T: fmt::Debug> fmt::Debug for RwLockReadGuard<'_, T> {
@@ -568,6 +629,8 @@ impl<T: ?Sized> DerefMut for RwLockWriteGuard<'_, T> {
 impl<T: ?Sized> Drop for RwLockReadGuard<'_, T> {
     fn drop(&mut self) {
         // SAFETY: the conditions of `RwLockReadGuard::new` were satisfied when created.
+        #[cfg(all(target_os = "dragonos", feature = "deadlock-detection"))]
+        lock_order::release(self.lock_id);
         unsafe {
             self.inner_lock.read_unlock();
         }
@@ -577,6 +640,8 @@ impl<T: ?Sized> Drop for RwLockReadGuard<'_, T> {
 impl<T: ?Sized> Drop for RwLockWriteGuard<'_, T> {
     fn drop(&mut self) {
         self.lock.poison.done(&self.poison);
+        #[cfg(all(target_os = "dragonos", feature = "deadlock-detection"))]
+        lock_order::release(&self.lock.id);
         // SAFETY: the conditions of `RwLockWriteGuard::new` were satisfied when created.
         unsafe {
             self.lock.inner.write_unlock();
@@ -44,6 +44,7 @@ fn frob() {
 }
 
 #[test]
+#[cfg(not(feature = "no-poison"))]
 fn test_rw_arc_poison_wr() {
     let arc = Arc::new(RwLock::new(1));
     let arc2 = arc.clone();
@@ -56,6 +57,7 @@ fn test_rw_arc_poison_wr() {
 }
 
 #[test]
+#[cfg(not(feature = "no-poison"))]
 fn test_rw_arc_poison_ww() {
     let arc = Arc::new(RwLock::new(1));
     assert!(!arc.is_poisoned());
@@ -209,6 +211,7 @@ fn test_into_inner_drop() {
 }
 
 #[test]
+#[cfg(not(feature = "no-poison"))]
 fn test_into_inner_poison() {
     let m = Arc::new(RwLock::new(NonCopy(10)));
     let m2 = m.clone();
@@ -233,6 +236,7 @@ fn test_get_mut() {
 }
 
 #[test]
+#[cfg(not(feature = "no-poison"))]
 fn test_get_mut_poison() {
     let m = Arc::new(RwLock::new(NonCopy(10)));
     let m2 = m.clone();
@@ -260,3 +264,89 @@ fn test_read_guard_covariance() {
     }
     drop(lock);
 }
+
+#[test]
+#[cfg(target_os = "dragonos")]
+fn test_downgrade() {
+    use crate::std::sync::RwLockWriteGuard;
+    use crate::std::time::Duration;
+
+    let lock = Arc::new(RwLock::new(0));
+    let mut w = lock.write().unwrap();
+    *w = 1;
+
+    // Queue a writer behind the write lock; it must not get in before the
+    // downgraded guard has been read.
+    let lock2 = lock.clone();
+    let writer = thread::spawn(move || *lock2.write().unwrap() = 2);
+    thread::sleep(Duration::from_millis(10));
+
+    let r = RwLockWriteGuard::downgrade(w);
+    thread::sleep(Duration::from_millis(10));
+    assert_eq!(*r, 1);
+    drop(r);
+
+    writer.join().unwrap();
+    assert_eq!(*lock.read().unwrap(), 2);
+}
+
+#[test]
+#[cfg(not(feature = "no-poison"))]
+fn test_clear_poison() {
+    let m = Arc::new(RwLock::new(NonCopy(10)));
+    let m2 = m.clone();
+    let _ = thread::spawn(move || {
+        let _lock = m2.write().unwrap();
+        panic!("test panic in inner thread to poison RwLock");
+    })
+    .join();
+
+    assert!(m.is_poisoned());
+    assert!(m.read().is_err());
+    m.clear_poison();
+    assert!(!m.is_poisoned());
+    assert_eq!(*m.read().unwrap(), NonCopy(10));
+}
+
+#[test]
+fn test_rwlock_try_contended() {
+    let lock = RwLock::new(0);
+    let write_guard = lock.write().unwrap();
+    assert!(matches!(lock.try_read(), Err(TryLockError::WouldBlock)));
+    assert!(matches!(lock.try_write(), Err(TryLockError::WouldBlock)));
+    drop(write_guard);
+
+    // Readers share the lock, but keep writers out.
+    let read_guard = lock.try_read().unwrap();
+    assert!(lock.try_read().is_ok());
+    assert!(matches!(lock.try_write(), Err(TryLockError::WouldBlock)));
+    drop(read_guard);
+    assert!(lock.try_write().is_ok());
+}
+
+#[test]
+#[cfg(not(feature = "no-poison"))]
+fn test_rwlock_try_poisoned() {
+    let lock = Arc::new(RwLock::new(1));
+    let lock2 = lock.clone();
+    let _ = thread::spawn(move || {
+        let _lock = lock2.write().unwrap();
+        panic!("test panic in inner thread to poison RwLock");
+    })
+    .join();
+
+    // A poisoned lock still locks, and reports the poison.
+    match lock.try_read() {
+        Err(TryLockError::Poisoned(e)) => assert_eq!(*e.into_inner(), 1),
+        _ => panic!("expected a poisoned lock"),
+    }
+    match lock.try_write() {
+        Err(TryLockError::Poisoned(e)) => assert_eq!(*e.into_inner(), 1),
+        _ => panic!("expected a poisoned lock"),
+    }
+    // The poison is only reported if the lock would have been acquired.
+    let write_guard = lock.write().unwrap_err().into_inner();
+    assert!(matches!(lock.try_read(), Err(TryLockError::WouldBlock)));
+    assert!(matches!(lock.try_write(), Err(TryLockError::WouldBlock)));
+    drop(write_guard);
+}
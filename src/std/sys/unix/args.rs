@@ -130,6 +130,9 @@ mod imp {
     };
 
     pub fn args() -> Args {
+        // Only `rt::init` stores the arguments here.
+        #[cfg(target_os = "dragonos")]
+        crate::std::rt::debug_assert_init(crate::std::rt::Stage::Sys, "std::env::args");
         Args {
             iter: clone().into_iter(),
         }
@@ -55,6 +55,7 @@ const fn max_iov() -> usize {
 
 #[cfg(any(
     target_os = "android",
+    target_os = "dragonos",
     target_os = "emscripten",
     target_os = "linux",
     target_os = "nto",
@@ -65,6 +66,7 @@ const fn max_iov() -> usize {
 
 #[cfg(not(any(
     target_os = "android",
+    target_os = "dragonos",
     target_os = "dragonfly",
     target_os = "emscripten",
     target_os = "freebsd",
@@ -407,6 +409,7 @@ impl FileDesc {
         target_os = "redox",
         target_os = "vxworks",
         target_os = "nto",
+        target_os = "dragonos",
     )))]
     pub fn set_cloexec(&self) -> io::Result<()> {
         unsafe {
@@ -429,6 +432,7 @@ impl FileDesc {
         target_os = "redox",
         target_os = "vxworks",
         target_os = "nto",
+        target_os = "dragonos",
     ))]
     pub fn set_cloexec(&self) -> io::Result<()> {
         unsafe {
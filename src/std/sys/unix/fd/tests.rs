@@ -1,5 +1,8 @@
-use super::{FileDesc, IoSlice};
+use super::{FileDesc, IoSlice, IoSliceMut};
+use crate::std::io::BorrowedBuf;
+use crate::std::mem::MaybeUninit;
 use crate::std::os::unix::io::FromRawFd;
+use crate::std::sys::pipe::anon_pipe;
 use core::mem::ManuallyDrop;
 
 #[test]
@@ -8,3 +11,42 @@ fn limit_vector_count() {
     let bufs = (0..1500).map(|_| IoSlice::new(&[])).collect::<Vec<_>>();
     assert!(stdout.write_vectored(&bufs).is_ok());
 }
+
+#[test]
+fn vectored_pipe() {
+    let (read, write) = anon_pipe().unwrap();
+    assert!(write.is_write_vectored());
+    assert!(read.is_read_vectored());
+    let n = write
+        .write_vectored(&[
+            IoSlice::new(b"ab"),
+            IoSlice::new(b""),
+            IoSlice::new(b"cdefg"),
+        ])
+        .unwrap();
+    assert_eq!(n, 7);
+
+    // Buffers of other sizes than the ones written, and more room than there
+    // is data.
+    let (mut a, mut b, mut c) = ([0; 3], [0; 1], [0; 8]);
+    let n = read
+        .read_vectored(&mut [
+            IoSliceMut::new(&mut a),
+            IoSliceMut::new(&mut b),
+            IoSliceMut::new(&mut c),
+        ])
+        .unwrap();
+    assert_eq!(n, 7);
+    assert_eq!((&a, &b, &c[..3]), (b"abc", b"d", &b"efg"[..]));
+}
+
+#[test]
+fn read_buf_leaves_rest_uninit() {
+    let (read, write) = anon_pipe().unwrap();
+    write.write(b"abc").unwrap();
+    let mut buf: [MaybeUninit<u8>; 64] = MaybeUninit::uninit_array();
+    let mut buf = BorrowedBuf::from(buf.as_mut_slice());
+    read.read_buf(buf.unfilled()).unwrap();
+    assert_eq!(buf.filled(), b"abc");
+    assert_eq!(buf.init_len(), 3);
+}
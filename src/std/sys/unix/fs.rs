@@ -31,6 +31,9 @@ use crate::std::sys::weak::weak;
 
 use dlibc::{c_int, mode_t};
 
+#[cfg(target_os = "dragonos")]
+use crate::std::os::dragonos::fs::retry;
+
 #[cfg(any(
     target_os = "macos",
     target_os = "ios",
@@ -101,6 +104,14 @@ pub use crate::std::sys_common::fs::try_exists;
 
 pub struct File(FileDesc);
 
+/// Runs an operation that can be repeated without changing its outcome.
+/// DragonOS retries it on transient errors, as configured with
+/// `os::dragonos::fs::set_retry_policy`.
+#[cfg(not(target_os = "dragonos"))]
+fn retry<T>(mut op: impl FnMut() -> io::Result<T>) -> io::Result<T> {
+    op()
+}
+
 // FIXME: This should be available on Linux with all `target_env`.
 // But currently only glibc exposes `statx` fn and structs.
 // We don't want to import unverified raw C structs here directly.
@@ -317,6 +328,8 @@ pub struct DirEntry {
 ))]
 struct dirent64_min {
     d_ino: u64,
+    #[cfg(target_os = "dragonos")]
+    d_off: i64,
     #[cfg(not(any(
         target_os = "solaris",
         target_os = "illumos",
@@ -786,6 +799,8 @@ impl Iterator for ReadDir {
                 #[cfg(not(target_os = "vita"))]
                 let entry = dirent64_min {
                     d_ino: *offset_ptr!(entry_ptr, d_ino) as u64,
+                    #[cfg(target_os = "dragonos")]
+                    d_off: *offset_ptr!(entry_ptr, d_off) as i64,
                     #[cfg(not(any(
                         target_os = "solaris",
                         target_os = "illumos",
@@ -975,6 +990,11 @@ impl DirEntry {
         self.entry.d_ino as u64
     }
 
+    #[cfg(target_os = "dragonos")]
+    pub fn offset(&self) -> i64 {
+        self.entry.d_off
+    }
+
     #[cfg(any(
         target_os = "freebsd",
         target_os = "openbsd",
@@ -1144,7 +1164,14 @@ impl File {
         // some platforms (like macOS, where `open64` is actually `open`), `mode_t` is `u16`.
         // However, since this is a variadic function, C integer promotion rules mean that on
         // the ABI level, this still gets passed as `c_int` (aka `u32` on Unix platforms).
-        let fd = cvt_r(|| unsafe { open64(path.as_ptr(), flags, opts.mode as c_int) })?;
+        let open = || cvt_r(|| unsafe { open64(path.as_ptr(), flags, opts.mode as c_int) });
+        // Opening again after a failure could find a file that the failed
+        // attempt created.
+        let fd = if opts.create_new {
+            open()?
+        } else {
+            retry(open)?
+        };
         Ok(File(unsafe { FileDesc::from_raw_fd(fd) }))
     }
 
@@ -1589,17 +1616,22 @@ impl fmt::Debug for File {
 }
 
 pub fn readdir(path: &Path) -> io::Result<ReadDir> {
-    let ptr = run_path_with_cstr(path, |p| unsafe { Ok(dlibc::opendir(p.as_ptr())) })?;
-    if ptr.is_null() {
-        Err(Error::last_os_error())
-    } else {
-        let root = path.to_path_buf();
-        let inner = InnerReadDir {
-            dirp: Dir(ptr),
-            root,
-        };
-        Ok(ReadDir::new(inner))
-    }
+    let ptr = run_path_with_cstr(path, |p| {
+        retry(|| {
+            let ptr = unsafe { dlibc::opendir(p.as_ptr()) };
+            if ptr.is_null() {
+                Err(Error::last_os_error())
+            } else {
+                Ok(ptr)
+            }
+        })
+    })?;
+    let root = path.to_path_buf();
+    let inner = InnerReadDir {
+        dirp: Dir(ptr),
+        root,
+    };
+    Ok(ReadDir::new(inner))
 }
 
 pub fn unlink(p: &Path) -> io::Result<()> {
@@ -1631,9 +1663,9 @@ pub fn readlink(p: &Path) -> io::Result<PathBuf> {
         let mut buf = Vec::with_capacity(256);
 
         loop {
-            let buf_read =
-                cvt(unsafe { dlibc::readlink(p, buf.as_mut_ptr() as *mut _, buf.capacity()) })?
-                    as usize;
+            let buf_read = retry(|| {
+                cvt(unsafe { dlibc::readlink(p, buf.as_mut_ptr() as *mut _, buf.capacity()) })
+            })? as usize;
 
             unsafe {
                 buf.set_len(buf_read);
@@ -1710,7 +1742,7 @@ pub fn stat(p: &Path) -> io::Result<FileAttr> {
         }
 
         let mut stat: stat64 = unsafe { mem::zeroed() };
-        cvt(unsafe { stat64(p.as_ptr(), &mut stat) })?;
+        retry(|| cvt(unsafe { stat64(p.as_ptr(), &mut stat) }))?;
         Ok(FileAttr::from_stat64(stat))
     })
 }
@@ -1729,18 +1761,22 @@ pub fn lstat(p: &Path) -> io::Result<FileAttr> {
         }
 
         let mut stat: stat64 = unsafe { mem::zeroed() };
-        cvt(unsafe { lstat64(p.as_ptr(), &mut stat) })?;
+        retry(|| cvt(unsafe { lstat64(p.as_ptr(), &mut stat) }))?;
         Ok(FileAttr::from_stat64(stat))
     })
 }
 
 pub fn canonicalize(p: &Path) -> io::Result<PathBuf> {
-    let r = run_path_with_cstr(p, |path| unsafe {
-        Ok(dlibc::realpath(path.as_ptr(), ptr::null_mut()))
+    let r = run_path_with_cstr(p, |path| {
+        retry(|| {
+            let r = unsafe { dlibc::realpath(path.as_ptr(), ptr::null_mut()) };
+            if r.is_null() {
+                Err(io::Error::last_os_error())
+            } else {
+                Ok(r)
+            }
+        })
     })?;
-    if r.is_null() {
-        return Err(io::Error::last_os_error());
-    }
     Ok(PathBuf::from(OsString::from_vec(unsafe {
         let buf = CStr::from_ptr(r).to_bytes().to_vec();
         dlibc::free(r as *mut _);
@@ -25,6 +25,24 @@ use dlibc;
     target_os = "dragonos",
 ))]
 pub fn futex_wait(futex: &AtomicU32, expected: u32, timeout: Option<Duration>) -> bool {
+    futex_wait_with(futex, expected, timeout, false)
+}
+
+// The body of `futex_wait` and `futex_wait_shared`. Private futexes are
+// keyed by the address space, so a process waiting on one is never woken by
+// another process, but they are cheaper for the kernel to look up.
+#[cfg(any(
+    target_os = "linux",
+    target_os = "android",
+    target_os = "freebsd",
+    target_os = "dragonos",
+))]
+fn futex_wait_with(
+    futex: &AtomicU32,
+    expected: u32,
+    timeout: Option<Duration>,
+    shared: bool,
+) -> bool {
     use super::time::Timespec;
     use crate::std::ptr::null;
     use crate::std::sync::atomic::Ordering::Relaxed;
@@ -49,6 +67,8 @@ pub fn futex_wait(futex: &AtomicU32, expected: u32, timeout: Option<Duration>) -
                     // _umtx_op(UMTX_OP_WAIT_UINT_PRIVATE), which is nearly
                     // identical. It supports absolute timeouts through a flag
                     // in the _umtx_time struct.
+                    // Shared futexes are only used on DragonOS.
+                    debug_assert!(!shared);
                     let umtx_timeout = timespec.map(|t| dlibc::_umtx_time {
                         _timeout: t,
                         _flags: dlibc::UMTX_ABSTIME,
@@ -66,10 +86,11 @@ pub fn futex_wait(futex: &AtomicU32, expected: u32, timeout: Option<Duration>) -
                 } else if #[cfg(any(target_os = "linux", target_os = "android",target_os = "dragonos",))] {
                     // Use FUTEX_WAIT_BITSET rather than FUTEX_WAIT to be able to give an
                     // absolute time rather than a relative time.
+                    let private = if shared { 0 } else { dlibc::FUTEX_PRIVATE_FLAG };
                     dlibc::syscall(
                         dlibc::SYS_futex,
                         futex as *const AtomicU32,
-                        dlibc::FUTEX_WAIT_BITSET | dlibc::FUTEX_PRIVATE_FLAG,
+                        dlibc::FUTEX_WAIT_BITSET | private,
                         expected,
                         timespec.as_ref().map_or(null(), |t| t as *const dlibc::timespec),
                         null::<u32>(), // This argument is unused for FUTEX_WAIT_BITSET.
@@ -97,21 +118,80 @@ pub fn futex_wait(futex: &AtomicU32, expected: u32, timeout: Option<Duration>) -
 /// On some platforms, this always returns false.
 #[cfg(any(target_os = "linux", target_os = "android", target_os = "dragonos",))]
 pub fn futex_wake(futex: &AtomicU32) -> bool {
-    let ptr = futex as *const AtomicU32;
-    let op = dlibc::FUTEX_WAKE | dlibc::FUTEX_PRIVATE_FLAG;
-    unsafe { dlibc::syscall(dlibc::SYS_futex, ptr, op, 1) > 0 }
+    futex_wake_with(futex, 1, false) > 0
 }
 
 /// Wake up all threads that are waiting on futex_wait on this futex.
 #[cfg(any(target_os = "linux", target_os = "android", target_os = "dragonos",))]
 pub fn futex_wake_all(futex: &AtomicU32) {
+    futex_wake_with(futex, i32::MAX, false);
+}
+
+// The body of the `futex_wake` functions: wakes up to `count` waiters and
+// returns how many were woken, or -1 on error. See `futex_wait_with` for
+// `shared`.
+#[cfg(any(target_os = "linux", target_os = "android", target_os = "dragonos",))]
+fn futex_wake_with(futex: *const AtomicU32, count: i32, shared: bool) -> dlibc::c_long {
+    let private = if shared { 0 } else { dlibc::FUTEX_PRIVATE_FLAG };
+    let op = dlibc::FUTEX_WAKE | private;
+    unsafe { dlibc::syscall(dlibc::SYS_futex, futex, op, count) }
+}
+
+/// Wake up one thread waiting on `futex`, and make all others wait on
+/// `target` instead, as long as `futex` still holds `expected`.
+///
+/// Returns false, having woken nobody, if `futex` held another value or the
+/// kernel can't requeue waiters.
+#[cfg(target_os = "dragonos")]
+pub fn futex_requeue(futex: &AtomicU32, expected: u32, target: *const AtomicU32) -> bool {
     let ptr = futex as *const AtomicU32;
-    let op = dlibc::FUTEX_WAKE | dlibc::FUTEX_PRIVATE_FLAG;
+    let op = dlibc::FUTEX_CMP_REQUEUE | dlibc::FUTEX_PRIVATE_FLAG;
+    // The number of waiters to requeue goes where other operations take a
+    // timeout.
     unsafe {
-        dlibc::syscall(dlibc::SYS_futex, ptr, op, i32::MAX);
+        dlibc::syscall(
+            dlibc::SYS_futex,
+            ptr,
+            op,
+            1,
+            i32::MAX as usize,
+            target,
+            expected,
+        ) >= 0
     }
 }
 
+/// Like `futex_wake_all`, but for an address that may no longer hold a
+/// futex, so no reference to it can be formed.
+///
+/// Waking an address that is now used for something else only causes a
+/// spurious wakeup for whoever waits there.
+#[cfg(target_os = "dragonos")]
+pub fn futex_wake_all_at(futex: *const AtomicU32) {
+    futex_wake_with(futex, i32::MAX, false);
+}
+
+/// Like `futex_wait`, but for a futex in memory that is shared with other
+/// processes.
+#[cfg(target_os = "dragonos")]
+pub fn futex_wait_shared(futex: &AtomicU32, expected: u32, timeout: Option<Duration>) -> bool {
+    futex_wait_with(futex, expected, timeout, true)
+}
+
+/// Like `futex_wake`, but for a futex in memory that is shared with other
+/// processes.
+#[cfg(target_os = "dragonos")]
+pub fn futex_wake_shared(futex: &AtomicU32) -> bool {
+    futex_wake_with(futex, 1, true) > 0
+}
+
+/// Like `futex_wake_all`, but for a futex in memory that is shared with other
+/// processes.
+#[cfg(target_os = "dragonos")]
+pub fn futex_wake_all_shared(futex: &AtomicU32) {
+    futex_wake_with(futex, i32::MAX, true);
+}
+
 // FreeBSD doesn't tell us how many threads are woken up, so this always returns false.
 #[cfg(target_os = "freebsd")]
 pub fn futex_wake(futex: &AtomicU32) -> bool {
@@ -46,8 +46,8 @@ use crate::std::cmp::min;
 use crate::std::fs::{File, Metadata};
 use crate::std::io::copy::generic_copy;
 use crate::std::io::{
-    BufRead, BufReader, BufWriter, Error, Read, Result, StderrLock, StdinLock, StdoutLock, Take,
-    Write,
+    BufRead, BufReader, BufWriter, Error, PipeReader, PipeWriter, Read, Result, StderrLock,
+    StdinLock, StdoutLock, Take, Write,
 };
 use crate::std::mem::ManuallyDrop;
 use crate::std::net::TcpStream;
@@ -58,6 +58,7 @@ use crate::std::process::{ChildStderr, ChildStdin, ChildStdout};
 use crate::std::ptr;
 use crate::std::sync::atomic::{AtomicBool, AtomicU8, Ordering};
 use crate::std::sys::cvt;
+#[cfg(not(target_os = "dragonos"))]
 use crate::std::sys::weak::syscall;
 use dlibc;
 #[cfg(not(all(target_os = "linux", target_env = "gnu")))]
@@ -420,6 +421,30 @@ impl CopyRead for ChildStderr {
     }
 }
 
+impl CopyRead for PipeReader {
+    fn properties(&self) -> CopyParams {
+        CopyParams(FdMeta::Pipe, Some(self.as_raw_fd()))
+    }
+}
+
+impl CopyRead for &PipeReader {
+    fn properties(&self) -> CopyParams {
+        CopyParams(FdMeta::Pipe, Some(self.as_raw_fd()))
+    }
+}
+
+impl CopyWrite for PipeWriter {
+    fn properties(&self) -> CopyParams {
+        CopyParams(FdMeta::Pipe, Some(self.as_raw_fd()))
+    }
+}
+
+impl CopyWrite for &PipeWriter {
+    fn properties(&self) -> CopyParams {
+        CopyParams(FdMeta::Pipe, Some(self.as_raw_fd()))
+    }
+}
+
 impl CopyRead for StdinLock<'_> {
     fn drain_to<W: Write>(&mut self, writer: &mut W, outer_limit: u64) -> Result<u64> {
         let buf_reader = self.as_mut_buf();
@@ -558,6 +583,7 @@ pub(super) fn copy_regular_files(reader: RawFd, writer: RawFd, max_len: u64) ->
     // We store the availability in a global to avoid unnecessary syscalls
     static HAS_COPY_FILE_RANGE: AtomicU8 = AtomicU8::new(NOT_PROBED);
 
+    #[cfg(not(target_os = "dragonos"))]
     syscall! {
         fn copy_file_range(
             fd_in: dlibc::c_int,
@@ -569,6 +595,10 @@ pub(super) fn copy_regular_files(reader: RawFd, writer: RawFd, max_len: u64) ->
         ) -> dlibc::ssize_t
     }
 
+    // Resolving the weak symbol fails on DragonOS, so call the libc wrapper.
+    #[cfg(target_os = "dragonos")]
+    use dlibc::copy_file_range;
+
     match HAS_COPY_FILE_RANGE.load(Ordering::Relaxed) {
         NOT_PROBED => {
             // EPERM can indicate seccomp filters or an immutable file.
@@ -1,5 +1,11 @@
 use super::Mutex;
+#[cfg(target_os = "dragonos")]
+use crate::std::ptr;
+#[cfg(target_os = "dragonos")]
+use crate::std::sync::atomic::AtomicPtr;
 use crate::std::sync::atomic::{AtomicU32, Ordering::Relaxed};
+#[cfg(target_os = "dragonos")]
+use crate::std::sys::futex::{futex_requeue, futex_wake_all_at};
 use crate::std::sys::futex::{futex_wait, futex_wake, futex_wake_all};
 use crate::std::time::Duration;
 
@@ -8,6 +14,14 @@ pub struct Condvar {
     // This is used by `.wait()` to not miss any notifications after
     // unlocking the mutex and before waiting for notifications.
     futex: AtomicU32,
+    // The mutex of the current waiters, or null if nobody waits. Waits that
+    // overlap must use the same one; see `verify`.
+    #[cfg(target_os = "dragonos")]
+    mutex: AtomicPtr<Mutex>,
+    // The number of threads inside `wait_optional_timeout`. Only changed by
+    // threads that hold the mutex.
+    #[cfg(target_os = "dragonos")]
+    waiters: AtomicU32,
 }
 
 impl Condvar {
@@ -15,6 +29,10 @@ impl Condvar {
     pub const fn new() -> Self {
         Self {
             futex: AtomicU32::new(0),
+            #[cfg(target_os = "dragonos")]
+            mutex: AtomicPtr::new(ptr::null_mut()),
+            #[cfg(target_os = "dragonos")]
+            waiters: AtomicU32::new(0),
         }
     }
 
@@ -26,11 +44,38 @@ impl Condvar {
         futex_wake(&self.futex);
     }
 
+    #[cfg(not(target_os = "dragonos"))]
     pub fn notify_all(&self) {
         self.futex.fetch_add(1, Relaxed);
         futex_wake_all(&self.futex);
     }
 
+    // Waking every waiter at once would only have all but one of them go
+    // back to sleep on the mutex, so wake one and move the others straight
+    // onto the futex of the mutex, where each unlock wakes the next.
+    #[cfg(target_os = "dragonos")]
+    pub fn notify_all(&self) {
+        let value = self.futex.fetch_add(1, Relaxed).wrapping_add(1);
+        let mutex = self.mutex.load(Relaxed);
+        // Nobody waits yet if there is no mutex. Requeueing fails if another
+        // notification came in between, in which case waking everyone is
+        // still correct.
+        if mutex.is_null() || !futex_requeue(&self.futex, value, mutex.cast()) {
+            futex_wake_all(&self.futex);
+            return;
+        }
+        // The address was read without holding the mutex, so the waiters it
+        // belonged to may have left and the mutex may have moved before the
+        // requeue, which then put the waiters of its new address onto the
+        // old one. The mutex can't move while anyone it requeued still waits,
+        // so the address is only still registered if it was right. Otherwise
+        // wake whoever ended up on the old address; they relock their own
+        // mutex.
+        if self.mutex.load(Relaxed) != mutex {
+            futex_wake_all_at(mutex.cast());
+        }
+    }
+
     pub unsafe fn wait(&self, mutex: &Mutex) {
         self.wait_optional_timeout(mutex, None);
     }
@@ -40,6 +85,9 @@ impl Condvar {
     }
 
     unsafe fn wait_optional_timeout(&self, mutex: &Mutex, timeout: Option<Duration>) -> bool {
+        #[cfg(target_os = "dragonos")]
+        self.verify(mutex);
+
         // Examine the notification counter _before_ we unlock the mutex.
         let futex_value = self.futex.load(Relaxed);
 
@@ -51,8 +99,50 @@ impl Condvar {
         let r = futex_wait(&self.futex, futex_value, timeout);
 
         // Lock the mutex again.
+        #[cfg(not(target_os = "dragonos"))]
         mutex.lock();
+        #[cfg(target_os = "dragonos")]
+        {
+            mutex.lock_after_wait();
+            self.leave();
+        }
 
         r
     }
+
+    /// Registers the calling thread as a waiter with `mutex`, and panics if
+    /// other threads are waiting with a different mutex.
+    ///
+    /// A waiter that wakes up relocks whatever mutex it was given, so if two
+    /// mutexes were in use, a notifier holding one of them would not exclude
+    /// the waiters of the other, and `wait_while` predicates could observe
+    /// unsynchronized state.
+    ///
+    /// Only overlapping waits are compared: the mutex and the condition
+    /// variable may be moved while nobody waits, so an address from an
+    /// earlier wait says nothing about the mutex of a later one.
+    #[cfg(target_os = "dragonos")]
+    fn verify(&self, mutex: &Mutex) {
+        let addr = mutex as *const Mutex as *mut Mutex;
+        self.waiters.fetch_add(1, Relaxed);
+        match self
+            .mutex
+            .compare_exchange(ptr::null_mut(), addr, Relaxed, Relaxed)
+        {
+            Ok(_) => {}
+            Err(current) if current == addr => {}
+            Err(_) => {
+                self.leave();
+                panic!("attempted to use a condition variable with two mutexes");
+            }
+        }
+    }
+
+    /// Unregisters a waiter, forgetting the mutex once the last one leaves.
+    #[cfg(target_os = "dragonos")]
+    fn leave(&self) {
+        if self.waiters.fetch_sub(1, Relaxed) == 1 {
+            self.mutex.store(ptr::null_mut(), Relaxed);
+        }
+    }
 }
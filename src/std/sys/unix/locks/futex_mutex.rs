@@ -1,17 +1,41 @@
+//! A mutex on top of a single futex word.
+//!
+//! Taking an unlocked mutex is one compare-exchange, and releasing a mutex
+//! nobody waits for is one swap; neither makes a system call. A thread that
+//! finds the mutex locked spins for a bounded number of iterations, since
+//! critical sections are usually short, and only then marks the mutex as
+//! contended and sleeps in `futex_wait`. Unlocking a contended mutex wakes
+//! a single waiter.
+//!
+//! On DragonOS the same protocol also backs the process-shared mutex of
+//! `std::os::dragonos::sync`, which only differs in waiting on a shared
+//! futex instead of one private to the process.
+
 use crate::std::sync::atomic::{
     AtomicU32,
     Ordering::{Acquire, Relaxed, Release},
 };
 use crate::std::sys::futex::{futex_wait, futex_wake};
+#[cfg(target_os = "dragonos")]
+use crate::std::sys::futex::{futex_wait_shared, futex_wake_shared};
+
+pub type Mutex = FutexMutex<false>;
+
+/// A mutex whose futex may live in memory shared with other processes.
+#[cfg(target_os = "dragonos")]
+pub type SharedMutex = FutexMutex<true>;
 
-pub struct Mutex {
+// `Condvar::notify_all` hands a pointer to the mutex to the kernel as the
+// address of its futex.
+#[cfg_attr(target_os = "dragonos", repr(transparent))]
+pub struct FutexMutex<const SHARED: bool> {
     /// 0: unlocked
     /// 1: locked, no other threads waiting
     /// 2: locked, and other threads waiting (contended)
     futex: AtomicU32,
 }
 
-impl Mutex {
+impl<const SHARED: bool> FutexMutex<SHARED> {
     #[inline]
     pub const fn new() -> Self {
         Self {
@@ -31,6 +55,19 @@ impl Mutex {
         }
     }
 
+    /// Locks the mutex again at the end of a condition variable wait.
+    ///
+    /// `Condvar::notify_all` moves its waiters onto the futex of the mutex
+    /// without marking the mutex as contended, so they are only woken by
+    /// unlocks that see it marked. A thread leaving a wait therefore always
+    /// marks it, and unlocking it then wakes the next of those waiters.
+    #[cfg(target_os = "dragonos")]
+    pub fn lock_after_wait(&self) {
+        while self.futex.swap(2, Acquire) != 0 {
+            self.wait();
+        }
+    }
+
     #[cold]
     fn lock_contended(&self) {
         // Spin first to speed things up if the lock is released quickly.
@@ -55,7 +92,7 @@ impl Mutex {
             }
 
             // Wait for the futex to change state, assuming it is still 2.
-            futex_wait(&self.futex, 2, None);
+            self.wait();
 
             // Spin again after waking up.
             state = self.spin();
@@ -91,8 +128,28 @@ impl Mutex {
         }
     }
 
+    /// Returns whether some thread holds the mutex, for `Debug` output.
+    #[cfg(target_os = "dragonos")]
+    pub fn is_locked(&self) -> bool {
+        self.futex.load(Relaxed) != 0
+    }
+
+    fn wait(&self) {
+        #[cfg(target_os = "dragonos")]
+        if SHARED {
+            futex_wait_shared(&self.futex, 2, None);
+            return;
+        }
+        futex_wait(&self.futex, 2, None);
+    }
+
     #[cold]
     fn wake(&self) {
+        #[cfg(target_os = "dragonos")]
+        if SHARED {
+            futex_wake_shared(&self.futex);
+            return;
+        }
         futex_wake(&self.futex);
     }
 }
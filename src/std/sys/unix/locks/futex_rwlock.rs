@@ -24,6 +24,9 @@ const WRITE_LOCKED: u32 = MASK;
 const MAX_READERS: u32 = MASK - 1;
 const READERS_WAITING: u32 = 1 << 30;
 const WRITERS_WAITING: u32 = 1 << 31;
+// Turns a write lock into a single read lock when added to the state.
+#[cfg(target_os = "dragonos")]
+const DOWNGRADE: u32 = READ_LOCKED.wrapping_sub(WRITE_LOCKED);
 
 #[inline]
 fn is_unlocked(state: u32) -> bool {
@@ -180,6 +183,25 @@ impl RwLock {
         }
     }
 
+    /// Turns the write lock held by the caller into a read lock, without
+    /// letting a writer in between.
+    ///
+    /// Waiting readers are woken so they can share the lock. Waiting writers
+    /// keep waiting, and since they still block new readers, writers are not
+    /// starved by a stream of downgrades.
+    #[cfg(target_os = "dragonos")]
+    #[inline]
+    pub unsafe fn downgrade(&self) {
+        let state = self.state.fetch_add(DOWNGRADE, Release);
+        debug_assert!(is_write_locked(state));
+
+        if has_readers_waiting(state) {
+            // Only the write lock holder clears this bit, and that was us.
+            self.state.fetch_sub(READERS_WAITING, Relaxed);
+            futex_wake_all(&self.state);
+        }
+    }
+
     #[cold]
     fn write_contended(&self) {
         let mut state = self.spin_write();
@@ -12,6 +12,8 @@ cfg_if::cfg_if! {
         mod futex_rwlock;
         mod futex_condvar;
         pub(crate) use futex_mutex::Mutex;
+        #[cfg(target_os = "dragonos")]
+        pub(crate) use futex_mutex::SharedMutex;
         pub(crate) use futex_rwlock::RwLock;
         pub(crate) use futex_condvar::Condvar;
     } else if #[cfg(target_os = "fuchsia")] {
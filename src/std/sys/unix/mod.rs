@@ -78,68 +78,102 @@ pub unsafe fn init(argc: isize, argv: *const *const u8, sigpipe: u8) {
     if cfg!(target_os = "macos") {
         thread::Thread::set_name(&CStr::from_bytes_with_nul_unchecked(b"main\0"));
     }
+}
 
-    unsafe fn sanitize_standard_fds() {
-        // fast path with a single syscall for systems with poll()
-        #[cfg(not(any(
-            miri,
-            target_os = "emscripten",
-            target_os = "fuchsia",
-            target_os = "vxworks",
-            // The poll on Darwin doesn't set POLLNVAL for closed fds.
-            target_os = "macos",
-            target_os = "ios",
-            target_os = "tvos",
-            target_os = "watchos",
-            target_os = "redox",
-            target_os = "l4re",
-            target_os = "horizon",
-            target_os = "vita",
-        )))]
-        'poll: {
-            use crate::std::sys::os::errno;
-            #[cfg(not(all(target_os = "linux", target_env = "gnu")))]
-            use dlibc::open as open64;
-            #[cfg(all(target_os = "linux", target_env = "gnu"))]
-            use dlibc::open64;
-            let pfds: &mut [_] = &mut [
-                dlibc::pollfd {
-                    fd: 0,
-                    events: 0,
-                    revents: 0,
-                },
-                dlibc::pollfd {
-                    fd: 1,
-                    events: 0,
-                    revents: 0,
-                },
-                dlibc::pollfd {
-                    fd: 2,
-                    events: 0,
-                    revents: 0,
-                },
-            ];
-
-            while dlibc::poll(pfds.as_mut_ptr(), 3, 0) == -1 {
-                match errno() {
-                    dlibc::EINTR => continue,
-                    #[cfg(target_vendor = "unikraft")]
-                    dlibc::ENOSYS => {
-                        // Not all configurations of Unikraft enable `LIBPOSIX_EVENT`.
-                        break 'poll;
-                    }
-                    dlibc::EINVAL | dlibc::EAGAIN | dlibc::ENOMEM => {
-                        // RLIMIT_NOFILE or temporary allocation failures
-                        // may be preventing use of poll(), fall back to fcntl
-                        break 'poll;
-                    }
-                    _ => dlibc::abort(),
+#[cfg(not(target_os = "espidf"))]
+unsafe fn sanitize_standard_fds() {
+    // fast path with a single syscall for systems with poll()
+    #[cfg(not(any(
+        miri,
+        target_os = "emscripten",
+        target_os = "fuchsia",
+        target_os = "vxworks",
+        // The poll on Darwin doesn't set POLLNVAL for closed fds.
+        target_os = "macos",
+        target_os = "ios",
+        target_os = "tvos",
+        target_os = "watchos",
+        target_os = "redox",
+        target_os = "l4re",
+        target_os = "horizon",
+        target_os = "vita",
+    )))]
+    'poll: {
+        use crate::std::sys::os::errno;
+        #[cfg(not(all(target_os = "linux", target_env = "gnu")))]
+        use dlibc::open as open64;
+        #[cfg(all(target_os = "linux", target_env = "gnu"))]
+        use dlibc::open64;
+        let pfds: &mut [_] = &mut [
+            dlibc::pollfd {
+                fd: 0,
+                events: 0,
+                revents: 0,
+            },
+            dlibc::pollfd {
+                fd: 1,
+                events: 0,
+                revents: 0,
+            },
+            dlibc::pollfd {
+                fd: 2,
+                events: 0,
+                revents: 0,
+            },
+        ];
+
+        while dlibc::poll(pfds.as_mut_ptr(), 3, 0) == -1 {
+            match errno() {
+                dlibc::EINTR => continue,
+                #[cfg(target_vendor = "unikraft")]
+                dlibc::ENOSYS => {
+                    // Not all configurations of Unikraft enable `LIBPOSIX_EVENT`.
+                    break 'poll;
                 }
-            }
-            for pfd in pfds {
-                if pfd.revents & dlibc::POLLNVAL == 0 {
-                    continue;
+                dlibc::EINVAL | dlibc::EAGAIN | dlibc::ENOMEM => {
+                    // RLIMIT_NOFILE or temporary allocation failures
+                    // may be preventing use of poll(), fall back to fcntl
+                    break 'poll;
                 }
+                _ => dlibc::abort(),
+            }
+        }
+        for pfd in pfds {
+            if pfd.revents & dlibc::POLLNVAL == 0 {
+                continue;
+            }
+            if open64("/dev/null\0".as_ptr().cast(), dlibc::O_RDWR, 0) == -1 {
+                // If the stream is closed but we failed to reopen it, abort the
+                // process. Otherwise we wouldn't preserve the safety of
+                // operations on the corresponding Rust object Stdin, Stdout, or
+                // Stderr.
+                dlibc::abort();
+            }
+        }
+        return;
+    }
+
+    // fallback in case poll isn't available or limited by RLIMIT_NOFILE
+    #[cfg(not(any(
+        // The standard fds are always available in Miri.
+        miri,
+        target_os = "emscripten",
+        target_os = "fuchsia",
+        target_os = "vxworks",
+        target_os = "l4re",
+        target_os = "horizon",
+        target_os = "vita",
+    )))]
+    {
+        use crate::std::sys::os::errno;
+        // #[cfg(not(all(target_os = "linux", target_env = "gnu")))]
+        // use dlibc::open as open64;
+        #[cfg(target_os = "dragonos")]
+        use dlibc::open as open64;
+        #[cfg(all(target_os = "linux", target_env = "gnu"))]
+        use dlibc::open64;
+        for fd in 0..3 {
+            if dlibc::fcntl(fd, dlibc::F_GETFD) == -1 && errno() == dlibc::EBADF {
                 if open64("/dev/null\0".as_ptr().cast(), dlibc::O_RDWR, 0) == -1 {
                     // If the stream is closed but we failed to reopen it, abort the
                     // process. Otherwise we wouldn't preserve the safety of
@@ -148,83 +182,67 @@ pub unsafe fn init(argc: isize, argv: *const *const u8, sigpipe: u8) {
                     dlibc::abort();
                 }
             }
-            return;
-        }
-
-        // fallback in case poll isn't available or limited by RLIMIT_NOFILE
-        #[cfg(not(any(
-            // The standard fds are always available in Miri.
-            miri,
-            target_os = "emscripten",
-            target_os = "fuchsia",
-            target_os = "vxworks",
-            target_os = "l4re",
-            target_os = "horizon",
-            target_os = "vita",
-        )))]
-        {
-            use crate::std::sys::os::errno;
-            // #[cfg(not(all(target_os = "linux", target_env = "gnu")))]
-            // use dlibc::open as open64;
-            #[cfg(target_os = "dragonos")]
-            use dlibc::open as open64;
-            #[cfg(all(target_os = "linux", target_env = "gnu"))]
-            use dlibc::open64;
-            for fd in 0..3 {
-                if dlibc::fcntl(fd, dlibc::F_GETFD) == -1 && errno() == dlibc::EBADF {
-                    if open64("/dev/null\0".as_ptr().cast(), dlibc::O_RDWR, 0) == -1 {
-                        // If the stream is closed but we failed to reopen it, abort the
-                        // process. Otherwise we wouldn't preserve the safety of
-                        // operations on the corresponding Rust object Stdin, Stdout, or
-                        // Stderr.
-                        dlibc::abort();
-                    }
-                }
-            }
         }
     }
+}
 
-    unsafe fn reset_sigpipe(#[allow(unused_variables)] sigpipe: u8) {
-        #[cfg(not(any(
-            target_os = "emscripten",
-            target_os = "fuchsia",
-            target_os = "horizon",
-            // Unikraft's `signal` implementation is currently broken:
-            // https://github.com/unikraft/lib-musl/issues/57
-            target_vendor = "unikraft",
-        )))]
-        {
-            // We don't want to add this as a public type to std, nor do we
-            // want to `include!` a file from the compiler (which would break
-            // Miri and xargo for example), so we choose to duplicate these
-            // constants from `compiler/rustc_session/src/config/sigpipe.rs`.
-            // See the other file for docs. NOTE: Make sure to keep them in
-            // sync!
-            mod sigpipe {
-                pub const DEFAULT: u8 = 0;
-                pub const INHERIT: u8 = 1;
-                pub const SIG_IGN: u8 = 2;
-                pub const SIG_DFL: u8 = 3;
-            }
+#[cfg(not(target_os = "espidf"))]
+unsafe fn reset_sigpipe(#[allow(unused_variables)] sigpipe: u8) {
+    #[cfg(not(any(
+        target_os = "emscripten",
+        target_os = "fuchsia",
+        target_os = "horizon",
+        // Unikraft's `signal` implementation is currently broken:
+        // https://github.com/unikraft/lib-musl/issues/57
+        target_vendor = "unikraft",
+    )))]
+    {
+        // We don't want to add this as a public type to std, nor do we
+        // want to `include!` a file from the compiler (which would break
+        // Miri and xargo for example), so we choose to duplicate these
+        // constants from `compiler/rustc_session/src/config/sigpipe.rs`.
+        // See the other file for docs. NOTE: Make sure to keep them in
+        // sync!
+        mod sigpipe {
+            pub const DEFAULT: u8 = 0;
+            pub const INHERIT: u8 = 1;
+            pub const SIG_IGN: u8 = 2;
+            pub const SIG_DFL: u8 = 3;
+        }
 
-            let (sigpipe_attr_specified, handler) = match sigpipe {
-                sigpipe::DEFAULT => (false, Some(dlibc::SIG_IGN)),
-                sigpipe::INHERIT => (true, None),
-                sigpipe::SIG_IGN => (true, Some(dlibc::SIG_IGN)),
-                sigpipe::SIG_DFL => (true, Some(dlibc::SIG_DFL)),
-                _ => unreachable!(),
-            };
-            if sigpipe_attr_specified {
-                UNIX_SIGPIPE_ATTR_SPECIFIED
-                    .store(true, crate::std::sync::atomic::Ordering::Relaxed);
-            }
-            if let Some(handler) = handler {
-                rtassert!(signal(dlibc::SIGPIPE, handler) != dlibc::SIG_ERR);
-            }
+        let (sigpipe_attr_specified, handler) = match sigpipe {
+            sigpipe::DEFAULT => (false, Some(dlibc::SIG_IGN)),
+            sigpipe::INHERIT => (true, None),
+            sigpipe::SIG_IGN => (true, Some(dlibc::SIG_IGN)),
+            sigpipe::SIG_DFL => (true, Some(dlibc::SIG_DFL)),
+            _ => unreachable!(),
+        };
+        if sigpipe_attr_specified {
+            UNIX_SIGPIPE_ATTR_SPECIFIED.store(true, crate::std::sync::atomic::Ordering::Relaxed);
+        }
+        if let Some(handler) = handler {
+            rtassert!(signal(dlibc::SIGPIPE, handler) != dlibc::SIG_ERR);
         }
     }
 }
 
+// The part of `init` that needs neither the file system nor the console, for
+// the first processes DragonOS starts. See `os::dragonos::rt`.
+// SAFETY: must be called only once, before `init_late` and instead of `init`.
+#[cfg(target_os = "dragonos")]
+pub unsafe fn init_early(sigpipe: u8) {
+    reset_sigpipe(sigpipe);
+    stack_overflow::init();
+}
+
+// The rest of `init`, once `/dev/null` exists.
+// SAFETY: must be called only once, after `init_early`.
+#[cfg(target_os = "dragonos")]
+pub unsafe fn init_late(argc: isize, argv: *const *const u8) {
+    sanitize_standard_fds();
+    args::init(argc, argv);
+}
+
 // This is set (up to once) in reset_sigpipe.
 #[cfg(not(any(
     target_os = "espidf",
@@ -301,6 +319,25 @@ pub fn decode_error_kind(errno: i32) -> ErrorKind {
 
         dlibc::EACCES | dlibc::EPERM => PermissionDenied,
 
+        // Socket and driver errors that DragonOS returns in places where
+        // callers match on the kind rather than the code.
+        #[cfg(target_os = "dragonos")]
+        dlibc::EOPNOTSUPP
+        | dlibc::EAFNOSUPPORT
+        | dlibc::EPFNOSUPPORT
+        | dlibc::EPROTONOSUPPORT
+        | dlibc::ESOCKTNOSUPPORT => Unsupported,
+        #[cfg(target_os = "dragonos")]
+        dlibc::EHOSTDOWN => HostUnreachable,
+        #[cfg(target_os = "dragonos")]
+        dlibc::ENETRESET => ConnectionReset,
+        #[cfg(target_os = "dragonos")]
+        dlibc::ETIME => TimedOut,
+        #[cfg(target_os = "dragonos")]
+        dlibc::ENOBUFS => OutOfMemory,
+        #[cfg(target_os = "dragonos")]
+        dlibc::EILSEQ => InvalidData,
+
         // These two constants can have the same value on some systems,
         // but different values on others, so we can't use a match
         // clause
@@ -82,6 +82,7 @@ impl Socket {
                     target_os = "netbsd",
                     target_os = "openbsd",
                     target_os = "nto",
+                    target_os = "dragonos",
                 ))] {
                     // On platforms that support it we pass the SOCK_CLOEXEC
                     // flag to atomically create the socket and set it as
@@ -120,6 +121,7 @@ impl Socket {
                     target_os = "netbsd",
                     target_os = "openbsd",
                     target_os = "nto",
+                    target_os = "dragonos",
                 ))] {
                     // Like above, set cloexec atomically
                     cvt(dlibc::socketpair(fam, ty | dlibc::SOCK_CLOEXEC, 0, fds.as_mut_ptr()))?;
@@ -232,6 +234,7 @@ impl Socket {
                 target_os = "linux",
                 target_os = "netbsd",
                 target_os = "openbsd",
+                target_os = "dragonos",
             ))] {
                 unsafe {
                     let fd = cvt_r(|| dlibc::accept4(self.as_raw_fd(), storage, len, dlibc::SOCK_CLOEXEC))?;
@@ -665,6 +665,7 @@ pub fn env() -> Env {
     }
 }
 
+#[cfg(not(target_os = "dragonos"))]
 pub fn getenv(k: &OsStr) -> Option<OsString> {
     // environment variables with a nul byte can't be set, so their value is
     // always None as well
@@ -685,6 +686,35 @@ pub fn getenv(k: &OsStr) -> Option<OsString> {
     .flatten()
 }
 
+// Rather than asking dlibc's `getenv`, which needs `k` nul-terminated, this
+// walks `environ` itself and compares entries with `k` in place.
+#[cfg(target_os = "dragonos")]
+pub fn getenv(k: &OsStr) -> Option<OsString> {
+    let k = k.as_bytes();
+    // Variables with such names can't be set, so they are never found.
+    if k.is_empty() || k.contains(&b'=') || k.contains(&0) {
+        return None;
+    }
+    let _guard = env_read_lock();
+    unsafe {
+        let mut environ = *environ();
+        if environ.is_null() {
+            return None;
+        }
+        while !(*environ).is_null() {
+            let entry = (*environ).cast::<u8>();
+            // `k` has no nul, so this stops at the end of shorter entries.
+            let matches = k.iter().enumerate().all(|(i, &b)| *entry.add(i) == b);
+            if matches && *entry.add(k.len()) == b'=' {
+                let value = CStr::from_ptr(entry.add(k.len() + 1).cast()).to_bytes();
+                return Some(OsStringExt::from_vec(value.to_vec()));
+            }
+            environ = environ.add(1);
+        }
+    }
+    None
+}
+
 pub fn setenv(k: &OsStr, v: &OsStr) -> io::Result<()> {
     run_with_cstr(k.as_bytes(), |k| {
         run_with_cstr(v.as_bytes(), |v| {
@@ -779,6 +809,103 @@ pub fn exit(code: i32) -> ! {
     unsafe { dlibc::exit(code as c_int) }
 }
 
+/// Hooks registered through `std::os::dragonos::process::at_exit`.
+///
+/// They are run by a single trampoline registered with the C library's
+/// `atexit` the first time a hook is added, so they run in the same pass as
+/// C handlers, before C stdio is flushed.
+#[cfg(target_os = "dragonos")]
+static EXIT_HOOKS: crate::std::sync::Mutex<Vec<Box<dyn FnOnce() + Send>>> =
+    crate::std::sync::Mutex::new(Vec::new());
+
+#[cfg(target_os = "dragonos")]
+pub fn at_exit(hook: Box<dyn FnOnce() + Send>) -> io::Result<()> {
+    use crate::std::sync::atomic::{AtomicBool, Ordering};
+
+    static REGISTERED: AtomicBool = AtomicBool::new(false);
+
+    extern "C" fn run_exit_hooks() {
+        // Hooks may register further hooks, so drain until nothing is left,
+        // running each batch in reverse order of registration like `atexit`.
+        loop {
+            let hooks = mem::take(&mut *EXIT_HOOKS.lock().unwrap_or_else(PoisonError::into_inner));
+            if hooks.is_empty() {
+                break;
+            }
+            for hook in hooks.into_iter().rev() {
+                hook();
+            }
+        }
+    }
+
+    let mut hooks = EXIT_HOOKS.lock().unwrap_or_else(PoisonError::into_inner);
+    if !REGISTERED.load(Ordering::Relaxed) {
+        if unsafe { dlibc::atexit(run_exit_hooks) } != 0 {
+            return Err(io::const_io_error!(
+                io::ErrorKind::Other,
+                "no room left in the atexit table",
+            ));
+        }
+        REGISTERED.store(true, Ordering::Relaxed);
+    }
+    hooks.push(hook);
+    Ok(())
+}
+
+#[cfg(target_os = "dragonos")]
+pub fn build_id() -> Option<&'static [u8]> {
+    const NT_GNU_BUILD_ID: u32 = 3;
+
+    unsafe {
+        let phdr = dlibc::getauxval(dlibc::AT_PHDR as dlibc::c_ulong) as *const dlibc::Elf64_Phdr;
+        let phnum = dlibc::getauxval(dlibc::AT_PHNUM as dlibc::c_ulong) as usize;
+        if phdr.is_null() {
+            return None;
+        }
+        // The program headers and the notes stay mapped for as long as the
+        // program runs.
+        let phdrs = slice::from_raw_parts(phdr, phnum);
+        // Addresses in the program headers are relative to where the
+        // executable was loaded, which PT_PHDR tells for position-independent
+        // executables.
+        let bias = phdrs
+            .iter()
+            .find(|p| p.p_type == dlibc::PT_PHDR)
+            .map_or(0, |p| (phdr as usize).wrapping_sub(p.p_vaddr as usize));
+        phdrs
+            .iter()
+            .filter(|p| p.p_type == dlibc::PT_NOTE)
+            .find_map(|p| {
+                let notes = slice::from_raw_parts(
+                    bias.wrapping_add(p.p_vaddr as usize) as *const u8,
+                    p.p_memsz as usize,
+                );
+                find_note(notes, b"GNU\0", NT_GNU_BUILD_ID)
+            })
+    }
+}
+
+/// Returns the description of the first note in `notes` with the given name
+/// and type.
+///
+/// Each note is a header of three words, the name size, the description
+/// size and the type, followed by the name and the description, each padded
+/// to a multiple of four bytes.
+#[cfg(target_os = "dragonos")]
+fn find_note<'a>(mut notes: &'a [u8], name: &[u8], ty: u32) -> Option<&'a [u8]> {
+    let pad = |len: usize| len.checked_add(3).map(|len| len & !3);
+    while notes.len() >= 12 {
+        let word = |i: usize| u32::from_ne_bytes(notes[i * 4..i * 4 + 4].try_into().unwrap());
+        let (namesz, descsz, n_type) = (word(0) as usize, word(1) as usize, word(2));
+        let desc = 12 + pad(namesz)?;
+        if n_type == ty && notes.get(12..12 + namesz)? == name {
+            return notes.get(desc..desc.checked_add(descsz)?);
+        }
+        notes = notes.get(desc.checked_add(pad(descsz)?)?..)?;
+    }
+    None
+}
+
 pub fn getpid() -> u32 {
     unsafe { dlibc::getpid() as u32 }
 }
@@ -21,3 +21,69 @@ fn test_parse_glibc_version() {
         assert_eq!(parsed, super::parse_glibc_version(version_str));
     }
 }
+
+#[test]
+#[cfg(target_os = "dragonos")]
+fn test_find_note() {
+    use super::find_note;
+
+    let mut notes = Vec::new();
+    for (name, ty, desc) in [
+        (&b"XY\0"[..], 3u32, &[1u8, 2][..]),
+        (&b"GNU\0"[..], 1, &[3][..]),
+        (&b"GNU\0"[..], 3, &[4, 5, 6, 7, 8][..]),
+    ] {
+        notes.extend_from_slice(&(name.len() as u32).to_ne_bytes());
+        notes.extend_from_slice(&(desc.len() as u32).to_ne_bytes());
+        notes.extend_from_slice(&ty.to_ne_bytes());
+        for field in [name, desc] {
+            notes.extend_from_slice(field);
+            notes.resize((notes.len() + 3) & !3, 0);
+        }
+    }
+    assert_eq!(find_note(&notes, b"GNU\0", 3), Some(&[4, 5, 6, 7, 8][..]));
+    assert_eq!(find_note(&notes, b"GNU\0", 2), None);
+    // A truncated note is not read past the end.
+    assert_eq!(find_note(&notes[..notes.len() - 4], b"GNU\0", 3), None);
+}
+
+#[test]
+#[cfg(target_os = "dragonos")]
+fn test_build_id() {
+    // Whether the test binary has a build ID depends on the linker, but if
+    // it has one, it is never empty.
+    if let Some(id) = super::build_id() {
+        assert!(!id.is_empty());
+    }
+}
+
+#[test]
+fn test_getenv() {
+    use super::{getenv, setenv, unsetenv};
+    use crate::std::ffi::OsStr;
+
+    let key = OsStr::new("DRSTD_TEST_GETENV");
+    setenv(key, OsStr::new("a=b")).unwrap();
+    assert_eq!(getenv(key).as_deref(), Some(OsStr::new("a=b")));
+    // Only whole names match, not prefixes of a name or of name=value.
+    assert_eq!(getenv(OsStr::new("DRSTD_TEST_GETEN")), None);
+    assert_eq!(getenv(OsStr::new("DRSTD_TEST_GETENV_LONGER")), None);
+    // Names that can't be set are never found.
+    assert_eq!(getenv(OsStr::new("")), None);
+    assert_eq!(getenv(OsStr::new("DRSTD_TEST_GETENV=a")), None);
+    assert_eq!(getenv(OsStr::new("DRSTD_TEST_GETENV\0")), None);
+    unsetenv(key).unwrap();
+    assert_eq!(getenv(key), None);
+}
+
+#[test]
+fn test_env_is_a_snapshot() {
+    use super::{env, setenv, unsetenv};
+    use crate::std::ffi::OsStr;
+
+    let key = OsStr::new("DRSTD_TEST_ENV_SNAPSHOT");
+    setenv(key, OsStr::new("1")).unwrap();
+    let vars = env();
+    unsetenv(key).unwrap();
+    assert!(vars.into_iter().any(|(k, v)| k == key && v == "1"));
+}
@@ -3,7 +3,7 @@ use crate::std::mem;
 use crate::std::os::unix::io::{AsFd, AsRawFd, BorrowedFd, FromRawFd, IntoRawFd, RawFd};
 use crate::std::sys::fd::FileDesc;
 use crate::std::sys::{cvt, cvt_r};
-use crate::std::sys_common::IntoInner;
+use crate::std::sys_common::{AsInner, FromInner, IntoInner};
 use dlibc;
 
 ////////////////////////////////////////////////////////////////////////////////
@@ -25,7 +25,8 @@ pub fn anon_pipe() -> io::Result<(AnonPipe, AnonPipe)> {
             target_os = "linux",
             target_os = "netbsd",
             target_os = "openbsd",
-            target_os = "redox"
+            target_os = "redox",
+            target_os = "dragonos",
         ))] {
             unsafe {
                 cvt(dlibc::pipe2(fds.as_mut_ptr(), dlibc::O_CLOEXEC))?;
@@ -46,6 +47,10 @@ pub fn anon_pipe() -> io::Result<(AnonPipe, AnonPipe)> {
 }
 
 impl AnonPipe {
+    pub fn try_clone(&self) -> io::Result<Self> {
+        self.0.duplicate().map(Self)
+    }
+
     pub fn read(&self, buf: &mut [u8]) -> io::Result<usize> {
         self.0.read(buf)
     }
@@ -81,12 +86,24 @@ impl AnonPipe {
     }
 }
 
+impl AsInner<FileDesc> for AnonPipe {
+    fn as_inner(&self) -> &FileDesc {
+        &self.0
+    }
+}
+
 impl IntoInner<FileDesc> for AnonPipe {
     fn into_inner(self) -> FileDesc {
         self.0
     }
 }
 
+impl FromInner<FileDesc> for AnonPipe {
+    fn from_inner(fd: FileDesc) -> Self {
+        Self(fd)
+    }
+}
+
 pub fn read2(p1: AnonPipe, v1: &mut Vec<u8>, p2: AnonPipe, v2: &mut Vec<u8>) -> io::Result<()> {
     // Set both pipes into nonblocking mode as we're gonna be reading from both
     // in the `select` loop below, and we wouldn't want one to block the other!
@@ -109,9 +109,29 @@ pub struct Command {
     create_pidfd: bool,
     #[cfg(target_os = "dragonos")]
     create_pidfd: bool,
+    #[cfg(target_os = "dragonos")]
+    fd_mappings: Vec<FdMapping>,
+    #[cfg(target_os = "dragonos")]
+    umask: Option<dlibc::mode_t>,
+    #[cfg(target_os = "dragonos")]
+    sigmask: Option<dlibc::sigset_t>,
+    #[cfg(target_os = "dragonos")]
+    syscall_filter: Option<Box<[dlibc::sock_filter]>>,
+    #[cfg(target_os = "dragonos")]
+    close_other_fds: bool,
+    #[cfg(target_os = "dragonos")]
+    exec_fd: Option<OwnedFd>,
     pgroup: Option<pid_t>,
 }
 
+/// A descriptor to install in the child at a fixed number, see
+/// `std::os::dragonos::process::CommandExt::fd_mapping`.
+#[cfg(target_os = "dragonos")]
+pub struct FdMapping {
+    pub parent: OwnedFd,
+    pub child: c_int,
+}
+
 // Create a new type for argv, so that we can make it `Send` and `Sync`
 struct Argv(Vec<*const c_char>);
 
@@ -249,6 +269,12 @@ impl Command {
             stdout: None,
             stderr: None,
             create_pidfd: false,
+            fd_mappings: Vec::new(),
+            umask: None,
+            sigmask: None,
+            syscall_filter: None,
+            close_other_fds: false,
+            exec_fd: None,
             pgroup: None,
         }
     }
@@ -299,6 +325,76 @@ impl Command {
         self.create_pidfd = val;
     }
 
+    #[cfg(target_os = "dragonos")]
+    pub fn fd_mapping(&mut self, parent: OwnedFd, child: c_int) {
+        self.fd_mappings.push(FdMapping { parent, child });
+    }
+
+    #[cfg(target_os = "dragonos")]
+    pub fn get_fd_mappings(&self) -> &[FdMapping] {
+        &self.fd_mappings
+    }
+
+    #[cfg(target_os = "dragonos")]
+    pub fn get_fd_mappings_mut(&mut self) -> &mut [FdMapping] {
+        &mut self.fd_mappings
+    }
+
+    #[cfg(target_os = "dragonos")]
+    pub fn umask(&mut self, mask: dlibc::mode_t) {
+        self.umask = Some(mask);
+    }
+
+    #[cfg(target_os = "dragonos")]
+    pub fn get_umask(&self) -> Option<dlibc::mode_t> {
+        self.umask
+    }
+
+    #[cfg(target_os = "dragonos")]
+    pub fn sigmask(&mut self, set: dlibc::sigset_t) {
+        self.sigmask = Some(set);
+    }
+
+    #[cfg(target_os = "dragonos")]
+    pub fn get_sigmask(&self) -> Option<&dlibc::sigset_t> {
+        self.sigmask.as_ref()
+    }
+
+    #[cfg(target_os = "dragonos")]
+    pub fn syscall_filter(&mut self, program: Box<[dlibc::sock_filter]>) {
+        self.syscall_filter = Some(program);
+    }
+
+    #[cfg(target_os = "dragonos")]
+    pub fn get_syscall_filter(&self) -> Option<&[dlibc::sock_filter]> {
+        self.syscall_filter.as_deref()
+    }
+
+    #[cfg(target_os = "dragonos")]
+    pub fn close_other_fds(&mut self, close: bool) {
+        self.close_other_fds = close;
+    }
+
+    #[cfg(target_os = "dragonos")]
+    pub fn get_close_other_fds(&self) -> bool {
+        self.close_other_fds
+    }
+
+    #[cfg(target_os = "dragonos")]
+    pub fn exec_fd(&mut self, fd: OwnedFd) {
+        self.exec_fd = Some(fd);
+    }
+
+    #[cfg(target_os = "dragonos")]
+    pub fn get_exec_fd(&self) -> Option<&OwnedFd> {
+        self.exec_fd.as_ref()
+    }
+
+    #[cfg(target_os = "dragonos")]
+    pub fn get_exec_fd_mut(&mut self) -> Option<&mut OwnedFd> {
+        self.exec_fd.as_mut()
+    }
+
     #[cfg(not(target_os = "linux"))]
     #[allow(dead_code)]
     pub fn get_create_pidfd(&self) -> bool {
@@ -11,6 +11,9 @@ use dlibc;
 #[cfg(target_os = "linux")]
 use crate::std::os::linux::process::PidFd;
 
+#[cfg(target_os = "dragonos")]
+use crate::std::os::unix::io::AsRawFd;
+
 #[cfg(any(
     target_os = "macos",
     target_os = "watchos",
@@ -73,113 +76,127 @@ impl Command {
         default: Stdio,
         needs_stdin: bool,
     ) -> io::Result<(Process, StdioPipes)> {
-        // const CLOEXEC_MSG_FOOTER: [u8; 4] = *b"NOEX";
-
-        // let envp = self.capture_env();
-
-        // if self.saw_nul() {
-        //     return Err(io::const_io_error!(
-        //         ErrorKind::InvalidInput,
-        //         "nul byte found in provided data",
-        //     ));
-        // }
-
-        // let (ours, theirs) = self.setup_io(default, needs_stdin)?;
-
-        // if let Some(ret) = self.posix_spawn(&theirs, envp.as_ref())? {
-        //     return Ok((ret, ours));
-        // }
-
-        // #[cfg(target_os = "linux")]
-        // let (input, output) = sys::net::Socket::new_pair(dlibc::AF_UNIX, dlibc::SOCK_SEQPACKET)?;
-
-        // #[cfg(not(target_os = "linux"))]
-        // let (input, output) = sys::pipe::anon_pipe()?;
-
-        // // Whatever happens after the fork is almost for sure going to touch or
-        // // look at the environment in one way or another (PATH in `execvp` or
-        // // accessing the `environ` pointer ourselves). Make sure no other thread
-        // // is accessing the environment when we do the fork itself.
-        // //
-        // // Note that as soon as we're done with the fork there's no need to hold
-        // // a lock any more because the parent won't do anything and the child is
-        // // in its own process. Thus the parent drops the lock guard immediately.
-        // // The child calls `mem::forget` to leak the lock, which is crucial because
-        // // releasing a lock is not async-signal-safe.
-        // let env_lock = sys::os::env_read_lock();
-        // let pid = unsafe { self.do_fork()? };
-
-        // if pid == 0 {
-        //     crate::std::panic::always_abort();
-        //     mem::forget(env_lock); // avoid non-async-signal-safe unlocking
-        //     drop(input);
-        //     #[cfg(target_os = "linux")]
-        //     if self.get_create_pidfd() {
-        //         self.send_pidfd(&output);
-        //     }
-        //     let Err(err) = unsafe { self.do_exec(theirs, envp.as_ref()) };
-        //     let errno = err.raw_os_error().unwrap_or(dlibc::EINVAL) as u32;
-        //     let errno = errno.to_be_bytes();
-        //     let bytes = [
-        //         errno[0],
-        //         errno[1],
-        //         errno[2],
-        //         errno[3],
-        //         CLOEXEC_MSG_FOOTER[0],
-        //         CLOEXEC_MSG_FOOTER[1],
-        //         CLOEXEC_MSG_FOOTER[2],
-        //         CLOEXEC_MSG_FOOTER[3],
-        //     ];
-        //     // pipe I/O up to PIPE_BUF bytes should be atomic, and then
-        //     // we want to be sure we *don't* run at_exit destructors as
-        //     // we're being torn down regardless
-        //     rtassert!(output.write(&bytes).is_ok());
-        //     unsafe { dlibc::_exit(1) }
-        // }
-
-        // drop(env_lock);
-        // drop(output);
-
-        // #[cfg(target_os = "linux")]
-        // let pidfd = if self.get_create_pidfd() { self.recv_pidfd(&input) } else { -1 };
-
-        // #[cfg(not(target_os = "linux"))]
-        // let pidfd = -1;
-
-        // // Safety: We obtained the pidfd from calling `clone3` with
-        // // `CLONE_PIDFD` so it's valid an otherwise unowned.
-        // let mut p = unsafe { Process::new(pid, pidfd) };
-        // let mut bytes = [0; 8];
-
-        // // loop to handle EINTR
-        // loop {
-        //     match input.read(&mut bytes) {
-        //         Ok(0) => return Ok((p, ours)),
-        //         Ok(8) => {
-        //             let (errno, footer) = bytes.split_at(4);
-        //             assert_eq!(
-        //                 CLOEXEC_MSG_FOOTER, footer,
-        //                 "Validation on the CLOEXEC pipe failed: {:?}",
-        //                 bytes
-        //             );
-        //             let errno = i32::from_be_bytes(errno.try_into().unwrap());
-        //             assert!(p.wait().is_ok(), "wait() should either return Ok or panic");
-        //             return Err(Error::from_raw_os_error(errno));
-        //         }
-        //         Err(ref e) if e.is_interrupted() => {}
-        //         Err(e) => {
-        //             assert!(p.wait().is_ok(), "wait() should either return Ok or panic");
-        //             panic!("the CLOEXEC pipe failed: {e:?}")
-        //         }
-        //         Ok(..) => {
-        //             // pipe I/O up to PIPE_BUF bytes should be atomic
-        //             // similarly SOCK_SEQPACKET messages should arrive whole
-        //             assert!(p.wait().is_ok(), "wait() should either return Ok or panic");
-        //             panic!("short read on the CLOEXEC pipe")
-        //         }
-        //     }
-        // }
-        unimplemented!();
+        const CLOEXEC_MSG_FOOTER: [u8; 4] = *b"NOEX";
+
+        let envp = self.capture_env();
+
+        if self.saw_nul() {
+            return Err(io::const_io_error!(
+                ErrorKind::InvalidInput,
+                "nul byte found in provided data",
+            ));
+        }
+
+        let (ours, theirs) = self.setup_io(default, needs_stdin)?;
+
+        if let Some(ret) = self.posix_spawn(&theirs, envp.as_ref())? {
+            return Ok((ret, ours));
+        }
+
+        #[cfg(target_os = "linux")]
+        let (input, output) = sys::net::Socket::new_pair(dlibc::AF_UNIX, dlibc::SOCK_SEQPACKET)?;
+
+        #[cfg(not(target_os = "linux"))]
+        let (input, output) = sys::pipe::anon_pipe()?;
+
+        // Every descriptor std creates (files, sockets, pipes, including the
+        // error pipe above) is opened with CLOEXEC, so after `exec` the child
+        // only holds its stdio, the descriptors installed by `fd_mapping`,
+        // and whatever the caller explicitly marked inheritable.
+
+        // Whatever happens after the fork is almost for sure going to touch or
+        // look at the environment in one way or another (PATH in `execvp` or
+        // accessing the `environ` pointer ourselves). Make sure no other thread
+        // is accessing the environment when we do the fork itself.
+        //
+        // Note that as soon as we're done with the fork there's no need to hold
+        // a lock any more because the parent won't do anything and the child is
+        // in its own process. Thus the parent drops the lock guard immediately.
+        // The child calls `mem::forget` to leak the lock, which is crucial because
+        // releasing a lock is not async-signal-safe.
+        let env_lock = sys::os::env_read_lock();
+        let pid = unsafe { self.do_fork()? };
+
+        if pid == 0 {
+            crate::std::panic::always_abort();
+            mem::forget(env_lock); // avoid non-async-signal-safe unlocking
+            drop(input);
+            // The child's only thread has the process ID as its task ID.
+            #[cfg(target_os = "dragonos")]
+            crate::std::os::dragonos::thread::forget_tid();
+            #[cfg(target_os = "linux")]
+            if self.get_create_pidfd() {
+                self.send_pidfd(&output);
+            }
+            let err = match unsafe { self.do_exec(theirs, envp.as_ref()) } {
+                Ok(never) => never,
+                Err(err) => err,
+            };
+            let errno = err.raw_os_error().unwrap_or(dlibc::EINVAL) as u32;
+            let errno = errno.to_be_bytes();
+            let bytes = [
+                errno[0],
+                errno[1],
+                errno[2],
+                errno[3],
+                CLOEXEC_MSG_FOOTER[0],
+                CLOEXEC_MSG_FOOTER[1],
+                CLOEXEC_MSG_FOOTER[2],
+                CLOEXEC_MSG_FOOTER[3],
+            ];
+            // pipe I/O up to PIPE_BUF bytes should be atomic, and then
+            // we want to be sure we *don't* run at_exit destructors as
+            // we're being torn down regardless
+            rtassert!(output.write(&bytes).is_ok());
+            unsafe { dlibc::_exit(1) }
+        }
+
+        drop(env_lock);
+        drop(output);
+
+        #[cfg(target_os = "linux")]
+        let pidfd = if self.get_create_pidfd() {
+            self.recv_pidfd(&input)
+        } else {
+            -1
+        };
+
+        #[cfg(not(target_os = "linux"))]
+        let pidfd = -1;
+
+        // Safety: We obtained the pidfd from calling `clone3` with
+        // `CLONE_PIDFD` so it's valid an otherwise unowned.
+        let mut p = unsafe { Process::new(pid, pidfd) };
+        let mut bytes = [0; 8];
+
+        // loop to handle EINTR
+        loop {
+            match input.read(&mut bytes) {
+                Ok(0) => return Ok((p, ours)),
+                Ok(8) => {
+                    let (errno, footer) = bytes.split_at(4);
+                    assert_eq!(
+                        CLOEXEC_MSG_FOOTER, footer,
+                        "Validation on the CLOEXEC pipe failed: {:?}",
+                        bytes
+                    );
+                    let errno = i32::from_be_bytes(errno.try_into().unwrap());
+                    assert!(p.wait().is_ok(), "wait() should either return Ok or panic");
+                    return Err(Error::from_raw_os_error(errno));
+                }
+                Err(ref e) if e.is_interrupted() => {}
+                Err(e) => {
+                    assert!(p.wait().is_ok(), "wait() should either return Ok or panic");
+                    panic!("the CLOEXEC pipe failed: {e:?}")
+                }
+                Ok(..) => {
+                    // pipe I/O up to PIPE_BUF bytes should be atomic
+                    // similarly SOCK_SEQPACKET messages should arrive whole
+                    assert!(p.wait().is_ok(), "wait() should either return Ok or panic");
+                    panic!("short read on the CLOEXEC pipe")
+                }
+            }
+        }
     }
 
     pub fn output(&mut self) -> io::Result<(ExitStatus, Vec<u8>, Vec<u8>)> {
@@ -321,6 +338,14 @@ impl Command {
             cvt_r(|| dlibc::dup2(fd, dlibc::STDERR_FILENO))?;
         }
 
+        #[cfg(target_os = "dragonos")]
+        {
+            self.install_fd_mappings()?;
+            if self.get_close_other_fds() {
+                self.cloexec_unmapped_fds()?;
+            }
+        }
+
         #[cfg(not(target_os = "l4re"))]
         {
             if let Some(_g) = self.get_groups() {
@@ -350,6 +375,9 @@ impl Command {
             cvt(dlibc::chdir(cwd.as_ptr()))?;
         }
 
+        #[cfg(target_os = "dragonos")]
+        self.apply_spawn_context()?;
+
         if let Some(pgroup) = self.get_pgroup() {
             cvt(dlibc::setpgid(0, pgroup))?;
         }
@@ -358,7 +386,7 @@ impl Command {
         #[cfg(not(target_os = "emscripten"))]
         {
             // Inherit the signal mask from the parent rather than resetting it (i.e. do not call
-            // pthread_sigmask).
+            // pthread_sigmask), unless a `SpawnContext` asked for a specific one.
 
             // If #[unix_sigpipe] is specified, don't reset SIGPIPE to SIG_DFL.
             // If #[unix_sigpipe] is not specified, reset SIGPIPE to SIG_DFL for backward compatibility.
@@ -410,10 +438,166 @@ impl Command {
             *sys::os::environ() = envp.as_ptr();
         }
 
+        // Installed last, so that only the program the child runs is
+        // restricted and not the setup above.
+        #[cfg(target_os = "dragonos")]
+        if let Some(program) = self.get_syscall_filter() {
+            crate::std::os::dragonos::seccomp::install(program)?;
+        }
+
+        #[cfg(target_os = "dragonos")]
+        if let Some(fd) = self.get_exec_fd() {
+            return Err(self.exec_program_fd(fd.as_raw_fd(), *sys::os::environ()));
+        }
+
         dlibc::execvp(self.get_program_cstr().as_ptr(), self.get_argv().as_ptr());
         Err(io::Error::last_os_error())
     }
 
+    // Installs the descriptors requested through `CommandExt::fd_mapping`.
+    //
+    // A mapping's target may be another mapping's source, so every source is
+    // first moved above the highest target (with CLOEXEC set, so these
+    // temporaries vanish on exec) and only then `dup2`ed into place, which
+    // also clears CLOEXEC on the target. Like the rest of `do_exec`, this
+    // must not allocate.
+    #[cfg(target_os = "dragonos")]
+    unsafe fn install_fd_mappings(&mut self) -> io::Result<()> {
+        use crate::std::os::unix::io::{FromRawFd, OwnedFd};
+        use crate::std::sys::cvt_r;
+
+        let mappings = self.get_fd_mappings_mut();
+        let mut floor = dlibc::STDERR_FILENO + 1;
+        for (i, mapping) in mappings.iter().enumerate() {
+            if mapping.child <= dlibc::STDERR_FILENO
+                || mappings[..i].iter().any(|m| m.child == mapping.child)
+            {
+                return Err(io::const_io_error!(
+                    ErrorKind::InvalidInput,
+                    "invalid or duplicate child fd in fd mapping",
+                ));
+            }
+            floor = floor.max(mapping.child + 1);
+        }
+
+        // The program to run must survive the mappings as well.
+        if let Some(fd) = self.get_exec_fd_mut() {
+            if fd.as_raw_fd() < floor {
+                let staged = cvt(dlibc::fcntl(fd.as_raw_fd(), dlibc::F_DUPFD_CLOEXEC, floor))?;
+                drop(mem::replace(fd, OwnedFd::from_raw_fd(staged)));
+            }
+        }
+
+        let mappings = self.get_fd_mappings_mut();
+        for mapping in mappings.iter_mut() {
+            let staged = cvt(dlibc::fcntl(
+                mapping.parent.as_raw_fd(),
+                dlibc::F_DUPFD_CLOEXEC,
+                floor,
+            ))?;
+            // Closing the original is async-signal-safe and frees its number
+            // in case it is the target of a later mapping.
+            drop(mem::replace(
+                &mut mapping.parent,
+                OwnedFd::from_raw_fd(staged),
+            ));
+        }
+
+        for mapping in mappings.iter() {
+            cvt_r(|| dlibc::dup2(mapping.parent.as_raw_fd(), mapping.child))?;
+        }
+        Ok(())
+    }
+
+    // Executes the program open as `fd`, for `CommandExt::exec_fd`, and
+    // returns the error if that fails. Kernels without `execveat` get the
+    // same file through `/proc/self/fd`, with the path built on the stack
+    // since this runs after `fork`.
+    #[cfg(target_os = "dragonos")]
+    unsafe fn exec_program_fd(&self, fd: c_int, envp: *const *const dlibc::c_char) -> io::Error {
+        let argv = self.get_argv().as_ptr();
+        dlibc::syscall(
+            dlibc::SYS_execveat,
+            fd,
+            b"\0".as_ptr(),
+            argv,
+            envp,
+            dlibc::AT_EMPTY_PATH,
+        );
+        let err = io::Error::last_os_error();
+        if err.raw_os_error() != Some(dlibc::ENOSYS) {
+            return err;
+        }
+
+        const PREFIX: &[u8] = b"/proc/self/fd/";
+        let mut path = [0u8; PREFIX.len() + 11];
+        path[..PREFIX.len()].copy_from_slice(PREFIX);
+        let mut digits = [0u8; 10];
+        let mut n = fd as u32;
+        let mut i = digits.len();
+        loop {
+            i -= 1;
+            digits[i] = b'0' + (n % 10) as u8;
+            n /= 10;
+            if n == 0 {
+                break;
+            }
+        }
+        let len = digits.len() - i;
+        path[PREFIX.len()..][..len].copy_from_slice(&digits[i..]);
+        dlibc::execve(path.as_ptr().cast(), argv, envp);
+        io::Error::last_os_error()
+    }
+
+    // Marks every descriptor above the standard streams that isn't the
+    // target of an `fd_mapping` close-on-exec, for
+    // `CommandExt::close_other_fds`. The targets are found by scanning
+    // rather than by sorting them, since the child must not allocate after
+    // `fork`.
+    #[cfg(target_os = "dragonos")]
+    fn cloexec_unmapped_fds(&self) -> io::Result<()> {
+        use crate::std::os::dragonos::io::set_cloexec_range;
+
+        let mappings = self.get_fd_mappings();
+        let mut first = dlibc::STDERR_FILENO + 1;
+        loop {
+            let next = mappings
+                .iter()
+                .map(|m| m.child)
+                .filter(|&fd| fd >= first)
+                .min();
+            match next {
+                Some(fd) => {
+                    if fd > first {
+                        set_cloexec_range(first, fd - 1)?;
+                    }
+                    match fd.checked_add(1) {
+                        Some(fd) => first = fd,
+                        None => return Ok(()),
+                    }
+                }
+                None => return set_cloexec_range(first, dlibc::c_int::MAX),
+            }
+        }
+    }
+
+    // Sets the umask and signal mask recorded by
+    // `std::os::dragonos::process::CommandExt::with_context`.
+    #[cfg(target_os = "dragonos")]
+    unsafe fn apply_spawn_context(&self) -> io::Result<()> {
+        if let Some(mask) = self.get_umask() {
+            dlibc::umask(mask);
+        }
+        if let Some(set) = self.get_sigmask() {
+            sys::cvt_nz(dlibc::pthread_sigmask(
+                dlibc::SIG_SETMASK,
+                set,
+                crate::std::ptr::null_mut(),
+            ))?;
+        }
+        Ok(())
+    }
+
     #[cfg(any(target_os = "tvos", target_os = "watchos"))]
     unsafe fn do_exec(
         &mut self,
@@ -827,13 +1011,29 @@ impl Process {
     }
 
     pub fn kill(&mut self) -> io::Result<()> {
+        self.send_signal(dlibc::SIGKILL)
+    }
+
+    pub fn send_signal(&mut self, signal: c_int) -> io::Result<()> {
         // If we've already waited on this process then the pid can be recycled
         // and used for another process, and we probably shouldn't be killing
         // random processes, so return Ok because the process has exited already.
         if self.status.is_some() {
             Ok(())
         } else {
-            cvt(unsafe { dlibc::kill(self.pid, dlibc::SIGKILL) }).map(drop)
+            cvt(unsafe { dlibc::kill(self.pid, signal) }).map(drop)
+        }
+    }
+
+    #[cfg(target_os = "dragonos")]
+    pub fn send_signal_group(&mut self, signal: c_int) -> io::Result<()> {
+        // Once the child has been reaped its group may live on through its
+        // descendants, but the id can also be recycled. Be conservative, as
+        // with single-process signals.
+        if self.status.is_some() {
+            Ok(())
+        } else {
+            cvt(unsafe { dlibc::killpg(self.pid, signal) }).map(drop)
         }
     }
 
@@ -1088,6 +1288,21 @@ impl crate::std::os::linux::process::ChildExt for crate::std::process::Child {
     }
 }
 
+#[cfg(target_os = "dragonos")]
+impl crate::std::os::dragonos::process::ChildExt for crate::std::process::Child {
+    fn send_signal(&mut self, signal: i32) -> io::Result<()> {
+        self.handle.send_signal(signal)
+    }
+
+    fn send_signal_group(&mut self, signal: i32) -> io::Result<()> {
+        self.handle.send_signal_group(signal)
+    }
+
+    fn kill_group(&mut self) -> io::Result<()> {
+        self.handle.send_signal_group(dlibc::SIGKILL)
+    }
+}
+
 #[cfg(test)]
 #[path = "process_unix/tests.rs"]
 mod tests;
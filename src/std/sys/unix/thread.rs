@@ -1,9 +1,23 @@
+#[cfg(target_os = "dragonos")]
+use crate::std::cell::UnsafeCell;
 use crate::std::cmp;
 use crate::std::ffi::CStr;
+#[cfg(target_os = "dragonos")]
+use crate::std::ffi::CString;
 use crate::std::io;
 use crate::std::mem;
 use crate::std::num::NonZeroUsize;
+#[cfg(target_os = "dragonos")]
+use crate::std::ops::Range;
 use crate::std::ptr;
+#[cfg(target_os = "dragonos")]
+use crate::std::sync::atomic::{AtomicBool, AtomicU32, Ordering};
+#[cfg(target_os = "dragonos")]
+use crate::std::sync::{Arc, Mutex, PoisonError};
+#[cfg(target_os = "dragonos")]
+use crate::std::sys::futex::{futex_wait, futex_wake_all};
+#[cfg(target_os = "dragonos")]
+use crate::std::sys::time::Instant;
 use crate::std::sys::{os, stack_overflow};
 use crate::std::time::Duration;
 use dlibc;
@@ -12,6 +26,10 @@ use dlibc;
 use crate::std::sys::weak::dlsym;
 #[cfg(any(target_os = "solaris", target_os = "illumos", target_os = "nto"))]
 use crate::std::sys::weak::weak;
+// The kernel's limit on thread names, including the nul terminator.
+#[cfg(target_os = "dragonos")]
+const TASK_COMM_LEN: usize = 16;
+
 #[cfg(not(any(target_os = "l4re", target_os = "vxworks", target_os = "espidf")))]
 pub const DEFAULT_MIN_STACK_SIZE: usize = 2 * 1024 * 1024;
 #[cfg(target_os = "l4re")]
@@ -40,8 +58,58 @@ mod zircon {
 
 pub struct Thread {
     id: dlibc::pthread_t,
+    #[cfg(target_os = "dragonos")]
+    stack: Stack,
+    #[cfg(target_os = "dragonos")]
+    start: Arc<Start<dyn Main>>,
+}
+
+/// What a new thread starts with, in the same allocation as `main`, which
+/// `std::thread` uses to share the closure and its result with the thread.
+#[cfg(target_os = "dragonos")]
+pub struct Start<M: ?Sized> {
+    // The kernel task ID, 0 until the thread has started and stored it.
+    tid: AtomicU32,
+    // Set by the new thread once it no longer runs any Rust code.
+    finished: AtomicBool,
+    // Only written by `Thread::new` before the thread is created.
+    guard: UnsafeCell<guard::Guard>,
+    main: M,
+}
+
+/// The code a thread spawned by `Thread::new` runs.
+#[cfg(target_os = "dragonos")]
+pub trait Main: Send + Sync {
+    /// Runs the thread's code, once, on the new thread.
+    unsafe fn run(&self);
+}
+
+#[cfg(target_os = "dragonos")]
+impl<M> Start<M> {
+    pub fn new(main: M) -> Start<M> {
+        Start {
+            tid: AtomicU32::new(0),
+            finished: AtomicBool::new(false),
+            guard: UnsafeCell::new(0..0),
+            main,
+        }
+    }
 }
 
+#[cfg(target_os = "dragonos")]
+impl<M: ?Sized> crate::std::ops::Deref for Start<M> {
+    type Target = M;
+
+    fn deref(&self) -> &M {
+        &self.main
+    }
+}
+
+#[cfg(target_os = "dragonos")]
+unsafe impl<M: ?Sized + Send> Send for Start<M> {}
+#[cfg(target_os = "dragonos")]
+unsafe impl<M: ?Sized + Sync> Sync for Start<M> {}
+
 // Some platforms may have pthread_t as a pointer in which case we still want
 // a thread to be Send/Sync
 unsafe impl Send for Thread {}
@@ -49,6 +117,7 @@ unsafe impl Sync for Thread {}
 
 impl Thread {
     // unsafe: see thread::Builder::spawn_unchecked for safety requirements
+    #[cfg(not(target_os = "dragonos"))]
     pub unsafe fn new(stack: usize, p: Box<dyn FnOnce()>) -> io::Result<Thread> {
         let p = Box::into_raw(Box::new(p));
         let mut native: dlibc::pthread_t = mem::zeroed();
@@ -112,6 +181,83 @@ impl Thread {
         }
     }
 
+    // unsafe: see thread::Builder::spawn_unchecked for safety requirements.
+    // `start` is the only allocation spawning takes, besides the stack: the
+    // new thread runs `start.main` and keeps `start` until it exits.
+    #[cfg(target_os = "dragonos")]
+    pub unsafe fn new<'a, M: Main + 'a>(stack: usize, start: Arc<Start<M>>) -> io::Result<Thread> {
+        let mut native: dlibc::pthread_t = mem::zeroed();
+        let mut attr: dlibc::pthread_attr_t = mem::zeroed();
+        assert_eq!(dlibc::pthread_attr_init(&mut attr), 0);
+
+        // The thread library would pick a fixed default, so allocate the
+        // requested size ourselves, with a guard page below it. Free the
+        // stacks of finished threads first.
+        reap_detached();
+        let stack = match Stack::new(cmp::max(stack, min_stack_size(&attr))) {
+            Ok(stack) => stack,
+            Err(e) => {
+                assert_eq!(dlibc::pthread_attr_destroy(&mut attr), 0);
+                return Err(e);
+            }
+        };
+        assert_eq!(
+            dlibc::pthread_attr_setstack(&mut attr, stack.bottom(), stack.size()),
+            0
+        );
+
+        // Only the new thread can tell its kernel task ID, and only this
+        // function knows where its guard page is, so hand them to the new
+        // thread along with `main`. Nothing else reads `guard`.
+        *start.guard.get() = stack.guard();
+        let p = Arc::into_raw(start.clone()) as *mut Start<M>;
+
+        let ret = dlibc::pthread_create(&mut native, &attr, thread_start::<M>, p as *mut _);
+        assert_eq!(dlibc::pthread_attr_destroy(&mut attr), 0);
+
+        return if ret != 0 {
+            // The thread failed to start and as a result p was not consumed.
+            drop(Arc::from_raw(p));
+            stack.unmap();
+            Err(io::Error::from_raw_os_error(ret))
+        } else {
+            // `Thread` only uses the fields of `Start` other than `main`, and
+            // the caller is done with `main` by the time the last reference
+            // is dropped, so it may outlive `'a`.
+            let start: Arc<Start<dyn Main + 'a>> = start;
+            Ok(Thread {
+                id: native,
+                stack,
+                start: mem::transmute::<Arc<Start<dyn Main + 'a>>, Arc<Start<dyn Main>>>(start),
+            })
+        };
+
+        extern "C" fn thread_start<M: Main>(main: *mut dlibc::c_void) -> *mut dlibc::c_void {
+            let start = unsafe { Arc::from_raw(main as *const Start<M>) };
+            unsafe {
+                // Next, set up our stack overflow handler which may get triggered if we run
+                // out of stack.
+                let _handler = stack_overflow::Handler::new();
+                guard::set_current((*start.guard.get()).clone());
+                let tid = crate::std::os::dragonos::thread::gettid();
+                start.tid.store(tid, Ordering::Release);
+                futex_wake_all(&start.tid);
+                // Finally, let's run some code.
+                start.main.run();
+                // Free the stacks of dropped threads that finished before
+                // this one, so that they don't wait for the next spawn.
+                reap_detached();
+                // The pthread library won't run the thread-local destructors,
+                // so do it before the thread exits.
+                crate::std::sys::thread_local_key::run_dtors();
+            }
+            // Only the pthread library's exit path is left to run on the
+            // stack, so `reap_detached` may now join the thread.
+            start.finished.store(true, Ordering::Release);
+            ptr::null_mut()
+        }
+    }
+
     pub fn yield_now() {
         let ret = unsafe { dlibc::sched_yield() };
         debug_assert_eq!(ret, 0);
@@ -143,19 +289,31 @@ impl Thread {
             debug_assert_eq!(res, 0);
         }
     }
+    // Only ever called on the thread being named, so the kernel's per-task
+    // name can be set directly instead of going through the thread library.
     #[cfg(target_os = "dragonos")]
     pub fn set_name(name: &CStr) {
-        const TASK_COMM_LEN: usize = 16;
-
         unsafe {
-            // Available since glibc 2.12, musl 1.1.16, and uClibc 1.0.20.
             let name = truncate_cstr::<{ TASK_COMM_LEN }>(name);
-            let res = dlibc::pthread_setname_np(dlibc::pthread_self(), name.as_ptr());
+            let res = dlibc::prctl(dlibc::PR_SET_NAME, name.as_ptr());
             // We have no good way of propagating errors here, but in debug-builds let's check that this actually worked.
             debug_assert_eq!(res, 0);
         }
     }
 
+    // Returns the kernel's name for the calling thread, so that threads std
+    // didn't spawn still have a name.
+    #[cfg(target_os = "dragonos")]
+    pub fn get_name() -> Option<CString> {
+        let mut name = [0u8; TASK_COMM_LEN];
+        let res = unsafe { dlibc::prctl(dlibc::PR_GET_NAME, name.as_mut_ptr()) };
+        if res != 0 {
+            return None;
+        }
+        let name = CStr::from_bytes_until_nul(&name).ok()?;
+        (!name.is_empty()).then(|| name.to_owned())
+    }
+
     #[cfg(any(target_os = "freebsd", target_os = "dragonfly", target_os = "openbsd"))]
     pub fn set_name(name: &CStr) {
         unsafe {
@@ -263,6 +421,33 @@ impl Thread {
         }
     }
 
+    /// Sleeps until CLOCK_MONOTONIC reaches `deadline`. Unlike a relative
+    /// sleep, time spent being interrupted by signals or rescheduled
+    /// before the call doesn't push the wakeup back.
+    #[cfg(target_os = "dragonos")]
+    pub fn sleep_until(deadline: Instant) {
+        let Some(ts) = deadline.to_timespec() else {
+            // Too far in the future to represent; `thread::sleep_until`
+            // falls back to relative sleeps.
+            return;
+        };
+        loop {
+            let ret = unsafe {
+                dlibc::clock_nanosleep(
+                    dlibc::CLOCK_MONOTONIC,
+                    dlibc::TIMER_ABSTIME,
+                    &ts,
+                    ptr::null_mut(),
+                )
+            };
+            // On any other error, `thread::sleep_until` falls back to
+            // relative sleeps.
+            if ret != dlibc::EINTR {
+                break;
+            }
+        }
+    }
+
     #[cfg(target_os = "espidf")]
     pub fn sleep(dur: Duration) {
         let mut micros = dur.as_micros();
@@ -283,7 +468,16 @@ impl Thread {
     pub fn join(self) {
         unsafe {
             let ret = dlibc::pthread_join(self.id, ptr::null_mut());
+            #[cfg(target_os = "dragonos")]
+            if ret == 0 {
+                // The thread is gone, so nothing runs on its stack anymore.
+                ptr::read(&self.stack).unmap();
+            }
+            #[cfg(target_os = "dragonos")]
+            drop(ptr::read(&self.start));
             mem::forget(self);
+            #[cfg(target_os = "dragonos")]
+            reap_detached();
             assert!(
                 ret == 0,
                 "failed to join thread: {}",
@@ -292,6 +486,20 @@ impl Thread {
         }
     }
 
+    /// Returns the kernel task ID of the thread, waiting for the thread to
+    /// start if it hasn't yet.
+    #[cfg(target_os = "dragonos")]
+    pub fn tid(&self) -> u32 {
+        loop {
+            match self.start.tid.load(Ordering::Acquire) {
+                0 => {
+                    futex_wait(&self.start.tid, 0, None);
+                }
+                tid => return tid,
+            }
+        }
+    }
+
     pub fn id(&self) -> dlibc::pthread_t {
         self.id
     }
@@ -305,11 +513,236 @@ impl Thread {
 
 impl Drop for Thread {
     fn drop(&mut self) {
-        let ret = unsafe { dlibc::pthread_detach(self.id) };
+        // On DragonOS the thread runs on a stack that `Thread::new` mapped,
+        // and only joining the thread tells when it has stopped using it. So
+        // instead of detaching it, leave it to `reap_detached`.
+        #[cfg(target_os = "dragonos")]
+        {
+            let thread = Detached {
+                id: self.id,
+                stack: unsafe { ptr::read(&self.stack) },
+                start: self.start.clone(),
+            };
+            DETACHED
+                .lock()
+                .unwrap_or_else(PoisonError::into_inner)
+                .push(thread);
+            reap_detached();
+        }
+        #[cfg(not(target_os = "dragonos"))]
+        {
+            let ret = unsafe { dlibc::pthread_detach(self.id) };
+            debug_assert_eq!(ret, 0);
+        }
+    }
+}
+
+// A thread whose `Thread` was dropped without joining it.
+#[cfg(target_os = "dragonos")]
+struct Detached {
+    id: dlibc::pthread_t,
+    stack: Stack,
+    start: Arc<Start<dyn Main>>,
+}
+
+#[cfg(target_os = "dragonos")]
+static DETACHED: Mutex<Vec<Detached>> = Mutex::new(Vec::new());
+
+// Joins the dropped threads that have finished, which takes no longer than
+// their return from `thread_start`, and unmaps their stacks. This runs
+// whenever a thread is spawned, dropped or joined, and by every thread
+// spawned here right before it exits. So once threads stop being spawned
+// and finishing, only the last ones to finish keep their stacks: the final
+// one, and any that finished at the same time as it.
+#[cfg(target_os = "dragonos")]
+fn reap_detached() {
+    let mut detached = DETACHED.lock().unwrap_or_else(PoisonError::into_inner);
+    let mut i = 0;
+    while i < detached.len() {
+        if detached[i].start.finished.load(Ordering::Acquire) {
+            let thread = detached.swap_remove(i);
+            unsafe {
+                if dlibc::pthread_join(thread.id, ptr::null_mut()) == 0 {
+                    thread.stack.unmap();
+                }
+            }
+        } else {
+            i += 1;
+        }
+    }
+}
+
+/// A thread stack allocated by `Thread::new`, with a `PROT_NONE` guard page
+/// below it so that overflowing it faults instead of silently corrupting
+/// whatever is mapped next to it.
+///
+/// This doesn't unmap itself on drop, since the stack must stay mapped for
+/// as long as a thread might run on it; see `unmap`.
+#[cfg(target_os = "dragonos")]
+struct Stack {
+    // Start of the mapping, i.e. of the guard page.
+    base: *mut dlibc::c_void,
+    len: usize,
+}
+
+#[cfg(target_os = "dragonos")]
+unsafe impl Send for Stack {}
+
+#[cfg(target_os = "dragonos")]
+impl Stack {
+    fn new(size: usize) -> io::Result<Stack> {
+        let page_size = os::page_size();
+        // Round up to whole pages and add one for the guard.
+        let len = size
+            .checked_add(2 * page_size - 1)
+            .map(|len| len & !(page_size - 1))
+            .ok_or(io::const_io_error!(
+                io::ErrorKind::InvalidInput,
+                "thread stack size is too large",
+            ))?;
+        unsafe {
+            // Map the whole range read/write and then revoke access to the
+            // guard page, as `guard::init` does for the main thread.
+            let base = dlibc::mmap(
+                ptr::null_mut(),
+                len,
+                dlibc::PROT_READ | dlibc::PROT_WRITE,
+                dlibc::MAP_PRIVATE | dlibc::MAP_ANON,
+                -1,
+                0,
+            );
+            if base == dlibc::MAP_FAILED {
+                return Err(io::Error::last_os_error());
+            }
+            let stack = Stack { base, len };
+            if dlibc::mprotect(base, page_size, dlibc::PROT_NONE) != 0 {
+                let err = io::Error::last_os_error();
+                stack.unmap();
+                return Err(err);
+            }
+            stacks::register(stack.range());
+            Ok(stack)
+        }
+    }
+
+    /// The lowest usable address, just above the guard page.
+    fn bottom(&self) -> *mut dlibc::c_void {
+        self.base.cast::<u8>().wrapping_add(os::page_size()).cast()
+    }
+
+    /// The usable size, excluding the guard page.
+    fn size(&self) -> usize {
+        self.len - os::page_size()
+    }
+
+    /// The addresses of the guard page.
+    fn guard(&self) -> guard::Guard {
+        self.base.addr()..self.bottom().addr()
+    }
+
+    /// The addresses of the usable part of the stack.
+    fn range(&self) -> Range<usize> {
+        self.bottom().addr()..self.base.addr() + self.len
+    }
+
+    /// Unmaps the stack.
+    ///
+    /// # Safety
+    ///
+    /// No thread may be running on the stack, now or later.
+    unsafe fn unmap(self) {
+        stacks::unregister(self.range());
+        let ret = dlibc::munmap(self.base, self.len);
         debug_assert_eq!(ret, 0);
     }
 }
 
+/// Returns the addresses of the stack the calling thread runs on, if it is
+/// the main thread or one spawned by `Thread::new`. Everything between the
+/// caller's frame and the end of the range is mapped.
+///
+/// This doesn't use thread-locals, which may allocate, so it can be called
+/// from allocator hooks.
+#[cfg(target_os = "dragonos")]
+pub fn current_stack() -> Option<Range<usize>> {
+    let marker = 0u8;
+    stacks::find(ptr::addr_of!(marker).addr())
+}
+
+// The known stacks, for `current_stack`. Each is packed into one word, as
+// its first page and its number of pages, so that it can't be read half
+// written. There are slots for a fixed number of stacks; any beyond that
+// aren't found.
+#[cfg(target_os = "dragonos")]
+mod stacks {
+    use crate::std::ops::Range;
+    use crate::std::sync::atomic::{AtomicU64, Ordering};
+    use crate::std::sys::os;
+
+    const LEN_BITS: u32 = 28;
+    const SLOTS: usize = 256;
+
+    const EMPTY: AtomicU64 = AtomicU64::new(0);
+    static STACKS: [AtomicU64; SLOTS] = [EMPTY; SLOTS];
+
+    // The page size is a power of two, so this is its logarithm.
+    fn page_shift() -> u32 {
+        os::page_size().trailing_zeros()
+    }
+
+    fn pack(stack: &Range<usize>) -> Option<u64> {
+        let page_shift = page_shift();
+        let first = (stack.start >> page_shift) as u64;
+        let pages = (stack.len() >> page_shift) as u64;
+        if pages == 0 || pages >= 1 << LEN_BITS || first >= 1 << (64 - LEN_BITS) {
+            return None;
+        }
+        Some(first << LEN_BITS | pages)
+    }
+
+    fn unpack(word: u64, page_shift: u32) -> Range<usize> {
+        let start = ((word >> LEN_BITS) as usize) << page_shift;
+        let pages = (word & ((1 << LEN_BITS) - 1)) as usize;
+        start..start + (pages << page_shift)
+    }
+
+    // `stack` must be page aligned.
+    pub fn register(stack: Range<usize>) {
+        let Some(word) = pack(&stack) else { return };
+        for slot in &STACKS {
+            if slot
+                .compare_exchange(0, word, Ordering::Release, Ordering::Relaxed)
+                .is_ok()
+            {
+                return;
+            }
+        }
+    }
+
+    // Must be called before the stack is unmapped.
+    pub fn unregister(stack: Range<usize>) {
+        let Some(word) = pack(&stack) else { return };
+        for slot in &STACKS {
+            if slot
+                .compare_exchange(word, 0, Ordering::Relaxed, Ordering::Relaxed)
+                .is_ok()
+            {
+                return;
+            }
+        }
+    }
+
+    pub fn find(addr: usize) -> Option<Range<usize>> {
+        let page_shift = page_shift();
+        STACKS
+            .iter()
+            .map(|slot| slot.load(Ordering::Acquire))
+            .filter(|&word| word != 0)
+            .map(|word| unpack(word, page_shift))
+            .find(|stack| stack.contains(&addr))
+    }
+}
+
 #[cfg(any(
     target_os = "linux",
     target_os = "macos",
@@ -725,7 +1158,8 @@ mod cgroups {
     not(target_os = "macos"),
     not(target_os = "netbsd"),
     not(target_os = "openbsd"),
-    not(target_os = "solaris")
+    not(target_os = "solaris"),
+    not(target_os = "dragonos")
 ))]
 #[cfg_attr(test, allow(dead_code))]
 pub mod guard {
@@ -739,6 +1173,54 @@ pub mod guard {
     }
 }
 
+// Threads spawned by `Thread::new` run on a stack with a guard page that it
+// allocated, see `Stack`. The main thread's stack is set up by the kernel,
+// which doesn't say where it ends, so it has no known guard.
+#[cfg(target_os = "dragonos")]
+#[cfg_attr(test, allow(dead_code))]
+pub mod guard {
+    use crate::std::cell::Cell;
+    use crate::std::ops::Range;
+    use crate::std::sys::os;
+    use crate::std::{cmp, mem, ptr};
+    pub type Guard = Range<usize>;
+
+    thread_local! {
+        static CURRENT: Cell<Option<Guard>> = const { Cell::new(None) };
+    }
+
+    // Called by the wrapper `Thread::new` puts around the thread's main
+    // function, before anything else runs on the thread.
+    pub(super) fn set_current(guard: Guard) {
+        CURRENT.set(Some(guard));
+    }
+
+    pub unsafe fn current() -> Option<Guard> {
+        CURRENT.with(|current| {
+            let guard = current.take();
+            current.set(guard.clone());
+            guard
+        })
+    }
+
+    pub unsafe fn init() -> Option<Guard> {
+        // Record what is known of the main thread's stack for
+        // `current_stack`: it holds this frame and the rest of its page, and
+        // may grow down to the stack limit.
+        let marker = 0u8;
+        let page_size = os::page_size();
+        let top = (ptr::addr_of!(marker).addr() | (page_size - 1)) + 1;
+        let mut limit: dlibc::rlimit = mem::zeroed();
+        let size = if dlibc::getrlimit(dlibc::RLIMIT_STACK, &mut limit) == 0 {
+            cmp::min(limit.rlim_cur, 1 << 30) as usize & !(page_size - 1)
+        } else {
+            8 << 20
+        };
+        super::stacks::register(top.saturating_sub(size)..top);
+        None
+    }
+}
+
 #[cfg(any(
     target_os = "linux",
     target_os = "freebsd",
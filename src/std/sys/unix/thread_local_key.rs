@@ -5,10 +5,27 @@ use dlibc;
 
 pub type Key = dlibc::pthread_key_t;
 
+type Dtor = unsafe extern "C" fn(*mut u8);
+
+/// The keys that have a destructor. DragonOS's pthread library doesn't run
+/// key destructors when a thread exits, so the thread exit path calls
+/// [`run_dtors`] to do that instead.
+#[cfg(target_os = "dragonos")]
+static DTORS: crate::std::sync::Mutex<Vec<(Key, Dtor)>> = crate::std::sync::Mutex::new(Vec::new());
+
+/// How often [`run_dtors`] goes over the keys before giving up on
+/// destructors that keep setting values, like `PTHREAD_DESTRUCTOR_ITERATIONS`.
+#[cfg(target_os = "dragonos")]
+pub const DESTRUCTOR_ITERATIONS: usize = 4;
+
 #[inline]
-pub unsafe fn create(dtor: Option<unsafe extern "C" fn(*mut u8)>) -> Key {
+pub unsafe fn create(dtor: Option<Dtor>) -> Key {
     let mut key = 0;
     assert_eq!(dlibc::pthread_key_create(&mut key, mem::transmute(dtor)), 0);
+    #[cfg(target_os = "dragonos")]
+    if let Some(dtor) = dtor {
+        dtors().push((key, dtor));
+    }
     key
 }
 
@@ -25,6 +42,43 @@ pub unsafe fn get(key: Key) -> *mut u8 {
 
 #[inline]
 pub unsafe fn destroy(key: Key) {
+    #[cfg(target_os = "dragonos")]
+    dtors().retain(|&(k, _)| k != key);
     let r = dlibc::pthread_key_delete(key);
     debug_assert_eq!(r, 0);
 }
+
+#[cfg(target_os = "dragonos")]
+fn dtors() -> crate::std::sync::MutexGuard<'static, Vec<(Key, Dtor)>> {
+    DTORS
+        .lock()
+        .unwrap_or_else(crate::std::sync::PoisonError::into_inner)
+}
+
+/// Runs the destructors of the calling thread's keys, the way `pthread_exit`
+/// does elsewhere.
+///
+/// Each key with a non-null value is reset to null before its destructor is
+/// called with the old value. Destructors may set values again, including
+/// their own, so this repeats until all values are null, but at most
+/// [`DESTRUCTOR_ITERATIONS`] times. Whatever is still set after that is
+/// leaked.
+#[cfg(target_os = "dragonos")]
+pub unsafe fn run_dtors() {
+    for _ in 0..DESTRUCTOR_ITERATIONS {
+        // Destructors may create keys, so don't hold the lock while they run.
+        let keys = dtors().clone();
+        let mut ran = false;
+        for (key, dtor) in keys {
+            let value = get(key);
+            if !value.is_null() {
+                set(key, crate::std::ptr::null_mut());
+                dtor(value);
+                ran = true;
+            }
+        }
+        if !ran {
+            break;
+        }
+    }
+}
@@ -8,6 +8,7 @@
     target_os = "openbsd",
     target_os = "dragonfly",
     target_os = "fuchsia",
+    target_os = "dragonos",
 )))]
 
 cfg_if::cfg_if! {
@@ -410,6 +410,18 @@ mod inner {
             }
         }
 
+        #[cfg(target_os = "dragonos")]
+        pub fn now_coarse() -> Instant {
+            Instant {
+                t: Timespec::now(dlibc::CLOCK_MONOTONIC_COARSE),
+            }
+        }
+
+        #[cfg(target_os = "dragonos")]
+        pub fn to_timespec(&self) -> Option<dlibc::timespec> {
+            self.t.to_timespec()
+        }
+
         pub fn checked_sub_instant(&self, other: &Instant) -> Option<Duration> {
             self.t.sub_timespec(&other.t).ok()
         }
@@ -442,6 +454,13 @@ mod inner {
                 t: Timespec::now(dlibc::CLOCK_REALTIME),
             }
         }
+
+        #[cfg(target_os = "dragonos")]
+        pub fn now_coarse() -> SystemTime {
+            SystemTime {
+                t: Timespec::now(dlibc::CLOCK_REALTIME_COARSE),
+            }
+        }
     }
 
     impl Timespec {
@@ -13,7 +13,7 @@ use crate::std::sys_common::net;
 use crate::std::sys_common::{AsInner, FromInner, IntoInner};
 use crate::std::time::Duration;
 use dlibc;
-use dlibc::{c_int, c_long, c_ulong, c_ushort};
+use dlibc::{c_int, c_long};
 
 pub type wrlen_t = i32;
 
@@ -414,25 +414,15 @@ impl Socket {
     }
 
     pub fn set_nonblocking(&self, nonblocking: bool) -> io::Result<()> {
-        let mut nonblocking = nonblocking as c_ulong;
-        let result =
-            unsafe { c::ioctlsocket(self.as_raw_socket(), c::FIONBIO as c_int, &mut nonblocking) };
-        cvt(result).map(drop)
+        self.0.set_nonblocking(nonblocking)
     }
 
     pub fn set_linger(&self, linger: Option<Duration>) -> io::Result<()> {
-        let linger = c::linger {
-            l_onoff: linger.is_some() as c_ushort,
-            l_linger: linger.unwrap_or_default().as_secs() as c_ushort,
-        };
-
-        net::setsockopt(self, c::SOL_SOCKET, c::SO_LINGER, linger)
+        self.0.set_linger(linger)
     }
 
     pub fn linger(&self) -> io::Result<Option<Duration>> {
-        let val: c::linger = net::getsockopt(self, c::SOL_SOCKET, c::SO_LINGER)?;
-
-        Ok((val.l_onoff != 0).then(|| Duration::from_secs(val.l_linger as u64)))
+        self.0.linger()
     }
 
     pub fn set_nodelay(&self, nodelay: bool) -> io::Result<()> {
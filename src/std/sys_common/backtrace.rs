@@ -211,3 +211,63 @@ where
 //     }
 //     fmt::Display::fmt(&file.display(), fmt)
 // }
+
+/// Fills `frames` with the return addresses of the calling function and its
+/// callers, by following the chain of saved frame pointers, and returns how
+/// many it found.
+///
+/// This needs code built with `-C force-frame-pointers=yes`; the walk stops
+/// at the first frame that doesn't look like part of the chain. Only frames
+/// on the calling thread's stack, between the current stack pointer and the
+/// top, are read, so a register that doesn't hold a frame pointer ends the
+/// walk rather than making it fault. Threads whose stack isn't known, see
+/// `sys::thread::current_stack`, get no frames.
+#[cfg(target_os = "dragonos")]
+#[inline(never)]
+pub fn trace_frame_pointers(frames: &mut [usize]) -> usize {
+    // Frames bigger than this are taken to be the end of the chain.
+    const MAX_FRAME_SIZE: usize = 1 << 20;
+
+    let marker = 0u8;
+    let sp = crate::std::ptr::addr_of!(marker).addr();
+    let Some(stack) = crate::std::sys::thread::current_stack() else {
+        return 0;
+    };
+
+    #[cfg(target_arch = "x86_64")]
+    let mut fp: usize = {
+        let fp;
+        unsafe { core::arch::asm!("mov {}, rbp", out(reg) fp, options(nomem, nostack)) };
+        fp
+    };
+    #[cfg(target_arch = "aarch64")]
+    let mut fp: usize = {
+        let fp;
+        unsafe { core::arch::asm!("mov {}, x29", out(reg) fp, options(nomem, nostack)) };
+        fp
+    };
+    #[cfg(not(any(target_arch = "x86_64", target_arch = "aarch64")))]
+    let mut fp: usize = 0;
+
+    // On both, a frame pointer points at the caller's frame pointer, with the
+    // return address right above it.
+    let word = crate::std::mem::size_of::<usize>();
+    let mut len = 0;
+    while len < frames.len()
+        && fp >= sp
+        && fp <= stack.end.saturating_sub(2 * word)
+        && fp % word == 0
+    {
+        let (next, ret) = unsafe { (*(fp as *const usize), *(fp as *const usize).add(1)) };
+        if ret == 0 {
+            break;
+        }
+        frames[len] = ret;
+        len += 1;
+        if next <= fp || next - fp > MAX_FRAME_SIZE {
+            break;
+        }
+        fp = next;
+    }
+    len
+}
@@ -0,0 +1,78 @@
+use crate::std::sync::atomic::{
+    AtomicU32, AtomicU64,
+    Ordering::{AcqRel, Acquire, Relaxed, Release},
+};
+use crate::std::sys::futex::{futex_wait, futex_wake_all};
+
+pub struct Barrier {
+    // The current generation in the upper 32 bits and the number of threads
+    // that have arrived in it in the lower 32 bits. Keeping both in one word
+    // lets an arriving thread learn which generation it joined, and lets the
+    // last thread start the next generation, in a single atomic operation.
+    state: AtomicU64,
+    // The generation, for waiting threads to sleep on. It is advanced by the
+    // last thread of a generation after `state`, so it may briefly lag
+    // behind.
+    generation: AtomicU32,
+    num_threads: u32,
+}
+
+impl Barrier {
+    pub const fn new(n: usize) -> Barrier {
+        // No more threads than this can ever arrive at the same time.
+        let num_threads = if n > u32::MAX as usize {
+            u32::MAX
+        } else {
+            n as u32
+        };
+        Barrier {
+            state: AtomicU64::new(0),
+            generation: AtomicU32::new(0),
+            num_threads,
+        }
+    }
+
+    /// Returns whether the calling thread is the leader.
+    pub fn wait(&self) -> bool {
+        let mut state = self.state.load(Relaxed);
+        loop {
+            let generation = (state >> 32) as u32;
+            let arrived = state as u32 + 1;
+            let leader = arrived >= self.num_threads;
+            let new = if leader {
+                (generation.wrapping_add(1) as u64) << 32
+            } else {
+                state + 1
+            };
+            // Acquire and release, so that the leader sees everything the
+            // others did before arriving, and passes it on through
+            // `generation`.
+            match self
+                .state
+                .compare_exchange_weak(state, new, AcqRel, Relaxed)
+            {
+                Ok(_) if leader => {
+                    // With more than `num_threads` threads, the leader of a
+                    // later generation may have been faster; don't go back.
+                    let next = generation.wrapping_add(1);
+                    let _ = self.generation.fetch_update(Release, Relaxed, |current| {
+                        (next.wrapping_sub(current) as i32 > 0).then_some(next)
+                    });
+                    futex_wake_all(&self.generation);
+                    return true;
+                }
+                Ok(_) => loop {
+                    // The leader of the previous generation may not have
+                    // advanced `generation` yet, so wait until it is past
+                    // ours rather than for it to change.
+                    let current = self.generation.load(Acquire);
+                    if current.wrapping_sub(generation) as i32 > 0 {
+                        return false;
+                    }
+                    futex_wait(&self.generation, current, None);
+                },
+                Err(s) => state = s,
+            }
+        }
+    }
+}
@@ -0,0 +1,23 @@
+// Where futexes are available, threads arriving at a barrier only touch two
+// atomics, and the last one to arrive wakes the others with a single system
+// call. Elsewhere, the barrier is built from a mutex and a condition variable.
+
+cfg_if::cfg_if! {
+    if #[cfg(any(
+        target_os = "linux",
+        target_os = "dragonos",
+        target_os = "android",
+        all(target_arch = "wasm32", target_feature = "atomics"),
+        target_os = "freebsd",
+        target_os = "openbsd",
+        target_os = "dragonfly",
+        target_os = "fuchsia",
+        target_os = "hermit",
+    ))] {
+        mod futex;
+        pub use futex::Barrier;
+    } else {
+        mod generic;
+        pub use generic::Barrier;
+    }
+}
@@ -81,12 +81,12 @@ const POISONED: usize = 0x1;
 const RUNNING: usize = 0x2;
 const COMPLETE: usize = 0x3;
 
-// Mask to learn about the state. All other bits are the queue of waiters if
-// this is in the RUNNING state.
+// Mask to learn about the state. All other bits are the queue of waiters,
+// which is empty once the state is COMPLETE.
 const STATE_MASK: usize = 0x3;
 
-// Representation of a node in the linked list of waiters, used while in the
-// RUNNING state.
+// Representation of a node in the linked list of waiters, used until the
+// state is COMPLETE.
 // Note: `Waiter` can't hold a mutable pointer to the next thread, because then
 // `wait` would both hand out a mutable reference to its `Waiter` node, and keep
 // a shared reference to check `signaled`. Instead we hold shared references and
@@ -149,17 +149,19 @@ impl Once {
     pub fn call(&self, ignore_poisoning: bool, init: &mut dyn FnMut(&public::OnceState)) {
         let mut state_and_queue = self.state_and_queue.load(Ordering::Acquire);
         loop {
-            match state_and_queue.addr() {
+            let state = state_and_queue.addr() & STATE_MASK;
+            match state {
                 COMPLETE => break,
                 POISONED if !ignore_poisoning => {
                     // Panic to propagate the poison.
                     panic!("Once instance has previously been poisoned");
                 }
                 POISONED | INCOMPLETE => {
-                    // Try to register this thread as the one RUNNING.
+                    // Try to register this thread as the one RUNNING, keeping
+                    // the threads that are already waiting in `Once::wait`.
                     let exchange_result = self.state_and_queue.compare_exchange(
                         state_and_queue,
-                        ptr::invalid_mut(RUNNING),
+                        state_and_queue.with_addr((state_and_queue.addr() & !STATE_MASK) | RUNNING),
                         Ordering::Acquire,
                         Ordering::Acquire,
                     );
@@ -177,7 +179,7 @@ impl Once {
                     // poisoned or not.
                     let init_state = public::OnceState {
                         inner: OnceState {
-                            poisoned: state_and_queue.addr() == POISONED,
+                            poisoned: state == POISONED,
                             set_state_on_drop_to: Cell::new(ptr::invalid_mut(COMPLETE)),
                         },
                     };
@@ -188,8 +190,27 @@ impl Once {
                 _ => {
                     // All other values must be RUNNING with possibly a
                     // pointer to the waiter queue in the more significant bits.
-                    assert!(state_and_queue.addr() & STATE_MASK == RUNNING);
-                    wait(&self.state_and_queue, state_and_queue);
+                    assert!(state == RUNNING);
+                    wait(&self.state_and_queue, state_and_queue, true);
+                    state_and_queue = self.state_and_queue.load(Ordering::Acquire);
+                }
+            }
+        }
+    }
+
+    #[cold]
+    #[track_caller]
+    pub fn wait(&self, ignore_poisoning: bool) {
+        let mut state_and_queue = self.state_and_queue.load(Ordering::Acquire);
+        loop {
+            match state_and_queue.addr() & STATE_MASK {
+                COMPLETE => break,
+                POISONED if !ignore_poisoning => {
+                    // Panic to propagate the poison.
+                    panic!("Once instance has previously been poisoned");
+                }
+                _ => {
+                    wait(&self.state_and_queue, state_and_queue, !ignore_poisoning);
                     state_and_queue = self.state_and_queue.load(Ordering::Acquire);
                 }
             }
@@ -197,13 +218,19 @@ impl Once {
     }
 }
 
-fn wait(state_and_queue: &AtomicPtr<Masked>, mut current_state: *mut Masked) {
+fn wait(
+    state_and_queue: &AtomicPtr<Masked>,
+    mut current_state: *mut Masked,
+    return_on_poisoned: bool,
+) {
     // Note: the following code was carefully written to avoid creating a
     // mutable reference to `node` that gets aliased.
     loop {
-        // Don't queue this thread if the status is no longer running,
-        // otherwise we will not be woken up.
-        if current_state.addr() & STATE_MASK != RUNNING {
+        // Don't queue this thread if the Once is done, otherwise we will not
+        // be woken up. Threads queued while the Once is INCOMPLETE or
+        // POISONED are carried over to RUNNING by the next `call`.
+        let state = current_state.addr() & STATE_MASK;
+        if state == COMPLETE || (state == POISONED && return_on_poisoned) {
             return;
         }
 
@@ -219,7 +246,7 @@ fn wait(state_and_queue: &AtomicPtr<Masked>, mut current_state: *mut Masked) {
         // that another thread didn't just replace the head of the linked list.
         let exchange_result = state_and_queue.compare_exchange(
             current_state,
-            me.with_addr(me.addr() | RUNNING),
+            me.with_addr(me.addr() | state),
             Ordering::Release,
             Ordering::Relaxed,
         );
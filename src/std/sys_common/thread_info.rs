@@ -1,8 +1,9 @@
 #![allow(dead_code)] // stack_guard isn't used right now on all platforms
 
-use crate::std::cell::RefCell;
+use crate::std::cell::{Cell, RefCell};
+use crate::std::ffi::CString;
 use crate::std::sys::thread::guard::Guard;
-use crate::std::thread::Thread;
+use crate::std::thread::{Thread, ThreadId};
 
 struct ThreadInfo {
     stack_guard: Option<Guard>,
@@ -11,6 +12,11 @@ struct ThreadInfo {
 
 thread_local! { static THREAD_INFO: RefCell<Option<ThreadInfo>> = const { RefCell::new(None) } }
 
+// The ID of `THREAD_INFO.thread`, once it is set. Kept apart so that reading
+// it needs no borrow and no clone, and still works after `THREAD_INFO` is
+// destroyed, since it has no destructor.
+thread_local! { static CURRENT_ID: Cell<Option<ThreadId>> = const { Cell::new(None) } }
+
 impl ThreadInfo {
     fn with<R, F>(f: F) -> Option<R>
     where
@@ -19,9 +25,13 @@ impl ThreadInfo {
         THREAD_INFO
             .try_with(move |thread_info| {
                 let mut thread_info = thread_info.borrow_mut();
-                let thread_info = thread_info.get_or_insert_with(|| ThreadInfo {
-                    stack_guard: None,
-                    thread: Thread::new(None),
+                let thread_info = thread_info.get_or_insert_with(|| {
+                    let thread = Thread::new(foreign_thread_name());
+                    CURRENT_ID.set(Some(thread.id()));
+                    ThreadInfo {
+                        stack_guard: None,
+                        thread,
+                    }
                 });
                 f(thread_info)
             })
@@ -29,10 +39,38 @@ impl ThreadInfo {
     }
 }
 
+// The name of a thread that std didn't spawn, and so has no name of its own.
+#[cfg(target_os = "dragonos")]
+fn foreign_thread_name() -> Option<CString> {
+    crate::std::sys::thread::Thread::get_name()
+}
+
+#[cfg(not(target_os = "dragonos"))]
+fn foreign_thread_name() -> Option<CString> {
+    None
+}
+
 pub fn current_thread() -> Option<Thread> {
     ThreadInfo::with(|info| info.thread.clone())
 }
 
+pub fn current_id() -> Option<ThreadId> {
+    match CURRENT_ID.get() {
+        Some(id) => Some(id),
+        None => ThreadInfo::with(|info| info.thread.id()),
+    }
+}
+
+// Like `current_id`, but gives a thread whose `THREAD_INFO` is already
+// destroyed an ID of its own, kept in `CURRENT_ID`.
+pub fn current_id_or_new() -> ThreadId {
+    current_id().unwrap_or_else(|| {
+        let id = ThreadId::new();
+        CURRENT_ID.set(Some(id));
+        id
+    })
+}
+
 pub fn stack_guard() -> Option<Guard> {
     ThreadInfo::with(|info| info.stack_guard.clone()).and_then(|o| o)
 }
@@ -41,6 +79,7 @@ pub fn set(stack_guard: Option<Guard>, thread: Thread) {
     THREAD_INFO.with(move |thread_info| {
         let mut thread_info = thread_info.borrow_mut();
         rtassert!(thread_info.is_none());
+        CURRENT_ID.set(Some(thread.id()));
         *thread_info = Some(ThreadInfo {
             stack_guard,
             thread,
@@ -15,3 +15,25 @@ fn statik() {
         assert_eq!(K2.get() as usize, 2);
     }
 }
+
+#[test]
+#[cfg(target_os = "dragonos")]
+fn destructor_iterations() {
+    use crate::std::sync::atomic::{AtomicUsize, Ordering};
+    use crate::std::sys::thread_local_key::DESTRUCTOR_ITERATIONS;
+    use crate::std::thread;
+
+    static RUNS: AtomicUsize = AtomicUsize::new(0);
+    static KEY: StaticKey = StaticKey::new(Some(dtor));
+
+    // Sets the value again every time, so only the iteration limit stops it.
+    unsafe extern "C" fn dtor(_: *mut u8) {
+        RUNS.fetch_add(1, Ordering::Relaxed);
+        KEY.set(ptr::invalid_mut(1));
+    }
+
+    thread::spawn(|| unsafe { KEY.set(ptr::invalid_mut(1)) })
+        .join()
+        .unwrap();
+    assert_eq!(RUNS.load(Ordering::Relaxed), DESTRUCTOR_ITERATIONS);
+}
@@ -8,6 +8,7 @@ cfg_if::cfg_if! {
         target_os = "dragonfly",
         target_os = "fuchsia",
         target_os = "hermit",
+        target_os = "dragonos",
     ))] {
         mod futex;
         pub use futex::Parker;
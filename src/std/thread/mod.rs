@@ -166,18 +166,20 @@ use crate::std::marker::PhantomData;
 use crate::std::mem::{self, forget};
 use crate::std::num::NonZeroU64;
 use crate::std::num::NonZeroUsize;
+use crate::std::ops::Deref;
 use crate::std::panic;
 use crate::std::panicking;
 use crate::std::pin::Pin;
 use crate::std::ptr::addr_of_mut;
 use crate::std::str;
+use crate::std::sync::atomic::{AtomicBool, Ordering};
 use crate::std::sync::Arc;
 use crate::std::sys::thread as imp;
 use crate::std::sys_common::thread;
 use crate::std::sys_common::thread_info;
 use crate::std::sys_common::thread_parking::Parker;
 use crate::std::sys_common::{AsInner, IntoInner};
-use crate::std::time::Duration;
+use crate::std::time::{Duration, Instant};
 
 mod scoped;
 
@@ -469,12 +471,12 @@ impl Builder {
         }));
         let their_thread = my_thread.clone();
 
-        let my_packet: Arc<Packet<'scope, T>> = Arc::new(Packet {
+        let packet = Packet {
             scope: scope_data,
             result: UnsafeCell::new(None),
+            released: AtomicBool::new(false),
             _marker: PhantomData,
-        });
-        let their_packet = my_packet.clone();
+        };
 
         let output_capture = crate::std::io::set_output_capture(None);
         crate::std::io::set_output_capture(output_capture.clone());
@@ -517,50 +519,77 @@ impl Builder {
             // This means the current thread's stack and the new thread's stack
             // are properly set and protected from each other.
             thread_info::set(unsafe { imp::guard::current() }, their_thread);
-            let try_result = panic::catch_unwind(panic::AssertUnwindSafe(|| {
+            panic::catch_unwind(panic::AssertUnwindSafe(|| {
                 crate::std::sys_common::backtrace::__rust_begin_short_backtrace(f)
-            }));
-            // SAFETY: `their_packet` as been built just above and moved by the
-            // closure (it is an Arc<...>) and `my_packet` will be stored in the
-            // same `JoinInner` as this closure meaning the mutation will be
-            // safe (not modify it and affect a value far away).
-            unsafe { *their_packet.result.get() = Some(try_result) };
-            // Here `their_packet` gets dropped, and if this is the last `Arc` for that packet that
-            // will call `decrement_num_running_threads` and therefore signal that this thread is
-            // done.
-            drop(their_packet);
-            // Here, the lifetime `'a` and even `'scope` can end. `main` keeps running for a bit
-            // after that before returning itself.
+            }))
         };
 
-        if let Some(scope_data) = &my_packet.scope {
+        // The closure and the packet for its result share one allocation,
+        // which on DragonOS also holds what `imp::Thread` starts the thread
+        // with.
+        let spawned = share(Spawned {
+            packet,
+            closure: UnsafeCell::new(Some(main)),
+        });
+
+        if let Some(scope_data) = &spawned.packet.scope {
             scope_data.increment_num_running_threads();
         }
 
+        // SAFETY:
+        //
+        // `imp::Thread::new` takes a closure with a `'static` lifetime, since it's passed
+        // through FFI or otherwise used with low-level threading primitives that have no
+        // notion of or way to enforce lifetimes.
+        //
+        // As mentioned in the `Safety` section of this function's documentation, the caller of
+        // this function needs to guarantee that the passed-in lifetime is sufficiently long
+        // for the lifetime of the thread.
+        //
+        // Similarly, the `sys` implementation must guarantee that no references to the closure
+        // exist after the thread has terminated, which is signaled by `Thread::join`
+        // returning.
+        #[cfg(not(target_os = "dragonos"))]
+        let native = unsafe {
+            let their_spawned = spawned.clone();
+            imp::Thread::new(
+                stack_size,
+                mem::transmute::<Box<dyn FnOnce() + 'a>, Box<dyn FnOnce() + 'static>>(Box::new(
+                    move || their_spawned.run(),
+                )),
+            )
+        };
+        // On DragonOS, `imp::Thread` starts the thread from the shared
+        // allocation itself, so spawning doesn't allocate again.
+        #[cfg(target_os = "dragonos")]
+        let native = unsafe { imp::Thread::new(stack_size, spawned.clone()) };
+
+        let native = match native {
+            Ok(native) => native,
+            Err(e) => {
+                // SAFETY: the thread never started, so nothing else uses
+                // the closure or the packet.
+                unsafe {
+                    *spawned.closure.get() = None;
+                    spawned.packet.finish();
+                }
+                return Err(e);
+            }
+        };
+
+        let spawned: Arc<Shared<dyn Spawn<'scope, T> + 'a>> = spawned;
         Ok(JoinInner {
-            // SAFETY:
-            //
-            // `imp::Thread::new` takes a closure with a `'static` lifetime, since it's passed
-            // through FFI or otherwise used with low-level threading primitives that have no
-            // notion of or way to enforce lifetimes.
-            //
-            // As mentioned in the `Safety` section of this function's documentation, the caller of
-            // this function needs to guarantee that the passed-in lifetime is sufficiently long
-            // for the lifetime of the thread.
-            //
-            // Similarly, the `sys` implementation must guarantee that no references to the closure
-            // exist after the thread has terminated, which is signaled by `Thread::join`
-            // returning.
-            native: unsafe {
-                imp::Thread::new(
-                    stack_size,
-                    mem::transmute::<Box<dyn FnOnce() + 'a>, Box<dyn FnOnce() + 'static>>(
-                        Box::new(main),
-                    ),
-                )?
-            },
+            native,
             thread: my_thread,
-            packet: my_packet,
+            // SAFETY: only the lifetime of the closure is erased, as for
+            // `imp::Thread::new` above, and the thread takes the closure
+            // out of the allocation.
+            packet: PacketRef(unsafe {
+                mem::transmute::<
+                    Arc<Shared<dyn Spawn<'scope, T> + 'a>>,
+                    Arc<Shared<dyn Spawn<'scope, T> + 'scope>>,
+                >(spawned)
+            }),
         })
     }
 }
@@ -704,6 +733,28 @@ pub fn current() -> Thread {
     )
 }
 
+/// Gets the ID of the thread that invokes it.
+///
+/// This is `thread::current().id()` without cloning the [`Thread`] handle,
+/// cheap enough to call on every record a logger writes. Once the ID is
+/// known, it also keeps working while the thread's local data is being
+/// destroyed, when [`current`] panics.
+///
+/// # Examples
+///
+/// ```
+/// use std::thread;
+///
+/// assert_eq!(thread::current_id(), thread::current().id());
+/// ```
+#[must_use]
+pub fn current_id() -> ThreadId {
+    thread_info::current_id().expect(
+        "use of std::thread::current_id() is not possible \
+         after the thread's local data has been destroyed",
+    )
+}
+
 /// Cooperatively gives up a timeslice to the OS scheduler.
 ///
 /// This calls the underlying OS scheduler's yield primitive, signaling
@@ -858,6 +909,71 @@ pub fn sleep(dur: Duration) {
     imp::Thread::sleep(dur)
 }
 
+/// Puts the current thread to sleep until the specified deadline has passed.
+///
+/// The thread may still be asleep after the deadline specified due to
+/// scheduling specifics or platform-dependent functionality. It will never
+/// wake before.
+///
+/// This function is blocking, and should not be used in `async` functions.
+///
+/// # Platform-specific behavior
+///
+/// On DragonOS, this sleeps until the deadline with [`clock_nanosleep`] and
+/// `TIMER_ABSTIME`, so that the thread isn't woken late by the time it took
+/// to get from computing a delay to the system call. Elsewhere, this
+/// function uses [`sleep`] internally, see its platform-specific behaviour.
+///
+/// [`clock_nanosleep`]: https://linux.die.net/man/3/clock_nanosleep
+///
+/// # Examples
+///
+/// A simple game loop that limits the game to 60 frames per second. Since
+/// each deadline is computed from the previous one rather than from the
+/// time the frame took, the loop doesn't drift.
+///
+/// ```no_run
+/// # use std::time::{Duration, Instant};
+/// # use std::thread;
+/// #
+/// # fn update() {}
+/// # fn render() {}
+/// #
+/// let max_fps = 60.0;
+/// let frame_time = Duration::from_secs_f32(1.0 / max_fps);
+/// let mut next_frame = Instant::now();
+/// loop {
+///     thread::sleep_until(next_frame);
+///     next_frame += frame_time;
+///     update();
+///     render();
+/// }
+/// ```
+///
+/// A deadline in the past makes this return immediately:
+///
+/// ```no_run
+/// use std::time::{Duration, Instant};
+/// use std::thread;
+///
+/// let deadline = Instant::now() - Duration::from_millis(1);
+/// thread::sleep_until(deadline);
+/// ```
+pub fn sleep_until(deadline: Instant) {
+    #[cfg(target_os = "dragonos")]
+    imp::Thread::sleep_until(deadline.into_inner());
+
+    // `sleep` may wake a little early on platforms whose clock for sleeping
+    // is not the one `Instant` uses, so check again afterwards.
+    loop {
+        let now = Instant::now();
+        match deadline.checked_duration_since(now) {
+            Some(delay) if !delay.is_zero() => sleep(delay),
+            _ => break,
+        }
+    }
+}
+
 /// Used to ensure that `park` and `park_timeout` do not unwind, as that can
 /// cause undefined behaviour if not handled correctly (see #102398 for context).
 struct PanicGuard;
@@ -1053,7 +1169,8 @@ pub fn park_timeout(dur: Duration) {
 /// of Rust's standard library and there may not be any relationship between
 /// `ThreadId` and the underlying platform's notion of a thread identifier --
 /// the two concepts cannot, therefore, be used interchangeably. A `ThreadId`
-/// can be retrieved from the [`id`] method on a [`Thread`].
+/// can be retrieved from the [`id`] method on a [`Thread`], or for the current
+/// thread with [`current_id`].
 ///
 /// # Examples
 ///
@@ -1074,7 +1191,7 @@ pub struct ThreadId(NonZeroU64);
 
 impl ThreadId {
     // Generate a new unique thread ID.
-    fn new() -> ThreadId {
+    pub(crate) fn new() -> ThreadId {
         #[cold]
         fn exhausted() -> ! {
             panic!("failed to generate unique thread ID: bitspace exhausted")
@@ -1282,6 +1399,11 @@ impl Thread {
     /// handler.join().unwrap();
     /// ```
     ///
+    /// **DragonOS**: A thread's name is also set as its kernel task name
+    /// (truncated to 15 bytes), so it shows up in `ps` and debuggers. Threads
+    /// not spawned through this module, including the main thread, report
+    /// the kernel's name for them.
+    ///
     /// [naming-threads]: ./index.html#naming-threads
     #[must_use]
     pub fn name(&self) -> Option<&str> {
@@ -1352,15 +1474,19 @@ impl fmt::Debug for Thread {
 pub type Result<T> = crate::std::result::Result<T, Box<dyn Any + Send + 'static>>;
 
 // This packet is used to communicate the return value between the spawned
-// thread and the rest of the program. It is shared through an `Arc` and
-// there's no need for a mutex here because synchronization happens with `join()`
-// (the caller will never read this packet until the thread has exited).
+// thread and the rest of the program. It is shared with the closure the
+// thread runs, in a `Spawned`, and there's no need for a mutex here because
+// synchronization happens with `join()` (the caller will never read this
+// packet until the thread has exited).
 //
-// An Arc to the packet is stored into a `JoinInner` which in turns is placed
-// in `JoinHandle`.
+// A reference to the packet is stored into a `JoinInner` which in turns is
+// placed in `JoinHandle`.
 struct Packet<'scope, T> {
     scope: Option<Arc<scoped::ScopeData>>,
     result: UnsafeCell<Option<Result<T>>>,
+    // Set by whichever of the thread and its `JoinInner` is done with the
+    // packet first, so that the other one finishes it.
+    released: AtomicBool,
     _marker: PhantomData<Option<&'scope scoped::ScopeData>>,
 }
 
@@ -1370,12 +1496,25 @@ struct Packet<'scope, T> {
 // `UnsafeCell` synchronized (by the `join()` boundary), and `ScopeData` is Sync.
 unsafe impl<'scope, T: Sync> Sync for Packet<'scope, T> {}
 
-impl<'scope, T> Drop for Packet<'scope, T> {
-    fn drop(&mut self) {
+impl<'scope, T> Packet<'scope, T> {
+    // Called by the thread once it has stored its result, and by its
+    // `JoinInner` when that is dropped. The second call finishes the packet,
+    // as dropping the last reference to it would.
+    fn release(&self) {
+        if self.released.swap(true, Ordering::AcqRel) {
+            // SAFETY: both the thread and its `JoinInner` are done with it.
+            unsafe { self.finish() };
+        }
+    }
+
+    // SAFETY: nothing else may use the packet, now or later.
+    unsafe fn finish(&self) {
+        // SAFETY: nothing else uses `result`.
+        let result = unsafe { &mut *self.result.get() };
         // If this packet was for a thread that ran in a scope, the thread
         // panicked, and nobody consumed the panic payload, we make sure
         // the scope function will panic.
-        let unhandled_panic = matches!(self.result.get_mut(), Some(Err(_)));
+        let unhandled_panic = matches!(result, Some(Err(_)));
         // Drop the result without causing unwinding.
         // This is only relevant for threads that aren't join()ed, as
         // join() will take the `result` and set it to None, such that
@@ -1387,7 +1526,7 @@ impl<'scope, T> Drop for Packet<'scope, T> {
         // the case where the panic payload we get out of it also panics on
         // drop, and so on. See issue #86027.)
         if let Err(_) = panic::catch_unwind(panic::AssertUnwindSafe(|| {
-            *self.result.get_mut() = None;
+            *result = None;
         })) {
             rtabort!("thread result panicked on drop");
         }
@@ -1402,22 +1541,103 @@ impl<'scope, T> Drop for Packet<'scope, T> {
     }
 }
 
+// The closure a spawned thread runs and the packet for its result, which
+// are shared between the thread and its `JoinInner` in one allocation.
+struct Spawned<'scope, T, F> {
+    packet: Packet<'scope, T>,
+    // Taken by the thread when it starts.
+    closure: UnsafeCell<Option<F>>,
+}
+
+// `closure` is only used by the thread, and `packet` is synchronized as above.
+unsafe impl<'scope, T: Send, F: Send> Send for Spawned<'scope, T, F> {}
+unsafe impl<'scope, T: Send, F: Send> Sync for Spawned<'scope, T, F> {}
+
+impl<'scope, T: Send, F: FnOnce() -> Result<T> + Send> Spawned<'scope, T, F> {
+    // Runs the closure on the spawned thread, once.
+    unsafe fn run(&self) {
+        // SAFETY: only the thread uses `closure`, and `JoinInner` only reads
+        // the result once the thread has exited.
+        unsafe {
+            if let Some(main) = (*self.closure.get()).take() {
+                *self.packet.result.get() = Some(main());
+            }
+        }
+        // Here, the lifetime `'a` and even `'scope` can end, unless the
+        // `JoinInner` is still around. The thread keeps running for a bit
+        // after that before returning.
+        self.packet.release();
+    }
+}
+
+#[cfg(target_os = "dragonos")]
+impl<'scope, T: Send, F: FnOnce() -> Result<T> + Send> imp::Main for Spawned<'scope, T, F> {
+    unsafe fn run(&self) {
+        unsafe { Spawned::run(self) }
+    }
+}
+
+// A `Spawned` with the type of its closure erased, for `JoinInner`.
+trait Spawn<'scope, T>: Send + Sync {
+    fn packet(&self) -> &Packet<'scope, T>;
+}
+
+impl<'scope, T: Send, F: Send> Spawn<'scope, T> for Spawned<'scope, T, F> {
+    fn packet(&self) -> &Packet<'scope, T> {
+        &self.packet
+    }
+}
+
+// The allocation a `Spawned` is shared in. On DragonOS, `imp::Thread` starts
+// the thread with it, so it also holds what `imp::Thread` needs for that.
+#[cfg(target_os = "dragonos")]
+type Shared<S> = imp::Start<S>;
+#[cfg(not(target_os = "dragonos"))]
+type Shared<S> = S;
+
+#[cfg(target_os = "dragonos")]
+fn share<S>(spawned: S) -> Arc<Shared<S>> {
+    Arc::new(imp::Start::new(spawned))
+}
+
+#[cfg(not(target_os = "dragonos"))]
+fn share<S>(spawned: S) -> Arc<Shared<S>> {
+    Arc::new(spawned)
+}
+
+// The `JoinInner`'s reference to the packet, which releases it when dropped.
+struct PacketRef<'scope, T>(Arc<Shared<dyn Spawn<'scope, T> + 'scope>>);
+
+impl<'scope, T> Deref for PacketRef<'scope, T> {
+    type Target = Packet<'scope, T>;
+
+    fn deref(&self) -> &Packet<'scope, T> {
+        self.0.packet()
+    }
+}
+
+impl<'scope, T> Drop for PacketRef<'scope, T> {
+    fn drop(&mut self) {
+        self.release();
+    }
+}
+
 /// Inner representation for JoinHandle
 struct JoinInner<'scope, T> {
     native: imp::Thread,
     thread: Thread,
-    packet: Arc<Packet<'scope, T>>,
+    packet: PacketRef<'scope, T>,
 }
 
 impl<'scope, T> JoinInner<'scope, T> {
-    fn join(mut self) -> Result<T> {
+    fn join(self) -> Result<T> {
         self.native.join();
-        Arc::get_mut(&mut self.packet)
-            .unwrap()
-            .result
-            .get_mut()
-            .take()
-            .unwrap()
+        // SAFETY: the thread has exited, after storing its result.
+        unsafe { (*self.packet.result.get()).take().unwrap() }
+    }
+
+    fn is_finished(&self) -> bool {
+        self.packet.released.load(Ordering::Acquire)
     }
 }
 
@@ -1521,7 +1741,10 @@ impl<T> JoinHandle<T> {
     /// operations that happen after `join` returns.
     ///
     /// If the associated thread panics, [`Err`] is returned with the parameter given
-    /// to [`panic!`].
+    /// to [`panic!`]. This requires the program to be built with
+    /// `panic = "unwind"`; with `panic = "abort"`, which DragonOS builds use
+    /// until unwinding is supported there, a panicking thread aborts the whole
+    /// process instead.
     ///
     /// [`Err`]: crate::std::result::Result::Err
     /// [atomic memory orderings]: crate::std::sync::atomic
@@ -1558,10 +1781,12 @@ impl<T> JoinHandle<T> {
     /// However, once this returns `true`, [`join`][Self::join] can be expected
     /// to return quickly, without blocking for any significant amount of time.
     pub fn is_finished(&self) -> bool {
-        Arc::strong_count(&self.0.packet) == 1
+        self.0.is_finished()
     }
 }
 
+impl<T> crate::std::sealed::Sealed for JoinHandle<T> {}
+
 impl<T> AsInner<imp::Thread> for JoinHandle<T> {
     fn as_inner(&self) -> &imp::Thread {
         &self.0.native
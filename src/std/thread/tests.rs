@@ -4,7 +4,7 @@ use crate::std::mem;
 use crate::std::panic::panic_any;
 use crate::std::result;
 use crate::std::sync::{
-    atomic::{AtomicBool, Ordering},
+    atomic::{AtomicBool, AtomicUsize, Ordering},
     mpsc::{channel, Sender},
     Arc, Barrier,
 };
@@ -117,6 +117,40 @@ fn test_is_finished() {
     assert!(join_time.elapsed() < Duration::from_secs(2));
 }
 
+#[test]
+fn test_is_finished_polling_workers() {
+    // Poll a pool of workers the way a test harness would, reaping each one
+    // as it finishes without ever blocking on a running one.
+    let gates: Vec<_> = (0..4).map(|_| Arc::new(Barrier::new(2))).collect();
+    let mut workers: Vec<_> = gates
+        .iter()
+        .enumerate()
+        .map(|(i, gate)| {
+            let gate = gate.clone();
+            thread::spawn(move || {
+                gate.wait();
+                i
+            })
+        })
+        .collect();
+
+    let mut reaped = Vec::new();
+    for gate in gates.iter().rev() {
+        assert!(workers.iter().all(|w| !w.is_finished()));
+        gate.wait();
+        let start = Instant::now();
+        loop {
+            if let Some(pos) = workers.iter().position(|w| w.is_finished()) {
+                reaped.push(workers.swap_remove(pos).join().unwrap());
+                break;
+            }
+            assert!(start.elapsed() < Duration::from_secs(2));
+            thread::sleep(Duration::from_millis(15));
+        }
+    }
+    assert_eq!(reaped, [3, 2, 1, 0]);
+}
+
 #[test]
 fn test_join_panic() {
     match thread::spawn(move || panic!()).join() {
@@ -335,6 +369,21 @@ fn sleep_ms_smoke() {
     thread::sleep(Duration::from_millis(2));
 }
 
+#[test]
+fn sleep_until() {
+    let now = Instant::now();
+    let period = Duration::from_millis(10);
+    let mut deadline = now;
+    for _ in 0..5 {
+        deadline += period;
+        thread::sleep_until(deadline);
+        assert!(Instant::now() >= deadline);
+    }
+
+    // A deadline that has passed returns right away.
+    thread::sleep_until(now);
+}
+
 #[test]
 fn test_size_of_option_thread_id() {
     assert_eq!(
@@ -354,6 +403,21 @@ fn test_thread_id_not_equal() {
     assert!(thread::current().id() != spawned_id);
 }
 
+#[test]
+fn test_current_id() {
+    assert_eq!(thread::current_id(), thread::current().id());
+    let (id, current_id) = thread::spawn(|| {
+        // Before anything else asks for the current thread.
+        let current_id = thread::current_id();
+        (thread::current().id(), current_id)
+    })
+    .join()
+    .unwrap();
+    assert_eq!(id, current_id);
+    assert_ne!(id, thread::current_id());
+    assert_eq!(id.as_u64(), current_id.as_u64());
+}
+
 #[test]
 fn test_scoped_threads_drop_result_before_join() {
     let actually_finished = &AtomicBool::new(false);
@@ -392,6 +456,50 @@ fn test_scoped_threads_nll() {
     foo(&x);
 }
 
+#[test]
+fn test_scoped_threads_borrow_stack_data() {
+    let mut data = [1, 2, 3, 4, 5, 6, 7, 8];
+    let total = AtomicUsize::new(0);
+    thread::scope(|s| {
+        for chunk in data.chunks_mut(2) {
+            let total = &total;
+            s.spawn(move || {
+                for x in chunk.iter_mut() {
+                    *x *= 10;
+                }
+                total.fetch_add(chunk.iter().sum::<usize>(), Ordering::Relaxed);
+            });
+        }
+    });
+    // Every scoped thread has been joined, so their writes are visible.
+    assert_eq!(data, [10, 20, 30, 40, 50, 60, 70, 80]);
+    assert_eq!(total.into_inner(), 360);
+}
+
+#[test]
+fn test_scoped_join_handle_returns_value() {
+    let text = String::from("borrowed");
+    let len = thread::scope(|s| s.spawn(|| text.len()).join().unwrap());
+    assert_eq!(len, 8);
+}
+
+#[test]
+fn test_spawn_unchecked_borrows_stack_data() {
+    let data = [1, 2, 3];
+    let data = &data;
+    let handle = unsafe { Builder::new().spawn_unchecked(move || data.iter().sum::<i32>()) };
+    assert_eq!(handle.unwrap().join().unwrap(), 6);
+}
+
+#[test]
+fn test_closure_is_dropped_after_thread_exits() {
+    let captured = Arc::new(());
+    let clone = captured.clone();
+    let handle = thread::spawn(move || drop(clone));
+    handle.join().unwrap();
+    assert_eq!(Arc::strong_count(&captured), 1);
+}
+
 // Regression test for https://github.com/rust-lang/rust/issues/98498.
 #[test]
 #[cfg(miri)] // relies on Miri's data race detector
@@ -410,3 +518,57 @@ fn scope_join_race() {
         });
     }
 }
+
+#[test]
+#[cfg(target_os = "dragonos")]
+fn test_large_stack_size() {
+    // Uses more stack than the thread library's default would provide.
+    const SIZE: usize = 16 << 20;
+    let sum = Builder::new()
+        .stack_size(2 * SIZE)
+        .spawn(|| {
+            let buf = crate::std::hint::black_box([1u8; SIZE]);
+            buf.iter().map(|&b| b as usize).sum::<usize>()
+        })
+        .unwrap()
+        .join()
+        .unwrap();
+    assert_eq!(sum, SIZE);
+}
+
+#[test]
+#[cfg(target_os = "dragonos")]
+fn test_name_reaches_kernel() {
+    use crate::std::ffi::CString;
+    use crate::std::sys::thread::Thread as NativeThread;
+
+    Builder::new()
+        .name("a-rather-long-thread-name".to_string())
+        .spawn(|| {
+            assert_eq!(
+                NativeThread::get_name(),
+                Some(CString::new("a-rather-long-t").unwrap())
+            );
+            assert_eq!(thread::current().name(), Some("a-rather-long-thread-name"));
+        })
+        .unwrap()
+        .join()
+        .unwrap();
+}
+
+#[test]
+#[cfg(target_os = "dragonos")]
+fn test_spawned_thread_knows_its_guard_page() {
+    use crate::std::sys::os::page_size;
+    use crate::std::sys_common::thread_info;
+
+    thread::spawn(|| {
+        let guard = thread_info::stack_guard().expect("no guard page");
+        assert_eq!(guard.end - guard.start, page_size());
+        // The stack grows down towards the guard page.
+        let local = 0u8;
+        assert!(guard.end <= &local as *const u8 as usize);
+    })
+    .join()
+    .unwrap();
+}
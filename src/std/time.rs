@@ -671,6 +671,18 @@ impl fmt::Display for SystemTimeError {
     }
 }
 
+impl FromInner<time::Instant> for Instant {
+    fn from_inner(time: time::Instant) -> Instant {
+        Instant(time)
+    }
+}
+
+impl IntoInner<time::Instant> for Instant {
+    fn into_inner(self) -> time::Instant {
+        self.0
+    }
+}
+
 impl FromInner<time::SystemTime> for SystemTime {
     fn from_inner(time: time::SystemTime) -> SystemTime {
         SystemTime(time)